@@ -51,14 +51,97 @@ ENUM ('active','inactive','banned');
 ALTER TYPE forum.user_status OWNER TO postgres;
 -- ddl-end --
 
+-- object: forum.permission | type: TYPE --
+-- DROP TYPE IF EXISTS forum.permission CASCADE;
+CREATE TYPE forum.permission AS
+ENUM ('can_post','can_create_thread','can_lock','can_move','can_warn','can_upload','can_pm','can_moderate','can_moderate_chat','can_admin');
+-- ddl-end --
+ALTER TYPE forum.permission OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.notification_kind | type: TYPE --
+-- DROP TYPE IF EXISTS forum.notification_kind CASCADE;
+CREATE TYPE forum.notification_kind AS
+ENUM ('reply','quote','mention','new_thread','pm','warning','report_handled','approved','rejected');
+-- ddl-end --
+ALTER TYPE forum.notification_kind OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.pm_privacy | type: TYPE --
+-- DROP TYPE IF EXISTS forum.pm_privacy CASCADE;
+CREATE TYPE forum.pm_privacy AS
+ENUM ('everyone','min_posts','nobody');
+-- ddl-end --
+ALTER TYPE forum.pm_privacy OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.moderation_action | type: TYPE --
+-- DROP TYPE IF EXISTS forum.moderation_action CASCADE;
+CREATE TYPE forum.moderation_action AS
+ENUM ('lock_thread','unlock_thread','pin_thread','edit_thread','delete_thread','move_thread','merge_thread','split_thread','edit_post','delete_post','create_section','warn_user','unban_user','update_group','update_permissions','update_moderators','restore_revision','restore_thread','restore_post','add_ban','remove_ban','approve_thread','approve_post','reject_thread','reject_post');
+-- ddl-end --
+ALTER TYPE forum.moderation_action OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.moderation_target | type: TYPE --
+-- DROP TYPE IF EXISTS forum.moderation_target CASCADE;
+CREATE TYPE forum.moderation_target AS
+ENUM ('user','thread','post','section','group','ban');
+-- ddl-end --
+ALTER TYPE forum.moderation_target OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.report_target | type: TYPE --
+-- DROP TYPE IF EXISTS forum.report_target CASCADE;
+CREATE TYPE forum.report_target AS
+ENUM ('post','thread','message','chat','user');
+-- ddl-end --
+ALTER TYPE forum.report_target OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.report_reason | type: TYPE --
+-- DROP TYPE IF EXISTS forum.report_reason CASCADE;
+CREATE TYPE forum.report_reason AS
+ENUM ('spam','abuse','off_topic','illegal','other');
+-- ddl-end --
+ALTER TYPE forum.report_reason OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.report_status | type: TYPE --
+-- DROP TYPE IF EXISTS forum.report_status CASCADE;
+CREATE TYPE forum.report_status AS
+ENUM ('open','claimed','resolved','dismissed');
+-- ddl-end --
+ALTER TYPE forum.report_status OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.ban_kind | type: TYPE --
+-- DROP TYPE IF EXISTS forum.ban_kind CASCADE;
+CREATE TYPE forum.ban_kind AS
+ENUM ('ip','email','email_domain');
+-- ddl-end --
+ALTER TYPE forum.ban_kind OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.filter_action | type: TYPE --
+-- DROP TYPE IF EXISTS forum.filter_action CASCADE;
+CREATE TYPE forum.filter_action AS
+ENUM ('replace','block','moderate');
+-- ddl-end --
+ALTER TYPE forum.filter_action OWNER TO postgres;
+-- ddl-end --
+
 -- object: forum.sections | type: TABLE --
 -- DROP TABLE IF EXISTS forum.sections CASCADE;
 CREATE TABLE forum.sections (
 	id SERIAL PRIMARY KEY,
 	name varchar(100) NOT NULL,
-	description varchar(255)
+	description varchar(255),
+	moderated boolean NOT NULL DEFAULT false
 );
 -- ddl-end --
+COMMENT ON COLUMN forum.sections.moderated IS E'New threads and posts wait for a moderator before anyone else can read them';
+-- ddl-end --
 ALTER TABLE forum.sections OWNER TO postgres;
 -- ddl-end --
 
@@ -69,12 +152,45 @@ CREATE TABLE forum.threads (
 	title varchar(255) NOT NULL,
 	created_at timestamptz NOT NULL DEFAULT NOW(),
 	content text NOT NULL,
+	source text NOT NULL DEFAULT '',
 	author uuid NOT NULL,
 	section int8 NOT NULL,
 	locked boolean NOT NULL DEFAULT false,
-	sticky boolean NOT NULL DEFAULT false
+	sticky boolean NOT NULL DEFAULT false,
+	announcement boolean NOT NULL DEFAULT false,
+	sticky_until timestamptz,
+	moved_to int4,
+	modified_at timestamptz,
+	modified_by uuid,
+	deleted_at timestamptz,
+	deleted_by uuid,
+	delete_reason varchar(255),
+	pending boolean NOT NULL DEFAULT false,
+	approved_at timestamptz DEFAULT NOW(),
+	search_vector tsvector GENERATED ALWAYS AS (
+		setweight(to_tsvector('english'::regconfig, coalesce(title, '')), 'A') ||
+		setweight(to_tsvector('english'::regconfig, coalesce(content, '')), 'B')
+	) STORED
 );
 -- ddl-end --
+COMMENT ON COLUMN forum.threads.content IS E'HTML rendered from source and sanitised by the server';
+-- ddl-end --
+COMMENT ON COLUMN forum.threads.source IS E'Markdown written by the author, handed out for editing';
+-- ddl-end --
+COMMENT ON COLUMN forum.threads.announcement IS E'Pinned at the top of every section';
+-- ddl-end --
+COMMENT ON COLUMN forum.threads.sticky_until IS E'Sticky and announcement flags stop applying after this time, NULL for never';
+-- ddl-end --
+COMMENT ON COLUMN forum.threads.moved_to IS E'Set on the redirect stubs left behind by moved and merged threads';
+-- ddl-end --
+COMMENT ON COLUMN forum.threads.modified_by IS E'Author of the latest edit of the title or opening post';
+-- ddl-end --
+COMMENT ON COLUMN forum.threads.deleted_at IS E'Soft deleted threads are only shown to moderators until they are purged';
+-- ddl-end --
+COMMENT ON COLUMN forum.threads.pending IS E'Waiting for approval, only shown to the author and moderators';
+-- ddl-end --
+COMMENT ON COLUMN forum.threads.approved_at IS E'When it was first shown to everyone, NULL until then. Edits sent back to the queue keep it';
+-- ddl-end --
 ALTER TABLE forum.threads OWNER TO postgres;
 -- ddl-end --
 
@@ -97,6 +213,8 @@ CREATE TABLE forum.users (
 	discord varchar(100),
 	x_id varchar(100),
 	banned_until timestamptz,
+	registration_ip inet,
+	last_login_ip inet,
 	last_online timestamptz,
 	oauth_provider VARCHAR(50),
 	oauth_uid VARCHAR(255),
@@ -108,6 +226,10 @@ CREATE TABLE forum.users (
 	CONSTRAINT one_user_per_email UNIQUE (email)
 );
 -- ddl-end --
+COMMENT ON COLUMN forum.users.registration_ip IS E'Kept to find accounts of banned users';
+-- ddl-end --
+COMMENT ON COLUMN forum.users.last_login_ip IS E'Updated on password and OAuth logins';
+-- ddl-end --
 ALTER TABLE forum.users OWNER TO postgres;
 -- ddl-end --
 
@@ -116,17 +238,49 @@ ALTER TABLE forum.users OWNER TO postgres;
 CREATE TABLE forum.posts (
 	id BIGSERIAL PRIMARY KEY,
 	content text NOT NULL,
+	source text NOT NULL DEFAULT '',
 	author uuid,
 	topic int8 NOT NULL,
 	comments int8,
 	created_at timestamptz NOT NULL DEFAULT NOW(),
 	modified_at timestamptz,
-	likes int4 NOT NULL DEFAULT 0
+	modified_by uuid,
+	deleted_at timestamptz,
+	deleted_by uuid,
+	delete_reason varchar(255),
+	likes int4 NOT NULL DEFAULT 0,
+	pending boolean NOT NULL DEFAULT false,
+	approved_at timestamptz DEFAULT NOW(),
+	search_vector tsvector GENERATED ALWAYS AS (
+		to_tsvector('english'::regconfig, coalesce(content, ''))
+	) STORED
 );
 -- ddl-end --
+COMMENT ON COLUMN forum.posts.content IS E'HTML rendered from source and sanitised by the server';
+-- ddl-end --
+COMMENT ON COLUMN forum.posts.source IS E'Markdown written by the author, handed out for editing';
+-- ddl-end --
+COMMENT ON COLUMN forum.posts.modified_by IS E'Author of the latest edit';
+-- ddl-end --
+COMMENT ON COLUMN forum.posts.deleted_at IS E'Soft deleted posts are only shown to moderators until they are purged';
+-- ddl-end --
+COMMENT ON COLUMN forum.posts.pending IS E'Waiting for approval, only shown to the author and moderators';
+-- ddl-end --
+COMMENT ON COLUMN forum.posts.approved_at IS E'When it was first shown to everyone, NULL until then. Edits sent back to the queue keep it';
+-- ddl-end --
 ALTER TABLE forum.posts OWNER TO postgres;
 -- ddl-end --
 
+-- object: threads_search_idx | type: INDEX --
+-- DROP INDEX IF EXISTS forum.threads_search_idx CASCADE;
+CREATE INDEX threads_search_idx ON forum.threads USING gin (search_vector);
+-- ddl-end --
+
+-- object: posts_search_idx | type: INDEX --
+-- DROP INDEX IF EXISTS forum.posts_search_idx CASCADE;
+CREATE INDEX posts_search_idx ON forum.posts USING gin (search_vector);
+-- ddl-end --
+
 -- object: forum.sections_allowed | type: TABLE --
 -- DROP TABLE IF EXISTS forum.sections_allowed CASCADE;
 CREATE TABLE forum.sections_allowed (
@@ -162,6 +316,48 @@ CREATE TABLE forum.chat_posts (
 ALTER TABLE forum.chat_posts OWNER TO postgres;
 -- ddl-end --
 
+-- object: forum.warning_categories | type: TABLE --
+-- DROP TABLE IF EXISTS forum.warning_categories CASCADE;
+CREATE TABLE forum.warning_categories (
+	id SERIAL PRIMARY KEY,
+	name varchar(64) NOT NULL UNIQUE,
+	points int4 NOT NULL DEFAULT 1,
+	expire_days int4,
+	CONSTRAINT warning_points_positive CHECK (points > 0),
+	CONSTRAINT warning_expiry_positive CHECK (expire_days > 0)
+);
+-- ddl-end --
+COMMENT ON COLUMN forum.warning_categories.expire_days IS E'Warnings stop counting towards the level after this many days, NULL keeps them forever';
+-- ddl-end --
+ALTER TABLE forum.warning_categories OWNER TO postgres;
+-- ddl-end --
+
+INSERT INTO forum.warning_categories (name, points, expire_days) VALUES
+	('general', 1, 90),
+	('spam', 2, 180),
+	('abuse', 3, 365);
+-- ddl-end --
+
+-- object: forum.escalation_rules | type: TABLE --
+-- DROP TABLE IF EXISTS forum.escalation_rules CASCADE;
+CREATE TABLE forum.escalation_rules (
+	id SERIAL PRIMARY KEY,
+	points int4 NOT NULL,
+	period_days int4 NOT NULL,
+	ban_days int4 NOT NULL,
+	CONSTRAINT escalation_positive CHECK (points > 0 AND period_days > 0 AND ban_days > 0)
+);
+-- ddl-end --
+COMMENT ON COLUMN forum.escalation_rules.points IS E'Active warning points reached within period_days that trigger the ban';
+-- ddl-end --
+ALTER TABLE forum.escalation_rules OWNER TO postgres;
+-- ddl-end --
+
+INSERT INTO forum.escalation_rules (points, period_days, ban_days) VALUES
+	(3, 30, 7),
+	(6, 90, 30);
+-- ddl-end --
+
 -- object: forum.user_warning | type: TABLE --
 -- DROP TABLE IF EXISTS forum.user_warning CASCADE;
 CREATE TABLE forum.user_warning (
@@ -171,25 +367,533 @@ CREATE TABLE forum.user_warning (
 	comment varchar(255),
 	warned_by uuid NOT NULL,
 	banned boolean NOT NULL DEFAULT false,
+	category int4,
+	points int4 NOT NULL DEFAULT 1,
+	expires_at timestamptz,
 	CONSTRAINT user_no_self_warn CHECK (warned_by <> user_id)
 );
 -- ddl-end --
+COMMENT ON COLUMN forum.user_warning.points IS E'Copied from the category, so editing categories keeps past warnings';
+-- ddl-end --
+COMMENT ON COLUMN forum.user_warning.expires_at IS E'NULL never expires';
+-- ddl-end --
 ALTER TABLE forum.user_warning OWNER TO postgres;
 -- ddl-end --
 
+-- object: forum.conversations | type: TABLE --
+-- DROP TABLE IF EXISTS forum.conversations CASCADE;
+CREATE TABLE forum.conversations (
+	id BIGSERIAL PRIMARY KEY,
+	subject varchar(255) NOT NULL,
+	created_by uuid,
+	created_at timestamptz NOT NULL DEFAULT NOW(),
+	updated_at timestamptz NOT NULL DEFAULT NOW()
+);
+-- ddl-end --
+COMMENT ON COLUMN forum.conversations.updated_at IS E'Time of the latest message';
+-- ddl-end --
+ALTER TABLE forum.conversations OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.conversation_participants | type: TABLE --
+-- DROP TABLE IF EXISTS forum.conversation_participants CASCADE;
+CREATE TABLE forum.conversation_participants (
+	conversation_id int8 NOT NULL,
+	user_id uuid NOT NULL,
+	last_read_message int8 NOT NULL DEFAULT 0,
+	deleted_message int8 NOT NULL DEFAULT 0,
+	joined_at timestamptz NOT NULL DEFAULT NOW(),
+	CONSTRAINT conversation_participants_pk PRIMARY KEY (conversation_id, user_id)
+);
+-- ddl-end --
+COMMENT ON COLUMN forum.conversation_participants.deleted_message IS E'Messages up to this id were deleted by the participant, newer ones bring the conversation back';
+-- ddl-end --
+ALTER TABLE forum.conversation_participants OWNER TO postgres;
+-- ddl-end --
+
 -- object: forum.private_messages | type: TABLE --
 -- DROP TABLE IF EXISTS forum.private_messages CASCADE;
 CREATE TABLE forum.private_messages (
 	id BIGSERIAL PRIMARY KEY,
+	conversation_id int8 NOT NULL,
 	author uuid,
-	receiver uuid NOT NULL,
-	content varchar(255) NOT NULL,
-	CONSTRAINT pm_author_recv CHECK (author <> receiver)
+	content text NOT NULL,
+	source text NOT NULL DEFAULT '',
+	created_at timestamptz NOT NULL DEFAULT NOW()
 );
 -- ddl-end --
+COMMENT ON COLUMN forum.private_messages.content IS E'HTML rendered from source and sanitised by the server';
+-- ddl-end --
+COMMENT ON COLUMN forum.private_messages.source IS E'Markdown written by the author';
+-- ddl-end --
 ALTER TABLE forum.private_messages OWNER TO postgres;
 -- ddl-end --
 
+-- object: forum.uploads | type: TABLE --
+-- DROP TABLE IF EXISTS forum.uploads CASCADE;
+CREATE TABLE forum.uploads (
+	filename varchar(255) NOT NULL,
+	uploader uuid NOT NULL,
+	thread_id int8 NOT NULL,
+	created_at timestamptz NOT NULL DEFAULT NOW(),
+	CONSTRAINT uploads_pk PRIMARY KEY (filename)
+);
+-- ddl-end --
+COMMENT ON TABLE forum.uploads IS E'Images uploaded for posts, readable by whoever can read the section of the thread. Files not in here (avatars) are public';
+-- ddl-end --
+ALTER TABLE forum.uploads OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.groups | type: TABLE --
+-- DROP TABLE IF EXISTS forum.groups CASCADE;
+CREATE TABLE forum.groups (
+	id SERIAL PRIMARY KEY,
+	name varchar(100) NOT NULL,
+	description varchar(255),
+	role forum.user_role,
+	CONSTRAINT group_name_unique UNIQUE (name),
+	CONSTRAINT group_role_unique UNIQUE (role)
+);
+-- ddl-end --
+COMMENT ON COLUMN forum.groups.role IS E'Every user with this role is implicitly a member of the group';
+-- ddl-end --
+ALTER TABLE forum.groups OWNER TO postgres;
+-- ddl-end --
+
+INSERT INTO forum.groups (name, description, role) VALUES
+	('Administrators', 'Full access to the forum', 'admin'),
+	('Moderators', 'Forum moderators', 'mod'),
+	('Registered users', 'Everyone with an account', 'user');
+-- ddl-end --
+
+-- object: forum.group_permissions | type: TABLE --
+-- DROP TABLE IF EXISTS forum.group_permissions CASCADE;
+CREATE TABLE forum.group_permissions (
+	group_id int4 NOT NULL,
+	permission forum.permission NOT NULL,
+	CONSTRAINT group_permissions_pk PRIMARY KEY (group_id, permission)
+);
+-- ddl-end --
+ALTER TABLE forum.group_permissions OWNER TO postgres;
+-- ddl-end --
+
+INSERT INTO forum.group_permissions (group_id, permission)
+	SELECT g.id, p.permission FROM forum.groups g
+	CROSS JOIN unnest(enum_range(NULL::forum.permission)) AS p(permission)
+	WHERE g.role = 'admin'
+		OR (g.role = 'mod' AND p.permission IN ('can_post','can_create_thread','can_warn','can_upload','can_pm','can_moderate_chat'))
+		OR (g.role = 'user' AND p.permission IN ('can_post','can_create_thread','can_upload','can_pm'));
+-- ddl-end --
+
+-- object: forum.user_groups | type: TABLE --
+-- DROP TABLE IF EXISTS forum.user_groups CASCADE;
+CREATE TABLE forum.user_groups (
+	user_id uuid NOT NULL,
+	group_id int4 NOT NULL,
+	CONSTRAINT user_groups_pk PRIMARY KEY (user_id, group_id)
+);
+-- ddl-end --
+ALTER TABLE forum.user_groups OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.section_permissions | type: TABLE --
+-- DROP TABLE IF EXISTS forum.section_permissions CASCADE;
+CREATE TABLE forum.section_permissions (
+	section int8 NOT NULL,
+	group_id int4 NOT NULL,
+	permission forum.permission NOT NULL,
+	allowed boolean NOT NULL,
+	CONSTRAINT section_permissions_pk PRIMARY KEY (section, group_id, permission)
+);
+-- ddl-end --
+ALTER TABLE forum.section_permissions OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.section_moderators | type: TABLE --
+-- DROP TABLE IF EXISTS forum.section_moderators CASCADE;
+CREATE TABLE forum.section_moderators (
+	section int8 NOT NULL,
+	user_id uuid NOT NULL,
+	CONSTRAINT section_moderators_pk PRIMARY KEY (section, user_id)
+);
+-- ddl-end --
+ALTER TABLE forum.section_moderators OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.reaction_kinds | type: TABLE --
+-- DROP TABLE IF EXISTS forum.reaction_kinds CASCADE;
+CREATE TABLE forum.reaction_kinds (
+	name varchar(32) PRIMARY KEY,
+	emoji varchar(16) NOT NULL,
+	position int4 NOT NULL DEFAULT 0
+);
+-- ddl-end --
+ALTER TABLE forum.reaction_kinds OWNER TO postgres;
+-- ddl-end --
+
+INSERT INTO forum.reaction_kinds (name, emoji, position) VALUES
+	('like', '👍', 0),
+	('love', '❤️', 1),
+	('laugh', '😂', 2),
+	('wow', '😮', 3),
+	('sad', '😢', 4);
+-- ddl-end --
+
+-- object: forum.post_reactions | type: TABLE --
+-- DROP TABLE IF EXISTS forum.post_reactions CASCADE;
+CREATE TABLE forum.post_reactions (
+	post_id int8 NOT NULL,
+	user_id uuid NOT NULL,
+	kind varchar(32) NOT NULL,
+	created_at timestamptz NOT NULL DEFAULT NOW(),
+	CONSTRAINT post_reactions_pk PRIMARY KEY (post_id, user_id, kind)
+);
+-- ddl-end --
+ALTER TABLE forum.post_reactions OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.thread_subscriptions | type: TABLE --
+-- DROP TABLE IF EXISTS forum.thread_subscriptions CASCADE;
+CREATE TABLE forum.thread_subscriptions (
+	user_id uuid NOT NULL,
+	thread_id int8 NOT NULL,
+	created_at timestamptz NOT NULL DEFAULT NOW(),
+	CONSTRAINT thread_subscriptions_pk PRIMARY KEY (user_id, thread_id)
+);
+-- ddl-end --
+ALTER TABLE forum.thread_subscriptions OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.section_subscriptions | type: TABLE --
+-- DROP TABLE IF EXISTS forum.section_subscriptions CASCADE;
+CREATE TABLE forum.section_subscriptions (
+	user_id uuid NOT NULL,
+	section int8 NOT NULL,
+	created_at timestamptz NOT NULL DEFAULT NOW(),
+	CONSTRAINT section_subscriptions_pk PRIMARY KEY (user_id, section)
+);
+-- ddl-end --
+ALTER TABLE forum.section_subscriptions OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.notifications | type: TABLE --
+-- DROP TABLE IF EXISTS forum.notifications CASCADE;
+CREATE TABLE forum.notifications (
+	id BIGSERIAL PRIMARY KEY,
+	user_id uuid NOT NULL,
+	kind forum.notification_kind NOT NULL,
+	actor uuid,
+	thread_id int8,
+	post_id int8,
+	content varchar(255),
+	is_read boolean NOT NULL DEFAULT false,
+	created_at timestamptz NOT NULL DEFAULT NOW()
+);
+-- ddl-end --
+ALTER TABLE forum.notifications OWNER TO postgres;
+-- ddl-end --
+
+-- object: notifications_unread_idx | type: INDEX --
+-- DROP INDEX IF EXISTS forum.notifications_unread_idx CASCADE;
+CREATE INDEX notifications_unread_idx ON forum.notifications (user_id, is_read, created_at DESC);
+-- ddl-end --
+
+-- object: forum.mentions | type: TABLE --
+-- DROP TABLE IF EXISTS forum.mentions CASCADE;
+CREATE TABLE forum.mentions (
+	thread_id int8 NOT NULL,
+	post_id int8,
+	user_id uuid NOT NULL,
+	created_at timestamptz NOT NULL DEFAULT NOW(),
+	CONSTRAINT mentions_unique UNIQUE NULLS NOT DISTINCT (thread_id, post_id, user_id)
+);
+-- ddl-end --
+COMMENT ON COLUMN forum.mentions.post_id IS E'NULL when the mention is in the opening post of the thread';
+-- ddl-end --
+ALTER TABLE forum.mentions OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.thread_reads | type: TABLE --
+-- DROP TABLE IF EXISTS forum.thread_reads CASCADE;
+CREATE TABLE forum.thread_reads (
+	user_id uuid NOT NULL,
+	thread_id int8 NOT NULL,
+	last_read_post int8 NOT NULL DEFAULT 0,
+	read_at timestamptz NOT NULL DEFAULT NOW(),
+	CONSTRAINT thread_reads_pk PRIMARY KEY (user_id, thread_id)
+);
+-- ddl-end --
+COMMENT ON COLUMN forum.thread_reads.last_read_post IS E'Highest post id the user has seen in the thread, 0 when only the opening post was read';
+-- ddl-end --
+ALTER TABLE forum.thread_reads OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.user_blocks | type: TABLE --
+-- DROP TABLE IF EXISTS forum.user_blocks CASCADE;
+CREATE TABLE forum.user_blocks (
+	user_id uuid NOT NULL,
+	blocked_id uuid NOT NULL,
+	created_at timestamptz NOT NULL DEFAULT NOW(),
+	CONSTRAINT user_blocks_pk PRIMARY KEY (user_id, blocked_id),
+	CONSTRAINT user_blocks_self CHECK (user_id <> blocked_id)
+);
+-- ddl-end --
+ALTER TABLE forum.user_blocks OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.user_privacy | type: TABLE --
+-- DROP TABLE IF EXISTS forum.user_privacy CASCADE;
+CREATE TABLE forum.user_privacy (
+	user_id uuid NOT NULL PRIMARY KEY,
+	pm_privacy forum.pm_privacy NOT NULL DEFAULT 'everyone',
+	pm_min_posts int4 NOT NULL DEFAULT 0 CHECK (pm_min_posts >= 0),
+	invisible bool NOT NULL DEFAULT false
+);
+-- ddl-end --
+COMMENT ON COLUMN forum.user_privacy.pm_min_posts IS E'Posts a sender needs with min_posts privacy';
+-- ddl-end --
+COMMENT ON COLUMN forum.user_privacy.invisible IS E'Hides the user from the online list and presence indicators';
+-- ddl-end --
+ALTER TABLE forum.user_privacy OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.moderation_log | type: TABLE --
+-- DROP TABLE IF EXISTS forum.moderation_log CASCADE;
+CREATE TABLE forum.moderation_log (
+	id BIGSERIAL PRIMARY KEY,
+	actor uuid,
+	action forum.moderation_action NOT NULL,
+	target_kind forum.moderation_target NOT NULL,
+	target_id varchar(64) NOT NULL,
+	section int8,
+	reason varchar(255),
+	before jsonb,
+	after jsonb,
+	created_at timestamptz NOT NULL DEFAULT NOW()
+);
+-- ddl-end --
+COMMENT ON COLUMN forum.moderation_log.actor IS E'No foreign key, entries outlive the staff member';
+-- ddl-end --
+COMMENT ON COLUMN forum.moderation_log.target_id IS E'Thread, post and section ids or user uuids as text';
+-- ddl-end --
+COMMENT ON COLUMN forum.moderation_log.section IS E'Section the target was in, kept without a foreign key like the rest of the log';
+-- ddl-end --
+COMMENT ON COLUMN forum.moderation_log.before IS E'Snapshot of the changed fields before the action';
+-- ddl-end --
+ALTER TABLE forum.moderation_log OWNER TO postgres;
+-- ddl-end --
+
+-- object: moderation_log_created | type: INDEX --
+-- DROP INDEX IF EXISTS forum.moderation_log_created CASCADE;
+CREATE INDEX moderation_log_created ON forum.moderation_log USING btree (created_at DESC);
+-- ddl-end --
+
+-- object: moderation_log_target | type: INDEX --
+-- DROP INDEX IF EXISTS forum.moderation_log_target CASCADE;
+CREATE INDEX moderation_log_target ON forum.moderation_log USING btree (target_kind, target_id);
+-- ddl-end --
+
+-- object: forum.revisions | type: TABLE --
+-- DROP TABLE IF EXISTS forum.revisions CASCADE;
+CREATE TABLE forum.revisions (
+	id BIGSERIAL PRIMARY KEY,
+	thread_id int4,
+	post_id int8,
+	editor uuid,
+	edited_at timestamptz NOT NULL DEFAULT NOW(),
+	title varchar(255),
+	content text NOT NULL,
+	reason varchar(255),
+	CONSTRAINT revision_target CHECK ((thread_id IS NULL) <> (post_id IS NULL))
+);
+-- ddl-end --
+COMMENT ON COLUMN forum.revisions.thread_id IS E'Set for edits of the opening post, post_id for replies';
+-- ddl-end --
+COMMENT ON COLUMN forum.revisions.title IS E'Thread title before the edit, NULL for replies';
+-- ddl-end --
+COMMENT ON COLUMN forum.revisions.content IS E'Markdown source before the edit';
+-- ddl-end --
+ALTER TABLE forum.revisions OWNER TO postgres;
+-- ddl-end --
+
+-- object: revisions_post | type: INDEX --
+-- DROP INDEX IF EXISTS forum.revisions_post CASCADE;
+CREATE INDEX revisions_post ON forum.revisions USING btree (post_id);
+-- ddl-end --
+
+-- object: revisions_thread | type: INDEX --
+-- DROP INDEX IF EXISTS forum.revisions_thread CASCADE;
+CREATE INDEX revisions_thread ON forum.revisions USING btree (thread_id);
+-- ddl-end --
+
+-- object: threads_deleted_at | type: INDEX --
+-- DROP INDEX IF EXISTS forum.threads_deleted_at CASCADE;
+CREATE INDEX threads_deleted_at ON forum.threads USING btree (deleted_at) WHERE (deleted_at IS NOT NULL);
+-- ddl-end --
+
+-- object: posts_deleted_at | type: INDEX --
+-- DROP INDEX IF EXISTS forum.posts_deleted_at CASCADE;
+CREATE INDEX posts_deleted_at ON forum.posts USING btree (deleted_at) WHERE (deleted_at IS NOT NULL);
+-- ddl-end --
+
+-- object: threads_pending | type: INDEX --
+-- DROP INDEX IF EXISTS forum.threads_pending CASCADE;
+CREATE INDEX threads_pending ON forum.threads USING btree (created_at) WHERE pending;
+-- ddl-end --
+
+-- object: posts_pending | type: INDEX --
+-- DROP INDEX IF EXISTS forum.posts_pending CASCADE;
+CREATE INDEX posts_pending ON forum.posts USING btree (created_at) WHERE pending;
+-- ddl-end --
+
+-- object: forum.reports | type: TABLE --
+-- DROP TABLE IF EXISTS forum.reports CASCADE;
+CREATE TABLE forum.reports (
+	id BIGSERIAL PRIMARY KEY,
+	reporter uuid,
+	target_kind forum.report_target NOT NULL,
+	target_id varchar(64) NOT NULL,
+	reported_user uuid,
+	section int8,
+	reason forum.report_reason NOT NULL,
+	comment varchar(500),
+	snapshot text NOT NULL,
+	status forum.report_status NOT NULL DEFAULT 'open',
+	claimed_by uuid,
+	handled_by uuid,
+	action forum.moderation_action,
+	resolution varchar(255),
+	created_at timestamptz NOT NULL DEFAULT NOW(),
+	handled_at timestamptz
+);
+-- ddl-end --
+COMMENT ON COLUMN forum.reports.reported_user IS E'Author of the reported content, or the reported user';
+-- ddl-end --
+COMMENT ON COLUMN forum.reports.section IS E'Section of reported posts and threads, NULL for items only global moderators handle';
+-- ddl-end --
+COMMENT ON COLUMN forum.reports.snapshot IS E'Content of the item when it was reported';
+-- ddl-end --
+COMMENT ON COLUMN forum.reports.action IS E'Moderation action the report was resolved with';
+-- ddl-end --
+ALTER TABLE forum.reports OWNER TO postgres;
+-- ddl-end --
+
+-- object: reports_status | type: INDEX --
+-- DROP INDEX IF EXISTS forum.reports_status CASCADE;
+CREATE INDEX reports_status ON forum.reports USING btree (status, created_at);
+-- ddl-end --
+
+-- object: reports_target | type: INDEX --
+-- DROP INDEX IF EXISTS forum.reports_target CASCADE;
+CREATE INDEX reports_target ON forum.reports USING btree (target_kind, target_id);
+-- ddl-end --
+
+-- object: reports_pending_unique | type: INDEX --
+-- DROP INDEX IF EXISTS forum.reports_pending_unique CASCADE;
+CREATE UNIQUE INDEX reports_pending_unique ON forum.reports USING btree (reporter, target_kind, target_id) WHERE (status IN ('open', 'claimed'));
+-- ddl-end --
+
+-- object: forum.bans | type: TABLE --
+-- DROP TABLE IF EXISTS forum.bans CASCADE;
+CREATE TABLE forum.bans (
+	id BIGSERIAL PRIMARY KEY,
+	kind forum.ban_kind NOT NULL,
+	ip cidr,
+	value varchar(255),
+	reason varchar(255),
+	created_by uuid,
+	created_at timestamptz NOT NULL DEFAULT NOW(),
+	expires_at timestamptz,
+	CONSTRAINT ban_target CHECK ((kind = 'ip') = (ip IS NOT NULL) AND (kind = 'ip') = (value IS NULL))
+);
+-- ddl-end --
+COMMENT ON COLUMN forum.bans.ip IS E'Single addresses are stored as /32 or /128 ranges';
+-- ddl-end --
+COMMENT ON COLUMN forum.bans.value IS E'Lowercase email address or domain, subdomains of a banned domain are banned too';
+-- ddl-end --
+COMMENT ON COLUMN forum.bans.expires_at IS E'NULL never expires';
+-- ddl-end --
+ALTER TABLE forum.bans OWNER TO postgres;
+-- ddl-end --
+
+-- object: bans_ip | type: INDEX --
+-- DROP INDEX IF EXISTS forum.bans_ip CASCADE;
+CREATE INDEX bans_ip ON forum.bans USING gist (ip inet_ops);
+-- ddl-end --
+
+-- object: bans_value | type: INDEX --
+-- DROP INDEX IF EXISTS forum.bans_value CASCADE;
+CREATE INDEX bans_value ON forum.bans USING btree (value);
+-- ddl-end --
+
+-- object: forum.word_filters | type: TABLE --
+-- DROP TABLE IF EXISTS forum.word_filters CASCADE;
+CREATE TABLE forum.word_filters (
+	id SERIAL PRIMARY KEY,
+	pattern varchar(255) NOT NULL,
+	is_regex boolean NOT NULL DEFAULT false,
+	action forum.filter_action NOT NULL DEFAULT 'replace',
+	replacement varchar(64) NOT NULL DEFAULT '***'
+);
+-- ddl-end --
+COMMENT ON COLUMN forum.word_filters.pattern IS E'Whole word matched case insensitively, a regular expression when is_regex';
+-- ddl-end --
+COMMENT ON COLUMN forum.word_filters.action IS E'moderate holds forum posts for approval and refuses chat and private messages';
+-- ddl-end --
+ALTER TABLE forum.word_filters OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.spam_settings | type: TABLE --
+-- DROP TABLE IF EXISTS forum.spam_settings CASCADE;
+CREATE TABLE forum.spam_settings (
+	id boolean PRIMARY KEY DEFAULT true,
+	max_links int4 NOT NULL DEFAULT 2,
+	new_user_posts int4 NOT NULL DEFAULT 10,
+	duplicate_minutes int4 NOT NULL DEFAULT 10,
+	flood_seconds int4 NOT NULL DEFAULT 15,
+	CONSTRAINT spam_settings_single CHECK (id),
+	CONSTRAINT spam_settings_positive CHECK (max_links >= 0 AND new_user_posts >= 0 AND duplicate_minutes >= 0 AND flood_seconds >= 0)
+);
+-- ddl-end --
+COMMENT ON COLUMN forum.spam_settings.max_links IS E'Links a new user may put into one post or message';
+-- ddl-end --
+COMMENT ON COLUMN forum.spam_settings.new_user_posts IS E'Users with fewer threads and posts are new';
+-- ddl-end --
+COMMENT ON COLUMN forum.spam_settings.duplicate_minutes IS E'Identical content of a user within this time is refused, 0 turns the check off';
+-- ddl-end --
+COMMENT ON COLUMN forum.spam_settings.flood_seconds IS E'Shortest time between two posts or two private messages of a user, 0 turns the check off';
+-- ddl-end --
+ALTER TABLE forum.spam_settings OWNER TO postgres;
+-- ddl-end --
+
+INSERT INTO forum.spam_settings DEFAULT VALUES;
+-- ddl-end --
+
+-- object: threads_author_created | type: INDEX --
+-- DROP INDEX IF EXISTS forum.threads_author_created CASCADE;
+CREATE INDEX threads_author_created ON forum.threads USING btree (author, created_at);
+-- ddl-end --
+
+-- object: posts_author_created | type: INDEX --
+-- DROP INDEX IF EXISTS forum.posts_author_created CASCADE;
+CREATE INDEX posts_author_created ON forum.posts USING btree (author, created_at);
+-- ddl-end --
+
+-- object: private_messages_author_created | type: INDEX --
+-- DROP INDEX IF EXISTS forum.private_messages_author_created CASCADE;
+CREATE INDEX private_messages_author_created ON forum.private_messages USING btree (author, created_at);
+-- ddl-end --
+
+-- object: users_registration_ip | type: INDEX --
+-- DROP INDEX IF EXISTS forum.users_registration_ip CASCADE;
+CREATE INDEX users_registration_ip ON forum.users USING btree (registration_ip);
+-- ddl-end --
+
+-- object: users_last_login_ip | type: INDEX --
+-- DROP INDEX IF EXISTS forum.users_last_login_ip CASCADE;
+CREATE INDEX users_last_login_ip ON forum.users USING btree (last_login_ip);
+-- ddl-end --
+
 -- object: forum.delete_related_threads | type: FUNCTION --
 -- DROP FUNCTION IF EXISTS forum.delete_related_threads() CASCADE;
 CREATE OR REPLACE FUNCTION forum.delete_related_threads()
@@ -202,14 +906,20 @@ CREATE OR REPLACE FUNCTION forum.delete_related_threads()
     COST 1
 AS $$
 BEGIN
-    -- Delete rows from the "threads" table where section_id matches the deleted row
+    -- Delete rows from the "threads" table where section matches the deleted row
     DELETE FROM forum.threads
-    WHERE section_id = OLD.id;
+    WHERE section = OLD.id;
 
     -- Delete permissions for deleted section
     DELETE FROM forum.sections_allowed
     WHERE section = OLD.id;
 
+    DELETE FROM forum.section_permissions
+    WHERE section = OLD.id;
+
+    DELETE FROM forum.section_moderators
+    WHERE section = OLD.id;
+
     -- Return the deleted row (required for BEFORE DELETE triggers)
     RETURN OLD;
 END;
@@ -219,6 +929,155 @@ $$;
 ALTER FUNCTION forum.delete_related_threads() OWNER TO postgres;
 -- ddl-end --
 
+-- object: forum.section_visible | type: FUNCTION --
+-- DROP FUNCTION IF EXISTS forum.section_visible(int8, forum.user_role) CASCADE;
+CREATE OR REPLACE FUNCTION forum.section_visible (s_id int8, r forum.user_role)
+	RETURNS boolean
+	LANGUAGE sql
+	STABLE
+	CALLED ON NULL INPUT
+	SECURITY INVOKER
+	PARALLEL SAFE
+	COST 1
+	AS $$
+    -- Sections without restrictions are public, admins see everything else,
+    -- anonymous visitors (a NULL role) only the public ones
+    SELECT COALESCE(NOT EXISTS (SELECT 1 FROM forum.sections_allowed WHERE section = s_id)
+        OR r = 'admin'::forum.user_role
+        OR EXISTS (SELECT 1 FROM forum.sections_allowed WHERE section = s_id AND role = r), false);
+$$;
+-- ddl-end --
+ALTER FUNCTION forum.section_visible(int8, forum.user_role) OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.unread_posts | type: FUNCTION --
+-- DROP FUNCTION IF EXISTS forum.unread_posts(uuid, int8) CASCADE;
+CREATE OR REPLACE FUNCTION forum.unread_posts (u_id uuid, t_id int8)
+	RETURNS int8
+	LANGUAGE sql
+	STABLE
+	CALLED ON NULL INPUT
+	SECURITY INVOKER
+	PARALLEL SAFE
+	COST 1
+	AS $$
+    -- Posts older than the account and the user's own posts are never unread
+    SELECT COUNT(*)
+    FROM forum.posts p
+    INNER JOIN forum.users u ON u.id = u_id
+    LEFT JOIN forum.thread_reads r ON r.user_id = u_id AND r.thread_id = t_id
+    WHERE p.topic = t_id
+        AND p.id > COALESCE(r.last_read_post, 0)
+        AND p.created_at > u.created_at
+        AND p.author IS DISTINCT FROM u_id
+        AND p.deleted_at IS NULL
+        AND NOT p.pending;
+$$;
+-- ddl-end --
+ALTER FUNCTION forum.unread_posts(uuid, int8) OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.thread_unread | type: FUNCTION --
+-- DROP FUNCTION IF EXISTS forum.thread_unread(uuid, int8) CASCADE;
+CREATE OR REPLACE FUNCTION forum.thread_unread (u_id uuid, t_id int8)
+	RETURNS boolean
+	LANGUAGE sql
+	STABLE
+	CALLED ON NULL INPUT
+	SECURITY INVOKER
+	PARALLEL SAFE
+	COST 1
+	AS $$
+    -- A thread the user never opened is unread, as is one with new replies
+    SELECT EXISTS (
+            SELECT 1 FROM forum.threads t
+            INNER JOIN forum.users u ON u.id = u_id
+            WHERE t.id = t_id
+                AND t.created_at > u.created_at
+                AND t.author <> u_id
+                AND t.deleted_at IS NULL
+                AND NOT t.pending
+                AND NOT EXISTS (SELECT 1 FROM forum.thread_reads r WHERE r.user_id = u_id AND r.thread_id = t_id)
+        )
+        OR forum.unread_posts(u_id, t_id) > 0;
+$$;
+-- ddl-end --
+ALTER FUNCTION forum.thread_unread(uuid, int8) OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.is_blocked | type: FUNCTION --
+-- DROP FUNCTION IF EXISTS forum.is_blocked(uuid, uuid) CASCADE;
+CREATE OR REPLACE FUNCTION forum.is_blocked (u_id uuid, other uuid)
+	RETURNS boolean
+	LANGUAGE sql
+	STABLE
+	CALLED ON NULL INPUT
+	SECURITY INVOKER
+	PARALLEL SAFE
+	COST 1
+	AS $$
+    -- True when u_id has blocked other
+    SELECT EXISTS (SELECT 1 FROM forum.user_blocks WHERE user_id = u_id AND blocked_id = other);
+$$;
+-- ddl-end --
+ALTER FUNCTION forum.is_blocked(uuid, uuid) OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.warning_points | type: FUNCTION --
+-- DROP FUNCTION IF EXISTS forum.warning_points(uuid, timestamptz) CASCADE;
+CREATE OR REPLACE FUNCTION forum.warning_points (u_id uuid, since timestamptz)
+	RETURNS int8
+	LANGUAGE sql
+	STABLE
+	CALLED ON NULL INPUT
+	SECURITY INVOKER
+	PARALLEL SAFE
+	COST 1
+	AS $$
+	SELECT COALESCE(SUM(w.points), 0)::int8
+	FROM forum.user_warning w
+	WHERE w.user_id = u_id
+		AND (w.expires_at IS NULL OR w.expires_at > NOW())
+		AND (since IS NULL OR w.warn_time > since)
+$$;
+-- ddl-end --
+ALTER FUNCTION forum.warning_points(uuid, timestamptz) OWNER TO postgres;
+-- ddl-end --
+
+-- object: forum.has_permission | type: FUNCTION --
+-- DROP FUNCTION IF EXISTS forum.has_permission(uuid, forum.permission, int8) CASCADE;
+CREATE OR REPLACE FUNCTION forum.has_permission (u_id uuid, p forum.permission, s_id int8)
+	RETURNS boolean
+	LANGUAGE sql
+	STABLE
+	CALLED ON NULL INPUT
+	SECURITY INVOKER
+	PARALLEL SAFE
+	COST 1
+	AS $$
+    -- Admins can't lock themselves out and section moderators may moderate
+    -- their sections. Section overrides of any of the user's groups win
+    -- over the group defaults, any allow beats a deny.
+    WITH member_of AS (
+        SELECT g.id FROM forum.groups g
+        INNER JOIN forum.users u ON u.id = u_id
+        WHERE g.role = u.role
+            OR EXISTS (SELECT 1 FROM forum.user_groups ug WHERE ug.group_id = g.id AND ug.user_id = u_id)
+    )
+    SELECT EXISTS (SELECT 1 FROM forum.users WHERE id = u_id AND role = 'admin'::forum.user_role)
+        OR (p IN ('can_lock', 'can_move', 'can_moderate')
+            AND EXISTS (SELECT 1 FROM forum.section_moderators sm WHERE sm.section = s_id AND sm.user_id = u_id))
+        OR COALESCE(
+            (SELECT bool_or(sp.allowed) FROM forum.section_permissions sp
+                WHERE sp.section = s_id AND sp.permission = p AND sp.group_id IN (SELECT id FROM member_of)),
+            EXISTS (SELECT 1 FROM forum.group_permissions gp
+                WHERE gp.permission = p AND gp.group_id IN (SELECT id FROM member_of))
+        );
+$$;
+-- ddl-end --
+ALTER FUNCTION forum.has_permission(uuid, forum.permission, int8) OWNER TO postgres;
+-- ddl-end --
+
 -- object: tr_on_delete_section | type: TRIGGER --
 -- DROP TRIGGER IF EXISTS tr_on_delete_section ON forum.sections CASCADE;
 CREATE OR REPLACE TRIGGER tr_on_delete_section
@@ -240,10 +1099,13 @@ CREATE OR REPLACE FUNCTION forum.delete_related_posts ()
 	COST 1
 	AS $$
 BEGIN
-    -- Delete rows from the "thread" table where section_id matches the deleted row
+    -- Delete the posts and hashtags of the deleted thread
     DELETE FROM forum.posts
     WHERE topic = OLD.id;
 
+    DELETE FROM forum.hashtags
+    WHERE topic = OLD.id;
+
     -- Return the deleted row (required for BEFORE DELETE triggers)
     RETURN OLD;
 END;
@@ -257,10 +1119,38 @@ ALTER FUNCTION forum.delete_related_posts() OWNER TO postgres;
 CREATE OR REPLACE TRIGGER tr_delete_threads_posts
 	BEFORE DELETE
 	ON forum.threads
-	FOR EACH STATEMENT
+	FOR EACH ROW
 	EXECUTE PROCEDURE forum.delete_related_posts();
 -- ddl-end --
 
+-- object: forum.moderation_log_append_only | type: FUNCTION --
+-- DROP FUNCTION IF EXISTS forum.moderation_log_append_only() CASCADE;
+CREATE OR REPLACE FUNCTION forum.moderation_log_append_only ()
+	RETURNS trigger
+	LANGUAGE plpgsql
+	VOLATILE
+	CALLED ON NULL INPUT
+	SECURITY INVOKER
+	PARALLEL UNSAFE
+	COST 1
+	AS $$
+BEGIN
+    RAISE EXCEPTION 'forum.moderation_log is append-only';
+END;
+$$;
+-- ddl-end --
+ALTER FUNCTION forum.moderation_log_append_only() OWNER TO postgres;
+-- ddl-end --
+
+-- object: tr_moderation_log_append_only | type: TRIGGER --
+-- DROP TRIGGER IF EXISTS tr_moderation_log_append_only ON forum.moderation_log CASCADE;
+CREATE OR REPLACE TRIGGER tr_moderation_log_append_only
+	BEFORE UPDATE OR DELETE
+	ON forum.moderation_log
+	FOR EACH STATEMENT
+	EXECUTE PROCEDURE forum.moderation_log_append_only();
+-- ddl-end --
+
 -- object: topics_sections | type: CONSTRAINT --
 -- ALTER TABLE forum.threads DROP CONSTRAINT IF EXISTS topics_sections CASCADE;
 ALTER TABLE forum.threads ADD CONSTRAINT topics_sections FOREIGN KEY (section)
@@ -335,12 +1225,327 @@ ON DELETE NO ACTION ON UPDATE NO ACTION;
 -- ALTER TABLE forum.private_messages DROP CONSTRAINT IF EXISTS pm_author CASCADE;
 ALTER TABLE forum.private_messages ADD CONSTRAINT pm_author FOREIGN KEY (author)
 REFERENCES forum.users (id) MATCH SIMPLE
-ON DELETE NO ACTION ON UPDATE NO ACTION;
+ON DELETE SET NULL ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: pm_conversation | type: CONSTRAINT --
+-- ALTER TABLE forum.private_messages DROP CONSTRAINT IF EXISTS pm_conversation CASCADE;
+ALTER TABLE forum.private_messages ADD CONSTRAINT pm_conversation FOREIGN KEY (conversation_id)
+REFERENCES forum.conversations (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
 -- ddl-end --
 
--- object: pm_recv | type: CONSTRAINT --
--- ALTER TABLE forum.private_messages DROP CONSTRAINT IF EXISTS pm_recv CASCADE;
-ALTER TABLE forum.private_messages ADD CONSTRAINT pm_recv FOREIGN KEY (receiver)
+-- object: conversation_creator | type: CONSTRAINT --
+-- ALTER TABLE forum.conversations DROP CONSTRAINT IF EXISTS conversation_creator CASCADE;
+ALTER TABLE forum.conversations ADD CONSTRAINT conversation_creator FOREIGN KEY (created_by)
 REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE SET NULL ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: conversation_participants_conversation | type: CONSTRAINT --
+-- ALTER TABLE forum.conversation_participants DROP CONSTRAINT IF EXISTS conversation_participants_conversation CASCADE;
+ALTER TABLE forum.conversation_participants ADD CONSTRAINT conversation_participants_conversation FOREIGN KEY (conversation_id)
+REFERENCES forum.conversations (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: conversation_participants_user | type: CONSTRAINT --
+-- ALTER TABLE forum.conversation_participants DROP CONSTRAINT IF EXISTS conversation_participants_user CASCADE;
+ALTER TABLE forum.conversation_participants ADD CONSTRAINT conversation_participants_user FOREIGN KEY (user_id)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: uploads_thread | type: CONSTRAINT --
+-- ALTER TABLE forum.uploads DROP CONSTRAINT IF EXISTS uploads_thread CASCADE;
+ALTER TABLE forum.uploads ADD CONSTRAINT uploads_thread FOREIGN KEY (thread_id)
+REFERENCES forum.threads (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: uploads_user | type: CONSTRAINT --
+-- ALTER TABLE forum.uploads DROP CONSTRAINT IF EXISTS uploads_user CASCADE;
+ALTER TABLE forum.uploads ADD CONSTRAINT uploads_user FOREIGN KEY (uploader)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: group_permissions_group | type: CONSTRAINT --
+-- ALTER TABLE forum.group_permissions DROP CONSTRAINT IF EXISTS group_permissions_group CASCADE;
+ALTER TABLE forum.group_permissions ADD CONSTRAINT group_permissions_group FOREIGN KEY (group_id)
+REFERENCES forum.groups (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: user_groups_user | type: CONSTRAINT --
+-- ALTER TABLE forum.user_groups DROP CONSTRAINT IF EXISTS user_groups_user CASCADE;
+ALTER TABLE forum.user_groups ADD CONSTRAINT user_groups_user FOREIGN KEY (user_id)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: user_groups_group | type: CONSTRAINT --
+-- ALTER TABLE forum.user_groups DROP CONSTRAINT IF EXISTS user_groups_group CASCADE;
+ALTER TABLE forum.user_groups ADD CONSTRAINT user_groups_group FOREIGN KEY (group_id)
+REFERENCES forum.groups (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: section_permissions_section | type: CONSTRAINT --
+-- ALTER TABLE forum.section_permissions DROP CONSTRAINT IF EXISTS section_permissions_section CASCADE;
+ALTER TABLE forum.section_permissions ADD CONSTRAINT section_permissions_section FOREIGN KEY (section)
+REFERENCES forum.sections (id) MATCH SIMPLE
+ON DELETE NO ACTION ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: section_permissions_group | type: CONSTRAINT --
+-- ALTER TABLE forum.section_permissions DROP CONSTRAINT IF EXISTS section_permissions_group CASCADE;
+ALTER TABLE forum.section_permissions ADD CONSTRAINT section_permissions_group FOREIGN KEY (group_id)
+REFERENCES forum.groups (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: section_moderators_section | type: CONSTRAINT --
+-- ALTER TABLE forum.section_moderators DROP CONSTRAINT IF EXISTS section_moderators_section CASCADE;
+ALTER TABLE forum.section_moderators ADD CONSTRAINT section_moderators_section FOREIGN KEY (section)
+REFERENCES forum.sections (id) MATCH SIMPLE
 ON DELETE NO ACTION ON UPDATE NO ACTION;
 -- ddl-end --
+
+-- object: section_moderators_user | type: CONSTRAINT --
+-- ALTER TABLE forum.section_moderators DROP CONSTRAINT IF EXISTS section_moderators_user CASCADE;
+ALTER TABLE forum.section_moderators ADD CONSTRAINT section_moderators_user FOREIGN KEY (user_id)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: post_reactions_post | type: CONSTRAINT --
+-- ALTER TABLE forum.post_reactions DROP CONSTRAINT IF EXISTS post_reactions_post CASCADE;
+ALTER TABLE forum.post_reactions ADD CONSTRAINT post_reactions_post FOREIGN KEY (post_id)
+REFERENCES forum.posts (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: post_reactions_user | type: CONSTRAINT --
+-- ALTER TABLE forum.post_reactions DROP CONSTRAINT IF EXISTS post_reactions_user CASCADE;
+ALTER TABLE forum.post_reactions ADD CONSTRAINT post_reactions_user FOREIGN KEY (user_id)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: post_reactions_kind | type: CONSTRAINT --
+-- ALTER TABLE forum.post_reactions DROP CONSTRAINT IF EXISTS post_reactions_kind CASCADE;
+ALTER TABLE forum.post_reactions ADD CONSTRAINT post_reactions_kind FOREIGN KEY (kind)
+REFERENCES forum.reaction_kinds (name) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE CASCADE;
+-- ddl-end --
+
+-- object: thread_subscriptions_user | type: CONSTRAINT --
+-- ALTER TABLE forum.thread_subscriptions DROP CONSTRAINT IF EXISTS thread_subscriptions_user CASCADE;
+ALTER TABLE forum.thread_subscriptions ADD CONSTRAINT thread_subscriptions_user FOREIGN KEY (user_id)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: thread_subscriptions_thread | type: CONSTRAINT --
+-- ALTER TABLE forum.thread_subscriptions DROP CONSTRAINT IF EXISTS thread_subscriptions_thread CASCADE;
+ALTER TABLE forum.thread_subscriptions ADD CONSTRAINT thread_subscriptions_thread FOREIGN KEY (thread_id)
+REFERENCES forum.threads (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: section_subscriptions_user | type: CONSTRAINT --
+-- ALTER TABLE forum.section_subscriptions DROP CONSTRAINT IF EXISTS section_subscriptions_user CASCADE;
+ALTER TABLE forum.section_subscriptions ADD CONSTRAINT section_subscriptions_user FOREIGN KEY (user_id)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: section_subscriptions_section | type: CONSTRAINT --
+-- ALTER TABLE forum.section_subscriptions DROP CONSTRAINT IF EXISTS section_subscriptions_section CASCADE;
+ALTER TABLE forum.section_subscriptions ADD CONSTRAINT section_subscriptions_section FOREIGN KEY (section)
+REFERENCES forum.sections (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: notifications_user | type: CONSTRAINT --
+-- ALTER TABLE forum.notifications DROP CONSTRAINT IF EXISTS notifications_user CASCADE;
+ALTER TABLE forum.notifications ADD CONSTRAINT notifications_user FOREIGN KEY (user_id)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: notifications_actor | type: CONSTRAINT --
+-- ALTER TABLE forum.notifications DROP CONSTRAINT IF EXISTS notifications_actor CASCADE;
+ALTER TABLE forum.notifications ADD CONSTRAINT notifications_actor FOREIGN KEY (actor)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE SET NULL ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: notifications_thread | type: CONSTRAINT --
+-- ALTER TABLE forum.notifications DROP CONSTRAINT IF EXISTS notifications_thread CASCADE;
+ALTER TABLE forum.notifications ADD CONSTRAINT notifications_thread FOREIGN KEY (thread_id)
+REFERENCES forum.threads (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: notifications_post | type: CONSTRAINT --
+-- ALTER TABLE forum.notifications DROP CONSTRAINT IF EXISTS notifications_post CASCADE;
+ALTER TABLE forum.notifications ADD CONSTRAINT notifications_post FOREIGN KEY (post_id)
+REFERENCES forum.posts (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: mentions_thread | type: CONSTRAINT --
+-- ALTER TABLE forum.mentions DROP CONSTRAINT IF EXISTS mentions_thread CASCADE;
+ALTER TABLE forum.mentions ADD CONSTRAINT mentions_thread FOREIGN KEY (thread_id)
+REFERENCES forum.threads (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: mentions_post | type: CONSTRAINT --
+-- ALTER TABLE forum.mentions DROP CONSTRAINT IF EXISTS mentions_post CASCADE;
+ALTER TABLE forum.mentions ADD CONSTRAINT mentions_post FOREIGN KEY (post_id)
+REFERENCES forum.posts (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: mentions_user | type: CONSTRAINT --
+-- ALTER TABLE forum.mentions DROP CONSTRAINT IF EXISTS mentions_user CASCADE;
+ALTER TABLE forum.mentions ADD CONSTRAINT mentions_user FOREIGN KEY (user_id)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: thread_reads_user | type: CONSTRAINT --
+-- ALTER TABLE forum.thread_reads DROP CONSTRAINT IF EXISTS thread_reads_user CASCADE;
+ALTER TABLE forum.thread_reads ADD CONSTRAINT thread_reads_user FOREIGN KEY (user_id)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: thread_reads_thread | type: CONSTRAINT --
+-- ALTER TABLE forum.thread_reads DROP CONSTRAINT IF EXISTS thread_reads_thread CASCADE;
+ALTER TABLE forum.thread_reads ADD CONSTRAINT thread_reads_thread FOREIGN KEY (thread_id)
+REFERENCES forum.threads (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: user_blocks_user | type: CONSTRAINT --
+-- ALTER TABLE forum.user_blocks DROP CONSTRAINT IF EXISTS user_blocks_user CASCADE;
+ALTER TABLE forum.user_blocks ADD CONSTRAINT user_blocks_user FOREIGN KEY (user_id)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: user_blocks_blocked | type: CONSTRAINT --
+-- ALTER TABLE forum.user_blocks DROP CONSTRAINT IF EXISTS user_blocks_blocked CASCADE;
+ALTER TABLE forum.user_blocks ADD CONSTRAINT user_blocks_blocked FOREIGN KEY (blocked_id)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: user_privacy_user | type: CONSTRAINT --
+-- ALTER TABLE forum.user_privacy DROP CONSTRAINT IF EXISTS user_privacy_user CASCADE;
+ALTER TABLE forum.user_privacy ADD CONSTRAINT user_privacy_user FOREIGN KEY (user_id)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: threads_moved_to | type: CONSTRAINT --
+-- ALTER TABLE forum.threads DROP CONSTRAINT IF EXISTS threads_moved_to CASCADE;
+ALTER TABLE forum.threads ADD CONSTRAINT threads_moved_to FOREIGN KEY (moved_to)
+REFERENCES forum.threads (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: threads_modified_by | type: CONSTRAINT --
+-- ALTER TABLE forum.threads DROP CONSTRAINT IF EXISTS threads_modified_by CASCADE;
+ALTER TABLE forum.threads ADD CONSTRAINT threads_modified_by FOREIGN KEY (modified_by)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE SET NULL ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: posts_modified_by | type: CONSTRAINT --
+-- ALTER TABLE forum.posts DROP CONSTRAINT IF EXISTS posts_modified_by CASCADE;
+ALTER TABLE forum.posts ADD CONSTRAINT posts_modified_by FOREIGN KEY (modified_by)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE SET NULL ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: revision_thread | type: CONSTRAINT --
+-- ALTER TABLE forum.revisions DROP CONSTRAINT IF EXISTS revision_thread CASCADE;
+ALTER TABLE forum.revisions ADD CONSTRAINT revision_thread FOREIGN KEY (thread_id)
+REFERENCES forum.threads (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: revision_post | type: CONSTRAINT --
+-- ALTER TABLE forum.revisions DROP CONSTRAINT IF EXISTS revision_post CASCADE;
+ALTER TABLE forum.revisions ADD CONSTRAINT revision_post FOREIGN KEY (post_id)
+REFERENCES forum.posts (id) MATCH SIMPLE
+ON DELETE CASCADE ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: revision_editor | type: CONSTRAINT --
+-- ALTER TABLE forum.revisions DROP CONSTRAINT IF EXISTS revision_editor CASCADE;
+ALTER TABLE forum.revisions ADD CONSTRAINT revision_editor FOREIGN KEY (editor)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE SET NULL ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: threads_deleted_by | type: CONSTRAINT --
+-- ALTER TABLE forum.threads DROP CONSTRAINT IF EXISTS threads_deleted_by CASCADE;
+ALTER TABLE forum.threads ADD CONSTRAINT threads_deleted_by FOREIGN KEY (deleted_by)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE SET NULL ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: posts_deleted_by | type: CONSTRAINT --
+-- ALTER TABLE forum.posts DROP CONSTRAINT IF EXISTS posts_deleted_by CASCADE;
+ALTER TABLE forum.posts ADD CONSTRAINT posts_deleted_by FOREIGN KEY (deleted_by)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE SET NULL ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: reports_reporter | type: CONSTRAINT --
+-- ALTER TABLE forum.reports DROP CONSTRAINT IF EXISTS reports_reporter CASCADE;
+ALTER TABLE forum.reports ADD CONSTRAINT reports_reporter FOREIGN KEY (reporter)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE SET NULL ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: reports_reported_user | type: CONSTRAINT --
+-- ALTER TABLE forum.reports DROP CONSTRAINT IF EXISTS reports_reported_user CASCADE;
+ALTER TABLE forum.reports ADD CONSTRAINT reports_reported_user FOREIGN KEY (reported_user)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE SET NULL ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: reports_claimed_by | type: CONSTRAINT --
+-- ALTER TABLE forum.reports DROP CONSTRAINT IF EXISTS reports_claimed_by CASCADE;
+ALTER TABLE forum.reports ADD CONSTRAINT reports_claimed_by FOREIGN KEY (claimed_by)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE SET NULL ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: reports_handled_by | type: CONSTRAINT --
+-- ALTER TABLE forum.reports DROP CONSTRAINT IF EXISTS reports_handled_by CASCADE;
+ALTER TABLE forum.reports ADD CONSTRAINT reports_handled_by FOREIGN KEY (handled_by)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE SET NULL ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: warning_category_fk | type: CONSTRAINT --
+-- ALTER TABLE forum.user_warning DROP CONSTRAINT IF EXISTS warning_category_fk CASCADE;
+ALTER TABLE forum.user_warning ADD CONSTRAINT warning_category_fk FOREIGN KEY (category)
+REFERENCES forum.warning_categories (id) MATCH SIMPLE
+ON DELETE SET NULL ON UPDATE NO ACTION;
+-- ddl-end --
+
+-- object: ban_created_by_fk | type: CONSTRAINT --
+-- ALTER TABLE forum.bans DROP CONSTRAINT IF EXISTS ban_created_by_fk CASCADE;
+ALTER TABLE forum.bans ADD CONSTRAINT ban_created_by_fk FOREIGN KEY (created_by)
+REFERENCES forum.users (id) MATCH SIMPLE
+ON DELETE SET NULL ON UPDATE NO ACTION;
+-- ddl-end --
@@ -7,9 +7,17 @@ pub struct Config {
     pub port_https: u16,
     pub email_verification: bool,
     pub host_url: String,
+    /// Address browsers reach the backend on, images in posts have to be uploaded under it
+    pub public_url: String,
     pub upload_dir: String,
     pub max_file_size: usize,
     pub allowed_image_types: Vec<String>,
+    /// Days soft deleted threads and posts are kept, 0 keeps them forever
+    pub purge_after_days: i64,
+    /// Users with fewer posts have theirs approved first, 0 turns the check off
+    pub approval_min_posts: i64,
+    /// Users with younger accounts have their posts approved first, 0 turns the check off
+    pub approval_min_account_days: i64,
 }
 
 impl Config {
@@ -20,11 +28,25 @@ impl Config {
         let jwt_maxage = std::env::var("JWT_MAXAGE").expect("JWT_MAXAGE must be set");
         let email_verification =std::env::var("VERIFY_EMAIL").expect("VERIFY_EMAIL must be set");
         let host_url =std::env::var("HOST_URL").expect("HOST_URL must be set");
+        let public_url = std::env::var("PUBLIC_URL")
+            .unwrap_or_else(|_| "http://localhost:8080".to_string());
         let upload_dir = std::env::var("UPLOAD_DIR").unwrap_or_else(|_| "./uploads".to_string());
         let max_file_size = std::env::var("MAX_FILE_SIZE")
             .unwrap_or_else(|_| "5242880".to_string()) // 5MB default
             .parse::<usize>()
             .unwrap_or(5242880);
+        let purge_after_days = std::env::var("PURGE_DELETED_AFTER_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse::<i64>()
+            .unwrap_or(30);
+        let approval_min_posts = std::env::var("APPROVAL_MIN_POSTS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse::<i64>()
+            .unwrap_or(0);
+        let approval_min_account_days = std::env::var("APPROVAL_MIN_ACCOUNT_DAYS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse::<i64>()
+            .unwrap_or(0);
 
         Config {
             database_url,
@@ -34,8 +56,12 @@ impl Config {
             port_http: 8000,
             email_verification: email_verification.parse::<bool>().unwrap(),
             host_url,
+            public_url,
             upload_dir,
             max_file_size,
+            purge_after_days,
+            approval_min_posts,
+            approval_min_account_days,
             allowed_image_types: vec![
                 "image/jpeg".to_string(),
                 "image/jpg".to_string(),
@@ -0,0 +1,129 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::{error::{ForumError, ForumResult}, models::PendingItem};
+
+#[async_trait]
+pub trait ApprovalExt {
+    /// Moderated sections and users below either threshold post into the queue,
+    /// moderators of the section never do. A threshold of 0 is off.
+    async fn needs_approval(&self, user_id: Uuid, section: i64, min_posts: i64, min_account_days: i64) -> ForumResult<bool>;
+    /// Pending threads and posts in sections where `moderator` has `CanModerate`, the oldest first
+    async fn get_pending(&self, moderator: Uuid, page: u32, limit: usize) -> ForumResult<Vec<PendingItem>>;
+    async fn post_pending(&self, post_id: i64) -> ForumResult<bool>;
+    /// Returns whether the thread is shown for the first time, rather than an edit being approved
+    async fn approve_thread(&self, thread_id: i64) -> ForumResult<bool>;
+    /// Returns whether the post is shown for the first time, rather than an edit being approved
+    async fn approve_post(&self, post_id: i64) -> ForumResult<bool>;
+    /// Puts an edited thread back into the queue
+    async fn hold_thread(&self, thread_id: i64) -> ForumResult<()>;
+    /// Puts an edited post back into the queue
+    async fn hold_post(&self, post_id: i64) -> ForumResult<()>;
+}
+
+#[async_trait]
+impl ApprovalExt for crate::db::DBClient {
+    async fn needs_approval(&self, user_id: Uuid, section: i64, min_posts: i64, min_account_days: i64) -> ForumResult<bool> {
+        struct Helper {
+            pending: Option<bool>,
+        }
+
+        let r = sqlx::query_as!(Helper,
+            r#"SELECT NOT forum.has_permission(u.id, 'can_moderate', s.id::int8)
+                    AND (s.moderated
+                        OR ($3::int8 > 0 AND (
+                            (SELECT COUNT(*) FROM forum.posts p
+                                WHERE p.author = u.id AND p.deleted_at IS NULL AND NOT p.pending)
+                            + (SELECT COUNT(*) FROM forum.threads t
+                                WHERE t.author = u.id AND t.deleted_at IS NULL AND NOT t.pending)) < $3)
+                        OR ($4::int8 > 0 AND u.created_at > NOW() - $4 * interval '1 day')) as pending
+                FROM forum.users u
+                INNER JOIN forum.sections s ON s.id = $2
+                WHERE u.id = $1"#, user_id, section as i32, min_posts, min_account_days)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(r.pending.unwrap_or(true))
+    }
+
+    async fn get_pending(&self, moderator: Uuid, page: u32, limit: usize) -> ForumResult<Vec<PendingItem>> {
+        let offset = (page.max(1) - 1) as i64 * limit as i64;
+
+        let r = sqlx::query_as!(PendingItem,
+            r#"SELECT r.thread_id as "thread_id!", r.post_id, r.section as "section!", s.name as section_name,
+                    r.title as "title!", r.source as "content!", r.author, u.name as "author_name?",
+                    r.created_at as "created_at!"
+                FROM (
+                    SELECT t.id::int8 as thread_id, NULL::int8 as post_id, t.section, t.title, t.source,
+                        t.author, t.created_at
+                    FROM forum.threads t
+                    WHERE t.pending AND t.deleted_at IS NULL
+                    UNION ALL
+                    SELECT p.topic, p.id, t.section, t.title, p.source, p.author, p.created_at
+                    FROM forum.posts p
+                    INNER JOIN forum.threads t ON t.id = p.topic
+                    WHERE p.pending AND p.deleted_at IS NULL AND t.deleted_at IS NULL
+                ) r
+                INNER JOIN forum.sections s ON s.id = r.section
+                LEFT JOIN forum.users u ON u.id = r.author
+                WHERE forum.has_permission($1, 'can_moderate', r.section)
+                ORDER BY r.created_at ASC
+                LIMIT $2 OFFSET $3"#, moderator, limit as i64, offset)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn post_pending(&self, post_id: i64) -> ForumResult<bool> {
+        struct Helper {
+            pending: bool,
+        }
+
+        let r = sqlx::query_as!(Helper,
+            r#"SELECT pending FROM forum.posts WHERE id = $1"#, post_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(r.pending)
+    }
+
+    async fn approve_thread(&self, thread_id: i64) -> ForumResult<bool> {
+        let r = sqlx::query!(
+            r#"UPDATE forum.threads t SET pending = false, approved_at = COALESCE(t.approved_at, NOW())
+                FROM forum.threads old
+                WHERE t.id = $1 AND old.id = t.id AND t.pending AND t.deleted_at IS NULL
+                RETURNING old.approved_at IS NULL as "first!""#, thread_id as i32)
+            .fetch_optional(&self.pool)
+            .await?;
+        match r {
+            Some(r) => Ok(r.first),
+            None => Err(ForumError::Forum("The thread isn't waiting for approval".to_string())),
+        }
+    }
+
+    async fn approve_post(&self, post_id: i64) -> ForumResult<bool> {
+        let r = sqlx::query!(
+            r#"UPDATE forum.posts p SET pending = false, approved_at = COALESCE(p.approved_at, NOW())
+                FROM forum.posts old
+                WHERE p.id = $1 AND old.id = p.id AND p.pending AND p.deleted_at IS NULL
+                RETURNING old.approved_at IS NULL as "first!""#, post_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        match r {
+            Some(r) => Ok(r.first),
+            None => Err(ForumError::Forum("The post isn't waiting for approval".to_string())),
+        }
+    }
+
+    async fn hold_thread(&self, thread_id: i64) -> ForumResult<()> {
+        sqlx::query!(r#"UPDATE forum.threads SET pending = true WHERE id = $1"#, thread_id as i32)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn hold_post(&self, post_id: i64) -> ForumResult<()> {
+        sqlx::query!(r#"UPDATE forum.posts SET pending = true WHERE id = $1"#, post_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
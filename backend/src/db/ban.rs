@@ -0,0 +1,119 @@
+use std::net::IpAddr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{error::ForumResult, models::{Ban, BanKind, SharedIpUser}};
+
+#[async_trait]
+pub trait BanExt {
+    /// The first running ban matching the address or the email
+    async fn find_ban(&self, ip: Option<IpAddr>, email: Option<&str>) -> ForumResult<Option<Ban>>;
+    async fn get_bans(&self) -> ForumResult<Vec<Ban>>;
+    async fn get_ban(&self, id: i64) -> ForumResult<Ban>;
+    /// `ip` is an address or a CIDR range, `value` an email address or domain
+    async fn add_ban(&self, kind: BanKind, ip: Option<&str>, value: Option<&str>, reason: Option<&str>,
+        created_by: Uuid, expires_at: Option<DateTime<Utc>>) -> ForumResult<i64>;
+    async fn remove_ban(&self, id: i64) -> ForumResult<()>;
+    async fn record_ip(&self, user_id: Uuid, ip: IpAddr, registration: bool) -> ForumResult<()>;
+    /// Other accounts registered or last logged in from an address of the user
+    async fn shared_ip_users(&self, user_id: Uuid) -> ForumResult<Vec<SharedIpUser>>;
+}
+
+#[async_trait]
+impl BanExt for crate::db::DBClient {
+    async fn find_ban(&self, ip: Option<IpAddr>, email: Option<&str>) -> ForumResult<Option<Ban>> {
+        let ip = ip.map(|ip| ip.to_string());
+        let r = sqlx::query_as!(Ban,
+            r#"SELECT b.id, b.kind as "kind: BanKind", b.ip::text as ip, b.value, b.reason,
+                    u.name as "created_by_name?", b.created_at, b.expires_at
+                FROM forum.bans b
+                LEFT JOIN forum.users u ON u.id = b.created_by
+                WHERE (b.expires_at IS NULL OR b.expires_at > NOW())
+                    AND ((b.kind = 'ip' AND b.ip >>= $1::text::inet)
+                        OR (b.kind = 'email' AND b.value = lower($2))
+                        OR (b.kind = 'email_domain'
+                            AND (lower($2) LIKE '%@' || b.value OR lower($2) LIKE '%.' || b.value)))
+                ORDER BY b.expires_at DESC NULLS FIRST
+                LIMIT 1"#, ip, email)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn get_bans(&self) -> ForumResult<Vec<Ban>> {
+        let r = sqlx::query_as!(Ban,
+            r#"SELECT b.id, b.kind as "kind: BanKind", b.ip::text as ip, b.value, b.reason,
+                    u.name as "created_by_name?", b.created_at, b.expires_at
+                FROM forum.bans b
+                LEFT JOIN forum.users u ON u.id = b.created_by
+                WHERE b.expires_at IS NULL OR b.expires_at > NOW()
+                ORDER BY b.created_at DESC"#)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn get_ban(&self, id: i64) -> ForumResult<Ban> {
+        let r = sqlx::query_as!(Ban,
+            r#"SELECT b.id, b.kind as "kind: BanKind", b.ip::text as ip, b.value, b.reason,
+                    u.name as "created_by_name?", b.created_at, b.expires_at
+                FROM forum.bans b
+                LEFT JOIN forum.users u ON u.id = b.created_by
+                WHERE b.id = $1"#, id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn add_ban(&self, kind: BanKind, ip: Option<&str>, value: Option<&str>, reason: Option<&str>,
+        created_by: Uuid, expires_at: Option<DateTime<Utc>>) -> ForumResult<i64> {
+        struct Helper {
+            id: i64,
+        }
+
+        let r = sqlx::query_as!(Helper,
+            r#"INSERT INTO forum.bans (kind, ip, value, reason, created_by, expires_at)
+                VALUES ($1, network($2::text::inet), lower($3), $4, $5, $6)
+                RETURNING id"#,
+            kind as BanKind, ip, value, reason, created_by, expires_at)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(r.id)
+    }
+
+    async fn remove_ban(&self, id: i64) -> ForumResult<()> {
+        sqlx::query!(r#"DELETE FROM forum.bans WHERE id = $1"#, id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn record_ip(&self, user_id: Uuid, ip: IpAddr, registration: bool) -> ForumResult<()> {
+        sqlx::query!(
+            r#"UPDATE forum.users
+                SET last_login_ip = $2::text::inet,
+                    registration_ip = CASE WHEN $3 THEN $2::text::inet ELSE registration_ip END
+                WHERE id = $1"#,
+            user_id, ip.to_string(), registration)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn shared_ip_users(&self, user_id: Uuid) -> ForumResult<Vec<SharedIpUser>> {
+        let r = sqlx::query_as!(SharedIpUser,
+            r#"SELECT o.id, o.name, host(o.registration_ip) as registration_ip, host(o.last_login_ip) as last_login_ip,
+                    o.banned_until
+                FROM forum.users u
+                INNER JOIN forum.users o ON o.id <> u.id
+                    AND (o.registration_ip IN (u.registration_ip, u.last_login_ip)
+                        OR o.last_login_ip IN (u.registration_ip, u.last_login_ip))
+                WHERE u.id = $1
+                ORDER BY o.name"#, user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+}
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sqlx::postgres::PgListener;
+use tokio::sync::broadcast;
+use tracing::{error, warn};
+
+use crate::{error::{ForumError, ForumResult}, models::ForumEvent};
+
+/// NOTIFY channel shared by every backend instance
+pub const EVENT_CHANNEL: &str = "forum_events";
+
+#[async_trait]
+pub trait EventExt {
+    async fn publish_event(&self, event: &ForumEvent) -> ForumResult<()>;
+    async fn listen_events(&self, tx: broadcast::Sender<ForumEvent>);
+}
+
+#[async_trait]
+impl EventExt for crate::db::DBClient {
+    /// Delivered through LISTEN, so instances only ever broadcast what the database sent them
+    async fn publish_event(&self, event: &ForumEvent) -> ForumResult<()> {
+        let payload = serde_json::to_string(event)
+            .map_err(|e| ForumError::ServerError(e.to_string()))?;
+        sqlx::query!(r#"SELECT pg_notify($1, $2)"#, EVENT_CHANNEL, payload)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Forwards notifications to `tx` forever, reconnecting when the connection drops
+    async fn listen_events(&self, tx: broadcast::Sender<ForumEvent>) {
+        loop {
+            let mut listener = match PgListener::connect_with(&self.pool).await {
+                Ok(l) => l,
+                Err(e) => {
+                    error!("Event listener connection failed: {}", e);
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+            if let Err(e) = listener.listen(EVENT_CHANNEL).await {
+                error!("LISTEN {} failed: {}", EVENT_CHANNEL, e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+
+            loop {
+                match listener.recv().await {
+                    Ok(n) => match serde_json::from_str::<ForumEvent>(n.payload()) {
+                        // no receivers just means nobody is watching
+                        Ok(event) => { let _ = tx.send(event); }
+                        Err(e) => warn!("Unknown forum event {}: {}", n.payload(), e),
+                    },
+                    Err(e) => {
+                        error!("Event listener failed: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{error::ForumResult, models::{FilterAction, SpamSettings, WordFilter}};
+
+/// Where content is posted, flood and duplicate checks only look at the same place
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContentSource {
+    /// Threads and posts
+    Forum,
+    Chat,
+    Message,
+}
+
+#[async_trait]
+pub trait FilterExt {
+    async fn get_word_filters(&self) -> ForumResult<Vec<WordFilter>>;
+    async fn save_word_filter(&self, id: Option<i32>, pattern: &str, is_regex: bool, action: FilterAction, replacement: &str) -> ForumResult<i32>;
+    async fn delete_word_filter(&self, id: i32) -> ForumResult<()>;
+    async fn get_spam_settings(&self) -> ForumResult<SpamSettings>;
+    async fn save_spam_settings(&self, settings: &SpamSettings) -> ForumResult<()>;
+    /// Time of the latest content of the user
+    async fn last_posted(&self, user_id: Uuid, source: ContentSource) -> ForumResult<Option<DateTime<Utc>>>;
+    /// Whether the user posted exactly this content within the last minutes, compares the Markdown source
+    async fn is_duplicate(&self, user_id: Uuid, source: ContentSource, content: &str, minutes: i32) -> ForumResult<bool>;
+}
+
+#[async_trait]
+impl FilterExt for crate::db::DBClient {
+    async fn get_word_filters(&self) -> ForumResult<Vec<WordFilter>> {
+        let r = sqlx::query_as!(WordFilter,
+            r#"SELECT id, pattern, is_regex, action as "action: FilterAction", replacement
+                FROM forum.word_filters ORDER BY id"#)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn save_word_filter(&self, id: Option<i32>, pattern: &str, is_regex: bool, action: FilterAction, replacement: &str) -> ForumResult<i32> {
+        struct Helper {
+            id: i32,
+        }
+
+        let r = match id {
+            Some(id) => sqlx::query_as!(Helper,
+                r#"UPDATE forum.word_filters SET pattern = $2, is_regex = $3, action = $4, replacement = $5
+                    WHERE id = $1
+                    RETURNING id"#, id, pattern, is_regex, action as FilterAction, replacement)
+                .fetch_one(&self.pool)
+                .await?,
+            None => sqlx::query_as!(Helper,
+                r#"INSERT INTO forum.word_filters (pattern, is_regex, action, replacement)
+                    VALUES ($1, $2, $3, $4)
+                    RETURNING id"#, pattern, is_regex, action as FilterAction, replacement)
+                .fetch_one(&self.pool)
+                .await?,
+        };
+        Ok(r.id)
+    }
+
+    async fn delete_word_filter(&self, id: i32) -> ForumResult<()> {
+        sqlx::query!(r#"DELETE FROM forum.word_filters WHERE id = $1"#, id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_spam_settings(&self) -> ForumResult<SpamSettings> {
+        let r = sqlx::query_as!(SpamSettings,
+            r#"SELECT max_links, new_user_posts, duplicate_minutes, flood_seconds FROM forum.spam_settings"#)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn save_spam_settings(&self, settings: &SpamSettings) -> ForumResult<()> {
+        sqlx::query!(
+            r#"INSERT INTO forum.spam_settings (id, max_links, new_user_posts, duplicate_minutes, flood_seconds)
+                VALUES (true, $1, $2, $3, $4)
+                ON CONFLICT (id) DO UPDATE
+                SET max_links = $1, new_user_posts = $2, duplicate_minutes = $3, flood_seconds = $4"#,
+            settings.max_links, settings.new_user_posts, settings.duplicate_minutes, settings.flood_seconds)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn last_posted(&self, user_id: Uuid, source: ContentSource) -> ForumResult<Option<DateTime<Utc>>> {
+        struct Helper {
+            last: Option<DateTime<Utc>>,
+        }
+
+        let r = match source {
+            ContentSource::Forum => sqlx::query_as!(Helper,
+                r#"SELECT GREATEST(
+                        (SELECT MAX(created_at) FROM forum.threads WHERE author = $1),
+                        (SELECT MAX(created_at) FROM forum.posts WHERE author = $1)) as last"#, user_id)
+                .fetch_one(&self.pool)
+                .await?,
+            ContentSource::Chat => sqlx::query_as!(Helper,
+                r#"SELECT MAX(added) as last FROM forum.chat_posts WHERE author = $1"#, user_id)
+                .fetch_one(&self.pool)
+                .await?,
+            ContentSource::Message => sqlx::query_as!(Helper,
+                r#"SELECT MAX(created_at) as last FROM forum.private_messages WHERE author = $1"#, user_id)
+                .fetch_one(&self.pool)
+                .await?,
+        };
+        Ok(r.last)
+    }
+
+    async fn is_duplicate(&self, user_id: Uuid, source: ContentSource, content: &str, minutes: i32) -> ForumResult<bool> {
+        struct Helper {
+            duplicate: Option<bool>,
+        }
+
+        let r = match source {
+            ContentSource::Forum => sqlx::query_as!(Helper,
+                r#"SELECT EXISTS (SELECT 1 FROM forum.threads
+                        WHERE author = $1 AND source = $2 AND created_at > NOW() - make_interval(mins => $3))
+                    OR EXISTS (SELECT 1 FROM forum.posts
+                        WHERE author = $1 AND source = $2 AND created_at > NOW() - make_interval(mins => $3)) as duplicate"#,
+                user_id, content, minutes)
+                .fetch_one(&self.pool)
+                .await?,
+            ContentSource::Chat => sqlx::query_as!(Helper,
+                r#"SELECT EXISTS (SELECT 1 FROM forum.chat_posts
+                        WHERE author = $1 AND content = $2 AND added > NOW() - make_interval(mins => $3)) as duplicate"#,
+                user_id, content, minutes)
+                .fetch_one(&self.pool)
+                .await?,
+            ContentSource::Message => sqlx::query_as!(Helper,
+                r#"SELECT EXISTS (SELECT 1 FROM forum.private_messages
+                        WHERE author = $1 AND source = $2 AND created_at > NOW() - make_interval(mins => $3)) as duplicate"#,
+                user_id, content, minutes)
+                .fetch_one(&self.pool)
+                .await?,
+        };
+        Ok(r.duplicate.unwrap_or(false))
+    }
+}
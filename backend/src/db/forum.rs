@@ -1,73 +1,205 @@
 use async_trait::async_trait;
 use uuid::Uuid;
 
-use crate::{error::ForumResult, models::{ChatPost, Post, Section, Thread, UserRole}};
+use crate::{dto::forum::{SearchDto, SearchResultDto}, error::{ForumError, ForumResult}, models::{ChatPost, Post, PostReaction, Revision, ThreadedPost, ReactionCount, ReactionKind, Section, SectionModerator, Thread, UserRole}};
 
 #[async_trait]
 pub trait ForumExt {
-    async fn create_thread(&self, user: Uuid, section: i64, title: &str, content: &str, hash_tags: &[String]) -> ForumResult<()>;
-    async fn delete_thread(&self, thread_id: i64) -> ForumResult<()>;
-    async fn update_thread(&self, thread_id: i64, title: &str, content: &str) -> ForumResult<()>;
+    /// `pending` threads wait in the approval queue
+    /// `content` is the HTML rendered from the Markdown `source`
+    #[allow(clippy::too_many_arguments)]
+    async fn create_thread(&self, user: Uuid, section: i64, title: &str, content: &str, source: &str, hash_tags: &[String], pending: bool) -> ForumResult<i64>;
+    /// Soft deletion, the thread stays visible to moderators until it is purged
+    async fn delete_thread(&self, thread_id: i64, by: Uuid, reason: Option<&str>) -> ForumResult<()>;
+    async fn restore_thread(&self, thread_id: i64) -> ForumResult<()>;
+    /// Keeps the previous title and content as a revision
+    async fn update_thread(&self, thread_id: i64, title: &str, content: &str, source: &str, editor: Uuid, reason: Option<&str>) -> ForumResult<()>;
     async fn lock_thread(&self, thread_id: i64, locked: bool) -> ForumResult<()>;
+    async fn sticky_thread(&self, thread_id: i64, sticky: bool, announcement: bool, until: Option<chrono::DateTime<chrono::Utc>>) -> ForumResult<()>;
+    async fn move_thread(&self, thread_id: i64, section: i64, stub: bool) -> ForumResult<()>;
+    async fn merge_threads(&self, source: i64, target: i64) -> ForumResult<()>;
+    async fn split_thread(&self, thread_id: i64, post_ids: &[i64], title: &str, section: i64) -> ForumResult<i64>;
 
-    async fn create_section(&self, name: &str, description: &str, allowed_for: &[UserRole]) -> ForumResult<()>;
-    async fn get_sections(&self, user: Option<Uuid>) -> ForumResult<Vec<Section>>;
+    async fn create_section(&self, name: &str, description: &str, allowed_for: &[UserRole], moderated: bool) -> ForumResult<i64>;
+    async fn set_section_moderated(&self, s_id: i64, moderated: bool) -> ForumResult<()>;
+    async fn get_sections(&self, user: Option<Uuid>, role: Option<UserRole>) -> ForumResult<Vec<Section>>;
     async fn delete_section(&self, s_id: i32) -> ForumResult<()>;
+    async fn section_visible(&self, s_id: i64, role: Option<UserRole>) -> ForumResult<bool>;
+    async fn add_upload(&self, filename: &str, uploader: Uuid, thread_id: i64) -> ForumResult<()>;
+    /// Uploads made for a thread follow the visibility of its section, other files are public
+    async fn upload_visible(&self, filename: &str, role: Option<UserRole>) -> ForumResult<bool>;
 
     async fn get_chat(&self, limit: usize) -> ForumResult<Vec<ChatPost>>;
-    async fn post_chat(&self, u_id: Uuid, content: &str) -> ForumResult<()>;
-    async fn delete_chat(&self, post_id: i32) -> ForumResult<()>;
+    async fn post_chat(&self, u_id: Uuid, content: &str) -> ForumResult<ChatPost>;
+    async fn delete_chat(&self, post_id: i32) -> ForumResult<bool>;
 
-    async fn get_section(&self, s_id: i64, page: i32, limit: usize) -> ForumResult<Vec<crate::dto::forum::ThreadListItemDto>>;
-    async fn get_thread(&self, t_id: i64, page: i32, limit: usize) -> ForumResult<Vec<Post>>;
+    /// Deleted threads are only listed for `staff`, pending ones for `staff` and their author
+    async fn get_section(&self, s_id: i64, page: i32, limit: usize, user: Option<Uuid>, staff: bool) -> ForumResult<Vec<crate::dto::forum::ThreadListItemDto>>;
+    /// Deleted posts are only returned for `staff`, pending ones for `staff` and their author
+    async fn get_thread(&self, t_id: i64, page: i32, limit: usize, threaded: bool, user: Option<Uuid>, staff: bool) -> ForumResult<Vec<ThreadedPost>>;
+    async fn get_announcements(&self, user: Option<Uuid>, role: Option<UserRole>) -> ForumResult<Vec<crate::dto::forum::ThreadListItemDto>>;
     async fn get_thread_info(&self, t_id: i32) -> ForumResult<Thread>;
     async fn get_thread_author(&self, t_id: i32) -> ForumResult<Uuid>;
     async fn get_thread_reply_count(&self, t_id: i32) -> ForumResult<i64>;
 
-    async fn add_post(&self, author: Uuid, th_id: i64, content: &str, post_id: Option<i64>) -> ForumResult<()>;
-    async fn update_post(&self, p_id: i64, content: &str) -> ForumResult<()>;
-    async fn delete_post(&self, post_id: i64) -> ForumResult<()>;
+    /// `pending` posts wait in the approval queue
+    async fn add_post(&self, author: Uuid, th_id: i64, content: &str, source: &str, post_id: Option<i64>, pending: bool) -> ForumResult<i64>;
+    /// Keeps the previous content as a revision
+    async fn update_post(&self, p_id: i64, content: &str, source: &str, editor: Uuid, reason: Option<&str>) -> ForumResult<()>;
+    async fn get_thread_revisions(&self, thread_id: i64) -> ForumResult<Vec<Revision>>;
+    async fn get_post_revisions(&self, post_id: i64) -> ForumResult<Vec<Revision>>;
+    async fn get_revision(&self, revision_id: i64) -> ForumResult<Revision>;
+    /// Soft deletion, the post stays visible to moderators until it is purged
+    async fn delete_post(&self, post_id: i64, by: Uuid, reason: Option<&str>) -> ForumResult<()>;
+    async fn restore_post(&self, post_id: i64) -> ForumResult<()>;
+    /// Whether the post or its thread is soft deleted
+    async fn post_deleted(&self, post_id: i64) -> ForumResult<bool>;
+    /// Removes threads and posts soft deleted more than `days` ago, returns how many of each
+    async fn purge_deleted(&self, days: i64) -> ForumResult<(u64, u64)>;
     async fn get_post_author(&self, t_id: i64) -> ForumResult<Option<Uuid>>;
+    async fn get_post_section(&self, post_id: i64) -> ForumResult<i64>;
+    async fn get_post_topic(&self, post_id: i64) -> ForumResult<i64>;
+    async fn get_post_source(&self, post_id: i64) -> ForumResult<String>;
     async fn posts_since(&self, post_id: i64) -> ForumResult<i64>;
+
+    async fn first_unread_post(&self, user: Uuid, t_id: i64) -> ForumResult<Option<(i64, i64)>>;
+    async fn mark_thread_read(&self, user: Uuid, t_id: i64, last_post: i64) -> ForumResult<()>;
+    async fn mark_section_read(&self, user: Uuid, s_id: i64) -> ForumResult<()>;
+    async fn mark_forum_read(&self, user: Uuid, role: UserRole) -> ForumResult<()>;
+
+    async fn save_mentions(&self, thread_id: i64, post_id: Option<i64>, users: &[Uuid]) -> ForumResult<()>;
+
+    async fn search(&self, query: &SearchDto, role: Option<UserRole>) -> ForumResult<Vec<SearchResultDto>>;
+
+    async fn get_reaction_kinds(&self) -> ForumResult<Vec<ReactionKind>>;
+    async fn save_reaction_kind(&self, name: &str, emoji: &str, position: Option<i32>) -> ForumResult<()>;
+    async fn delete_reaction_kind(&self, name: &str) -> ForumResult<()>;
+    async fn add_reaction(&self, post_id: i64, user: Uuid, kind: &str) -> ForumResult<()>;
+    async fn remove_reaction(&self, post_id: i64, user: Uuid, kind: &str) -> ForumResult<()>;
+    async fn get_reaction_counts(&self, post_ids: &[i64], user: Option<Uuid>) -> ForumResult<Vec<ReactionCount>>;
+    async fn get_post_reactions(&self, post_id: i64) -> ForumResult<Vec<PostReaction>>;
+}
+
+struct SectionRow {
+    id: i64,
+    name: String,
+    description: Option<String>,
+    unread: i64,
+    moderated: bool,
+}
+
+impl crate::db::DBClient {
+    /// Keeps `posts.likes` in sync with the number of reactions on the post
+    async fn refresh_likes(&self, post_id: i64) -> ForumResult<()> {
+        sqlx::query!(
+            r#"UPDATE forum.posts
+                SET likes = (SELECT COUNT(*) FROM forum.post_reactions WHERE post_id = $1)
+                WHERE id = $1"#, post_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Attaches the assigned moderators to each section row
+    async fn with_moderators(&self, rows: Vec<SectionRow>) -> ForumResult<Vec<Section>> {
+        struct Helper {
+            section: i64,
+            id: Uuid,
+            name: String,
+        }
+
+        let ids: Vec<i64> = rows.iter().map(|s| s.id).collect();
+        let moderators = sqlx::query_as!(Helper,
+            r#"SELECT sm.section, u.id, u.name
+                FROM forum.section_moderators sm
+                INNER JOIN forum.users u ON u.id = sm.user_id
+                WHERE sm.section = ANY($1)
+                ORDER BY u.name"#, &ids)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter()
+            .map(|s| Section {
+                moderators: moderators.iter()
+                    .filter(|m| m.section == s.id)
+                    .map(|m| SectionModerator { id: m.id, name: m.name.clone() })
+                    .collect(),
+                id: s.id,
+                name: s.name,
+                description: s.description,
+                new_posts: s.unread > 0,
+                unread: s.unread,
+                moderated: s.moderated,
+            })
+            .collect())
+    }
 }
 
 #[async_trait]
 impl ForumExt for crate::db::DBClient {
-    async fn create_thread(&self, user: Uuid, section: i64, title: &str, content: &str, hash_tags: &[String]) -> ForumResult<()> {
+    async fn create_thread(&self, user: Uuid, section: i64, title: &str, content: &str, source: &str, hash_tags: &[String], pending: bool) -> ForumResult<i64> {
         struct ParsingHelper {
             id: i64,
         }
 
-        let r = sqlx::query_as!(ParsingHelper, r#"INSERT INTO forum.threads(title,created_at,content,author,section,locked)
-            VALUES ($1,LOCALTIMESTAMP,$2,$3,$4,false)
+        let r = sqlx::query_as!(ParsingHelper, r#"INSERT INTO forum.threads(title,created_at,content,source,author,section,locked,pending,approved_at)
+            VALUES ($1,LOCALTIMESTAMP,$2,$3,$4,$5,false,$6,CASE WHEN $6 THEN NULL ELSE NOW() END)
             RETURNING id"#,
-            title, content, user, section)
+            title, content, source, user, section, pending)
             .fetch_one(&self.pool)
             .await?;
-            let _ = hash_tags.iter().map(async |t| {
-                sqlx::query!(r#"INSERT INTO forum.hashtags(tag, topic) VALUES($1, $2)"#, t, r.id)
-                    .execute(&self.pool)
-                    .await
-                    .map_err(|e| e)
-            });
+        for t in hash_tags {
+            sqlx::query!(r#"INSERT INTO forum.hashtags(tag, topic) VALUES($1, $2)"#, t, r.id)
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(r.id)
+    }
+
+    async fn delete_thread(&self, thread_id: i64, by: Uuid, reason: Option<&str>) -> ForumResult<()> {
+        let r = sqlx::query!(
+            r#"UPDATE forum.threads
+                SET deleted_at = NOW(), deleted_by = $2, delete_reason = $3, sticky = false, announcement = false
+                WHERE id = $1 AND deleted_at IS NULL"#, thread_id as i32, by, reason)
+            .execute(&self.pool)
+            .await?;
+        if r.rows_affected() == 0 {
+            return Err(ForumError::NotFound);
+        }
         Ok(())
     }
 
-    async fn delete_thread(&self, thread_id: i64) -> ForumResult<()> {
-        sqlx::query!(r#"DELETE FROM forum.threads WHERE id = $1"#, thread_id as i32)
+    async fn restore_thread(&self, thread_id: i64) -> ForumResult<()> {
+        let r = sqlx::query!(
+            r#"UPDATE forum.threads
+                SET deleted_at = NULL, deleted_by = NULL, delete_reason = NULL
+                WHERE id = $1 AND deleted_at IS NOT NULL"#, thread_id as i32)
             .execute(&self.pool)
             .await?;
+        if r.rows_affected() == 0 {
+            return Err(ForumError::NotFound);
+        }
         Ok(())
     }
 
-    async fn update_thread(&self, thread_id: i64, title: &str, content: &str) -> ForumResult<()> {
+    async fn update_thread(&self, thread_id: i64, title: &str, content: &str, source: &str, editor: Uuid, reason: Option<&str>) -> ForumResult<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(r#"INSERT INTO forum.revisions (thread_id, editor, title, content, reason)
+                SELECT id, $2, title, source, $3 FROM forum.threads WHERE id = $1"#,
+            thread_id as i32, editor, reason)
+            .execute(&mut *tx)
+            .await?;
         sqlx::query!(r#"UPDATE forum.threads
             SET
                 title = $2,
-                content = $3
-            WHERE id = $1"#, thread_id as i32, title, content)
-            .execute(&self.pool)
+                content = $3,
+                source = $4,
+                modified_at = NOW(),
+                modified_by = $5
+            WHERE id = $1"#, thread_id as i32, title, content, source, editor)
+            .execute(&mut *tx)
             .await?;
+        tx.commit().await?;
         Ok(())
     }
 
@@ -78,75 +210,282 @@ impl ForumExt for crate::db::DBClient {
             WHERE id = $1
             "#,
             thread_id as i32, locked)
-            .fetch_one(&self.pool)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn sticky_thread(&self, thread_id: i64, sticky: bool, announcement: bool, until: Option<chrono::DateTime<chrono::Utc>>) -> ForumResult<()> {
+        let r = sqlx::query!(
+            r#"UPDATE forum.threads
+                SET sticky = $2, announcement = $3, sticky_until = $4
+                WHERE id = $1 AND moved_to IS NULL"#,
+            thread_id as i32, sticky, announcement, until)
+            .execute(&self.pool)
             .await?;
+        if r.rows_affected() == 0 {
+            return Err(ForumError::NotFound);
+        }
+        Ok(())
+    }
+
+    /// The stub keeps the old id out of the way, the thread itself keeps its id and posts
+    async fn move_thread(&self, thread_id: i64, section: i64, stub: bool) -> ForumResult<()> {
+        let mut tx = self.pool.begin().await?;
+        if stub {
+            sqlx::query!(
+                r#"INSERT INTO forum.threads (title, created_at, content, author, section, locked, moved_to)
+                    SELECT title, created_at, '', author, section, true, id
+                    FROM forum.threads
+                    WHERE id = $1"#, thread_id as i32)
+                .execute(&mut *tx)
+                .await?;
+        }
+        let r = sqlx::query!(
+            r#"UPDATE forum.threads SET section = $2 WHERE id = $1 AND moved_to IS NULL"#,
+            thread_id as i32, section)
+            .execute(&mut *tx)
+            .await?;
+        if r.rows_affected() == 0 {
+            return Err(ForumError::NotFound);
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// The opening post of `source` becomes a reply in `target`, the posts keep their
+    /// timestamps so they are shown in chronological order among the posts of `target`
+    async fn merge_threads(&self, source: i64, target: i64) -> ForumResult<()> {
+        struct Helper {
+            id: i64,
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let opening = sqlx::query_as!(Helper,
+            r#"INSERT INTO forum.posts (content, source, author, topic, created_at)
+                SELECT s.content, s.source, s.author, t.id, s.created_at
+                FROM forum.threads s, forum.threads t
+                WHERE s.id = $1 AND t.id = $2
+                AND s.moved_to IS NULL AND t.moved_to IS NULL
+                RETURNING id"#, source as i32, target as i32)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(ForumError::NotFound)?;
+
+        // Replies to the old opening post now answer its copy
+        sqlx::query!(
+            r#"UPDATE forum.posts
+                SET topic = $2, comments = COALESCE(comments, $3)
+                WHERE topic = $1"#, source, target, opening.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"UPDATE forum.mentions
+                SET thread_id = $2, post_id = COALESCE(post_id, $3)
+                WHERE thread_id = $1"#, source, target, opening.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"UPDATE forum.notifications
+                SET thread_id = $2, post_id = COALESCE(post_id, $3)
+                WHERE thread_id = $1"#, source, target, opening.id)
+            .execute(&mut *tx)
+            .await?;
+        // Edits of the old opening post become edits of its copy
+        sqlx::query!(
+            r#"UPDATE forum.revisions
+                SET thread_id = NULL, post_id = $2
+                WHERE thread_id = $1"#, source as i32, opening.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(r#"UPDATE forum.uploads SET thread_id = $2 WHERE thread_id = $1"#, source, target)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"INSERT INTO forum.thread_subscriptions (user_id, thread_id, created_at)
+                SELECT user_id, $2, created_at FROM forum.thread_subscriptions WHERE thread_id = $1
+                ON CONFLICT DO NOTHING"#, source, target)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"INSERT INTO forum.hashtags (tag, topic)
+                SELECT tag, $2 FROM forum.hashtags WHERE topic = $1
+                ON CONFLICT DO NOTHING"#, source, target)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(r#"DELETE FROM forum.thread_subscriptions WHERE thread_id = $1"#, source)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(r#"DELETE FROM forum.thread_reads WHERE thread_id = $1"#, source)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(r#"DELETE FROM forum.hashtags WHERE topic = $1"#, source)
+            .execute(&mut *tx)
+            .await?;
+
+        // The emptied source stays behind as a redirect, older redirects follow it
+        sqlx::query!(
+            r#"UPDATE forum.threads
+                SET content = '', source = '', locked = true, sticky = false, announcement = false, moved_to = $2
+                WHERE id = $1 OR moved_to = $1"#, source as i32, target as i32)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
         Ok(())
     }
 
-    async fn create_section(&self, name: &str, description: &str, allowed_for: &[UserRole]) -> ForumResult<()> {
+    /// The earliest selected post opens the new thread and is removed as a post,
+    /// together with its reactions. Replies whose parent ends up in the other thread become top level.
+    /// Deleted and pending posts can't be split off, the new thread would show them
+    async fn split_thread(&self, thread_id: i64, post_ids: &[i64], title: &str, section: i64) -> ForumResult<i64> {
+        struct PostHelper {
+            id: i64,
+            content: String,
+            source: String,
+            author: Option<Uuid>,
+            created_at: chrono::DateTime<chrono::Utc>,
+            hidden: Option<bool>,
+        }
+        struct Helper {
+            id: i64,
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let posts = sqlx::query_as!(PostHelper,
+            r#"SELECT id, content, source, author, created_at, (deleted_at IS NOT NULL OR pending) as hidden
+                FROM forum.posts
+                WHERE topic = $1 AND id = ANY($2)
+                ORDER BY created_at, id
+                FOR UPDATE"#, thread_id, post_ids)
+            .fetch_all(&mut *tx)
+            .await?;
+        let mut ids: Vec<i64> = post_ids.to_vec();
+        ids.sort_unstable();
+        ids.dedup();
+        if posts.len() != ids.len() {
+            return Err(ForumError::Forum("Posts don't belong to this thread".to_string()));
+        }
+        if posts.iter().any(|p| p.hidden.unwrap_or(false)) {
+            return Err(ForumError::Forum("Deleted posts and posts awaiting approval can't be split off".to_string()));
+        }
+        let first = &posts[0];
+        let author = first.author
+            .ok_or(ForumError::Forum("The first post has no author anymore".to_string()))?;
+        let rest: Vec<i64> = posts[1..].iter().map(|p| p.id).collect();
+
+        let thread = sqlx::query_as!(Helper,
+            r#"INSERT INTO forum.threads (title, created_at, content, source, author, section)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                RETURNING id::int8 as "id!""#,
+            title, first.created_at, first.content, first.source, author, section)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        sqlx::query!(
+            r#"UPDATE forum.mentions SET thread_id = $2, post_id = NULL WHERE post_id = $1"#,
+            first.id, thread.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"UPDATE forum.notifications SET thread_id = $2, post_id = NULL WHERE post_id = $1"#,
+            first.id, thread.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"UPDATE forum.mentions SET thread_id = $2 WHERE post_id = ANY($1)"#,
+            &rest, thread.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"UPDATE forum.notifications SET thread_id = $2 WHERE post_id = ANY($1)"#,
+            &rest, thread.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"UPDATE forum.posts SET topic = $2 WHERE id = ANY($1)"#,
+            &rest, thread.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"UPDATE forum.posts p
+                SET comments = NULL
+                WHERE p.topic IN ($1, $2)
+                AND NOT EXISTS (SELECT 1 FROM forum.posts pp WHERE pp.id = p.comments AND pp.topic = p.topic AND pp.id <> $3)"#,
+            thread_id, thread.id, first.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(r#"DELETE FROM forum.posts WHERE id = $1"#, first.id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(thread.id)
+    }
+
+    async fn create_section(&self, name: &str, description: &str, allowed_for: &[UserRole], moderated: bool) -> ForumResult<i64> {
         struct Helper {
             id: i64,
         }
 
         let r = sqlx::query_as!(Helper,
             r#" INSERT INTO forum.sections
-                    (name, description)
-                VALUES($1, $2)
-                RETURNING id"#, name, description)
+                    (name, description, moderated)
+                VALUES($1, $2, $3)
+                RETURNING id"#, name, description, moderated)
             .fetch_one(&self.pool)
             .await?;
 
-        let _ = allowed_for.iter().map(async |rl| {
+        for rl in allowed_for {
             sqlx::query!(r#"INSERT INTO forum.sections_allowed
                                 (section, role)
                             VALUES($1, $2)"#, r.id, *rl as UserRole)
                 .execute(&self.pool)
-                .await
-                .map_err(|e| e)
-        });
+                .await?;
+        }
 
-        Ok(())
+        Ok(r.id)
     }
 
-    async fn get_sections(&self, user: Option<Uuid>) -> ForumResult<Vec<Section>> {
+    async fn get_sections(&self, user: Option<Uuid>, role: Option<UserRole>) -> ForumResult<Vec<Section>> {
         match user {
             Some(user_id) => {
-                let r = sqlx::query_as!(Section,
+                let r = sqlx::query_as!(SectionRow,
                     r#" SELECT s.id, s.name, s.description,
-                        COALESCE(
-                            CASE
-                                WHEN u.last_online IS NOT NULL AND EXISTS (
-                                    SELECT 1
-                                    FROM forum.posts p
-                                    INNER JOIN forum.threads t ON p.topic = t.id
-                                    WHERE t.section = s.id
-                                    AND p.created_at > u.last_online
-                                ) THEN true
-                                ELSE false
-                            END, false
-                        ) as "new_posts!: bool"
+                        (SELECT COUNT(*) FROM forum.threads t
+                            WHERE t.section = s.id AND t.deleted_at IS NULL AND forum.thread_unread($1, t.id)) as "unread!",
+                        s.moderated
                         FROM forum.sections s
-                        CROSS JOIN forum.users u
-                        WHERE u.id = $1
-                    "#, user_id)
+                        WHERE forum.section_visible(s.id, $2)
+                    "#, user_id, role as Option<UserRole>)
                     .fetch_all(&self.pool)
                     .await?;
-                Ok(r)
+                self.with_moderators(r).await
             }
             None => {
-                // For anonymous users, return false for new_posts for all sections
-                let r = sqlx::query_as!(Section,
+                // Anonymous users have nothing to track
+                let r = sqlx::query_as!(SectionRow,
                     r#" SELECT s.id, s.name, s.description,
-                        false as "new_posts!: bool"
+                        0::int8 as "unread!", s.moderated
                         FROM forum.sections s
-                    "#)
+                        WHERE forum.section_visible(s.id, $1)
+                    "#, role as Option<UserRole>)
                     .fetch_all(&self.pool)
                     .await?;
-                Ok(r)
+                self.with_moderators(r).await
             }
         }
     }
 
+    async fn set_section_moderated(&self, s_id: i64, moderated: bool) -> ForumResult<()> {
+        let r = sqlx::query!(
+            r#"UPDATE forum.sections SET moderated = $2 WHERE id = $1"#, s_id as i32, moderated)
+            .execute(&self.pool)
+            .await?;
+        if r.rows_affected() == 0 {
+            return Err(ForumError::NotFound);
+        }
+        Ok(())
+    }
+
     async fn delete_section(&self, s_id: i32) -> ForumResult<()> {
         sqlx::query!(
             r#"DELETE FROM forum.sections
@@ -157,6 +496,47 @@ impl ForumExt for crate::db::DBClient {
         Ok(())
     }
 
+    async fn section_visible(&self, s_id: i64, role: Option<UserRole>) -> ForumResult<bool> {
+        struct Helper {
+            visible: Option<bool>,
+        }
+
+        let r = sqlx::query_as!(Helper,
+            r#" SELECT forum.section_visible(id, $2) as visible
+                FROM forum.sections
+                WHERE id = $1"#, s_id as i32, role as Option<UserRole>)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(r.and_then(|h| h.visible).unwrap_or(false))
+    }
+
+    async fn add_upload(&self, filename: &str, uploader: Uuid, thread_id: i64) -> ForumResult<()> {
+        sqlx::query!(
+            r#"INSERT INTO forum.uploads (filename, uploader, thread_id) VALUES ($1, $2, $3)"#,
+            filename, uploader, thread_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn upload_visible(&self, filename: &str, role: Option<UserRole>) -> ForumResult<bool> {
+        struct Helper {
+            visible: Option<bool>,
+        }
+
+        let r = sqlx::query_as!(Helper,
+            r#" SELECT forum.section_visible(t.section, $2) as visible
+                FROM forum.uploads up
+                INNER JOIN forum.threads t ON t.id = up.thread_id
+                WHERE up.filename = $1"#,
+            filename, role as Option<UserRole>)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(r.is_none_or(|h| h.visible.unwrap_or(false)))
+    }
+
     async fn get_chat(&self, limit: usize) -> ForumResult<Vec<ChatPost>> {
         let limit = limit as i64;
         let r = sqlx::query_as!(ChatPost,
@@ -169,60 +549,162 @@ impl ForumExt for crate::db::DBClient {
         Ok(r)
     }
 
-    async fn post_chat(&self, u_id: Uuid, content: &str) -> ForumResult<()> {
-        sqlx::query!(
-            r#" INSERT INTO forum.chat_posts(added, author, content)
-                VALUES (LOCALTIMESTAMP, $1, $2)"#, u_id, content)
-            .execute(&self.pool)
+    async fn post_chat(&self, u_id: Uuid, content: &str) -> ForumResult<ChatPost> {
+        let r = sqlx::query_as!(ChatPost,
+            r#" WITH p AS (
+                    INSERT INTO forum.chat_posts(author, content)
+                    VALUES ($1, $2)
+                    RETURNING id, added, author, content
+                )
+                SELECT p.id, p.added, p.author, u.name as author_name, p.content
+                FROM p INNER JOIN forum.users u ON u.id = p.author"#, u_id, content)
+            .fetch_one(&self.pool)
             .await?;
 
-        Ok(())
+        Ok(r)
     }
 
-    async fn delete_chat(&self, post_id: i32) -> ForumResult<()> {
-        sqlx::query!(
+    /// False when there was nothing to delete
+    async fn delete_chat(&self, post_id: i32) -> ForumResult<bool> {
+        let r = sqlx::query!(
             r#"DELETE FROM forum.chat_posts WHERE id = $1"#, post_id)
             .execute(&self.pool)
             .await?;
 
-        Ok(())
+        Ok(r.rows_affected() > 0)
     }
 
-    async fn get_section(&self, s_id: i64, page: i32, limit: usize) -> ForumResult<Vec<crate::dto::forum::ThreadListItemDto>> {
+    async fn get_section(&self, s_id: i64, page: i32, limit: usize, user: Option<Uuid>, staff: bool) -> ForumResult<Vec<crate::dto::forum::ThreadListItemDto>> {
         let offset = (page - 1) as usize * limit;
         let limit = limit as i64;
         let offset = offset as i64;
 
         let r = sqlx::query_as!(crate::dto::forum::ThreadListItemDto,
-            r#" SELECT t.id,t.title,t.created_at,t.content,t.author,u.name author_name,t.section as section_id,t.locked,t.sticky FROM forum.threads t
+            r#" SELECT t.id,t.title,t.created_at,t.content,t.author,u.name author_name,t.section as section_id,t.locked,
+                    (t.sticky AND (t.sticky_until IS NULL OR t.sticky_until > NOW())) as "sticky!",
+                    (t.announcement AND (t.sticky_until IS NULL OR t.sticky_until > NOW())) as "announcement!",
+                    t.sticky_until,
+                    forum.thread_unread($4, t.id) as "unread!",
+                    forum.unread_posts($4, t.id) as "unread_posts!",
+                    t.moved_to::int8 as moved_to, t.deleted_at, t.pending
+                FROM forum.threads t
                 INNER JOIN forum.users u ON t.author = u.id
                 WHERE section = $1
-                ORDER BY t.id DESC
-                LIMIT $2 OFFSET $3"#, s_id, limit, offset)
+                AND (t.deleted_at IS NULL OR $5)
+                AND (NOT t.pending OR $5 OR t.author = $4)
+                -- announcements are listed on their own
+                AND NOT (t.announcement AND (t.sticky_until IS NULL OR t.sticky_until > NOW()))
+                ORDER BY (t.sticky AND (t.sticky_until IS NULL OR t.sticky_until > NOW())) DESC, t.id DESC
+                LIMIT $2 OFFSET $3"#, s_id, limit, offset, user, staff)
             .fetch_all(&self.pool)
             .await?;
         Ok(r)
     }
 
-    async fn get_thread(&self, t_id: i64, page: i32, limit: usize) -> ForumResult<Vec<Post>> {
+    async fn get_announcements(&self, user: Option<Uuid>, role: Option<UserRole>) -> ForumResult<Vec<crate::dto::forum::ThreadListItemDto>> {
+        let r = sqlx::query_as!(crate::dto::forum::ThreadListItemDto,
+            r#" SELECT t.id,t.title,t.created_at,t.content,t.author,u.name author_name,t.section as section_id,t.locked,
+                    t.sticky as "sticky!", t.announcement as "announcement!", t.sticky_until,
+                    forum.thread_unread($1, t.id) as "unread!",
+                    forum.unread_posts($1, t.id) as "unread_posts!",
+                    t.moved_to::int8 as moved_to, t.deleted_at, t.pending
+                FROM forum.threads t
+                INNER JOIN forum.users u ON t.author = u.id
+                WHERE t.announcement AND (t.sticky_until IS NULL OR t.sticky_until > NOW())
+                AND t.deleted_at IS NULL AND NOT t.pending
+                AND forum.section_visible(t.section, $2)
+                ORDER BY t.id DESC"#, user, role as Option<UserRole>)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn get_thread(&self, t_id: i64, page: i32, limit: usize, threaded: bool, user: Option<Uuid>, staff: bool) -> ForumResult<Vec<ThreadedPost>> {
+        struct Helper {
+            id: i64,
+            content: String,
+            source: String,
+            author: Option<Uuid>,
+            author_name: Option<String>,
+            topic: i64,
+            comments: Option<i64>,
+            created_at: chrono::DateTime<chrono::Utc>,
+            modified_at: Option<chrono::DateTime<chrono::Utc>>,
+            modified_by_name: Option<String>,
+            deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+            deleted_by_name: Option<String>,
+            delete_reason: Option<String>,
+            likes: i32,
+            pending: bool,
+            depth: i32,
+        }
+
         let offset = (page - 1) as usize * limit;
         let limit = limit as i64;
         let offset = offset as i64;
-        let r = sqlx::query_as!(Post,
-            r#" SELECT p.id,p.content,p.author,u.name as author_name,p.topic,p.comments,p.created_at,p.modified_at,p.likes 
-                FROM forum.posts p
+        // Replies whose parent is missing from this thread, or hidden as deleted or pending, are shown as top level posts
+        let r = sqlx::query_as!(Helper,
+            r#" WITH RECURSIVE tree AS (
+                    SELECT p.id, 0 AS depth, ARRAY[p.id] AS path
+                    FROM forum.posts p
+                    WHERE p.topic = $1 AND (p.deleted_at IS NULL OR $5)
+                    AND (NOT p.pending OR $5 OR p.author = $6)
+                    AND NOT EXISTS (SELECT 1 FROM forum.posts pp WHERE pp.id = p.comments AND pp.topic = $1
+                        AND (pp.deleted_at IS NULL OR $5) AND (NOT pp.pending OR $5 OR pp.author = $6))
+                    UNION ALL
+                    SELECT c.id, t.depth + 1, t.path || c.id
+                    FROM forum.posts c
+                    INNER JOIN tree t ON c.comments = t.id
+                    WHERE c.topic = $1 AND (c.deleted_at IS NULL OR $5)
+                    AND (NOT c.pending OR $5 OR c.author = $6)
+                )
+                SELECT p.id,p.content,p.source,p.author,u.name as author_name,p.topic,p.comments,p.created_at,p.modified_at,
+                    (SELECT m.name FROM forum.users m WHERE m.id = p.modified_by) as modified_by_name,
+                    p.deleted_at, (SELECT d.name FROM forum.users d WHERE d.id = p.deleted_by) as deleted_by_name,
+                    p.delete_reason, p.likes, p.pending,
+                    t.depth as "depth!"
+                FROM tree t
+                INNER JOIN forum.posts p ON p.id = t.id
                 LEFT OUTER JOIN forum.users u ON u.id = p.author
-                WHERE topic = $1
-                ORDER BY p.created_at ASC
-                LIMIT $2 OFFSET $3"#, t_id, limit, offset)
+                ORDER BY CASE WHEN $4 THEN t.path END, p.created_at ASC
+                LIMIT $2 OFFSET $3"#, t_id, limit, offset, threaded, staff, user)
             .fetch_all(&self.pool)
             .await?;
-        Ok(r)
+
+        Ok(r.into_iter()
+            .map(|h| ThreadedPost {
+                post: Post {
+                    id: h.id,
+                    content: h.content,
+                    source: h.source,
+                    author: h.author,
+                    author_name: h.author_name,
+                    topic: h.topic,
+                    comments: h.comments,
+                    created_at: h.created_at,
+                    modified_at: h.modified_at,
+                    modified_by_name: h.modified_by_name,
+                    deleted_at: h.deleted_at,
+                    deleted_by_name: h.deleted_by_name,
+                    delete_reason: h.delete_reason,
+                    likes: h.likes,
+                    pending: h.pending,
+                },
+                depth: h.depth,
+            })
+            .collect())
     }
 
     async fn get_thread_info(&self, t_id: i32) -> ForumResult<Thread> {
         let r = sqlx::query_as!(Thread,
-            r#" SELECT * FROM forum.threads WHERE id = $1"#, t_id)
+            r#" SELECT id, title, created_at, content, source, author, section, locked,
+                    (sticky AND (sticky_until IS NULL OR sticky_until > NOW())) as "sticky!",
+                    (announcement AND (sticky_until IS NULL OR sticky_until > NOW())) as "announcement!",
+                    sticky_until, moved_to::int8 as moved_to, modified_at,
+                    (SELECT m.name FROM forum.users m WHERE m.id = modified_by) as modified_by_name,
+                    deleted_at, (SELECT d.name FROM forum.users d WHERE d.id = deleted_by) as deleted_by_name,
+                    delete_reason, pending
+                FROM forum.threads WHERE id = $1"#, t_id)
             .fetch_one(&self.pool)
             .await?;
         Ok(r)
@@ -241,6 +723,46 @@ impl ForumExt for crate::db::DBClient {
         Ok(res.author)
     }
 
+    async fn get_post_topic(&self, post_id: i64) -> ForumResult<i64> {
+        struct Helper {
+            topic: i64,
+        }
+
+        let res = sqlx::query_as!(Helper,
+            r#"SELECT topic FROM forum.posts WHERE id = $1"#, post_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(res.topic)
+    }
+
+    async fn get_post_source(&self, post_id: i64) -> ForumResult<String> {
+        struct Helper {
+            source: String,
+        }
+
+        let res = sqlx::query_as!(Helper,
+            r#"SELECT source FROM forum.posts WHERE id = $1"#, post_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(res.source)
+    }
+
+    async fn get_post_section(&self, post_id: i64) -> ForumResult<i64> {
+        struct Helper {
+            section: i64,
+        }
+
+        let res = sqlx::query_as!(Helper,
+            r#"SELECT t.section FROM forum.posts p
+                INNER JOIN forum.threads t ON t.id = p.topic
+                WHERE p.id = $1"#, post_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(res.section)
+    }
     async fn get_post_author(&self, t_id: i64) -> ForumResult<Option<Uuid>> {
         struct Helper {
             author: Option<Uuid>,
@@ -268,35 +790,160 @@ impl ForumExt for crate::db::DBClient {
         Ok(res.cnt.unwrap_or(-1))
     }
 
-    async fn add_post(&self, author: Uuid, th_id: i64, content: &str, post_id: Option<i64>) -> ForumResult<()> {
-        sqlx::query!(
-            r#" INSERT INTO forum.posts(content, author, topic, comments, created_at)
-                VALUES ($1, $2, $3, $4, LOCALTIMESTAMP)"#, content, author, th_id, post_id)
-            .execute(&self.pool)
+    async fn add_post(&self, author: Uuid, th_id: i64, content: &str, source: &str, post_id: Option<i64>, pending: bool) -> ForumResult<i64> {
+        struct Helper {
+            id: i64,
+        }
+
+        let r = sqlx::query_as!(Helper,
+            r#" INSERT INTO forum.posts(content, source, author, topic, comments, created_at, pending, approved_at)
+                VALUES ($1, $2, $3, $4, $5, LOCALTIMESTAMP, $6, CASE WHEN $6 THEN NULL ELSE NOW() END)
+                RETURNING id"#, content, source, author, th_id, post_id, pending)
+            .fetch_one(&self.pool)
             .await?;
-        Ok(())
+        Ok(r.id)
     }
 
-    async fn update_post(&self, p_id: i64, content: &str) -> ForumResult<()> {
+    async fn update_post(&self, p_id: i64, content: &str, source: &str, editor: Uuid, reason: Option<&str>) -> ForumResult<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#" INSERT INTO forum.revisions (post_id, editor, content, reason)
+                SELECT id, $2, source, $3 FROM forum.posts WHERE id = $1"#, p_id, editor, reason)
+            .execute(&mut *tx)
+            .await?;
         sqlx::query!(
             r#" UPDATE forum.posts
-                SET content = $1
-                WHERE id = $2"#, content, p_id)
-            .execute(&self.pool)
+                SET content = $1, source = $2, modified_at = NOW(), modified_by = $4
+                WHERE id = $3"#, content, source, p_id, editor)
+            .execute(&mut *tx)
             .await?;
+        tx.commit().await?;
 
         Ok(())
     }
 
-    async fn delete_post(&self, post_id: i64) -> ForumResult<()> {
-        sqlx::query!(
-            r#" DELETE FROM forum.posts
-                WHERE id = $1"#, post_id)
+    async fn get_thread_revisions(&self, thread_id: i64) -> ForumResult<Vec<Revision>> {
+        let r = sqlx::query_as!(Revision,
+            r#" SELECT r.id, r.thread_id::int8 as thread_id, r.post_id, r.editor, u.name as "editor_name?",
+                    r.edited_at, r.title, r.content, r.reason
+                FROM forum.revisions r
+                LEFT JOIN forum.users u ON u.id = r.editor
+                WHERE r.thread_id = $1
+                ORDER BY r.edited_at DESC, r.id DESC"#, thread_id as i32)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn get_post_revisions(&self, post_id: i64) -> ForumResult<Vec<Revision>> {
+        let r = sqlx::query_as!(Revision,
+            r#" SELECT r.id, r.thread_id::int8 as thread_id, r.post_id, r.editor, u.name as "editor_name?",
+                    r.edited_at, r.title, r.content, r.reason
+                FROM forum.revisions r
+                LEFT JOIN forum.users u ON u.id = r.editor
+                WHERE r.post_id = $1
+                ORDER BY r.edited_at DESC, r.id DESC"#, post_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn get_revision(&self, revision_id: i64) -> ForumResult<Revision> {
+        let r = sqlx::query_as!(Revision,
+            r#" SELECT r.id, r.thread_id::int8 as thread_id, r.post_id, r.editor, u.name as "editor_name?",
+                    r.edited_at, r.title, r.content, r.reason
+                FROM forum.revisions r
+                LEFT JOIN forum.users u ON u.id = r.editor
+                WHERE r.id = $1"#, revision_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn delete_post(&self, post_id: i64, by: Uuid, reason: Option<&str>) -> ForumResult<()> {
+        let r = sqlx::query!(
+            r#" UPDATE forum.posts
+                SET deleted_at = NOW(), deleted_by = $2, delete_reason = $3
+                WHERE id = $1 AND deleted_at IS NULL"#, post_id, by, reason)
             .execute(&self.pool)
             .await?;
+        if r.rows_affected() == 0 {
+            return Err(ForumError::NotFound);
+        }
+        Ok(())
+    }
 
+    async fn restore_post(&self, post_id: i64) -> ForumResult<()> {
+        let r = sqlx::query!(
+            r#" UPDATE forum.posts
+                SET deleted_at = NULL, deleted_by = NULL, delete_reason = NULL
+                WHERE id = $1 AND deleted_at IS NOT NULL"#, post_id)
+            .execute(&self.pool)
+            .await?;
+        if r.rows_affected() == 0 {
+            return Err(ForumError::NotFound);
+        }
         Ok(())
+    }
+
+    async fn post_deleted(&self, post_id: i64) -> ForumResult<bool> {
+        struct Helper {
+            deleted: Option<bool>,
+        }
+
+        let res = sqlx::query_as!(Helper,
+            r#" SELECT (p.deleted_at IS NOT NULL OR t.deleted_at IS NOT NULL) as deleted
+                FROM forum.posts p
+                INNER JOIN forum.threads t ON t.id = p.topic
+                WHERE p.id = $1"#, post_id)
+            .fetch_one(&self.pool)
+            .await?;
 
+        Ok(res.deleted.unwrap_or(false))
+    }
+
+    /// Replies to purged posts become top level, the posts of purged threads go with them
+    async fn purge_deleted(&self, days: i64) -> ForumResult<(u64, u64)> {
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(days);
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#" UPDATE forum.posts c SET comments = NULL
+                FROM forum.posts p
+                INNER JOIN forum.threads t ON t.id = p.topic
+                WHERE c.comments = p.id
+                    AND (p.deleted_at < $1 OR t.deleted_at < $1)"#, cutoff)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#" DELETE FROM forum.hashtags h
+                USING forum.threads t
+                WHERE t.id = h.topic AND t.deleted_at < $1"#, cutoff)
+            .execute(&mut *tx)
+            .await?;
+        // The edit history goes with the content it belongs to
+        sqlx::query!(
+            r#" DELETE FROM forum.revisions r
+                WHERE r.thread_id IN (SELECT t.id FROM forum.threads t WHERE t.deleted_at < $1)
+                    OR r.post_id IN (
+                        SELECT p.id FROM forum.posts p
+                        INNER JOIN forum.threads t ON t.id = p.topic
+                        WHERE p.deleted_at < $1 OR t.deleted_at < $1)"#, cutoff)
+            .execute(&mut *tx)
+            .await?;
+        let posts = sqlx::query!(
+            r#" DELETE FROM forum.posts p
+                USING forum.threads t
+                WHERE t.id = p.topic
+                    AND (p.deleted_at < $1 OR t.deleted_at < $1)"#, cutoff)
+            .execute(&mut *tx)
+            .await?;
+        let threads = sqlx::query!(
+            r#"DELETE FROM forum.threads WHERE deleted_at < $1"#, cutoff)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok((threads.rows_affected(), posts.rows_affected()))
     }
 
     async fn posts_since(&self, post_id: i64) -> ForumResult<i64> {
@@ -316,4 +963,238 @@ impl ForumExt for crate::db::DBClient {
 
         Ok(res.count.unwrap_or(-1))
     }
+
+    /// Id of the oldest unread post and how many posts come before it
+    async fn first_unread_post(&self, user: Uuid, t_id: i64) -> ForumResult<Option<(i64, i64)>> {
+        struct Helper {
+            id: i64,
+            position: Option<i64>,
+        }
+
+        let r = sqlx::query_as!(Helper,
+            r#" SELECT p.id,
+                    (SELECT COUNT(*) FROM forum.posts b
+                        WHERE b.topic = $2 AND b.created_at < p.created_at AND b.deleted_at IS NULL
+                            AND NOT b.pending) as position
+                FROM forum.posts p
+                INNER JOIN forum.users u ON u.id = $1
+                LEFT JOIN forum.thread_reads r ON r.user_id = $1 AND r.thread_id = $2
+                WHERE p.topic = $2
+                    AND p.id > COALESCE(r.last_read_post, 0)
+                    AND p.created_at > u.created_at
+                    AND p.author IS DISTINCT FROM $1
+                    AND p.deleted_at IS NULL
+                    AND NOT p.pending
+                ORDER BY p.created_at ASC
+                LIMIT 1"#, user, t_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(r.map(|h| (h.id, h.position.unwrap_or(0))))
+    }
+
+    /// Moves the read marker forward, it never goes back when an older page is opened
+    async fn mark_thread_read(&self, user: Uuid, t_id: i64, last_post: i64) -> ForumResult<()> {
+        sqlx::query!(
+            r#" INSERT INTO forum.thread_reads (user_id, thread_id, last_read_post, read_at)
+                VALUES ($1, $2, $3, NOW())
+                ON CONFLICT (user_id, thread_id) DO UPDATE
+                SET last_read_post = GREATEST(forum.thread_reads.last_read_post, EXCLUDED.last_read_post),
+                    read_at = NOW()"#, user, t_id, last_post)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn mark_section_read(&self, user: Uuid, s_id: i64) -> ForumResult<()> {
+        sqlx::query!(
+            r#" INSERT INTO forum.thread_reads (user_id, thread_id, last_read_post, read_at)
+                SELECT $1, t.id, COALESCE((SELECT MAX(p.id) FROM forum.posts p WHERE p.topic = t.id), 0), NOW()
+                FROM forum.threads t
+                WHERE t.section = $2
+                ON CONFLICT (user_id, thread_id) DO UPDATE
+                SET last_read_post = GREATEST(forum.thread_reads.last_read_post, EXCLUDED.last_read_post),
+                    read_at = NOW()"#, user, s_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn mark_forum_read(&self, user: Uuid, role: UserRole) -> ForumResult<()> {
+        sqlx::query!(
+            r#" INSERT INTO forum.thread_reads (user_id, thread_id, last_read_post, read_at)
+                SELECT $1, t.id, COALESCE((SELECT MAX(p.id) FROM forum.posts p WHERE p.topic = t.id), 0), NOW()
+                FROM forum.threads t
+                WHERE forum.section_visible(t.section, $2)
+                ON CONFLICT (user_id, thread_id) DO UPDATE
+                SET last_read_post = GREATEST(forum.thread_reads.last_read_post, EXCLUDED.last_read_post),
+                    read_at = NOW()"#, user, role as UserRole)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Replaces the mention records of a post, or of the opening post when `post_id` is None
+    async fn save_mentions(&self, thread_id: i64, post_id: Option<i64>, users: &[Uuid]) -> ForumResult<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"DELETE FROM forum.mentions
+                WHERE thread_id = $1
+                    AND post_id IS NOT DISTINCT FROM $2
+                    AND user_id <> ALL($3)"#, thread_id, post_id, users)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            r#"INSERT INTO forum.mentions (thread_id, post_id, user_id)
+                SELECT $1, $2, u FROM unnest($3::uuid[]) AS u
+                ON CONFLICT DO NOTHING"#, thread_id, post_id, users)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn search(&self, query: &SearchDto, role: Option<UserRole>) -> ForumResult<Vec<SearchResultDto>> {
+        let limit = query.limit.unwrap_or(20);
+        let offset = (query.page.unwrap_or(1) - 1) as usize * limit;
+        let limit = limit as i64;
+        let offset = offset as i64;
+
+        // Threads and posts are ranked together, posts inherit the section of their thread.
+        // Tags are stripped before building the snippet so only <mark> is left in it.
+        let r = sqlx::query_as!(SearchResultDto,
+            r#" WITH q AS (SELECT websearch_to_tsquery('english', $1) AS query)
+                SELECT r.kind as "kind!", r.thread_id as "thread_id!", r.post_id, r.title as "title!",
+                    r.section_id as "section_id!", r.author, r.author_name, r.created_at as "created_at!",
+                    r.rank as "rank!", r.snippet as "snippet!"
+                FROM (
+                    SELECT 'thread' as kind, t.id::int8 as thread_id, NULL::int8 as post_id, t.title, t.section as section_id,
+                        t.author, u.name as author_name, t.created_at,
+                        ts_rank(t.search_vector, q.query) as rank,
+                        ts_headline('english', regexp_replace(t.content, '<[^>]*>', ' ', 'g'), q.query,
+                            'StartSel=<mark>, StopSel=</mark>, MaxFragments=2') as snippet
+                    FROM forum.threads t
+                    CROSS JOIN q
+                    INNER JOIN forum.users u ON u.id = t.author
+                    WHERE t.search_vector @@ q.query AND t.moved_to IS NULL AND t.deleted_at IS NULL AND NOT t.pending
+                    UNION ALL
+                    SELECT 'post', p.topic, p.id, t.title, t.section,
+                        p.author, u.name, p.created_at,
+                        ts_rank(p.search_vector, q.query),
+                        ts_headline('english', regexp_replace(p.content, '<[^>]*>', ' ', 'g'), q.query,
+                            'StartSel=<mark>, StopSel=</mark>, MaxFragments=2')
+                    FROM forum.posts p
+                    CROSS JOIN q
+                    INNER JOIN forum.threads t ON t.id = p.topic
+                    LEFT OUTER JOIN forum.users u ON u.id = p.author
+                    WHERE p.search_vector @@ q.query AND p.deleted_at IS NULL AND t.deleted_at IS NULL
+                        AND NOT p.pending AND NOT t.pending
+                ) r
+                WHERE forum.section_visible(r.section_id, $2)
+                    AND ($3::int8 IS NULL OR r.section_id = $3)
+                    AND ($4::text IS NULL OR r.author_name = $4)
+                    AND ($5::timestamptz IS NULL OR r.created_at >= $5)
+                    AND ($6::timestamptz IS NULL OR r.created_at <= $6)
+                ORDER BY r.rank DESC, r.created_at DESC
+                LIMIT $7 OFFSET $8"#,
+            query.q, role as Option<UserRole>, query.section, query.author, query.from, query.to, limit, offset)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn get_reaction_kinds(&self) -> ForumResult<Vec<ReactionKind>> {
+        let r = sqlx::query_as!(ReactionKind,
+            r#"SELECT name, emoji, position FROM forum.reaction_kinds ORDER BY position, name"#)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn save_reaction_kind(&self, name: &str, emoji: &str, position: Option<i32>) -> ForumResult<()> {
+        sqlx::query!(
+            r#"INSERT INTO forum.reaction_kinds (name, emoji, position)
+                VALUES ($1, $2, COALESCE($3, (SELECT COALESCE(MAX(position) + 1, 0) FROM forum.reaction_kinds)))
+                ON CONFLICT (name) DO UPDATE
+                SET emoji = EXCLUDED.emoji,
+                    position = COALESCE($3, forum.reaction_kinds.position)"#, name, emoji, position)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_reaction_kind(&self, name: &str) -> ForumResult<()> {
+        struct Helper {
+            post_id: i64,
+        }
+
+        let affected = sqlx::query_as!(Helper,
+            r#"SELECT DISTINCT post_id FROM forum.post_reactions WHERE kind = $1"#, name)
+            .fetch_all(&self.pool)
+            .await?;
+        sqlx::query!(r#"DELETE FROM forum.reaction_kinds WHERE name = $1"#, name)
+            .execute(&self.pool)
+            .await?;
+        for p in affected {
+            self.refresh_likes(p.post_id).await?;
+        }
+        Ok(())
+    }
+
+    async fn add_reaction(&self, post_id: i64, user: Uuid, kind: &str) -> ForumResult<()> {
+        let r = sqlx::query!(
+            r#"INSERT INTO forum.post_reactions (post_id, user_id, kind)
+                SELECT $1, $2, k.name FROM forum.reaction_kinds k WHERE k.name = $3
+                ON CONFLICT DO NOTHING"#, post_id, user, kind)
+            .execute(&self.pool)
+            .await?;
+
+        if r.rows_affected() > 0 {
+            self.refresh_likes(post_id).await?;
+        } else if !self.get_reaction_kinds().await?.iter().any(|k| k.name == kind) {
+            return Err(crate::error::ForumError::Forum(format!("Unknown reaction: {}", kind)));
+        }
+        Ok(())
+    }
+
+    async fn remove_reaction(&self, post_id: i64, user: Uuid, kind: &str) -> ForumResult<()> {
+        let r = sqlx::query!(
+            r#"DELETE FROM forum.post_reactions WHERE post_id = $1 AND user_id = $2 AND kind = $3"#,
+            post_id, user, kind)
+            .execute(&self.pool)
+            .await?;
+
+        if r.rows_affected() > 0 {
+            self.refresh_likes(post_id).await?;
+        }
+        Ok(())
+    }
+
+    async fn get_reaction_counts(&self, post_ids: &[i64], user: Option<Uuid>) -> ForumResult<Vec<ReactionCount>> {
+        let r = sqlx::query_as!(ReactionCount,
+            r#"SELECT r.post_id, r.kind, k.emoji,
+                    COUNT(*) as "count!",
+                    COALESCE(bool_or(r.user_id = $2), false) as "reacted!"
+                FROM forum.post_reactions r
+                INNER JOIN forum.reaction_kinds k ON k.name = r.kind
+                WHERE r.post_id = ANY($1)
+                GROUP BY r.post_id, r.kind, k.emoji, k.position
+                ORDER BY r.post_id, k.position"#, post_ids, user)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn get_post_reactions(&self, post_id: i64) -> ForumResult<Vec<PostReaction>> {
+        let r = sqlx::query_as!(PostReaction,
+            r#"SELECT r.kind, k.emoji, r.user_id, u.name as user_name, r.created_at
+                FROM forum.post_reactions r
+                INNER JOIN forum.reaction_kinds k ON k.name = r.kind
+                INNER JOIN forum.users u ON u.id = r.user_id
+                WHERE r.post_id = $1
+                ORDER BY k.position, r.created_at"#, post_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
 }
@@ -0,0 +1,270 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::{error::ForumResult, models::{Conversation, ConversationParticipant, PrivateMessage}};
+
+#[async_trait]
+pub trait MessageExt {
+    /// `content` is the HTML rendered from the Markdown `source`
+    async fn create_conversation(&self, author: Uuid, recipients: &[Uuid], subject: &str, content: &str, source: &str) -> ForumResult<i64>;
+    async fn add_message(&self, conversation_id: i64, author: Uuid, content: &str, source: &str) -> ForumResult<i64>;
+    async fn get_conversations(&self, user_id: Uuid, sent: bool, page: u32, limit: usize) -> ForumResult<Vec<Conversation>>;
+    async fn get_conversation(&self, user_id: Uuid, conversation_id: i64) -> ForumResult<Conversation>;
+    async fn get_participants(&self, conversation_id: i64) -> ForumResult<Vec<ConversationParticipant>>;
+    async fn get_messages(&self, user_id: Uuid, conversation_id: i64) -> ForumResult<Vec<PrivateMessage>>;
+    async fn unread_conversations(&self, user_id: Uuid) -> ForumResult<i64>;
+    async fn mark_conversation_read(&self, user_id: Uuid, conversation_id: i64) -> ForumResult<()>;
+    async fn delete_conversation(&self, user_id: Uuid, conversation_id: i64) -> ForumResult<()>;
+}
+
+#[async_trait]
+impl MessageExt for crate::db::DBClient {
+    async fn create_conversation(&self, author: Uuid, recipients: &[Uuid], subject: &str, content: &str, source: &str) -> ForumResult<i64> {
+        struct Helper {
+            id: i64,
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let c = sqlx::query_as!(Helper,
+            r#"INSERT INTO forum.conversations (subject, created_by)
+                VALUES ($1, $2)
+                RETURNING id"#, subject, author)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        sqlx::query!(
+            r#"INSERT INTO forum.conversation_participants (conversation_id, user_id)
+                SELECT $1::int8, u FROM UNNEST($2::uuid[]) AS u
+                UNION
+                SELECT $1, $3::uuid
+                ON CONFLICT DO NOTHING"#,
+            c.id, recipients, author)
+            .execute(&mut *tx)
+            .await?;
+
+        let m = sqlx::query_as!(Helper,
+            r#"INSERT INTO forum.private_messages (conversation_id, author, content, source)
+                VALUES ($1, $2, $3, $4)
+                RETURNING id"#, c.id, author, content, source)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        sqlx::query!(
+            r#"UPDATE forum.conversation_participants
+                SET last_read_message = $3
+                WHERE conversation_id = $1 AND user_id = $2"#,
+            c.id, author, m.id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(c.id)
+    }
+
+    /// The author has read everything up to their own message
+    async fn add_message(&self, conversation_id: i64, author: Uuid, content: &str, source: &str) -> ForumResult<i64> {
+        struct Helper {
+            id: i64,
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let m = sqlx::query_as!(Helper,
+            r#"INSERT INTO forum.private_messages (conversation_id, author, content, source)
+                VALUES ($1, $2, $3, $4)
+                RETURNING id"#, conversation_id, author, content, source)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        sqlx::query!(
+            r#"UPDATE forum.conversations SET updated_at = NOW() WHERE id = $1"#,
+            conversation_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!(
+            r#"UPDATE forum.conversation_participants
+                SET last_read_message = $3
+                WHERE conversation_id = $1 AND user_id = $2"#,
+            conversation_id, author, m.id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(m.id)
+    }
+
+    /// Inbox holds conversations with messages from others, sent the ones with messages from the user,
+    /// deleted messages don't count
+    async fn get_conversations(&self, user_id: Uuid, sent: bool, page: u32, limit: usize) -> ForumResult<Vec<Conversation>> {
+        let offset = (page.max(1) - 1) as i64 * limit as i64;
+
+        let r = sqlx::query_as!(Conversation,
+            r#"SELECT c.id, c.subject, c.created_by, c.created_at, c.updated_at,
+                    ARRAY(
+                        SELECT u.name FROM forum.conversation_participants p
+                        INNER JOIN forum.users u ON u.id = p.user_id
+                        WHERE p.conversation_id = c.id
+                        ORDER BY u.name
+                    ) as "participants!",
+                    (
+                        SELECT u.name FROM forum.private_messages m
+                        LEFT JOIN forum.users u ON u.id = m.author
+                        WHERE m.conversation_id = c.id AND m.id > cp.deleted_message
+                        ORDER BY m.id DESC
+                        LIMIT 1
+                    ) as last_author,
+                    (
+                        SELECT COUNT(*) FROM forum.private_messages m
+                        WHERE m.conversation_id = c.id
+                            AND m.id > GREATEST(cp.last_read_message, cp.deleted_message)
+                            AND m.author IS DISTINCT FROM cp.user_id
+                    ) as "unread!"
+                FROM forum.conversations c
+                INNER JOIN forum.conversation_participants cp ON cp.conversation_id = c.id AND cp.user_id = $1
+                WHERE EXISTS (
+                    SELECT 1 FROM forum.private_messages m
+                    WHERE m.conversation_id = c.id
+                        AND m.id > cp.deleted_message
+                        AND (m.author = $1) = $2
+                )
+                ORDER BY c.updated_at DESC, c.id DESC
+                LIMIT $3 OFFSET $4"#,
+            user_id, sent, limit as i64, offset)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    /// NotFound unless the user takes part in the conversation
+    async fn get_conversation(&self, user_id: Uuid, conversation_id: i64) -> ForumResult<Conversation> {
+        let r = sqlx::query_as!(Conversation,
+            r#"SELECT c.id, c.subject, c.created_by, c.created_at, c.updated_at,
+                    ARRAY(
+                        SELECT u.name FROM forum.conversation_participants p
+                        INNER JOIN forum.users u ON u.id = p.user_id
+                        WHERE p.conversation_id = c.id
+                        ORDER BY u.name
+                    ) as "participants!",
+                    (
+                        SELECT u.name FROM forum.private_messages m
+                        LEFT JOIN forum.users u ON u.id = m.author
+                        WHERE m.conversation_id = c.id AND m.id > cp.deleted_message
+                        ORDER BY m.id DESC
+                        LIMIT 1
+                    ) as last_author,
+                    (
+                        SELECT COUNT(*) FROM forum.private_messages m
+                        WHERE m.conversation_id = c.id
+                            AND m.id > GREATEST(cp.last_read_message, cp.deleted_message)
+                            AND m.author IS DISTINCT FROM cp.user_id
+                    ) as "unread!"
+                FROM forum.conversations c
+                INNER JOIN forum.conversation_participants cp ON cp.conversation_id = c.id AND cp.user_id = $1
+                WHERE c.id = $2"#,
+            user_id, conversation_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn get_participants(&self, conversation_id: i64) -> ForumResult<Vec<ConversationParticipant>> {
+        let r = sqlx::query_as!(ConversationParticipant,
+            r#"SELECT p.user_id, u.name, u.avatar, p.last_read_message
+                FROM forum.conversation_participants p
+                INNER JOIN forum.users u ON u.id = p.user_id
+                WHERE p.conversation_id = $1
+                ORDER BY p.joined_at, u.name"#,
+            conversation_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    /// Messages the user hasn't deleted, oldest first
+    async fn get_messages(&self, user_id: Uuid, conversation_id: i64) -> ForumResult<Vec<PrivateMessage>> {
+        let r = sqlx::query_as!(PrivateMessage,
+            r#"SELECT m.id, m.conversation_id, m.author, u.name as "author_name?", m.content, m.source, m.created_at
+                FROM forum.private_messages m
+                INNER JOIN forum.conversation_participants cp ON cp.conversation_id = m.conversation_id AND cp.user_id = $1
+                LEFT JOIN forum.users u ON u.id = m.author
+                WHERE m.conversation_id = $2
+                    AND m.id > cp.deleted_message
+                ORDER BY m.id"#,
+            user_id, conversation_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    /// Number of conversations with unread messages
+    async fn unread_conversations(&self, user_id: Uuid) -> ForumResult<i64> {
+        struct Helper {
+            count: Option<i64>,
+        }
+
+        let r = sqlx::query_as!(Helper,
+            r#"SELECT COUNT(*) as count
+                FROM forum.conversation_participants cp
+                WHERE cp.user_id = $1
+                    AND EXISTS (
+                        SELECT 1 FROM forum.private_messages m
+                        WHERE m.conversation_id = cp.conversation_id
+                            AND m.id > GREATEST(cp.last_read_message, cp.deleted_message)
+                            AND m.author IS DISTINCT FROM cp.user_id
+                    )"#,
+            user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(r.count.unwrap_or(0))
+    }
+
+    async fn mark_conversation_read(&self, user_id: Uuid, conversation_id: i64) -> ForumResult<()> {
+        sqlx::query!(
+            r#"UPDATE forum.conversation_participants
+                SET last_read_message = COALESCE(
+                    (SELECT MAX(id) FROM forum.private_messages WHERE conversation_id = $2),
+                    last_read_message)
+                WHERE user_id = $1 AND conversation_id = $2"#,
+            user_id, conversation_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Hides the current messages from the user only,
+    /// the conversation is removed once every participant deleted it
+    async fn delete_conversation(&self, user_id: Uuid, conversation_id: i64) -> ForumResult<()> {
+        let mut tx = self.pool.begin().await?;
+        let r = sqlx::query!(
+            r#"UPDATE forum.conversation_participants
+                SET deleted_message = COALESCE(
+                        (SELECT MAX(id) FROM forum.private_messages WHERE conversation_id = $2),
+                        deleted_message),
+                    last_read_message = COALESCE(
+                        (SELECT MAX(id) FROM forum.private_messages WHERE conversation_id = $2),
+                        last_read_message)
+                WHERE user_id = $1 AND conversation_id = $2"#,
+            user_id, conversation_id)
+            .execute(&mut *tx)
+            .await?;
+        if r.rows_affected() == 0 {
+            return Err(crate::error::ForumError::NotFound);
+        }
+
+        sqlx::query!(
+            r#"DELETE FROM forum.conversations c
+                WHERE c.id = $1
+                    AND NOT EXISTS (
+                        SELECT 1 FROM forum.private_messages m
+                        INNER JOIN forum.conversation_participants cp ON cp.conversation_id = m.conversation_id
+                        WHERE m.conversation_id = c.id
+                            AND m.id > cp.deleted_message
+                    )"#,
+            conversation_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
@@ -1,5 +1,15 @@
 pub mod user;
 pub mod forum;
+pub mod permission;
+pub mod notification;
+pub mod message;
+pub mod privacy;
+pub mod event;
+pub mod moderation;
+pub mod report;
+pub mod ban;
+pub mod approval;
+pub mod filter;
 use sqlx::{Pool, Postgres};
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{dto::admin::ModerationLogQueryDto, error::ForumResult, models::{ModerationAction, ModerationLogEntry, ModerationTarget}};
+
+#[async_trait]
+pub trait ModerationExt {
+    #[allow(clippy::too_many_arguments)]
+    async fn log_moderation(&self, actor: Uuid, action: ModerationAction, target_kind: ModerationTarget, target_id: &str,
+        section: Option<i64>, reason: Option<&str>, before: Option<Value>, after: Option<Value>) -> ForumResult<()>;
+    async fn get_moderation_log(&self, query: &ModerationLogQueryDto) -> ForumResult<Vec<ModerationLogEntry>>;
+}
+
+#[async_trait]
+impl ModerationExt for crate::db::DBClient {
+    async fn log_moderation(&self, actor: Uuid, action: ModerationAction, target_kind: ModerationTarget, target_id: &str,
+        section: Option<i64>, reason: Option<&str>, before: Option<Value>, after: Option<Value>) -> ForumResult<()> {
+        sqlx::query!(r#"INSERT INTO forum.moderation_log (actor, action, target_kind, target_id, section, reason, before, after)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+            actor, action as ModerationAction, target_kind as ModerationTarget, target_id, section, reason, before, after)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_moderation_log(&self, query: &ModerationLogQueryDto) -> ForumResult<Vec<ModerationLogEntry>> {
+        let limit = query.limit.unwrap_or(50);
+        let offset = (query.page.unwrap_or(1).max(1) - 1) as i64 * limit as i64;
+
+        let r = sqlx::query_as!(ModerationLogEntry,
+            r#"SELECT l.id, l.actor, a.name as "actor_name?", l.action as "action: ModerationAction",
+                    l.target_kind as "target_kind: ModerationTarget", l.target_id, l.section, l.reason,
+                    l.before, l.after, l.created_at
+                FROM forum.moderation_log l
+                LEFT JOIN forum.users a ON a.id = l.actor
+                WHERE ($1::uuid IS NULL OR l.actor = $1)
+                    AND ($2::forum.moderation_action IS NULL OR l.action = $2)
+                    AND ($3::forum.moderation_target IS NULL OR l.target_kind = $3)
+                    AND ($4::varchar IS NULL OR l.target_id = $4)
+                    AND ($5::int8 IS NULL OR l.section = $5)
+                    AND ($6::timestamptz IS NULL OR l.created_at >= $6)
+                    AND ($7::timestamptz IS NULL OR l.created_at < $7)
+                ORDER BY l.created_at DESC, l.id DESC
+                LIMIT $8 OFFSET $9"#,
+            query.actor, query.action as Option<ModerationAction>, query.target_kind as Option<ModerationTarget>,
+            query.target_id.as_deref(), query.section, query.since, query.until, limit as i64, offset)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+}
@@ -0,0 +1,258 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::{error::ForumResult, models::{Notification, NotificationKind, SectionSubscription, ThreadSubscription}};
+
+#[async_trait]
+pub trait NotificationExt {
+    async fn subscribe_thread(&self, user_id: Uuid, thread_id: i64) -> ForumResult<()>;
+    async fn unsubscribe_thread(&self, user_id: Uuid, thread_id: i64) -> ForumResult<()>;
+    async fn subscribe_section(&self, user_id: Uuid, section: i64) -> ForumResult<()>;
+    async fn unsubscribe_section(&self, user_id: Uuid, section: i64) -> ForumResult<()>;
+    async fn thread_subscribed(&self, user_id: Uuid, thread_id: i64) -> ForumResult<bool>;
+    async fn get_thread_subscriptions(&self, user_id: Uuid) -> ForumResult<Vec<ThreadSubscription>>;
+    async fn get_section_subscriptions(&self, user_id: Uuid) -> ForumResult<Vec<SectionSubscription>>;
+
+    async fn notify(&self, user_id: Uuid, kind: NotificationKind, actor: Option<Uuid>,
+        thread_id: Option<i64>, post_id: Option<i64>, content: Option<&str>) -> ForumResult<()>;
+    async fn notify_reply(&self, post_id: i64, actor: Uuid) -> ForumResult<()>;
+    async fn notify_new_thread(&self, thread_id: i64, actor: Uuid) -> ForumResult<()>;
+    async fn notify_mention(&self, user_id: Uuid, actor: Uuid, thread_id: i64, post_id: Option<i64>) -> ForumResult<()>;
+    /// Notifies the users stored as mentioned in a post that was held back for approval,
+    /// users already notified about the post are skipped
+    async fn notify_mentions(&self, thread_id: i64, post_id: Option<i64>, actor: Uuid) -> ForumResult<()>;
+
+    async fn get_notifications(&self, user_id: Uuid, unread_only: bool, page: u32, limit: usize) -> ForumResult<Vec<Notification>>;
+    async fn unread_notifications(&self, user_id: Uuid) -> ForumResult<i64>;
+    async fn mark_notifications_read(&self, user_id: Uuid, ids: Option<&[i64]>) -> ForumResult<()>;
+}
+
+#[async_trait]
+impl NotificationExt for crate::db::DBClient {
+    async fn subscribe_thread(&self, user_id: Uuid, thread_id: i64) -> ForumResult<()> {
+        sqlx::query!(r#"INSERT INTO forum.thread_subscriptions (user_id, thread_id)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING"#, user_id, thread_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn unsubscribe_thread(&self, user_id: Uuid, thread_id: i64) -> ForumResult<()> {
+        sqlx::query!(r#"DELETE FROM forum.thread_subscriptions WHERE user_id = $1 AND thread_id = $2"#, user_id, thread_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn subscribe_section(&self, user_id: Uuid, section: i64) -> ForumResult<()> {
+        sqlx::query!(r#"INSERT INTO forum.section_subscriptions (user_id, section)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING"#, user_id, section)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn unsubscribe_section(&self, user_id: Uuid, section: i64) -> ForumResult<()> {
+        sqlx::query!(r#"DELETE FROM forum.section_subscriptions WHERE user_id = $1 AND section = $2"#, user_id, section)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn thread_subscribed(&self, user_id: Uuid, thread_id: i64) -> ForumResult<bool> {
+        struct Helper {
+            subscribed: Option<bool>,
+        }
+
+        let r = sqlx::query_as!(Helper,
+            r#"SELECT EXISTS (SELECT 1 FROM forum.thread_subscriptions WHERE user_id = $1 AND thread_id = $2) as subscribed"#,
+            user_id, thread_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(r.subscribed.unwrap_or(false))
+    }
+
+    async fn get_thread_subscriptions(&self, user_id: Uuid) -> ForumResult<Vec<ThreadSubscription>> {
+        let r = sqlx::query_as!(ThreadSubscription,
+            r#"SELECT s.thread_id, t.title, s.created_at
+                FROM forum.thread_subscriptions s
+                INNER JOIN forum.threads t ON t.id = s.thread_id
+                WHERE s.user_id = $1
+                ORDER BY s.created_at DESC"#, user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn get_section_subscriptions(&self, user_id: Uuid) -> ForumResult<Vec<SectionSubscription>> {
+        let r = sqlx::query_as!(SectionSubscription,
+            r#"SELECT s.section, sc.name, s.created_at
+                FROM forum.section_subscriptions s
+                INNER JOIN forum.sections sc ON sc.id = s.section
+                WHERE s.user_id = $1
+                ORDER BY sc.name"#, user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn notify(&self, user_id: Uuid, kind: NotificationKind, actor: Option<Uuid>,
+        thread_id: Option<i64>, post_id: Option<i64>, content: Option<&str>) -> ForumResult<()> {
+        if actor == Some(user_id) {
+            return Ok(());
+        }
+        // warnings and approval outcomes get through blocks
+        sqlx::query!(
+            r#"INSERT INTO forum.notifications (user_id, kind, actor, thread_id, post_id, content)
+                SELECT $1::uuid, $2::forum.notification_kind, $3::uuid, $4::int8, $5::int8, $6::varchar
+                WHERE $2 IN ('warning', 'approved', 'rejected') OR NOT forum.is_blocked($1, $3)"#,
+            user_id, kind as NotificationKind, actor, thread_id, post_id, content)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// The author of the replied post gets a quote notification,
+    /// every other subscriber of the thread gets a reply notification
+    async fn notify_reply(&self, post_id: i64, actor: Uuid) -> ForumResult<()> {
+        sqlx::query!(
+            r#"WITH p AS (
+                    SELECT p.id, p.topic, t.section, parent.author AS parent_author
+                    FROM forum.posts p
+                    INNER JOIN forum.threads t ON t.id = p.topic
+                    LEFT JOIN forum.posts parent ON parent.id = p.comments
+                    WHERE p.id = $1
+                ), recipients AS (
+                    SELECT p.parent_author AS user_id, 'quote'::forum.notification_kind AS kind
+                    FROM p
+                    WHERE p.parent_author IS NOT NULL
+                    UNION
+                    SELECT s.user_id, 'reply'::forum.notification_kind
+                    FROM forum.thread_subscriptions s, p
+                    WHERE s.thread_id = p.topic
+                        AND s.user_id IS DISTINCT FROM p.parent_author
+                )
+                INSERT INTO forum.notifications (user_id, kind, actor, thread_id, post_id)
+                SELECT r.user_id, r.kind, $2, p.topic, p.id
+                FROM recipients r
+                CROSS JOIN p
+                INNER JOIN forum.users u ON u.id = r.user_id
+                WHERE r.user_id <> $2
+                    AND NOT forum.is_blocked(r.user_id, $2)
+                    AND forum.section_visible(p.section, u.role)"#,
+            post_id, actor)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn notify_new_thread(&self, thread_id: i64, actor: Uuid) -> ForumResult<()> {
+        sqlx::query!(
+            r#"INSERT INTO forum.notifications (user_id, kind, actor, thread_id)
+                SELECT s.user_id, 'new_thread', $2, t.id
+                FROM forum.threads t
+                INNER JOIN forum.section_subscriptions s ON s.section = t.section
+                INNER JOIN forum.users u ON u.id = s.user_id
+                WHERE t.id = $1
+                    AND s.user_id <> $2
+                    AND NOT forum.is_blocked(s.user_id, $2)
+                    AND forum.section_visible(t.section, u.role)"#,
+            thread_id as i32, actor)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Editing a post doesn't notify the same user about the same mention twice,
+    /// users who blocked the author aren't notified at all
+    async fn notify_mention(&self, user_id: Uuid, actor: Uuid, thread_id: i64, post_id: Option<i64>) -> ForumResult<()> {
+        sqlx::query!(
+            r#"INSERT INTO forum.notifications (user_id, kind, actor, thread_id, post_id)
+                SELECT $1::uuid, 'mention', $2::uuid, $3::int8, $4::int8
+                WHERE $1 <> $2
+                    AND NOT forum.is_blocked($1, $2)
+                    AND NOT EXISTS (
+                        SELECT 1 FROM forum.notifications
+                        WHERE user_id = $1
+                            AND kind = 'mention'
+                            AND thread_id = $3
+                            AND post_id IS NOT DISTINCT FROM $4
+                    )"#,
+            user_id, actor, thread_id, post_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn notify_mentions(&self, thread_id: i64, post_id: Option<i64>, actor: Uuid) -> ForumResult<()> {
+        sqlx::query!(
+            r#"INSERT INTO forum.notifications (user_id, kind, actor, thread_id, post_id)
+                SELECT m.user_id, 'mention', $3, m.thread_id, m.post_id
+                FROM forum.mentions m
+                INNER JOIN forum.threads t ON t.id = m.thread_id
+                INNER JOIN forum.users u ON u.id = m.user_id
+                WHERE m.thread_id = $1
+                    AND m.post_id IS NOT DISTINCT FROM $2
+                    AND m.user_id <> $3
+                    AND NOT forum.is_blocked(m.user_id, $3)
+                    AND forum.section_visible(t.section, u.role)
+                    AND NOT EXISTS (
+                        SELECT 1 FROM forum.notifications n
+                        WHERE n.user_id = m.user_id
+                            AND n.kind = 'mention'
+                            AND n.thread_id = m.thread_id
+                            AND n.post_id IS NOT DISTINCT FROM m.post_id
+                    )"#,
+            thread_id, post_id, actor)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_notifications(&self, user_id: Uuid, unread_only: bool, page: u32, limit: usize) -> ForumResult<Vec<Notification>> {
+        let offset = (page.max(1) - 1) as i64 * limit as i64;
+
+        let r = sqlx::query_as!(Notification,
+            r#"SELECT n.id, n.kind as "kind: NotificationKind", n.actor, a.name as "actor_name?",
+                    n.thread_id, t.title as "thread_title?", t.section as "section?", n.post_id, n.content, n.is_read, n.created_at
+                FROM forum.notifications n
+                LEFT JOIN forum.users a ON a.id = n.actor
+                LEFT JOIN forum.threads t ON t.id = n.thread_id
+                WHERE n.user_id = $1
+                    AND (NOT $2 OR NOT n.is_read)
+                ORDER BY n.created_at DESC, n.id DESC
+                LIMIT $3 OFFSET $4"#,
+            user_id, unread_only, limit as i64, offset)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn unread_notifications(&self, user_id: Uuid) -> ForumResult<i64> {
+        struct Helper {
+            count: Option<i64>,
+        }
+
+        let r = sqlx::query_as!(Helper,
+            r#"SELECT COUNT(*) as count FROM forum.notifications WHERE user_id = $1 AND NOT is_read"#,
+            user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(r.count.unwrap_or(0))
+    }
+
+    async fn mark_notifications_read(&self, user_id: Uuid, ids: Option<&[i64]>) -> ForumResult<()> {
+        sqlx::query!(
+            r#"UPDATE forum.notifications
+                SET is_read = true
+                WHERE user_id = $1
+                    AND NOT is_read
+                    AND ($2::int8[] IS NULL OR id = ANY($2))"#,
+            user_id, ids)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
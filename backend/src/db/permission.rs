@@ -0,0 +1,244 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::{error::ForumResult, models::{Group, Permission, SectionModerator, SectionPermission, UserRole}};
+
+#[async_trait]
+pub trait PermissionExt {
+    async fn has_permission(&self, user_id: Uuid, permission: Permission, section: Option<i64>) -> ForumResult<bool>;
+
+    async fn get_groups(&self) -> ForumResult<Vec<Group>>;
+    async fn get_user_groups(&self, user_id: Uuid) -> ForumResult<Vec<Group>>;
+    async fn create_group(&self, name: &str, description: Option<&str>, role: Option<UserRole>, permissions: &[Permission]) -> ForumResult<i32>;
+    async fn update_group(&self, group_id: i32, name: &str, description: Option<&str>, permissions: &[Permission]) -> ForumResult<()>;
+    async fn delete_group(&self, group_id: i32) -> ForumResult<()>;
+    async fn add_group_member(&self, group_id: i32, user_id: Uuid) -> ForumResult<()>;
+    async fn remove_group_member(&self, group_id: i32, user_id: Uuid) -> ForumResult<()>;
+
+    async fn get_section_permissions(&self, section: i64) -> ForumResult<Vec<SectionPermission>>;
+    async fn set_section_permission(&self, section: i64, group_id: i32, permission: Permission, allowed: Option<bool>) -> ForumResult<()>;
+
+    async fn get_section_moderators(&self, section: i64) -> ForumResult<Vec<SectionModerator>>;
+    async fn add_section_moderator(&self, section: i64, user_id: Uuid) -> ForumResult<()>;
+    async fn remove_section_moderator(&self, section: i64, user_id: Uuid) -> ForumResult<()>;
+}
+
+struct GroupRow {
+    id: i32,
+    name: String,
+    description: Option<String>,
+    role: Option<UserRole>,
+}
+
+impl crate::db::DBClient {
+    /// Attaches the granted permissions to each group row
+    async fn with_permissions(&self, rows: Vec<GroupRow>) -> ForumResult<Vec<Group>> {
+        struct Helper {
+            group_id: i32,
+            permission: Permission,
+        }
+
+        let ids: Vec<i32> = rows.iter().map(|g| g.id).collect();
+        let granted = sqlx::query_as!(Helper,
+            r#"SELECT group_id, permission as "permission: Permission"
+                FROM forum.group_permissions
+                WHERE group_id = ANY($1)
+                ORDER BY permission"#, &ids)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter()
+            .map(|g| Group {
+                permissions: granted.iter()
+                    .filter(|p| p.group_id == g.id)
+                    .map(|p| p.permission)
+                    .collect(),
+                id: g.id,
+                name: g.name,
+                description: g.description,
+                role: g.role,
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl PermissionExt for crate::db::DBClient {
+    async fn has_permission(&self, user_id: Uuid, permission: Permission, section: Option<i64>) -> ForumResult<bool> {
+        struct Helper {
+            allowed: Option<bool>,
+        }
+
+        let r = sqlx::query_as!(Helper,
+            r#"SELECT forum.has_permission($1, $2, $3) as allowed"#,
+            user_id, permission as Permission, section)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(r.allowed.unwrap_or(false))
+    }
+
+    async fn get_groups(&self) -> ForumResult<Vec<Group>> {
+        let rows = sqlx::query_as!(GroupRow,
+            r#"SELECT id, name, description, role as "role: UserRole"
+                FROM forum.groups
+                ORDER BY id"#)
+            .fetch_all(&self.pool)
+            .await?;
+
+        self.with_permissions(rows).await
+    }
+
+    async fn get_user_groups(&self, user_id: Uuid) -> ForumResult<Vec<Group>> {
+        let rows = sqlx::query_as!(GroupRow,
+            r#"SELECT g.id, g.name, g.description, g.role as "role: UserRole"
+                FROM forum.groups g
+                INNER JOIN forum.users u ON u.id = $1
+                WHERE g.role = u.role
+                    OR EXISTS (SELECT 1 FROM forum.user_groups ug WHERE ug.group_id = g.id AND ug.user_id = $1)
+                ORDER BY g.id"#, user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        self.with_permissions(rows).await
+    }
+
+    async fn create_group(&self, name: &str, description: Option<&str>, role: Option<UserRole>, permissions: &[Permission]) -> ForumResult<i32> {
+        struct Helper {
+            id: i32,
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let r = sqlx::query_as!(Helper,
+            r#"INSERT INTO forum.groups (name, description, role)
+                VALUES ($1, $2, $3)
+                RETURNING id"#, name, description, role as Option<UserRole>)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        for p in permissions {
+            sqlx::query!(r#"INSERT INTO forum.group_permissions (group_id, permission)
+                            VALUES ($1, $2)
+                            ON CONFLICT DO NOTHING"#, r.id, *p as Permission)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(r.id)
+    }
+
+    async fn update_group(&self, group_id: i32, name: &str, description: Option<&str>, permissions: &[Permission]) -> ForumResult<()> {
+        let mut tx = self.pool.begin().await?;
+        let r = sqlx::query!(r#"UPDATE forum.groups
+            SET
+                name = $2,
+                description = $3
+            WHERE id = $1"#, group_id, name, description)
+            .execute(&mut *tx)
+            .await?;
+        if r.rows_affected() == 0 {
+            return Err(crate::error::ForumError::NotFound);
+        }
+
+        sqlx::query!(r#"DELETE FROM forum.group_permissions WHERE group_id = $1"#, group_id)
+            .execute(&mut *tx)
+            .await?;
+        for p in permissions {
+            sqlx::query!(r#"INSERT INTO forum.group_permissions (group_id, permission)
+                            VALUES ($1, $2)
+                            ON CONFLICT DO NOTHING"#, group_id, *p as Permission)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn delete_group(&self, group_id: i32) -> ForumResult<()> {
+        let r = sqlx::query!(r#"DELETE FROM forum.groups WHERE id = $1 AND role IS NULL"#, group_id)
+            .execute(&self.pool)
+            .await?;
+        if r.rows_affected() == 0 {
+            return Err(crate::error::ForumError::Forum("Only custom groups can be deleted".to_string()));
+        }
+        Ok(())
+    }
+
+    async fn add_group_member(&self, group_id: i32, user_id: Uuid) -> ForumResult<()> {
+        sqlx::query!(r#"INSERT INTO forum.user_groups (user_id, group_id)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING"#, user_id, group_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_group_member(&self, group_id: i32, user_id: Uuid) -> ForumResult<()> {
+        sqlx::query!(r#"DELETE FROM forum.user_groups WHERE user_id = $1 AND group_id = $2"#, user_id, group_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_section_permissions(&self, section: i64) -> ForumResult<Vec<SectionPermission>> {
+        let r = sqlx::query_as!(SectionPermission,
+            r#"SELECT section, group_id, permission as "permission: Permission", allowed
+                FROM forum.section_permissions
+                WHERE section = $1
+                ORDER BY group_id, permission"#, section)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn set_section_permission(&self, section: i64, group_id: i32, permission: Permission, allowed: Option<bool>) -> ForumResult<()> {
+        match allowed {
+            Some(allowed) => {
+                sqlx::query!(r#"INSERT INTO forum.section_permissions (section, group_id, permission, allowed)
+                        VALUES ($1, $2, $3, $4)
+                        ON CONFLICT (section, group_id, permission) DO UPDATE SET allowed = EXCLUDED.allowed"#,
+                        section, group_id, permission as Permission, allowed)
+                    .execute(&self.pool)
+                    .await?;
+            }
+            None => {
+                sqlx::query!(r#"DELETE FROM forum.section_permissions
+                        WHERE section = $1 AND group_id = $2 AND permission = $3"#,
+                        section, group_id, permission as Permission)
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn get_section_moderators(&self, section: i64) -> ForumResult<Vec<SectionModerator>> {
+        let r = sqlx::query_as!(SectionModerator,
+            r#"SELECT u.id, u.name
+                FROM forum.section_moderators sm
+                INNER JOIN forum.users u ON u.id = sm.user_id
+                WHERE sm.section = $1
+                ORDER BY u.name"#, section)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn add_section_moderator(&self, section: i64, user_id: Uuid) -> ForumResult<()> {
+        sqlx::query!(r#"INSERT INTO forum.section_moderators (section, user_id)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING"#, section, user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_section_moderator(&self, section: i64, user_id: Uuid) -> ForumResult<()> {
+        sqlx::query!(r#"DELETE FROM forum.section_moderators WHERE section = $1 AND user_id = $2"#, section, user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::{error::ForumResult, models::{BlockedUser, PmPrivacy, PrivacySettings}};
+
+#[async_trait]
+pub trait PrivacyExt {
+    async fn block_user(&self, user_id: Uuid, blocked_id: Uuid) -> ForumResult<()>;
+    async fn unblock_user(&self, user_id: Uuid, blocked_id: Uuid) -> ForumResult<()>;
+    async fn get_blocked_users(&self, user_id: Uuid) -> ForumResult<Vec<BlockedUser>>;
+    async fn is_blocked(&self, user_id: Uuid, other: Uuid) -> ForumResult<bool>;
+    async fn get_privacy(&self, user_id: Uuid) -> ForumResult<PrivacySettings>;
+    async fn set_privacy(&self, user_id: Uuid, settings: &PrivacySettings) -> ForumResult<()>;
+    async fn post_count(&self, user_id: Uuid) -> ForumResult<i64>;
+}
+
+#[async_trait]
+impl PrivacyExt for crate::db::DBClient {
+    async fn block_user(&self, user_id: Uuid, blocked_id: Uuid) -> ForumResult<()> {
+        sqlx::query!(r#"INSERT INTO forum.user_blocks (user_id, blocked_id)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING"#, user_id, blocked_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn unblock_user(&self, user_id: Uuid, blocked_id: Uuid) -> ForumResult<()> {
+        sqlx::query!(r#"DELETE FROM forum.user_blocks WHERE user_id = $1 AND blocked_id = $2"#, user_id, blocked_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_blocked_users(&self, user_id: Uuid) -> ForumResult<Vec<BlockedUser>> {
+        let r = sqlx::query_as!(BlockedUser,
+            r#"SELECT b.blocked_id as user_id, u.name, b.created_at
+                FROM forum.user_blocks b
+                INNER JOIN forum.users u ON u.id = b.blocked_id
+                WHERE b.user_id = $1
+                ORDER BY u.name"#, user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    /// True when `user_id` has blocked `other`
+    async fn is_blocked(&self, user_id: Uuid, other: Uuid) -> ForumResult<bool> {
+        struct Helper {
+            blocked: Option<bool>,
+        }
+
+        let r = sqlx::query_as!(Helper,
+            r#"SELECT forum.is_blocked($1, $2) as blocked"#, user_id, other)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(r.blocked.unwrap_or(false))
+    }
+
+    /// Users without saved settings accept messages from everyone and are visible
+    async fn get_privacy(&self, user_id: Uuid) -> ForumResult<PrivacySettings> {
+        let r = sqlx::query_as!(PrivacySettings,
+            r#"SELECT pm_privacy as "pm_privacy: PmPrivacy", pm_min_posts, invisible
+                FROM forum.user_privacy
+                WHERE user_id = $1"#, user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(r.unwrap_or_default())
+    }
+
+    async fn set_privacy(&self, user_id: Uuid, settings: &PrivacySettings) -> ForumResult<()> {
+        sqlx::query!(
+            r#"INSERT INTO forum.user_privacy (user_id, pm_privacy, pm_min_posts, invisible)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (user_id) DO UPDATE
+                SET pm_privacy = EXCLUDED.pm_privacy, pm_min_posts = EXCLUDED.pm_min_posts,
+                    invisible = EXCLUDED.invisible"#,
+            user_id, settings.pm_privacy as PmPrivacy, settings.pm_min_posts, settings.invisible)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Threads started count as posts too
+    async fn post_count(&self, user_id: Uuid) -> ForumResult<i64> {
+        struct Helper {
+            count: Option<i64>,
+        }
+
+        let r = sqlx::query_as!(Helper,
+            r#"SELECT (SELECT COUNT(*) FROM forum.posts WHERE author = $1)
+                    + (SELECT COUNT(*) FROM forum.threads WHERE author = $1) as count"#,
+            user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(r.count.unwrap_or(0))
+    }
+}
@@ -0,0 +1,199 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::{dto::report::ReportQueryDto, error::{ForumError, ForumResult}, models::{ModerationAction, Report, ReportReason, ReportStatus, ReportTarget}};
+
+/// What a report points at, looked up when it is filed
+pub struct ReportedItem {
+    pub section: Option<i64>,
+    pub author: Option<Uuid>,
+    pub content: String,
+}
+
+#[async_trait]
+pub trait ReportExt {
+    /// Fails with NotFound for items the reporter can't see
+    async fn reported_item(&self, kind: ReportTarget, target_id: &str, reporter: Uuid) -> ForumResult<ReportedItem>;
+    async fn create_report(&self, reporter: Uuid, kind: ReportTarget, target_id: &str, item: &ReportedItem,
+        reason: ReportReason, comment: Option<&str>) -> ForumResult<i64>;
+    /// Reports in sections where `moderator` has `CanModerate`, the oldest first
+    async fn get_reports(&self, moderator: Uuid, query: &ReportQueryDto) -> ForumResult<Vec<Report>>;
+    async fn get_report(&self, report_id: i64) -> ForumResult<Report>;
+    async fn claim_report(&self, report_id: i64, moderator: Uuid) -> ForumResult<()>;
+    /// Closes every pending report on the same item, returns their reporters
+    async fn handle_report(&self, report_id: i64, moderator: Uuid, status: ReportStatus,
+        action: Option<ModerationAction>, resolution: Option<&str>) -> ForumResult<Vec<Uuid>>;
+}
+
+fn parse_id(target_id: &str) -> ForumResult<i64> {
+    target_id.parse().map_err(|_| ForumError::NotFound)
+}
+
+#[async_trait]
+impl ReportExt for crate::db::DBClient {
+    async fn reported_item(&self, kind: ReportTarget, target_id: &str, reporter: Uuid) -> ForumResult<ReportedItem> {
+        struct Helper {
+            section: Option<i64>,
+            author: Option<Uuid>,
+            content: Option<String>,
+        }
+
+        let r = match kind {
+            ReportTarget::Post => sqlx::query_as!(Helper,
+                r#"SELECT t.section as "section?", p.author, p.source as "content?"
+                    FROM forum.posts p
+                    INNER JOIN forum.threads t ON t.id = p.topic
+                    INNER JOIN forum.users u ON u.id = $2
+                    WHERE p.id = $1 AND p.deleted_at IS NULL AND t.deleted_at IS NULL
+                        AND NOT p.pending AND NOT t.pending
+                        AND forum.section_visible(t.section, u.role)"#, parse_id(target_id)?, reporter)
+                .fetch_one(&self.pool)
+                .await?,
+            ReportTarget::Thread => sqlx::query_as!(Helper,
+                r#"SELECT t.section as "section?", t.author as "author?", t.title || E'\n\n' || t.source as content
+                    FROM forum.threads t
+                    INNER JOIN forum.users u ON u.id = $2
+                    WHERE t.id = $1 AND t.deleted_at IS NULL AND t.moved_to IS NULL AND NOT t.pending
+                        AND forum.section_visible(t.section, u.role)"#, parse_id(target_id)? as i32, reporter)
+                .fetch_one(&self.pool)
+                .await?,
+            ReportTarget::Message => sqlx::query_as!(Helper,
+                r#"SELECT NULL::int8 as section, m.author, m.source as "content?"
+                    FROM forum.private_messages m
+                    INNER JOIN forum.conversation_participants cp
+                        ON cp.conversation_id = m.conversation_id AND cp.user_id = $2
+                    WHERE m.id = $1"#, parse_id(target_id)?, reporter)
+                .fetch_one(&self.pool)
+                .await?,
+            ReportTarget::Chat => sqlx::query_as!(Helper,
+                r#"SELECT NULL::int8 as section, c.author as "author?", c.content as "content?"
+                    FROM forum.chat_posts c
+                    WHERE c.id = $1"#, parse_id(target_id)? as i32)
+                .fetch_one(&self.pool)
+                .await?,
+            ReportTarget::User => sqlx::query_as!(Helper,
+                r#"SELECT NULL::int8 as section, u.id as "author?", u.name || E'\n\n' || COALESCE(u.description, '') as content
+                    FROM forum.users u
+                    WHERE u.id = $1"#, Uuid::parse_str(target_id).map_err(|_| ForumError::NotFound)?)
+                .fetch_one(&self.pool)
+                .await?,
+        };
+
+        Ok(ReportedItem { section: r.section, author: r.author, content: r.content.unwrap_or_default() })
+    }
+
+    async fn create_report(&self, reporter: Uuid, kind: ReportTarget, target_id: &str, item: &ReportedItem,
+        reason: ReportReason, comment: Option<&str>) -> ForumResult<i64> {
+        struct Helper {
+            id: i64,
+        }
+
+        let r = sqlx::query_as!(Helper,
+            r#"INSERT INTO forum.reports (reporter, target_kind, target_id, reported_user, section, reason, comment, snapshot)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (reporter, target_kind, target_id) WHERE status IN ('open', 'claimed') DO NOTHING
+                RETURNING id"#,
+            reporter, kind as ReportTarget, target_id, item.author, item.section, reason as ReportReason, comment, item.content)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(ForumError::Forum("You already reported this".to_string()))?;
+        Ok(r.id)
+    }
+
+    async fn get_reports(&self, moderator: Uuid, query: &ReportQueryDto) -> ForumResult<Vec<Report>> {
+        let limit = query.limit.unwrap_or(50);
+        let offset = (query.page.unwrap_or(1).max(1) - 1) as i64 * limit as i64;
+
+        let r = sqlx::query_as!(Report,
+            r#"SELECT r.id, r.reporter, rp.name as "reporter_name?", r.target_kind as "target_kind: ReportTarget", r.target_id,
+                    r.reported_user, ru.name as "reported_user_name?", r.section,
+                    CASE r.target_kind
+                        WHEN 'thread' THEN r.target_id::int8
+                        WHEN 'post' THEN (SELECT p.topic FROM forum.posts p WHERE p.id::text = r.target_id)
+                    END as thread_id,
+                    r.reason as "reason: ReportReason", r.comment, r.snapshot, r.status as "status: ReportStatus",
+                    cb.name as "claimed_by_name?", hb.name as "handled_by_name?", r.action as "action: ModerationAction",
+                    r.resolution, r.created_at, r.handled_at,
+                    (SELECT COUNT(*) FROM forum.reports o
+                        WHERE o.target_kind = r.target_kind AND o.target_id = r.target_id) as "report_count!"
+                FROM forum.reports r
+                LEFT JOIN forum.users rp ON rp.id = r.reporter
+                LEFT JOIN forum.users ru ON ru.id = r.reported_user
+                LEFT JOIN forum.users cb ON cb.id = r.claimed_by
+                LEFT JOIN forum.users hb ON hb.id = r.handled_by
+                WHERE forum.has_permission($1, 'can_moderate', r.section)
+                    AND ($2::forum.report_status IS NULL OR r.status = $2)
+                    AND ($3::forum.report_target IS NULL OR r.target_kind = $3)
+                ORDER BY r.created_at ASC, r.id ASC
+                LIMIT $4 OFFSET $5"#,
+            moderator, query.status as Option<ReportStatus>, query.target_kind as Option<ReportTarget>, limit as i64, offset)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    async fn get_report(&self, report_id: i64) -> ForumResult<Report> {
+        let r = sqlx::query_as!(Report,
+            r#"SELECT r.id, r.reporter, rp.name as "reporter_name?", r.target_kind as "target_kind: ReportTarget", r.target_id,
+                    r.reported_user, ru.name as "reported_user_name?", r.section,
+                    CASE r.target_kind
+                        WHEN 'thread' THEN r.target_id::int8
+                        WHEN 'post' THEN (SELECT p.topic FROM forum.posts p WHERE p.id::text = r.target_id)
+                    END as thread_id,
+                    r.reason as "reason: ReportReason", r.comment, r.snapshot, r.status as "status: ReportStatus",
+                    cb.name as "claimed_by_name?", hb.name as "handled_by_name?", r.action as "action: ModerationAction",
+                    r.resolution, r.created_at, r.handled_at,
+                    (SELECT COUNT(*) FROM forum.reports o
+                        WHERE o.target_kind = r.target_kind AND o.target_id = r.target_id) as "report_count!"
+                FROM forum.reports r
+                LEFT JOIN forum.users rp ON rp.id = r.reporter
+                LEFT JOIN forum.users ru ON ru.id = r.reported_user
+                LEFT JOIN forum.users cb ON cb.id = r.claimed_by
+                LEFT JOIN forum.users hb ON hb.id = r.handled_by
+                WHERE r.id = $1"#, report_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(r)
+    }
+
+    /// Claims go to every pending report on the item, a claim by someone else is kept
+    async fn claim_report(&self, report_id: i64, moderator: Uuid) -> ForumResult<()> {
+        let r = sqlx::query!(
+            r#"UPDATE forum.reports o
+                SET status = 'claimed', claimed_by = $2
+                FROM forum.reports r
+                WHERE r.id = $1
+                    AND o.target_kind = r.target_kind AND o.target_id = r.target_id
+                    AND o.status IN ('open', 'claimed')
+                    AND (o.claimed_by IS NULL OR o.claimed_by = $2)"#, report_id, moderator)
+            .execute(&self.pool)
+            .await?;
+        if r.rows_affected() == 0 {
+            return Err(ForumError::Forum("The report is already handled or claimed".to_string()));
+        }
+        Ok(())
+    }
+
+    async fn handle_report(&self, report_id: i64, moderator: Uuid, status: ReportStatus,
+        action: Option<ModerationAction>, resolution: Option<&str>) -> ForumResult<Vec<Uuid>> {
+        struct Helper {
+            reporter: Option<Uuid>,
+        }
+
+        let r = sqlx::query_as!(Helper,
+            r#"UPDATE forum.reports o
+                SET status = $3, handled_by = $2, action = $4, resolution = $5, handled_at = NOW()
+                FROM forum.reports r
+                WHERE r.id = $1
+                    AND o.target_kind = r.target_kind AND o.target_id = r.target_id
+                    AND o.status IN ('open', 'claimed')
+                RETURNING o.reporter"#,
+            report_id, moderator, status as ReportStatus, action as Option<ModerationAction>, resolution)
+            .fetch_all(&self.pool)
+            .await?;
+        if r.is_empty() {
+            return Err(ForumError::Forum("The report is already handled".to_string()));
+        }
+        Ok(r.into_iter().filter_map(|h| h.reporter).collect())
+    }
+}
@@ -2,8 +2,8 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-use crate::error::ForumResult;
-use crate::models::{User, UserRole, Thread, Post, UserWarning, PrivateMessage};
+use crate::error::{ForumError, ForumResult};
+use crate::models::{EscalationRule, User, UserRole, Thread, Post, UserWarning, WarningCategory, WarningOutcome};
 use crate::dto::user::UserData;
 
 #[async_trait]
@@ -13,6 +13,7 @@ pub trait UserExt {
     async fn get_users(&self, page: u32, limit: usize) -> ForumResult<Vec<User>>;
     async fn recently_online(&self, since: DateTime<Utc>, page: u32, limit: usize) -> ForumResult<Vec<User>>;
     async fn add_user(&self, name: &str, email: &str, password: &str, verification_token: &str, token_expires_at: DateTime<Utc>) -> ForumResult<()>;
+    #[allow(clippy::too_many_arguments)]
     async fn save_user(&self, name: &str, email: &str, password: &str,  
         description: Option<&str>, facebook: Option<&str>, discord: Option<&str>, x_id: Option<&str>) -> ForumResult<()>;
     async fn update_user_avatar(&self, id: Uuid, avatar: Option<&str>) -> ForumResult<()>;
@@ -20,15 +21,22 @@ pub trait UserExt {
     async fn update_user_name(&self, user_id: Uuid, name: &str) -> ForumResult<()>;
     async fn update_user_role(&self, user_id: Uuid, role: UserRole) -> ForumResult<()>;
     async fn update_user_password(&self, user_id: Uuid, password: &str) -> ForumResult<()>;
-    async fn warn_user(&self, user_id: Uuid, comment: Option<&str>, warned_by: Uuid, ban: Option<i32>) -> ForumResult<()>;
+    /// Warns in `category`, the "general" one when None, and applies the escalation rules.
+    /// `ban` is a manual ban in days, the longer of it and an escalated ban wins
+    async fn warn_user(&self, user_id: Uuid, comment: Option<&str>, warned_by: Uuid, category: Option<i32>, ban: Option<i32>) -> ForumResult<WarningOutcome>;
     async fn unban_user(&self, user_id: Uuid) -> ForumResult<()>;
     async fn verifed_token(&self, token: &str) -> ForumResult<()>;
     async fn add_verifed_token(&self, user_id: Uuid, token: &str, expires_at: DateTime<Utc>) -> ForumResult<()>;
-    async fn get_user_posts(&self, user_id: Option<Uuid>, user_name: Option<&str>) -> ForumResult<Vec<Post>>;
-    async fn get_user_threads(&self, user_id: Option<Uuid>, user_name: Option<&str>) -> ForumResult<Vec<Thread>>;
+    async fn get_user_posts(&self, user_id: Option<Uuid>, user_name: Option<&str>, role: Option<UserRole>) -> ForumResult<Vec<Post>>;
+    async fn get_user_threads(&self, user_id: Option<Uuid>, user_name: Option<&str>, role: Option<UserRole>) -> ForumResult<Vec<Thread>>;
     async fn get_user_warnings(&self, user_id: Uuid, since: Option<DateTime<Utc>>) -> ForumResult<Vec<UserWarning>>;
-    async fn send_pm(&self, user_id: Uuid, send_to: Uuid, content: &str) -> ForumResult<()>;
-    async fn get_pms(&self, user: Uuid, page: u32, limit: usize) -> ForumResult<Vec<PrivateMessage>>;
+    async fn get_warning_categories(&self) -> ForumResult<Vec<WarningCategory>>;
+    async fn save_warning_category(&self, id: Option<i32>, name: &str, points: i32, expire_days: Option<i32>) -> ForumResult<i32>;
+    async fn delete_warning_category(&self, id: i32) -> ForumResult<()>;
+    async fn get_escalation_rules(&self) -> ForumResult<Vec<EscalationRule>>;
+    async fn save_escalation_rule(&self, id: Option<i32>, points: i32, period_days: i32, ban_days: i32) -> ForumResult<i32>;
+    async fn delete_escalation_rule(&self, id: i32) -> ForumResult<()>;
+    #[allow(clippy::too_many_arguments)]
     async fn update_user_data(&self, user_id: Uuid, name: &str, email: &str, role: UserRole, description: Option<&str>, avatar: Option<&str>, facebook: Option<&str>, discord: Option<&str>, x_id: Option<&str>) -> ForumResult<()>;
     async fn update_user_activity(&self, user_id: Uuid) -> ForumResult<()>;
 }
@@ -77,10 +85,11 @@ impl UserExt for crate::db::DBClient {
     async fn get_user_d(&self, user_id: Uuid) -> ForumResult<Option<UserData>> {
             let user = sqlx::query_as!(UserData,
                 r#" SELECT u.id, u.name, u.email, u.created_at, u.role as "role: UserRole", u.description, u.avatar, u.facebook, u.discord, u.x_id, u.verified, u.last_online,
-                        COUNT(p.id) AS posts_n, (banned_until > NOW()) as banned
+                        COUNT(p.id) AS posts_n, (banned_until > NOW()) as banned,
+                        forum.warning_points(u.id, NULL) as warning_level
                     FROM forum.users u
                     LEFT JOIN forum.posts p 
-                        ON u.id = p.author
+                        ON u.id = p.author AND p.deleted_at IS NULL AND NOT p.pending
                     WHERE u.id = $1
                     GROUP BY u.id
                 "#, user_id)
@@ -166,8 +175,7 @@ impl UserExt for crate::db::DBClient {
     }
 
     async fn update_user_avatar(&self, id: Uuid, avatar: Option<&str>) -> ForumResult<()> { 
-        sqlx::query_as!(
-            User,
+        sqlx::query!(
             r#"
             UPDATE forum.users
             SET 
@@ -177,7 +185,7 @@ impl UserExt for crate::db::DBClient {
             "#,
             id,
             avatar)
-        .fetch_one(&self.pool)
+        .execute(&self.pool)
         .await?;
 
         Ok(())
@@ -269,24 +277,68 @@ impl UserExt for crate::db::DBClient {
         Ok(())
     }
 
-    async fn warn_user(&self, user_id: Uuid, comment: Option<&str>, warned_by: Uuid, ban: Option<i32>) -> ForumResult<()> {
-        sqlx::query!(
-            r#"INSERT INTO forum.user_warning(user_id,warn_time,comment,warned_by) VALUES($1,LOCALTIMESTAMP,$2,$3)"#,
-            user_id, comment, warned_by)
-            .fetch_optional(&self.pool)
+    async fn warn_user(&self, user_id: Uuid, comment: Option<&str>, warned_by: Uuid, category: Option<i32>, ban: Option<i32>) -> ForumResult<WarningOutcome> {
+        struct Warning {
+            id: i64,
+            category: Option<i32>,
+            points: i32,
+        }
+        struct Escalation {
+            ban_days: Option<i32>,
+            level: i64,
+        }
+        struct Banned {
+            banned_until: Option<DateTime<Utc>>,
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let warning = sqlx::query_as!(Warning,
+            r#"INSERT INTO forum.user_warning (user_id, warn_time, comment, warned_by, category, points, expires_at)
+                SELECT $1, NOW(), $2, $3, c.id, COALESCE(c.points, 1), NOW() + make_interval(days => c.expire_days)
+                FROM (SELECT 1) d
+                LEFT JOIN forum.warning_categories c
+                    ON c.id = COALESCE($4, (SELECT id FROM forum.warning_categories WHERE name = 'general'))
+                RETURNING id, category, points"#,
+            user_id, comment, warned_by, category)
+            .fetch_one(&mut *tx)
             .await?;
+        if category.is_some() && warning.category.is_none() {
+            return Err(ForumError::NotFound);
+        }
 
-        if let Some(days) = ban {
+        // the longest ban of all rules whose threshold is reached
+        let escalation = sqlx::query_as!(Escalation,
+            r#"SELECT MAX(r.ban_days) as ban_days, forum.warning_points($1, NULL) as "level!"
+                FROM forum.escalation_rules r
+                WHERE forum.warning_points($1, NOW() - make_interval(days => r.period_days)) >= r.points"#,
+            user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        let mut banned_until = None;
+        if let Some(days) = ban.max(escalation.ban_days) {
             let days = sqlx::postgres::types::PgInterval { months: 0, days, microseconds: 0 };
-            sqlx::query!(
-                r#"UPDATE forum.users SET banned_until = LOCALTIMESTAMP + $2::interval
-                WHERE id = $1"#,
+            // a warning never shortens a longer running ban
+            let banned = sqlx::query_as!(Banned,
+                r#"UPDATE forum.users SET banned_until = GREATEST(banned_until, NOW() + $2::interval)
+                WHERE id = $1
+                RETURNING banned_until"#,
                 user_id, days)
-                .execute(&self.pool)
+                .fetch_one(&mut *tx)
                 .await?;
+            sqlx::query!(r#"UPDATE forum.user_warning SET banned = true WHERE id = $1"#, warning.id)
+                .execute(&mut *tx)
+                .await?;
+            banned_until = banned.banned_until;
         }
-
-        Ok(())
+        tx.commit().await?;
+
+        Ok(WarningOutcome {
+            points: warning.points,
+            level: escalation.level,
+            banned_until,
+            escalated: escalation.ban_days,
+        })
     }
     
     async fn unban_user(&self, user_id: Uuid) -> ForumResult<()> {
@@ -333,12 +385,17 @@ impl UserExt for crate::db::DBClient {
         Ok(())
     }
 
-    async fn get_user_posts(&self, user_id: Option<Uuid>, user_name: Option<&str>) -> ForumResult<Vec<Post>> {
+    async fn get_user_posts(&self, user_id: Option<Uuid>, user_name: Option<&str>, role: Option<UserRole>) -> ForumResult<Vec<Post>> {
         if let Some(id) = user_id {
             let r = sqlx::query_as!(
                 Post,
-                r#"SELECT forum.posts.id,content,author,NULL as author_name,topic,comments,forum.posts.created_at,modified_at,likes 
-                FROM forum.posts WHERE author = $1"#, id)
+                r#"SELECT p.id,p.content,p.source,p.author,NULL as author_name,p.topic,p.comments,p.created_at,p.modified_at,
+                    (SELECT m.name FROM forum.users m WHERE m.id = p.modified_by) as modified_by_name,
+                    NULL::timestamptz as deleted_at, NULL as deleted_by_name, NULL as delete_reason, p.likes, false as "pending!"
+                FROM forum.posts p INNER JOIN forum.threads t ON t.id = p.topic
+                WHERE p.author = $1 AND forum.section_visible(t.section, $2)
+                    AND p.deleted_at IS NULL AND t.deleted_at IS NULL
+                    AND NOT p.pending AND NOT t.pending"#, id, role as Option<UserRole>)
                 .fetch_all(&self.pool)
                 .await?;
             Ok(r)
@@ -346,20 +403,33 @@ impl UserExt for crate::db::DBClient {
             let name = user_name.unwrap();
             let r = sqlx::query_as!(
                 Post,
-                r#" SELECT forum.posts.id,content,author,u.name as author_name,topic,comments,forum.posts.created_at,modified_at,likes 
-                    FROM forum.posts INNER JOIN forum.users u ON u.id = author
-                    WHERE u.name = $1"#, name)
+                r#" SELECT p.id,p.content,p.source,p.author,u.name as author_name,p.topic,p.comments,p.created_at,p.modified_at,
+                    (SELECT m.name FROM forum.users m WHERE m.id = p.modified_by) as modified_by_name,
+                    NULL::timestamptz as deleted_at, NULL as deleted_by_name, NULL as delete_reason, p.likes, false as "pending!"
+                    FROM forum.posts p
+                    INNER JOIN forum.users u ON u.id = p.author
+                    INNER JOIN forum.threads t ON t.id = p.topic
+                    WHERE u.name = $1 AND forum.section_visible(t.section, $2)
+                        AND p.deleted_at IS NULL AND t.deleted_at IS NULL
+                        AND NOT p.pending AND NOT t.pending"#, name, role as Option<UserRole>)
                 .fetch_all(&self.pool)
                 .await?;
             Ok(r)
         }
     }
 
-    async fn get_user_threads(&self, user_id: Option<Uuid>, user_name: Option<&str>) -> ForumResult<Vec<Thread>> {
+    async fn get_user_threads(&self, user_id: Option<Uuid>, user_name: Option<&str>, role: Option<UserRole>) -> ForumResult<Vec<Thread>> {
       if let Some(id) = user_id {
             let r = sqlx::query_as!(
                 Thread,
-                r#"SELECT * FROM forum.threads WHERE author = $1"#, id)
+                r#"SELECT id, title, created_at, content, source, author, section, locked,
+                    (sticky AND (sticky_until IS NULL OR sticky_until > NOW())) as "sticky!",
+                    (announcement AND (sticky_until IS NULL OR sticky_until > NOW())) as "announcement!",
+                    sticky_until, moved_to::int8 as moved_to, modified_at,
+                    (SELECT m.name FROM forum.users m WHERE m.id = modified_by) as modified_by_name,
+                    NULL::timestamptz as deleted_at, NULL as deleted_by_name, NULL as delete_reason, pending
+                FROM forum.threads WHERE author = $1 AND moved_to IS NULL AND deleted_at IS NULL AND NOT pending
+                    AND forum.section_visible(section, $2)"#, id, role as Option<UserRole>)
                 .fetch_all(&self.pool)
                 .await?;
             Ok(r)
@@ -367,9 +437,15 @@ impl UserExt for crate::db::DBClient {
             let name = user_name.unwrap();
             let r = sqlx::query_as!(
                 Thread,
-                r#" SELECT forum.threads.id, title, forum.threads.created_at, content, author, section, locked, sticky
+                r#" SELECT forum.threads.id, title, forum.threads.created_at, content, source, author, section, locked,
+                        (sticky AND (sticky_until IS NULL OR sticky_until > NOW())) as "sticky!",
+                        (announcement AND (sticky_until IS NULL OR sticky_until > NOW())) as "announcement!",
+                        sticky_until, moved_to::int8 as moved_to, forum.threads.modified_at,
+                        (SELECT m.name FROM forum.users m WHERE m.id = forum.threads.modified_by) as modified_by_name,
+                        NULL::timestamptz as deleted_at, NULL as deleted_by_name, NULL as delete_reason, pending
                     FROM forum.threads INNER JOIN forum.users ON forum.users.id = author
-                    WHERE forum.users.name = $1"#, name)
+                    WHERE forum.users.name = $1 AND moved_to IS NULL AND forum.threads.deleted_at IS NULL AND NOT pending
+                        AND forum.section_visible(section, $2)"#, name, role as Option<UserRole>)
                 .fetch_all(&self.pool)
                 .await?;
             Ok(r)
@@ -377,40 +453,94 @@ impl UserExt for crate::db::DBClient {
     }
 
     async fn get_user_warnings(&self, user_id: Uuid, since: Option<DateTime<Utc>>) -> ForumResult<Vec<UserWarning>> {
-        let since = since.unwrap_or(DateTime::<Utc>::MIN_UTC);
         let r = sqlx::query_as!(UserWarning,
-            r#" SELECT forum.user_warning.id,user_id as user,warn_time,comment,users.name as warned_by,banned
-                FROM forum.user_warning INNER JOIN forum.users ON forum.user_warning.warned_by = forum.users.id
-                WHERE forum.user_warning.user_id = $1 AND warn_time > $2"#, user_id, since)
+            r#" SELECT w.id, w.user_id as user, w.warn_time, w.comment, users.name as warned_by, w.banned,
+                    c.name as "category?", w.points, w.expires_at
+                FROM forum.user_warning w
+                INNER JOIN forum.users ON w.warned_by = forum.users.id
+                LEFT JOIN forum.warning_categories c ON c.id = w.category
+                WHERE w.user_id = $1 AND ($2::timestamptz IS NULL OR w.warn_time > $2)
+                ORDER BY w.warn_time DESC"#, user_id, since)
             .fetch_all(&self.pool)
             .await?;
         Ok(r)
     }
 
-    async fn send_pm(&self, user_id: Uuid, send_to: Uuid, content: &str) -> ForumResult<()> {
-        sqlx::query!(
-            r#"INSERT INTO forum.private_messages (author, receiver, content)
-            VALUES($1, $2, $3)"#, user_id, send_to, content)
-            .execute(&self.pool)
+    async fn get_warning_categories(&self) -> ForumResult<Vec<WarningCategory>> {
+        let r = sqlx::query_as!(WarningCategory,
+            r#"SELECT id, name, points, expire_days FROM forum.warning_categories ORDER BY points, name"#)
+            .fetch_all(&self.pool)
             .await?;
+        Ok(r)
+    }
 
-        Ok(())
+    async fn save_warning_category(&self, id: Option<i32>, name: &str, points: i32, expire_days: Option<i32>) -> ForumResult<i32> {
+        struct Helper {
+            id: i32,
+        }
+
+        let r = match id {
+            Some(id) => sqlx::query_as!(Helper,
+                r#"UPDATE forum.warning_categories SET name = $2, points = $3, expire_days = $4
+                    WHERE id = $1
+                    RETURNING id"#, id, name, points, expire_days)
+                .fetch_one(&self.pool)
+                .await?,
+            None => sqlx::query_as!(Helper,
+                r#"INSERT INTO forum.warning_categories (name, points, expire_days)
+                    VALUES ($1, $2, $3)
+                    RETURNING id"#, name, points, expire_days)
+                .fetch_one(&self.pool)
+                .await?,
+        };
+        Ok(r.id)
     }
 
-    async fn get_pms(&self, user: Uuid, page: u32, limit: usize) -> ForumResult<Vec<PrivateMessage>> {
-       let offset = (page - 1) as usize * limit;
-       let limit = limit as i64;
-       let offset = offset as i64;
+    /// Past warnings keep their points and expiry
+    async fn delete_warning_category(&self, id: i32) -> ForumResult<()> {
+        sqlx::query!(r#"DELETE FROM forum.warning_categories WHERE id = $1"#, id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
 
-       let r = sqlx::query_as!(PrivateMessage,
-            r#" SELECT id,author,receiver,content FROM forum.private_messages WHERE receiver = $1
-                LIMIT $2 OFFSET $3"#,
-            user, limit, offset)
+    async fn get_escalation_rules(&self) -> ForumResult<Vec<EscalationRule>> {
+        let r = sqlx::query_as!(EscalationRule,
+            r#"SELECT id, points, period_days, ban_days FROM forum.escalation_rules ORDER BY points, period_days"#)
             .fetch_all(&self.pool)
             .await?;
         Ok(r)
     }
 
+    async fn save_escalation_rule(&self, id: Option<i32>, points: i32, period_days: i32, ban_days: i32) -> ForumResult<i32> {
+        struct Helper {
+            id: i32,
+        }
+
+        let r = match id {
+            Some(id) => sqlx::query_as!(Helper,
+                r#"UPDATE forum.escalation_rules SET points = $2, period_days = $3, ban_days = $4
+                    WHERE id = $1
+                    RETURNING id"#, id, points, period_days, ban_days)
+                .fetch_one(&self.pool)
+                .await?,
+            None => sqlx::query_as!(Helper,
+                r#"INSERT INTO forum.escalation_rules (points, period_days, ban_days)
+                    VALUES ($1, $2, $3)
+                    RETURNING id"#, points, period_days, ban_days)
+                .fetch_one(&self.pool)
+                .await?,
+        };
+        Ok(r.id)
+    }
+
+    async fn delete_escalation_rule(&self, id: i32) -> ForumResult<()> {
+        sqlx::query!(r#"DELETE FROM forum.escalation_rules WHERE id = $1"#, id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     async fn update_user_data(&self,
         user_id: Uuid, name: &str, email: &str, role: UserRole,
         description: Option<&str>, avatar: Option<&str>, facebook: Option<&str>, discord: Option<&str>, x_id: Option<&str>)
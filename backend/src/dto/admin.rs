@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{EscalationRule, FilterAction, Group, ModerationAction, ModerationLogEntry, ModerationTarget, Permission, SectionModerator, SectionPermission, SpamSettings, UserRole, WarningCategory, WordFilter};
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize)]
+pub struct CreateGroupDto {
+    #[validate(length(min = 1, max = 100, message = "Group name must be 1-100 characters"))]
+    pub name: String,
+    #[validate(length(max = 255))]
+    pub description: Option<String>,
+    pub role: Option<UserRole>,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateGroupDto {
+    #[validate(length(min = 1, max = 100, message = "Group name must be 1-100 characters"))]
+    pub name: String,
+    #[validate(length(max = 255))]
+    pub description: Option<String>,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize)]
+pub struct GroupMemberDto {
+    pub user_id: uuid::Uuid,
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize)]
+pub struct SectionModeratorDto {
+    pub user_id: uuid::Uuid,
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize)]
+pub struct SectionApprovalDto {
+    /// New threads and posts wait for approval
+    pub moderated: bool,
+}
+
+/// `allowed: null` removes the override so the group defaults apply again
+#[derive(Validate, Debug, Clone, Serialize, Deserialize)]
+pub struct SectionPermissionDto {
+    pub group_id: i32,
+    pub permission: Permission,
+    pub allowed: Option<bool>,
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize)]
+pub struct ReactionKindDto {
+    #[validate(length(min = 1, max = 32))]
+    pub name: String,
+    #[validate(length(min = 1, max = 16))]
+    pub emoji: String,
+    pub position: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GroupsResponseDto {
+    pub groups: Vec<Group>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GroupCreatedDto {
+    pub status: &'static str,
+    pub id: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SectionPermissionsResponseDto {
+    pub permissions: Vec<SectionPermission>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SectionModeratorsResponseDto {
+    pub moderators: Vec<SectionModerator>,
+}
+
+/// Filters of the moderation log, all optional
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ModerationLogQueryDto {
+    pub actor: Option<uuid::Uuid>,
+    pub action: Option<ModerationAction>,
+    pub target_kind: Option<ModerationTarget>,
+    #[validate(length(max = 64))]
+    pub target_id: Option<String>,
+    pub section: Option<i64>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub page: Option<u32>,
+    #[validate(range(min = 1, max = 200))]
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ModerationLogResponseDto {
+    pub entries: Vec<ModerationLogEntry>,
+}
+
+/// Creates a category, or updates the one with `id`
+#[derive(Validate, Debug, Clone, Serialize, Deserialize)]
+pub struct WarningCategoryDto {
+    pub id: Option<i32>,
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    #[validate(range(min = 1, max = 1000))]
+    pub points: i32,
+    /// Warnings never expire when missing
+    #[validate(range(min = 1))]
+    pub expire_days: Option<i32>,
+}
+
+/// Creates a rule, or updates the one with `id`
+#[derive(Validate, Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationRuleDto {
+    pub id: Option<i32>,
+    #[validate(range(min = 1))]
+    pub points: i32,
+    #[validate(range(min = 1))]
+    pub period_days: i32,
+    #[validate(range(min = 1))]
+    pub ban_days: i32,
+}
+
+fn default_replacement() -> String {
+    "***".to_string()
+}
+
+/// Creates a word filter, or updates the one with `id`
+#[derive(Validate, Debug, Clone, Serialize, Deserialize)]
+pub struct WordFilterDto {
+    pub id: Option<i32>,
+    #[validate(length(min = 1, max = 255))]
+    pub pattern: String,
+    #[serde(default)]
+    pub is_regex: bool,
+    pub action: FilterAction,
+    #[serde(default = "default_replacement")]
+    #[validate(length(max = 64))]
+    pub replacement: String,
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize)]
+pub struct SpamSettingsDto {
+    #[validate(range(min = 0, max = 1000))]
+    pub max_links: i32,
+    #[validate(range(min = 0))]
+    pub new_user_posts: i32,
+    #[validate(range(min = 0, max = 10080))]
+    pub duplicate_minutes: i32,
+    #[validate(range(min = 0, max = 3600))]
+    pub flood_seconds: i32,
+}
+
+#[derive(Serialize)]
+pub struct SavedDto {
+    pub status: &'static str,
+    pub id: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WarningPolicyResponseDto {
+    pub categories: Vec<WarningCategory>,
+    pub rules: Vec<EscalationRule>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ContentFilterResponseDto {
+    pub filters: Vec<WordFilter>,
+    pub spam: SpamSettings,
+}
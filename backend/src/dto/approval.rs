@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::models::PendingItem;
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PendingQueryDto {
+    #[validate(range(min = 1))]
+    pub page: Option<u32>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<usize>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct RejectDto {
+    /// Sent to the author
+    #[validate(length(max = 255))]
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PendingResponseDto {
+    pub items: Vec<PendingItem>,
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::models::{Ban, BanKind, SharedIpUser};
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize)]
+pub struct CreateBanDto {
+    pub kind: BanKind,
+    /// Address or CIDR range, email address or email domain depending on `kind`
+    #[validate(length(min = 1, max = 255))]
+    pub value: String,
+    #[validate(length(max = 255))]
+    pub reason: Option<String>,
+    /// Never expires when missing
+    #[validate(range(min = 1))]
+    pub days: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BansResponseDto {
+    pub bans: Vec<Ban>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SharedIpUsersResponseDto {
+    pub users: Vec<SharedIpUser>,
+}
+
+#[derive(Serialize)]
+pub struct BanCreatedDto {
+    pub status: &'static str,
+    pub id: i64,
+}
@@ -4,8 +4,8 @@ use validator::{Validate, ValidationError};
 use chrono::{DateTime, Utc};
 use crate::models::UserRole;
 
-pub fn validate_roles<T>(v: &Vec<T>) -> Result<(), ValidationError> {
-    if v.len() == 0 {
+pub fn validate_roles<T>(v: &[T]) -> Result<(), ValidationError> {
+    if v.is_empty() {
         return Err(ValidationError::new("Section must be allowed for at least one role"));
     }
     Ok(())
@@ -24,6 +24,17 @@ pub struct CreateThreadDto {
 #[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
 pub struct DeleteThreadDto {
     pub thread_id: i64,
+    /// Shown to moderators with the deleted thread, and kept in the moderation log
+    #[validate(length(max = 255))]
+    pub reason: Option<String>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct RestoreThreadDto {
+    pub thread_id: i64,
+    /// Kept in the moderation log
+    #[validate(length(max = 255))]
+    pub reason: Option<String>,
 }
 
 #[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
@@ -33,12 +44,72 @@ pub struct UpdateThreadDto {
     pub title: String,
     #[validate(length(min = 10, message = "A post must contain at least 10 characters"))]
     pub content: String,
+    /// Stored with the revision, and in the moderation log for edits by staff
+    #[validate(length(max = 255))]
+    pub reason: Option<String>,
 }
 
 #[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
 pub struct LockThreadDto {
     pub thread_id: i64,
     pub locked: bool,
+    /// Kept in the moderation log
+    #[validate(length(max = 255))]
+    pub reason: Option<String>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct StickyThreadDto {
+    pub thread_id: i64,
+    pub sticky: bool,
+    /// Pins the thread in every section, needs `CanAdmin`
+    pub announcement: Option<bool>,
+    /// Unpinned automatically afterwards
+    pub until: Option<DateTime<Utc>>,
+    /// Kept in the moderation log
+    #[validate(length(max = 255))]
+    pub reason: Option<String>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct MoveThreadDto {
+    pub thread_id: i64,
+    pub section: i64,
+    /// Leave a redirect in the old section, on by default
+    pub stub: Option<bool>,
+    /// Kept in the moderation log
+    #[validate(length(max = 255))]
+    pub reason: Option<String>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct MergeThreadsDto {
+    /// Emptied and turned into a redirect to `target`
+    pub source: i64,
+    pub target: i64,
+    /// Kept in the moderation log
+    #[validate(length(max = 255))]
+    pub reason: Option<String>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SplitThreadDto {
+    pub thread_id: i64,
+    #[validate(length(min = 1, max = 500, message = "Select the posts to split"))]
+    pub post_ids: Vec<i64>,
+    #[validate(length(min = 3, message = "Title too short"))]
+    pub title: String,
+    /// Section of the new thread, the old thread's when missing
+    pub section: Option<i64>,
+    /// Kept in the moderation log
+    #[validate(length(max = 255))]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SplitThreadResponseDto {
+    pub status: &'static str,
+    pub thread_id: i64,
 }
 
 #[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
@@ -48,6 +119,9 @@ pub struct CreateSectionDto {
     pub description: String,
     #[validate(custom(function = "validate_roles"))]
     pub allowed_for: Vec<UserRole>,
+    /// New threads and posts wait for approval
+    #[serde(default)]
+    pub moderated: bool,
 }
 
 #[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
@@ -88,18 +162,50 @@ pub struct UpdatePostDto {
     #[validate(range(min=0))]
     pub post_id: i64,
     pub content: String,
+    /// Stored with the revision, and in the moderation log for edits by staff
+    #[validate(length(max = 255))]
+    pub reason: Option<String>,
 }
 
 #[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
 pub struct DeletePostDto {
     #[validate(range(min=0))]
     pub post_id: i64,
+    /// Shown to moderators with the deleted post, and kept in the moderation log for deletions by staff
+    #[validate(length(max = 255))]
+    pub reason: Option<String>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct RestorePostDto {
+    pub post_id: i64,
+    /// Kept in the moderation log
+    #[validate(length(max = 255))]
+    pub reason: Option<String>,
 }
 
 #[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
 pub struct GetThreadsDto {
     pub page: Option<i32>,
     pub limit: Option<usize>,
+    /// Order replies depth-first under their parent instead of by time
+    pub threaded: Option<bool>,
+    /// Open the page holding the first unread post instead of `page`
+    pub unread: Option<bool>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SearchDto {
+    #[validate(length(min = 2, max = 200, message = "Search query must be between 2 and 200 characters"))]
+    pub q: String,
+    pub section: Option<i64>,
+    pub author: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    #[validate(range(min = 1))]
+    pub page: Option<i32>,
+    #[validate(range(min = 1, max = 50))]
+    pub limit: Option<usize>,
 }
 
 #[derive(Serialize, Deserialize, Validate)]
@@ -121,7 +227,29 @@ pub struct Response {
 #[derive(Serialize, Deserialize)]
 pub struct GetThreadResponseDto {
     pub info: crate::models::Thread,
-    pub posts: Vec<crate::models::Post>,
+    pub posts: Vec<crate::models::ThreadedPost>,
+    pub reactions: Vec<crate::models::ReactionCount>,
+    /// Whether the caller gets notified about new replies
+    pub subscribed: bool,
+    /// Page that was returned, differs from the requested one when jumping to unread posts
+    pub page: i32,
+    pub first_unread: Option<i64>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ReactionDto {
+    #[validate(length(min = 1, max = 32))]
+    pub kind: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReactionKindsResponseDto {
+    pub kinds: Vec<crate::models::ReactionKind>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PostReactionsResponseDto {
+    pub reactions: Vec<crate::models::PostReaction>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -140,16 +268,85 @@ pub struct ThreadListItemDto {
     pub section_id: i64,
     pub locked: bool,
     pub sticky: bool,
-
+    pub announcement: bool,
+    pub sticky_until: Option<DateTime<Utc>>,
+    /// Never opened by the caller or has new replies
+    pub unread: bool,
+    pub unread_posts: i64,
+    pub moved_to: Option<i64>,
+    /// Only listed for moderators
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Only listed for the author and moderators
+    pub pending: bool,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct GetSectionResponseDto {
+    /// Announcements of all visible sections, shown above the threads
+    pub announcements: Vec<ThreadListItemDto>,
     pub threads: Vec<ThreadListItemDto>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct SearchResultDto {
+    pub kind: String,
+    pub thread_id: i64,
+    pub post_id: Option<i64>,
+    pub title: String,
+    pub section_id: i64,
+    pub author: Option<uuid::Uuid>,
+    pub author_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub rank: f32,
+    pub snippet: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SearchResponseDto {
+    pub results: Vec<SearchResultDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UploadImageQueryDto {
+    /// Thread the image is posted in, the file is only served to readers of its section
+    pub thread: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ActiveUsersQueryDto {
+    pub thread: Option<i64>,
+}
+
 #[derive(Serialize)]
 pub struct ActiveUsersDto {
     pub count: usize,
     pub users: Vec<crate::UserSession>,
+    pub presence: Option<crate::handler::presence::ThreadPresence>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RevisionsResponseDto {
+    /// Newest first
+    pub revisions: Vec<crate::models::Revision>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct RevisionDiffDto {
+    /// Revision of the same post to compare with, the current content when missing
+    pub to: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RevisionDiffResponseDto {
+    /// Only set for opening posts
+    pub title_before: Option<String>,
+    pub title_after: Option<String>,
+    pub lines: Vec<crate::utils::diff::DiffLine>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct RestoreRevisionDto {
+    /// Kept with the new revision and in the moderation log
+    #[validate(length(max = 255))]
+    pub reason: Option<String>,
 }
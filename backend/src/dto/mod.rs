@@ -1,5 +1,9 @@
 pub mod user;
 pub mod forum;
+pub mod admin;
+pub mod report;
+pub mod ban;
+pub mod approval;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
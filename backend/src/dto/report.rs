@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::models::{ModerationAction, Report, ReportReason, ReportStatus, ReportTarget};
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize)]
+pub struct CreateReportDto {
+    pub target_kind: ReportTarget,
+    /// Numeric id of the item, the uuid for users
+    #[validate(length(min = 1, max = 64))]
+    pub target_id: String,
+    pub reason: ReportReason,
+    #[validate(length(max = 500))]
+    pub comment: Option<String>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ReportQueryDto {
+    pub status: Option<ReportStatus>,
+    pub target_kind: Option<ReportTarget>,
+    #[validate(range(min = 1))]
+    pub page: Option<u32>,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<usize>,
+}
+
+/// Resolving or dismissing a report
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct HandleReportDto {
+    /// What was done about the item, only for resolutions
+    pub action: Option<ModerationAction>,
+    /// Sent to the reporters
+    #[validate(length(max = 255))]
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReportsResponseDto {
+    pub reports: Vec<Report>,
+}
@@ -7,8 +7,7 @@ use crate::models::{User, UserRole};
 
 pub fn validate_password(s: &str) -> Result<(), ValidationError> {
     let mut r: u16 = 0;
-    let mut s_it = s.chars();
-    while let Some(c) = s_it.next() {
+    for c in s.chars() {
         r |= match c {
             ':'..='@' => 1,
             '!'..='/' => 2,
@@ -206,26 +205,70 @@ pub struct RecentlyOnlineDto {
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
 pub struct WarnUserDto {
     pub uuid: uuid::Uuid,
+    #[validate(length(max = 255))]
     pub comment: Option<String>,
     pub warned_by: uuid::Uuid,
+    /// Manual ban in days, on top of what the escalation rules decide
+    #[validate(range(min = 1))]
     pub banned: Option<i32>,
+    /// Warning category, the "general" one when missing
+    pub category: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
 pub struct UnbanUserDto {
     pub uuid: uuid::Uuid,
+    /// Kept in the moderation log
+    #[validate(length(max = 255))]
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
 pub struct SendPmDto {
-    pub recipient_id: uuid::Uuid,
+    /// User names
+    #[validate(length(min = 1, max = 20, message = "A conversation needs 1-20 recipients"))]
+    pub recipients: Vec<String>,
+    #[validate(length(min = 1, max = 255, message = "Subject must be 1-255 characters"))]
+    pub subject: String,
+    #[validate(length(min = 1, max = 20000, message = "A message must be 1-20000 characters"))]
     pub content: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+pub struct ReplyPmDto {
+    #[validate(length(min = 1, max = 20000, message = "A message must be 1-20000 characters"))]
+    pub content: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Validate, Clone)]
 pub struct GetUserPmsDto {
-    pub page: u32,
-    pub limit: usize,
+    /// "inbox" or "sent"
+    pub folder: Option<String>,
+    pub page: Option<u32>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Validate, Clone)]
+pub struct PrivacySettingsDto {
+    pub pm_privacy: crate::models::PmPrivacy,
+    /// Only used with min_posts
+    #[validate(range(min = 0, max = 100000))]
+    pub pm_min_posts: Option<i32>,
+    /// Hide from the online list, keeps the current value when missing
+    pub invisible: Option<bool>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Validate, Clone)]
+pub struct GetNotificationsDto {
+    pub unread: Option<bool>,
+    pub page: Option<u32>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Validate, Clone)]
+pub struct ReadNotificationsDto {
+    /// Marks every notification as read when missing
+    pub ids: Option<Vec<i64>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
@@ -253,6 +296,8 @@ pub struct UserData {
     pub last_online: Option<DateTime<Utc>>,
     pub banned: Option<bool>,
     pub posts_n: Option<i64>,
+    /// Active warning points
+    pub warning_level: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -302,7 +347,55 @@ pub struct UserWarningsResponseDto {
     pub warnings: Vec<crate::models::UserWarning>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WarningCategoriesResponseDto {
+    pub categories: Vec<crate::models::WarningCategory>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WarnUserResponseDto {
+    pub status: &'static str,
+    pub outcome: crate::models::WarningOutcome,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserPmsResponseDto {
-    pub pms: Vec<crate::models::PrivateMessage>,
+    pub conversations: Vec<crate::models::Conversation>,
+    /// Conversations with unread messages in any folder
+    pub unread: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConversationResponseDto {
+    pub conversation: crate::models::Conversation,
+    pub participants: Vec<crate::models::ConversationParticipant>,
+    pub messages: Vec<crate::models::PrivateMessage>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SendPmResponseDto {
+    pub status: &'static str,
+    pub conversation_id: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlocksResponseDto {
+    pub blocked: Vec<crate::models::BlockedUser>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationsResponseDto {
+    pub notifications: Vec<crate::models::Notification>,
+    pub unread: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnreadCountResponseDto {
+    pub unread: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubscriptionsResponseDto {
+    pub threads: Vec<crate::models::ThreadSubscription>,
+    pub sections: Vec<crate::models::SectionSubscription>,
 }
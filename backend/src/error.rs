@@ -65,7 +65,10 @@ pub enum ForumError {
 
 impl From<sqlx::Error> for ForumError {
     fn from(value: sqlx::Error) -> Self {
-        Self::Database(value.to_string())
+        match value {
+            sqlx::Error::RowNotFound => Self::NotFound,
+            _ => Self::Database(value.to_string()),
+        }
     }
 }
 
@@ -0,0 +1,478 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, Router, extract::{Path, Query}, middleware::from_fn, response::IntoResponse, routing::{delete, get, post, put}};
+use validator::Validate;
+use crate::{AppState, dto::{Response, admin}, error::ForumResult};
+use crate::{db::{filter::FilterExt, forum::ForumExt, moderation::ModerationExt, permission::PermissionExt, user::UserExt},
+    error::ForumError,
+    models::{ModerationAction, ModerationTarget, Permission, SpamSettings},
+    utils::filter,
+    middleware::{permission_check, JWTAuthMiddeware, auth},
+};
+
+pub fn admin_handler() -> Router<AppState> {
+    let can_admin = from_fn(|state, req, next|
+        permission_check(state, req, next, Permission::CanAdmin) );
+
+    Router::new()
+        .route("/groups", get(get_groups).put(create_group))
+        .route("/groups/{group_id}", post(update_group).delete(delete_group))
+        .route("/groups/{group_id}/members", put(add_group_member).delete(remove_group_member))
+        .route("/users/{user_id}/groups", get(user_groups))
+        .route("/sections/{s_id}/permissions", get(section_permissions).put(set_section_permission))
+        .route("/sections/{s_id}/approval", put(set_section_approval))
+        .route("/sections/{s_id}/moderators", get(section_moderators)
+            .put(add_section_moderator)
+            .delete(remove_section_moderator))
+        .route("/log", get(moderation_log))
+        .route("/reactions", put(save_reaction_kind))
+        .route("/reactions/{name}", delete(delete_reaction_kind))
+        .route("/warnings", get(warning_policy))
+        .route("/warnings/categories", put(save_warning_category))
+        .route("/warnings/categories/{id}", delete(delete_warning_category))
+        .route("/warnings/rules", put(save_escalation_rule))
+        .route("/warnings/rules/{id}", delete(delete_escalation_rule))
+        .route("/filters", get(content_filter))
+        .route("/filters/words", put(save_word_filter))
+        .route("/filters/words/{id}", delete(delete_word_filter))
+        .route("/filters/spam", put(save_spam_settings))
+        .layer(can_admin)
+        .layer(from_fn(auth))
+}
+
+/// List all groups with their granted permissions
+/// GET /admin/groups
+pub async fn get_groups(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    let groups = app_state.db_client.get_groups().await?;
+
+    Ok(Json(admin::GroupsResponseDto { groups }))
+}
+
+/// PUT /admin/groups
+/// Setting `role` makes every user with that role a member of the group
+pub async fn create_group(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<admin::CreateGroupDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    app_state.update_session(&user.user.id)?;
+
+    let id = app_state.db_client
+        .create_group(&body.name, body.description.as_deref(), body.role, &body.permissions)
+        .await?;
+    app_state.db_client
+        .log_moderation(user.user.id, ModerationAction::UpdateGroup, ModerationTarget::Group, &id.to_string(),
+            None, None, None, Some(serde_json::json!(body)))
+        .await?;
+
+    Ok(Json(admin::GroupCreatedDto { status: "success", id }))
+}
+
+/// POST /admin/groups/{group_id}
+/// Replaces the group's name, description and permission set
+pub async fn update_group(
+    Path(group_id): Path<i32>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<admin::UpdateGroupDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    app_state.update_session(&user.user.id)?;
+
+    let before = app_state.db_client.get_groups().await?
+        .into_iter()
+        .find(|g| g.id == group_id);
+    app_state.db_client
+        .update_group(group_id, &body.name, body.description.as_deref(), &body.permissions)
+        .await?;
+    app_state.db_client
+        .log_moderation(user.user.id, ModerationAction::UpdateGroup, ModerationTarget::Group, &group_id.to_string(),
+            None, None, before.map(|g| serde_json::json!(g)), Some(serde_json::json!(body)))
+        .await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "group updated".to_string(),
+    }))
+}
+
+/// DELETE /admin/groups/{group_id}
+/// The default groups bound to a role can't be deleted
+pub async fn delete_group(
+    Path(group_id): Path<i32>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    let before = app_state.db_client.get_groups().await?
+        .into_iter()
+        .find(|g| g.id == group_id);
+    app_state.db_client.delete_group(group_id).await?;
+    app_state.db_client
+        .log_moderation(user.user.id, ModerationAction::UpdateGroup, ModerationTarget::Group, &group_id.to_string(),
+            None, None, before.map(|g| serde_json::json!(g)), None)
+        .await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "group deleted".to_string(),
+    }))
+}
+
+/// PUT /admin/groups/{group_id}/members
+pub async fn add_group_member(
+    Path(group_id): Path<i32>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<admin::GroupMemberDto>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    app_state.db_client.add_group_member(group_id, body.user_id).await?;
+    app_state.db_client
+        .log_moderation(user.user.id, ModerationAction::UpdateGroup, ModerationTarget::User, &body.user_id.to_string(),
+            None, None, None, Some(serde_json::json!({ "joined_group": group_id })))
+        .await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "member added".to_string(),
+    }))
+}
+
+/// DELETE /admin/groups/{group_id}/members
+pub async fn remove_group_member(
+    Path(group_id): Path<i32>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<admin::GroupMemberDto>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    app_state.db_client.remove_group_member(group_id, body.user_id).await?;
+    app_state.db_client
+        .log_moderation(user.user.id, ModerationAction::UpdateGroup, ModerationTarget::User, &body.user_id.to_string(),
+            None, None, Some(serde_json::json!({ "left_group": group_id })), None)
+        .await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "member removed".to_string(),
+    }))
+}
+
+/// Groups a user belongs to, including the one implied by their role
+/// GET /admin/users/{user_id}/groups
+pub async fn user_groups(
+    Path(user_id): Path<uuid::Uuid>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    let groups = app_state.db_client.get_user_groups(user_id).await?;
+
+    Ok(Json(admin::GroupsResponseDto { groups }))
+}
+
+/// GET /admin/sections/{s_id}/permissions
+pub async fn section_permissions(
+    Path(s_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    let permissions = app_state.db_client.get_section_permissions(s_id).await?;
+
+    Ok(Json(admin::SectionPermissionsResponseDto { permissions }))
+}
+
+/// PUT /admin/sections/{s_id}/permissions
+/// Grants or denies a permission to a group inside one section
+pub async fn set_section_permission(
+    Path(s_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<admin::SectionPermissionDto>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    app_state.db_client
+        .set_section_permission(s_id, body.group_id, body.permission, body.allowed)
+        .await?;
+    app_state.db_client
+        .log_moderation(user.user.id, ModerationAction::UpdatePermissions, ModerationTarget::Section, &s_id.to_string(),
+            Some(s_id), None, None, Some(serde_json::json!(body)))
+        .await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "section permission updated".to_string(),
+    }))
+}
+
+/// PUT /admin/sections/{s_id}/approval
+/// Turns pre-moderation of new threads and posts on or off for the section
+pub async fn set_section_approval(
+    Path(s_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<admin::SectionApprovalDto>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    app_state.db_client.set_section_moderated(s_id, body.moderated).await?;
+    app_state.db_client
+        .log_moderation(user.user.id, ModerationAction::UpdatePermissions, ModerationTarget::Section, &s_id.to_string(),
+            Some(s_id), None, None, Some(serde_json::json!(body)))
+        .await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "section approval updated".to_string(),
+    }))
+}
+
+/// GET /admin/sections/{s_id}/moderators
+pub async fn section_moderators(
+    Path(s_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    let moderators = app_state.db_client.get_section_moderators(s_id).await?;
+
+    Ok(Json(admin::SectionModeratorsResponseDto { moderators }))
+}
+
+/// PUT /admin/sections/{s_id}/moderators
+/// Lets the user lock, move, edit and delete content inside this section only
+pub async fn add_section_moderator(
+    Path(s_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<admin::SectionModeratorDto>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    app_state.db_client.add_section_moderator(s_id, body.user_id).await?;
+    app_state.db_client
+        .log_moderation(user.user.id, ModerationAction::UpdateModerators, ModerationTarget::Section, &s_id.to_string(),
+            Some(s_id), None, None, Some(serde_json::json!({ "added": body.user_id })))
+        .await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "moderator added".to_string(),
+    }))
+}
+
+/// DELETE /admin/sections/{s_id}/moderators
+pub async fn remove_section_moderator(
+    Path(s_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<admin::SectionModeratorDto>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    app_state.db_client.remove_section_moderator(s_id, body.user_id).await?;
+    app_state.db_client
+        .log_moderation(user.user.id, ModerationAction::UpdateModerators, ModerationTarget::Section, &s_id.to_string(),
+            Some(s_id), None, None, Some(serde_json::json!({ "removed": body.user_id })))
+        .await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "moderator removed".to_string(),
+    }))
+}
+
+/// Privileged operations of the staff, newest first
+/// GET /admin/log?actor=&action=&target_kind=&target_id=&section=&since=&until=&page=&limit=
+pub async fn moderation_log(
+    Query(query_params): Query<admin::ModerationLogQueryDto>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    query_params.validate()?;
+    app_state.update_session(&user.user.id)?;
+    let entries = app_state.db_client.get_moderation_log(&query_params).await?;
+
+    Ok(Json(admin::ModerationLogResponseDto { entries }))
+}
+
+/// PUT /admin/reactions
+/// Adds a reaction kind or changes the emoji/position of an existing one
+pub async fn save_reaction_kind(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<admin::ReactionKindDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    app_state.update_session(&user.user.id)?;
+    app_state.db_client.save_reaction_kind(&body.name, &body.emoji, body.position).await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "reaction saved".to_string(),
+    }))
+}
+
+/// DELETE /admin/reactions/{name}
+/// Also removes every reaction of that kind from posts
+pub async fn delete_reaction_kind(
+    Path(name): Path<String>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    app_state.db_client.delete_reaction_kind(&name).await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "reaction deleted".to_string(),
+    }))
+}
+
+/// Warning categories and the escalation rules
+/// GET /admin/warnings
+pub async fn warning_policy(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    let categories = app_state.db_client.get_warning_categories().await?;
+    let rules = app_state.db_client.get_escalation_rules().await?;
+
+    Ok(Json(admin::WarningPolicyResponseDto { categories, rules }))
+}
+
+/// PUT /admin/warnings/categories
+/// Changes only apply to new warnings
+pub async fn save_warning_category(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<admin::WarningCategoryDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    app_state.update_session(&user.user.id)?;
+    let id = app_state.db_client
+        .save_warning_category(body.id, &body.name, body.points, body.expire_days)
+        .await?;
+
+    Ok(Json(admin::SavedDto { status: "success", id }))
+}
+
+/// DELETE /admin/warnings/categories/{id}
+pub async fn delete_warning_category(
+    Path(id): Path<i32>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    app_state.db_client.delete_warning_category(id).await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "warning category deleted".to_string(),
+    }))
+}
+
+/// PUT /admin/warnings/rules
+pub async fn save_escalation_rule(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<admin::EscalationRuleDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    app_state.update_session(&user.user.id)?;
+    let id = app_state.db_client
+        .save_escalation_rule(body.id, body.points, body.period_days, body.ban_days)
+        .await?;
+
+    Ok(Json(admin::SavedDto { status: "success", id }))
+}
+
+/// DELETE /admin/warnings/rules/{id}
+pub async fn delete_escalation_rule(
+    Path(id): Path<i32>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    app_state.db_client.delete_escalation_rule(id).await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "escalation rule deleted".to_string(),
+    }))
+}
+
+/// Word filters and the spam limits
+/// GET /admin/filters
+pub async fn content_filter(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    let filters = app_state.db_client.get_word_filters().await?;
+    let spam = app_state.db_client.get_spam_settings().await?;
+
+    Ok(Json(admin::ContentFilterResponseDto { filters, spam }))
+}
+
+/// PUT /admin/filters/words
+/// Applies to new posts and edits, stored content stays as it is
+pub async fn save_word_filter(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<admin::WordFilterDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    app_state.update_session(&user.user.id)?;
+    if let Err(e) = filter::compile(&body.pattern, body.is_regex) {
+        return Err(ForumError::Forum(format!("Invalid pattern: {}", e)));
+    }
+    let id = app_state.db_client
+        .save_word_filter(body.id, &body.pattern, body.is_regex, body.action, &body.replacement)
+        .await?;
+
+    Ok(Json(admin::SavedDto { status: "success", id }))
+}
+
+/// DELETE /admin/filters/words/{id}
+pub async fn delete_word_filter(
+    Path(id): Path<i32>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    app_state.db_client.delete_word_filter(id).await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "word filter deleted".to_string(),
+    }))
+}
+
+/// PUT /admin/filters/spam
+pub async fn save_spam_settings(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<admin::SpamSettingsDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    app_state.update_session(&user.user.id)?;
+    app_state.db_client
+        .save_spam_settings(&SpamSettings {
+            max_links: body.max_links,
+            new_user_posts: body.new_user_posts,
+            duplicate_minutes: body.duplicate_minutes,
+            flood_seconds: body.flood_seconds,
+        })
+        .await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "spam settings saved".to_string(),
+    }))
+}
@@ -0,0 +1,195 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, Router, extract::{Path, Query}, middleware::from_fn, response::IntoResponse, routing::{get, put}};
+use validator::Validate;
+
+use crate::{AppState, db::{approval::ApprovalExt, event::EventExt, forum::ForumExt, moderation::ModerationExt, notification::NotificationExt},
+    dto::{Response, approval},
+    error::{ForumError, ForumResult},
+    handler::forum::ensure_permission,
+    middleware::{JWTAuthMiddeware, auth},
+    models::{ForumEvent, ModerationAction, ModerationTarget, NotificationKind, Permission},
+};
+
+pub fn approval_handler() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_pending))
+        .route("/threads/{thread_id}/approve", put(approve_thread))
+        .route("/threads/{thread_id}/reject", put(reject_thread))
+        .route("/posts/{post_id}/approve", put(approve_post))
+        .route("/posts/{post_id}/reject", put(reject_post))
+        .layer(from_fn(auth))
+}
+
+fn rejection(what: &str, reason: Option<&str>) -> String {
+    match reason {
+        Some(reason) => format!("Your {} was rejected: {}", what, reason),
+        None => format!("Your {} was rejected", what),
+    }
+}
+
+/// Threads and posts waiting for the caller's approval
+/// GET /approvals
+pub async fn get_pending(
+    Query(query): Query<approval::PendingQueryDto>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    query.validate()?;
+    app_state.update_session(&user.user.id)?;
+    let items = app_state.db_client
+        .get_pending(user.user.id, query.page.unwrap_or(1), query.limit.unwrap_or(50))
+        .await?;
+
+    Ok(Json(approval::PendingResponseDto { items }))
+}
+
+/// Publish a pending thread and send the notifications held back until now
+/// PUT /approvals/threads/{thread_id}/approve
+pub async fn approve_thread(
+    Path(thread_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    let user_id = user.user.id;
+    app_state.update_session(&user_id)?;
+    let thread = app_state.db_client.get_thread_info(thread_id as i32).await?;
+    ensure_permission(&app_state, user_id, Permission::CanModerate, thread.section).await?;
+    let first = app_state.db_client.approve_thread(thread_id).await?;
+    app_state.db_client
+        .log_moderation(user_id, ModerationAction::ApproveThread, ModerationTarget::Thread, &thread_id.to_string(),
+            Some(thread.section), None, None, None)
+        .await?;
+
+    let author = thread.author;
+    app_state.db_client
+        .notify(author, NotificationKind::Approved, Some(user_id), Some(thread_id), None,
+            Some(&format!("Your thread \"{}\" was approved", thread.title)))
+        .await?;
+    app_state.db_client.notify_mentions(thread_id, None, author).await?;
+    // An approved edit was announced when the thread first went live
+    let event = if first {
+        app_state.db_client.notify_new_thread(thread_id, author).await?;
+        ForumEvent::NewThread { section: thread.section, thread_id }
+    } else {
+        ForumEvent::ThreadChanged { section: thread.section, thread_id }
+    };
+    app_state.db_client.publish_event(&event).await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "thread approved".to_string(),
+    }))
+}
+
+/// Turn a pending thread down, it is soft deleted like any other removed thread
+/// PUT /approvals/threads/{thread_id}/reject
+pub async fn reject_thread(
+    Path(thread_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<approval::RejectDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    let user_id = user.user.id;
+    app_state.update_session(&user_id)?;
+    let thread = app_state.db_client.get_thread_info(thread_id as i32).await?;
+    ensure_permission(&app_state, user_id, Permission::CanModerate, thread.section).await?;
+    if !thread.pending {
+        return Err(ForumError::Forum("The thread isn't waiting for approval".to_string()));
+    }
+    let reason = body.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+    app_state.db_client.delete_thread(thread_id, user_id, reason).await?;
+    app_state.db_client
+        .log_moderation(user_id, ModerationAction::RejectThread, ModerationTarget::Thread, &thread_id.to_string(),
+            Some(thread.section), reason, Some(serde_json::json!(thread)), None)
+        .await?;
+    app_state.db_client
+        .notify(thread.author, NotificationKind::Rejected, Some(user_id), None, None,
+            Some(&rejection(&format!("thread \"{}\"", thread.title), reason)))
+        .await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "thread rejected".to_string(),
+    }))
+}
+
+/// Publish a pending reply and send the notifications held back until now
+/// PUT /approvals/posts/{post_id}/approve
+pub async fn approve_post(
+    Path(post_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    let user_id = user.user.id;
+    app_state.update_session(&user_id)?;
+    let section = app_state.db_client.get_post_section(post_id).await?;
+    ensure_permission(&app_state, user_id, Permission::CanModerate, section).await?;
+    let thread_id = app_state.db_client.get_post_topic(post_id).await?;
+    let first = app_state.db_client.approve_post(post_id).await?;
+    app_state.db_client
+        .log_moderation(user_id, ModerationAction::ApprovePost, ModerationTarget::Post, &post_id.to_string(),
+            Some(section), None, None, None)
+        .await?;
+
+    if let Some(author) = app_state.db_client.get_post_author(post_id).await? {
+        app_state.db_client
+            .notify(author, NotificationKind::Approved, Some(user_id), Some(thread_id), Some(post_id),
+                Some("Your post was approved"))
+            .await?;
+        if first {
+            app_state.db_client.notify_reply(post_id, author).await?;
+        }
+        app_state.db_client.notify_mentions(thread_id, Some(post_id), author).await?;
+    }
+    // An approved edit was announced when the post first went live
+    let event = if first {
+        ForumEvent::NewPost { section, thread_id, post_id }
+    } else {
+        ForumEvent::PostEdited { section, thread_id, post_id }
+    };
+    app_state.db_client.publish_event(&event).await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "post approved".to_string(),
+    }))
+}
+
+/// Turn a pending reply down, it is soft deleted like any other removed post
+/// PUT /approvals/posts/{post_id}/reject
+pub async fn reject_post(
+    Path(post_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<approval::RejectDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    let user_id = user.user.id;
+    app_state.update_session(&user_id)?;
+    let section = app_state.db_client.get_post_section(post_id).await?;
+    ensure_permission(&app_state, user_id, Permission::CanModerate, section).await?;
+    if !app_state.db_client.post_pending(post_id).await? {
+        return Err(ForumError::Forum("The post isn't waiting for approval".to_string()));
+    }
+    let thread_id = app_state.db_client.get_post_topic(post_id).await?;
+    let content = app_state.db_client.get_post_source(post_id).await?;
+    let reason = body.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+    app_state.db_client.delete_post(post_id, user_id, reason).await?;
+    app_state.db_client
+        .log_moderation(user_id, ModerationAction::RejectPost, ModerationTarget::Post, &post_id.to_string(),
+            Some(section), reason, Some(serde_json::json!({ "content": content })), None)
+        .await?;
+    if let Some(author) = app_state.db_client.get_post_author(post_id).await? {
+        app_state.db_client
+            .notify(author, NotificationKind::Rejected, Some(user_id), Some(thread_id), None,
+                Some(&rejection("post", reason)))
+            .await?;
+    }
+
+    Ok(Json(Response {
+        status: "success",
+        message: "post rejected".to_string(),
+    }))
+}
@@ -1,13 +1,13 @@
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc};
 
-use axum::{Extension, Json, Router, extract::Query, http::{HeaderMap, StatusCode, header}, middleware::from_fn, response::{IntoResponse, Redirect}, routing::{get, post}};
+use axum::{Extension, Json, Router, extract::{ConnectInfo, Query}, http::{HeaderMap, StatusCode, header}, middleware::from_fn, response::{IntoResponse, Redirect}, routing::{get, post}};
 use axum_extra::extract::cookie::Cookie;
 use chrono::{Utc, Duration};
 use time::OffsetDateTime;
 use tracing::error;
 use validator::Validate;
 
-use crate::{AppState, db::user::UserExt, dto::{Response, user}, error::{ForumError, ForumResult}, mail::mails::{send_forgot_password_email, send_verification_email, send_welcome_email}, middleware::JWTAuthMiddeware, utils::{password, token}};
+use crate::{AppState, db::{ban::BanExt, user::UserExt}, handler::ban::ensure_not_banned, dto::{Response, user}, error::{ForumError, ForumResult}, mail::mails::{send_forgot_password_email, send_verification_email, send_welcome_email}, middleware::JWTAuthMiddeware, utils::{password, token}};
 
 pub fn auth_handler() -> Router<AppState> {
     Router::new()
@@ -21,9 +21,11 @@ pub fn auth_handler() -> Router<AppState> {
 
 pub async fn register(
     Extension(app_state): Extension<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Json(body): Json<user::RegisterUserDto>
 ) -> ForumResult<impl IntoResponse> {
     body.validate()?;
+    ensure_not_banned(&app_state, addr.ip(), Some(&body.email)).await?;
 
     let verification_token = uuid::Uuid::new_v4().to_string();
     let expires_at = Utc::now() + Duration::hours(24);
@@ -36,6 +38,10 @@ pub async fn register(
 
     match result {
         Ok(_) => {
+            if let Ok(Some(user)) = app_state.db_client.get_user(None, Some(&body.name), None).await
+                && let Err(e) = app_state.db_client.record_ip(user.id, addr.ip(), true).await {
+                error!("Failed to record registration address: {}", e);
+            }
             let verify = app_state.env.email_verification;
             if verify {
                 let send_email_result = send_verification_email(&body.email, &body.name, &verification_token).await;
@@ -44,7 +50,9 @@ pub async fn register(
                     error!("Failed to send verification email: {}", e);
                 }
             } else {
-                let _ = app_state.db_client.verifed_token(verification_token.as_str());
+                if let Err(e) = app_state.db_client.verifed_token(verification_token.as_str()).await {
+                    error!("Failed to verify user: {}", e);
+                }
             }
 
             Ok((StatusCode::CREATED, Json(Response {
@@ -58,6 +66,7 @@ pub async fn register(
 
 pub async fn login(
     Extension(app_state): Extension<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Json(body): Json<user::LoginUserDto>
 ) -> ForumResult<impl IntoResponse> {
     body.validate()?;
@@ -73,13 +82,15 @@ pub async fn login(
     if user.is_banned() {
         return Err(ForumError::Banned);
     }
+    ensure_not_banned(&app_state, addr.ip(), Some(&user.email)).await?;
 
     let password_matched = password::compare(&body.password, &user.password)?;
 
     if password_matched {
+        app_state.db_client.record_ip(user.id, addr.ip(), false).await?;
         let token = token::create_token(
             &user.id, 
-            &app_state.env.jwt_secret.as_bytes(), 
+            app_state.env.jwt_secret.as_bytes(), 
             app_state.env.jwt_maxage
         )?;
 
@@ -113,9 +124,10 @@ pub async fn login(
 }
 
 pub async fn logout(
-    Extension(_app_state): Extension<Arc<AppState>>,
-    Extension(_user): Extension<JWTAuthMiddeware>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
 ) -> ForumResult<impl IntoResponse> {
+    app_state.close_session(&user.user.id);
 
     let cookie = Cookie::build(("token", String::new()))
         .path("/")
@@ -260,7 +272,7 @@ pub async fn reset_password(
     let hash_password = password::hash(&body.new_password)?;
 
     app_state.db_client
-        .update_user_password(user_id.clone(), hash_password.as_str())
+        .update_user_password(user_id, hash_password.as_str())
         .await?;
 
     app_state.db_client
@@ -0,0 +1,135 @@
+use std::{net::IpAddr, sync::Arc};
+
+use axum::{Extension, Json, Router, extract::Path, middleware::from_fn, response::IntoResponse, routing::{delete, get}};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{AppState, db::{ban::BanExt, moderation::ModerationExt, permission::PermissionExt},
+    dto::{Response, ban},
+    error::{ForumError, ForumResult},
+    middleware::{JWTAuthMiddeware, auth, permission_check},
+    models::{BanKind, ModerationAction, ModerationTarget, Permission},
+};
+
+pub fn ban_handler() -> Router<AppState> {
+    let can_warn = from_fn(|state, req, next|
+        permission_check(state, req, next, Permission::CanWarn) );
+
+    Router::new()
+        .route("/", get(get_bans).put(add_ban))
+        .route("/{ban_id}", delete(remove_ban))
+        .route("/users/{user_id}", get(shared_ip_users))
+        .layer(can_warn)
+        .layer(from_fn(auth))
+}
+
+/// Refuses registrations and logins from banned addresses and emails
+pub async fn ensure_not_banned(app_state: &AppState, ip: IpAddr, email: Option<&str>) -> ForumResult<()> {
+    match app_state.db_client.find_ban(Some(ip), email).await? {
+        Some(_) => Err(ForumError::Banned),
+        None => Ok(()),
+    }
+}
+
+/// Checks the shape of the banned value before Postgres does
+fn check_ban_value(kind: BanKind, value: &str) -> ForumResult<()> {
+    let valid = match kind {
+        BanKind::Ip => match value.split_once('/') {
+            Some((ip, prefix)) => match (ip.parse::<IpAddr>(), prefix.parse::<u8>()) {
+                (Ok(IpAddr::V4(_)), Ok(prefix)) => prefix <= 32,
+                (Ok(IpAddr::V6(_)), Ok(prefix)) => prefix <= 128,
+                _ => false,
+            },
+            None => value.parse::<IpAddr>().is_ok(),
+        },
+        BanKind::Email => value.split_once('@').is_some_and(|(name, domain)| !name.is_empty() && domain.contains('.')),
+        BanKind::EmailDomain => !value.contains('@') && value.contains('.'),
+    };
+    match valid {
+        true => Ok(()),
+        false => Err(ForumError::Forum(format!("\"{}\" doesn't fit the ban kind", value))),
+    }
+}
+
+/// Running bans
+/// GET /bans
+pub async fn get_bans(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    let bans = app_state.db_client.get_bans().await?;
+
+    Ok(Json(ban::BansResponseDto { bans }))
+}
+
+/// Ban an address range, an email address or an email domain.
+/// Ranges can lock out whole networks, staff included, so they need `CanAdmin`
+/// PUT /bans
+pub async fn add_ban(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<ban::CreateBanDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    let user_id = user.user.id;
+    app_state.update_session(&user_id)?;
+    let value = body.value.trim();
+    check_ban_value(body.kind, value)?;
+    if body.kind == BanKind::Ip && value.contains('/')
+        && !app_state.db_client.has_permission(user_id, Permission::CanAdmin, None).await? {
+        return Err(ForumError::Forbidden);
+    }
+    let (ip, value) = match body.kind {
+        BanKind::Ip => (Some(value), None),
+        _ => (None, Some(value)),
+    };
+    let expires_at = body.days.map(|days| Utc::now() + Duration::days(days as i64));
+
+    let id = app_state.db_client
+        .add_ban(body.kind, ip, value, body.reason.as_deref(), user_id, expires_at)
+        .await?;
+    let created = app_state.db_client.get_ban(id).await?;
+    app_state.db_client
+        .log_moderation(user_id, ModerationAction::AddBan, ModerationTarget::Ban, &id.to_string(),
+            None, body.reason.as_deref(), None, Some(serde_json::json!(created)))
+        .await?;
+
+    Ok(Json(ban::BanCreatedDto { status: "success", id }))
+}
+
+/// Lift a ban early
+/// DELETE /bans/{ban_id}
+pub async fn remove_ban(
+    Path(ban_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    let user_id = user.user.id;
+    app_state.update_session(&user_id)?;
+    let removed = app_state.db_client.get_ban(ban_id).await?;
+    app_state.db_client.remove_ban(ban_id).await?;
+    app_state.db_client
+        .log_moderation(user_id, ModerationAction::RemoveBan, ModerationTarget::Ban, &ban_id.to_string(),
+            None, None, Some(serde_json::json!(removed)), None)
+        .await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "ban removed".to_string(),
+    }))
+}
+
+/// Accounts sharing a registration or login address with the user
+/// GET /bans/users/{user_id}
+pub async fn shared_ip_users(
+    Path(user_id): Path<Uuid>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    let users = app_state.db_client.shared_ip_users(user_id).await?;
+
+    Ok(Json(ban::SharedIpUsersResponseDto { users }))
+}
@@ -0,0 +1,191 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{Extension, extract::ws::{Message, Utf8Bytes, WebSocket, WebSocketUpgrade}, response::IntoResponse};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::{AppState, db::{filter::ContentSource, forum::ForumExt, permission::PermissionExt, user::UserExt},
+    error::{ForumError, ForumResult},
+    handler::filter,
+    middleware::JWTAuthMiddeware,
+    models::{ChatPost, Permission},
+};
+
+/// Messages sent to a client on connect
+const CHAT_HISTORY: usize = 50;
+/// Shortest time between two messages of one user
+const CHAT_INTERVAL: Duration = Duration::from_secs(3);
+const CHAT_MAX_LEN: usize = 255;
+
+/// Server to client messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatEvent {
+    History { posts: Vec<ChatPost> },
+    Message { post: ChatPost },
+    Deleted { id: i32 },
+    Error { message: String },
+}
+
+/// Client to server messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatCommand {
+    Post { content: String },
+    Delete { id: i32 },
+}
+
+/// Fans chat events out to every open socket
+#[derive(Debug, Clone)]
+pub struct ChatHub {
+    tx: broadcast::Sender<ChatEvent>,
+    last_post: Arc<DashMap<Uuid, Instant>>,
+}
+
+impl Default for ChatHub {
+    fn default() -> Self {
+        let (tx, _) = broadcast::channel(64);
+        Self {
+            tx,
+            last_post: Arc::new(DashMap::new()),
+        }
+    }
+}
+
+impl ChatHub {
+    pub fn publish(&self, event: ChatEvent) {
+        // no receivers just means nobody is connected
+        let _ = self.tx.send(event);
+    }
+
+    /// Records the attempt when the user may post again
+    fn allow(&self, user_id: Uuid) -> bool {
+        let now = Instant::now();
+        let mut last = self.last_post.entry(user_id).or_insert(now - CHAT_INTERVAL);
+        if now.duration_since(*last) < CHAT_INTERVAL {
+            return false;
+        }
+        *last = now;
+        true
+    }
+}
+
+/// Shoutbox socket, anyone can listen, posting needs a logged in user who isn't banned
+/// GET /forum/chat
+pub async fn chat_socket(
+    ws: WebSocketUpgrade,
+    Extension(app_state): Extension<Arc<AppState>>,
+    user: Option<Extension<JWTAuthMiddeware>>,
+) -> impl IntoResponse {
+    let user_id = user.map(|u| u.user.id);
+    ws.on_upgrade(move |socket| chat_session(socket, app_state, user_id))
+}
+
+async fn send_event(socket: &mut WebSocket, event: &ChatEvent) -> bool {
+    match serde_json::to_string(event) {
+        Ok(text) => socket.send(Message::Text(Utf8Bytes::from(text))).await.is_ok(),
+        Err(e) => {
+            error!("Chat event serialization failed: {}", e);
+            true
+        }
+    }
+}
+
+async fn chat_session(mut socket: WebSocket, app_state: Arc<AppState>, user_id: Option<Uuid>) {
+    let mut rx = app_state.chat.tx.subscribe();
+
+    match app_state.db_client.get_chat(CHAT_HISTORY).await {
+        Ok(mut posts) => {
+            posts.reverse();
+            if !send_event(&mut socket, &ChatEvent::History { posts }).await {
+                return;
+            }
+        }
+        Err(e) => error!("Loading chat failed: {}", e),
+    }
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => {
+                match event {
+                    Ok(event) => {
+                        if !send_event(&mut socket, &event).await {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => warn!("Chat client skipped {} messages", n),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            msg = socket.recv() => {
+                let text = match msg {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                let reply = match serde_json::from_str::<ChatCommand>(text.as_str()) {
+                    Ok(command) => handle_command(&app_state, user_id, command).await,
+                    Err(_) => Err("Unknown command".to_string()),
+                };
+                if let Err(message) = reply
+                    && !send_event(&mut socket, &ChatEvent::Error { message }).await {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Errors are reported to the sender only
+async fn handle_command(app_state: &AppState, user_id: Option<Uuid>, command: ChatCommand) -> Result<(), String> {
+    let user_id = user_id.ok_or("Log in to use the chat")?;
+    let result: ForumResult<Result<(), String>> = async {
+        // the ban may be newer than the socket
+        let user = match app_state.db_client.get_user(Some(user_id), None, None).await? {
+            Some(u) if !u.is_banned() => u,
+            _ => return Ok(Err("You have been banned".to_string())),
+        };
+
+        match command {
+            ChatCommand::Post { content } => {
+                let content = content.trim();
+                if content.is_empty() || content.chars().count() > CHAT_MAX_LEN {
+                    return Ok(Err(format!("A message must be 1-{} characters", CHAT_MAX_LEN)));
+                }
+                if !app_state.chat.allow(user.id) {
+                    return Ok(Err("Slow down".to_string()));
+                }
+                let content = match filter::check_content(app_state, user.id, ContentSource::Chat, None, None, content, false).await {
+                    Ok(checked) => checked.content,
+                    Err(ForumError::Forum(message)) | Err(ForumError::Http((_, message))) => return Ok(Err(message)),
+                    Err(e) => return Err(e),
+                };
+                // replacements may make it longer
+                if content.chars().count() > CHAT_MAX_LEN {
+                    return Ok(Err(format!("A message must be 1-{} characters", CHAT_MAX_LEN)));
+                }
+                app_state.update_session(&user.id)?;
+                let post = app_state.db_client.post_chat(user.id, &content).await?;
+                app_state.chat.publish(ChatEvent::Message { post });
+            }
+            ChatCommand::Delete { id } => {
+                if !app_state.db_client.has_permission(user.id, Permission::CanModerateChat, None).await? {
+                    return Ok(Err("Permission denied".to_string()));
+                }
+                if app_state.db_client.delete_chat(id).await? {
+                    app_state.chat.publish(ChatEvent::Deleted { id });
+                }
+            }
+        }
+        Ok(Ok(()))
+    }.await;
+
+    result.unwrap_or_else(|e| {
+        error!("Chat command failed: {}", e);
+        Err("Server error".to_string())
+    })
+}
@@ -0,0 +1,72 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{Extension, extract::Path, response::{IntoResponse, sse::{Event, KeepAlive, Sse}}};
+use futures_util::Stream;
+use tokio::sync::broadcast;
+
+use crate::{AppState, db::forum::ForumExt,
+    error::ForumResult,
+    handler::{forum::{caller_role, ensure_section_visible}, presence::ReaderGuard},
+    middleware::JWTAuthMiddeware,
+    models::{ForumEvent, Location},
+};
+
+/// Server-sent events out of the shared broadcast channel, limited to the ones `wanted` accepts,
+/// `guard` is dropped together with the stream
+fn event_stream<G: Send + 'static>(
+    rx: broadcast::Receiver<ForumEvent>,
+    wanted: impl Fn(&ForumEvent) -> bool + Send + 'static,
+    guard: G,
+) -> impl Stream<Item = Result<Event, axum::Error>> {
+    futures_util::stream::unfold((rx, wanted, guard), |(mut rx, wanted, guard)| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) if wanted(&event) => {
+                    return Some((Event::default().json_data(&event), (rx, wanted, guard)));
+                }
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+fn keep_alive() -> KeepAlive {
+    KeepAlive::new().interval(Duration::from_secs(20))
+}
+
+/// Live updates of a thread, the caller counts as reading it while the stream is open
+/// GET /forum/threads/{thread_id}/events
+pub async fn thread_events(
+    Path(thread_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    user: Option<Extension<JWTAuthMiddeware>>,
+) -> ForumResult<impl IntoResponse> {
+    let thread = app_state.db_client.get_thread_info(thread_id as i32).await?;
+    ensure_section_visible(&app_state, thread.section, caller_role(&user)).await?;
+
+    let user_id = user.map(|u| u.user.id);
+    if let Some(user_id) = user_id {
+        app_state.set_location(&user_id, Location::Thread { section: thread.section, thread_id });
+    }
+    // subscribe first so the reader gets its own presence update
+    let rx = app_state.events.subscribe();
+    let guard = ReaderGuard::new(app_state.clone(), thread.section, thread_id, user_id);
+    let stream = event_stream(rx, move |e| e.thread_id() == thread_id, guard);
+    Ok(Sse::new(stream).keep_alive(keep_alive()))
+}
+
+/// Live updates of every thread in a section
+/// GET /forum/section/{s_id}/events
+pub async fn section_events(
+    Path(s_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    user: Option<Extension<JWTAuthMiddeware>>,
+) -> ForumResult<impl IntoResponse> {
+    ensure_section_visible(&app_state, s_id, caller_role(&user)).await?;
+
+    // presence is only interesting inside the thread
+    let stream = event_stream(app_state.events.subscribe(),
+        move |e| e.section() == s_id && !matches!(e, ForumEvent::Presence { .. }), ());
+    Ok(Sse::new(stream).keep_alive(keep_alive()))
+}
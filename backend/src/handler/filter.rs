@@ -0,0 +1,61 @@
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{AppState, db::{filter::{ContentSource, FilterExt}, permission::PermissionExt, privacy::PrivacyExt},
+    error::{ForumError, ForumResult},
+    models::Permission,
+    utils::filter::{ContentFilter, count_links},
+};
+
+/// Content after the word filters ran
+#[derive(Debug)]
+pub struct CheckedContent {
+    pub title: Option<String>,
+    pub content: String,
+    /// A moderate filter matched, the forum content has to wait for approval
+    pub moderate: bool,
+}
+
+/// Runs the word filters and the spam checks over new or edited content, before Markdown is rendered.
+/// Moderators of the section skip the spam checks, edits skip flood and duplicate detection
+pub async fn check_content(app_state: &AppState, user_id: Uuid, source: ContentSource, section: Option<i64>,
+    title: Option<&str>, content: &str, edit: bool,
+) -> ForumResult<CheckedContent> {
+    let filter = ContentFilter::new(&app_state.db_client.get_word_filters().await?);
+    let title = title.map(|t| filter.apply(t));
+    let body = filter.apply(content);
+    let moderate = body.moderate || title.as_ref().is_some_and(|t| t.moderate);
+    if body.blocked || title.as_ref().is_some_and(|t| t.blocked) {
+        return Err(ForumError::Forum("Your text contains words that aren't allowed here".to_string()));
+    }
+    // only forum content has an approval queue
+    if moderate && source != ContentSource::Forum {
+        return Err(ForumError::Forum("Your text contains words that need a moderator's review".to_string()));
+    }
+
+    if app_state.db_client.has_permission(user_id, Permission::CanModerate, section).await? {
+        return Ok(CheckedContent { title: title.map(|t| t.text), content: body.text, moderate });
+    }
+    let settings = app_state.db_client.get_spam_settings().await?;
+
+    if app_state.db_client.post_count(user_id).await? < settings.new_user_posts as i64
+        && count_links(&body.text) > settings.max_links as usize {
+        return Err(ForumError::Forum(format!("New users can post at most {} links at once", settings.max_links)));
+    }
+    if edit {
+        return Ok(CheckedContent { title: title.map(|t| t.text), content: body.text, moderate });
+    }
+
+    // the shoutbox keeps its own, shorter interval
+    if settings.flood_seconds > 0 && source != ContentSource::Chat
+        && let Some(last) = app_state.db_client.last_posted(user_id, source).await?
+        && (Utc::now() - last).num_seconds() < settings.flood_seconds as i64 {
+        return Err(ForumError::Http((429, format!("Please wait {} seconds between two posts", settings.flood_seconds))));
+    }
+    if settings.duplicate_minutes > 0
+        && app_state.db_client.is_duplicate(user_id, source, &body.text, settings.duplicate_minutes).await? {
+        return Err(ForumError::Forum("You just posted the same text".to_string()));
+    }
+
+    Ok(CheckedContent { title: title.map(|t| t.text), content: body.text, moderate })
+}
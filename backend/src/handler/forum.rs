@@ -2,55 +2,114 @@ use std::sync::Arc;
 
 use axum::{Extension, Json, Router, extract::{Multipart, Path, Query}, middleware::from_fn, response::IntoResponse, routing::{delete, get, post, put}};
 use validator::Validate;
-use crate::{AppState, dto::{Response, forum::ActiveUsersDto}, error::ForumResult, middleware::is_banned, utils::file_upload};
-use crate::{db::forum::ForumExt,
+use crate::{AppState, dto::{Response, forum::ActiveUsersDto}, error::ForumResult, handler::{chat, events, filter, presence, revision}, middleware::is_banned, utils::{file_upload, markdown, mention}};
+use crate::{db::approval::ApprovalExt,
+    db::filter::ContentSource,
+    db::forum::ForumExt,
+    db::event::EventExt,
+    db::moderation::ModerationExt,
+    db::notification::NotificationExt,
+    db::permission::PermissionExt,
     db::user::UserExt,
-    models::UserRole,
+    models::{ForumEvent, Location, ModerationAction, ModerationTarget, Permission, Thread, User, UserRole},
     dto::forum,
     error::ForumError,
-    middleware::{role_check, JWTAuthMiddeware, auth},
+    middleware::{permission_check, JWTAuthMiddeware, auth, optional_auth},
 };
 
 pub fn forum_handler() -> Router<AppState> {
-    let admin_mod_only = from_fn(|state, req, next| 
-        role_check(state, req, next, vec![UserRole::Admin, UserRole::Mod]) );
-    let admin_only = from_fn(|state, req, next| 
-        role_check(state, req, next, vec![UserRole::Admin]) );
+    let can_admin = from_fn(|state, req, next| 
+        permission_check(state, req, next, Permission::CanAdmin) );
+    let can_upload = from_fn(|state, req, next| 
+        permission_check(state, req, next, Permission::CanUpload) );
 
     Router::new()
-        .route("/list", get(get_sections))
-        .route("/section/{s_id}", get(get_threads))
+        .route("/list", get(get_sections).layer(from_fn(optional_auth)))
+        .route("/chat", get(chat::chat_socket).layer(from_fn(optional_auth)))
+        .route("/section/{s_id}", get(get_threads).layer(from_fn(optional_auth)))
         .route("/section/add", put(add_section)
-            .layer(admin_only.clone())
+            .layer(can_admin)
             .layer(from_fn(auth))
             )
         .route("/threads/new", post(create_thread)
             .layer(from_fn(is_banned))
             .layer(from_fn(auth)) 
             )
-        .route("/threads", delete(delete_thread).layer(admin_mod_only.clone()) )
+        .route("/threads", delete(delete_thread)
+            .layer(from_fn(auth))
+            )
         .route("/threads", put(update_thread)
             .layer(from_fn(auth))
             )
-        .route("/threads/{thread_id}", get(get_thread))
+        .route("/threads/{thread_id}", get(get_thread).layer(from_fn(optional_auth)))
+        .route("/threads/{thread_id}/subscription", put(subscribe_thread)
+            .delete(unsubscribe_thread)
+            .layer(from_fn(auth))
+            )
+        .route("/threads/{thread_id}/events", get(events::thread_events).layer(from_fn(optional_auth)))
+        .route("/threads/{thread_id}/typing", put(presence::typing)
+            .layer(from_fn(is_banned))
+            .layer(from_fn(auth))
+            )
+        .route("/section/{s_id}/events", get(events::section_events).layer(from_fn(optional_auth)))
+        .route("/section/{s_id}/read", put(mark_section_read).layer(from_fn(auth)))
+        .route("/read", put(mark_forum_read).layer(from_fn(auth)))
+        .route("/section/{s_id}/subscription", put(subscribe_section)
+            .delete(unsubscribe_section)
+            .layer(from_fn(auth))
+            )
+        .route("/threads/sticky", put(sticky_thread)
+            .layer(from_fn(auth))
+            )
+        .route("/threads/move", put(move_thread)
+            .layer(from_fn(auth))
+            )
+        .route("/threads/merge", put(merge_threads)
+            .layer(from_fn(auth))
+            )
+        .route("/threads/split", post(split_thread)
+            .layer(from_fn(auth))
+            )
         .route("/post/new", 
             post(reply_thread)
                 .layer(from_fn(is_banned))
                 .layer(from_fn(auth))
             )
         .route("/threads/lock", put(lock_thread)
-            .layer(admin_mod_only.clone()) 
+            .layer(from_fn(auth))
+            )
+        .route("/threads/restore", put(restore_thread)
+            .layer(from_fn(auth))
             )
         .route("/post", put(update_post)
             .layer(from_fn(is_banned))
             .layer(from_fn(auth))
             )
+        .route("/post/{post_id}/revisions", get(revision::post_revisions).layer(from_fn(auth)))
+        .route("/threads/{thread_id}/revisions", get(revision::thread_revisions).layer(from_fn(auth)))
+        .route("/revisions/{revision_id}/diff", get(revision::revision_diff).layer(from_fn(auth)))
+        .route("/revisions/{revision_id}/restore", put(revision::restore_revision)
+            .layer(from_fn(is_banned))
+            .layer(from_fn(auth))
+            )
         .route("/post", delete(delete_post)
             .layer(from_fn(is_banned))
             .layer(from_fn(auth))
             )
-        .route("/active", get(list_active))
+        .route("/post/restore", put(restore_post)
+            .layer(from_fn(auth))
+            )
+        .route("/reactions", get(reaction_kinds))
+        .route("/post/{post_id}/reactions", get(post_reactions).layer(from_fn(optional_auth)))
+        .route("/post/{post_id}/reactions", put(add_reaction)
+            .delete(remove_reaction)
+            .layer(from_fn(is_banned))
+            .layer(from_fn(auth))
+            )
+        .route("/active", get(list_active).layer(from_fn(optional_auth)))
+        .route("/search", get(search).layer(from_fn(optional_auth)))
         .route("/upload_image", post(upload_image)
+            .layer(can_upload)
             .layer(from_fn(is_banned))
             .layer(from_fn(auth))
             )
@@ -59,6 +118,100 @@ pub fn forum_handler() -> Router<AppState> {
 
 }
 
+/// Role used for section visibility, `None` for anonymous callers who only see unrestricted sections
+pub fn caller_role(user: &Option<Extension<JWTAuthMiddeware>>) -> Option<UserRole> {
+    user.as_ref().map(|u| u.user.role)
+}
+
+/// Hidden sections are reported as missing so their existence doesn't leak
+pub async fn ensure_section_visible(app_state: &AppState, section: i64, role: Option<UserRole>) -> ForumResult<()> {
+    if app_state.db_client.section_visible(section, role).await? {
+        Ok(())
+    } else {
+        Err(ForumError::NotFound)
+    }
+}
+
+/// Deleted threads are hidden from everyone without `CanModerate` in their section
+pub async fn is_staff(app_state: &AppState, user_id: Option<uuid::Uuid>, section: i64) -> ForumResult<bool> {
+    match user_id {
+        Some(id) => app_state.db_client.has_permission(id, Permission::CanModerate, Some(section)).await,
+        None => Ok(false),
+    }
+}
+
+/// Deleted and pending posts are only there for moderators of the section
+async fn ensure_post_listed(app_state: &AppState, user_id: Option<uuid::Uuid>, post_id: i64, section: i64) -> ForumResult<()> {
+    if (app_state.db_client.post_deleted(post_id).await? || app_state.db_client.post_pending(post_id).await?)
+        && !is_staff(app_state, user_id, section).await? {
+        return Err(ForumError::NotFound);
+    }
+    Ok(())
+}
+
+/// Deleted threads can only be restored, anything else treats them as gone
+fn ensure_not_deleted(thread: &Thread) -> ForumResult<()> {
+    match thread.deleted_at {
+        Some(_) => Err(ForumError::NotFound),
+        None => Ok(()),
+    }
+}
+
+/// Pending threads are only shown to their author and the moderators
+fn ensure_not_pending(thread: &Thread, user_id: Option<uuid::Uuid>, staff: bool) -> ForumResult<()> {
+    if thread.pending && !staff && user_id != Some(thread.author) {
+        return Err(ForumError::NotFound);
+    }
+    Ok(())
+}
+
+/// Checks a permission inside a section, honouring the per-section overrides
+pub async fn ensure_permission(app_state: &AppState, user_id: uuid::Uuid, permission: Permission, section: i64) -> ForumResult<()> {
+    if app_state.db_client.has_permission(user_id, permission, Some(section)).await? {
+        Ok(())
+    } else {
+        Err(ForumError::Forbidden)
+    }
+}
+
+/// Upper bound of users resolved from a single post
+const MAX_MENTIONS: usize = 20;
+
+/// Renders the Markdown source to sanitised HTML, the users `@mentioned` in it are linked to their profile page
+pub async fn render_content(app_state: &AppState, source: &str) -> ForumResult<(String, Vec<User>)> {
+    let html = markdown::to_html(source);
+    let mut users = Vec::new();
+    for name in mention::extract_mentions(&html).iter().take(MAX_MENTIONS) {
+        if let Some(u) = app_state.db_client.get_user(None, Some(name), None).await? {
+            users.push(u);
+        }
+    }
+    let names: Vec<(uuid::Uuid, String)> = users.iter()
+        .map(|u| (u.id, u.name.clone()))
+        .collect();
+
+    let html = mention::link_mentions(&html, &names);
+
+    Ok((markdown::sanitize(&html, &file_upload::uploads_url(&app_state.env)), users))
+}
+
+/// Stores the mentions of a post and notifies the mentioned users who can read the section,
+/// mentions in pending posts are notified when the post is approved
+async fn record_mentions(app_state: &AppState, actor: uuid::Uuid, section: i64, thread_id: i64, post_id: Option<i64>, users: &[User], pending: bool) -> ForumResult<()> {
+    let ids: Vec<uuid::Uuid> = users.iter().map(|u| u.id).collect();
+    app_state.db_client.save_mentions(thread_id, post_id, &ids).await?;
+    if pending {
+        return Ok(());
+    }
+
+    for u in users {
+        if app_state.db_client.section_visible(section, Some(u.role)).await? {
+            app_state.db_client.notify_mention(u.id, actor, thread_id, post_id).await?;
+        }
+    }
+    Ok(())
+}
+
 pub async fn create_thread(Extension(app_state): Extension<Arc<AppState>>,
     Extension(user): Extension<JWTAuthMiddeware>,
     Json(body): Json<forum::CreateThreadDto>,
@@ -68,20 +221,39 @@ pub async fn create_thread(Extension(app_state): Extension<Arc<AppState>>,
     let user_id = uuid::Uuid::parse_str(&user.id.to_string()).unwrap();
     app_state.update_session(&user_id)?;
     app_state.db_client.update_user_activity(user_id).await?;
+    ensure_section_visible(&app_state, body.section, Some(user.role)).await?;
+    ensure_permission(&app_state, user_id, Permission::CanCreateThread, body.section).await?;
     let hash_tags = body.hash_tags;
-    app_state.db_client.create_thread(user_id, body.section, body.title.as_str(), body.content.as_str(), &hash_tags )
+    let checked = filter::check_content(&app_state, user_id, ContentSource::Forum, Some(body.section),
+        Some(&body.title), &body.content, false).await?;
+    let title = checked.title.unwrap_or(body.title);
+    let (content, mentioned) = render_content(&app_state, &checked.content).await?;
+    let pending = checked.moderate || app_state.db_client
+        .needs_approval(user_id, body.section, app_state.env.approval_min_posts, app_state.env.approval_min_account_days)
+        .await?;
+    let thread_id = app_state.db_client.create_thread(user_id, body.section, title.as_str(), content.as_str(), checked.content.as_str(), &hash_tags, pending)
         .await
         ?;
+    app_state.db_client.subscribe_thread(user_id, thread_id).await?;
+    record_mentions(&app_state, user_id, body.section, thread_id, None, &mentioned, pending).await?;
+    if !pending {
+        app_state.db_client.notify_new_thread(thread_id, user_id).await?;
+        app_state.db_client
+            .publish_event(&ForumEvent::NewThread { section: body.section, thread_id })
+            .await?;
+    }
 
     let response = forum::Response {
         status: "success",
-        message: "thread created".to_string(),
+        message: if pending { "thread awaits approval" } else { "thread created" }.to_string(),
     };
 
     Ok(Json(response))
 
 }
 
+/// Soft deletes a thread, it stays visible to moderators until it is purged
+/// DELETE /forum/threads
 pub async fn delete_thread(Extension(app_state): Extension<Arc<AppState>>,
     Extension(user): Extension<JWTAuthMiddeware>,
     Json(body): Json<forum::DeleteThreadDto>,
@@ -90,9 +262,18 @@ pub async fn delete_thread(Extension(app_state): Extension<Arc<AppState>>,
     let user_id = user.user.id;
     app_state.update_session(&user_id)?;
     app_state.db_client.update_user_activity(user_id).await?;
-    app_state.db_client.delete_thread(body.thread_id)
+    let thread = app_state.db_client.get_thread_info(body.thread_id as i32).await?;
+    ensure_permission(&app_state, user_id, Permission::CanModerate, thread.section).await?;
+    app_state.db_client.delete_thread(body.thread_id, user_id, body.reason.as_deref())
         .await
         ?;
+    app_state.db_client
+        .log_moderation(user_id, ModerationAction::DeleteThread, ModerationTarget::Thread, &body.thread_id.to_string(),
+            Some(thread.section), body.reason.as_deref(), Some(serde_json::json!(thread)), None)
+        .await?;
+    app_state.db_client
+        .publish_event(&ForumEvent::ThreadChanged { section: thread.section, thread_id: body.thread_id })
+        .await?;
 
     let response = forum::Response {
         status: "success",
@@ -102,6 +283,33 @@ pub async fn delete_thread(Extension(app_state): Extension<Arc<AppState>>,
     Ok(Json(response))
 }
 
+/// Brings back a soft deleted thread
+/// PUT /forum/threads/restore
+pub async fn restore_thread(Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<forum::RestoreThreadDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    let user_id = user.user.id;
+    app_state.update_session(&user_id)?;
+    let thread = app_state.db_client.get_thread_info(body.thread_id as i32).await?;
+    ensure_permission(&app_state, user_id, Permission::CanModerate, thread.section).await?;
+    app_state.db_client.restore_thread(body.thread_id).await?;
+    app_state.db_client
+        .log_moderation(user_id, ModerationAction::RestoreThread, ModerationTarget::Thread, &body.thread_id.to_string(),
+            Some(thread.section), body.reason.as_deref(),
+            Some(serde_json::json!({ "deleted_at": thread.deleted_at, "deleted_by": thread.deleted_by_name, "reason": thread.delete_reason })),
+            None)
+        .await?;
+    app_state.db_client
+        .publish_event(&ForumEvent::ThreadChanged { section: thread.section, thread_id: body.thread_id })
+        .await?;
+
+    Ok(Json(forum::Response {
+        status: "success",
+        message: "thread restored".to_string(),
+    }))
+}
 
 pub async fn update_thread(Extension(app_state): Extension<Arc<AppState>>,
     Extension(user): Extension<JWTAuthMiddeware>,
@@ -113,21 +321,39 @@ pub async fn update_thread(Extension(app_state): Extension<Arc<AppState>>,
     let user_id = uuid::Uuid::parse_str(&user.id.to_string()).unwrap();
     app_state.update_session(&user_id)?;
     app_state.db_client.update_user_activity(user_id).await?;
-    let user_role = user.role;
+    let thread = app_state.db_client.get_thread_info(body.thread_id as i32).await?;
+    ensure_not_deleted(&thread)?;
 
-    if user_role != UserRole::Admin && 
-        user_role != UserRole::Mod && 
-        app_state.db_client.get_thread_author(body.thread_id as i32).await? != user_id{
-        return Err(ForumError::Unauthorized);
+    if thread.author != user_id {
+        ensure_permission(&app_state, user_id, Permission::CanModerate, thread.section).await?;
     }
 
+    let checked = filter::check_content(&app_state, user_id, ContentSource::Forum, Some(thread.section),
+        Some(&body.title), &body.content, true).await?;
+    let title = checked.title.unwrap_or(body.title);
+    let (content, mentioned) = render_content(&app_state, &checked.content).await?;
+    app_state.db_client
+        .update_thread(body.thread_id, title.as_str(), content.as_str(), checked.content.as_str(), user_id, body.reason.as_deref())
+        .await?;
+    if checked.moderate && !thread.pending {
+        app_state.db_client.hold_thread(body.thread_id).await?;
+    }
+    if thread.author != user_id {
+        app_state.db_client
+            .log_moderation(user_id, ModerationAction::EditThread, ModerationTarget::Thread, &body.thread_id.to_string(),
+                Some(thread.section), body.reason.as_deref(),
+                Some(serde_json::json!({ "title": thread.title, "content": thread.source })),
+                Some(serde_json::json!({ "title": title, "content": checked.content })))
+            .await?;
+    }
+    record_mentions(&app_state, user_id, thread.section, body.thread_id, None, &mentioned, thread.pending || checked.moderate).await?;
     app_state.db_client
-        .update_thread(body.thread_id, body.title.as_str(), body.content.as_str() )
+        .publish_event(&ForumEvent::ThreadChanged { section: thread.section, thread_id: body.thread_id })
         .await?;
 
     let response = forum::Response {
         status: "success",
-        message: "thread updated".to_string(),
+        message: if checked.moderate { "thread awaits approval" } else { "thread updated" }.to_string(),
     };
 
     Ok(Json(response))
@@ -138,31 +364,88 @@ pub async fn get_thread(
     Path(thread_id) : Path<i64>,
     Query(query_params): Query<forum::GetThreadsDto>,
     Extension(app_state): Extension<Arc<AppState>>,
+    user: Option<Extension<JWTAuthMiddeware>>,
 ) -> ForumResult<impl IntoResponse> {
 
 
     let thread = app_state.db_client
         .get_thread_info(thread_id as i32)
         .await?;
+    ensure_section_visible(&app_state, thread.section, caller_role(&user)).await?;
+
+    let user_id = user.map(|u| u.user.id);
+    let staff = is_staff(&app_state, user_id, thread.section).await?;
+    if !staff {
+        ensure_not_deleted(&thread)?;
+    }
+    ensure_not_pending(&thread, user_id, staff)?;
+    if let Some(id) = user_id {
+        app_state.set_location(&id, Location::Thread { section: thread.section, thread_id });
+    }
+    let limit = query_params.limit.unwrap_or(10);
+    let threaded = query_params.threaded.unwrap_or(false);
+    let first_unread = match user_id {
+        Some(id) => app_state.db_client.first_unread_post(id, thread_id).await?,
+        None => None,
+    };
+    let mut page = query_params.page.unwrap_or(1);
+    if query_params.unread.unwrap_or(false) && !threaded
+        && let Some((_, position)) = first_unread {
+        page = (position / limit.max(1) as i64) as i32 + 1;
+    }
 
     let posts = app_state.db_client
-        .get_thread(thread_id,query_params.page.unwrap_or(1),query_params.limit.unwrap_or(10))
+        .get_thread(thread_id, page, limit, threaded, user_id, staff)
         .await?;
 
+    let post_ids: Vec<i64> = posts.iter().map(|p| p.post.id).collect();
+    let reactions = app_state.db_client
+        .get_reaction_counts(&post_ids, user_id)
+        .await?;
+    let subscribed = match user_id {
+        Some(id) => app_state.db_client.thread_subscribed(id, thread_id).await?,
+        None => false,
+    };
+    if let Some(id) = user_id {
+        // The threaded view doesn't show posts in id order, so it only records the visit
+        let last_post = if threaded { 0 } else { post_ids.iter().copied().max().unwrap_or(0) };
+        app_state.db_client.mark_thread_read(id, thread_id, last_post).await?;
+    }
+
     let response = forum::GetThreadResponseDto {
         info: thread,
         posts,
+        reactions,
+        subscribed,
+        page,
+        first_unread: first_unread.map(|(id, _)| id),
     };
 
     Ok(Json(response))
 }
 
 pub async fn lock_thread(Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
     Json(body): Json<forum::LockThreadDto>,
 ) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    let user_id = user.user.id;
+    app_state.update_session(&user_id)?;
+    let thread = app_state.db_client.get_thread_info(body.thread_id as i32).await?;
+    ensure_permission(&app_state, user_id, Permission::CanLock, thread.section).await?;
     app_state.db_client.lock_thread(body.thread_id, body.locked)
         .await
         ?;
+    let action = if body.locked { ModerationAction::LockThread } else { ModerationAction::UnlockThread };
+    app_state.db_client
+        .log_moderation(user_id, action, ModerationTarget::Thread, &body.thread_id.to_string(),
+            Some(thread.section), body.reason.as_deref(),
+            Some(serde_json::json!({ "locked": thread.locked })),
+            Some(serde_json::json!({ "locked": body.locked })))
+        .await?;
+    app_state.db_client
+        .publish_event(&ForumEvent::ThreadLocked { section: thread.section, thread_id: body.thread_id, locked: body.locked })
+        .await?;
 
     let response = forum::Response {
         status: "success",
@@ -172,18 +455,184 @@ pub async fn lock_thread(Extension(app_state): Extension<Arc<AppState>>,
     Ok(Json(response))
 }
 
+/// Pins a thread at the top of its section, or of every section as an announcement.
+/// Stickies need `CanLock` in the section, announcements `CanAdmin`
+/// PUT /forum/threads/sticky
+pub async fn sticky_thread(Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<forum::StickyThreadDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    let user_id = user.user.id;
+    app_state.update_session(&user_id)?;
+    let thread = app_state.db_client.get_thread_info(body.thread_id as i32).await?;
+    ensure_permission(&app_state, user_id, Permission::CanLock, thread.section).await?;
+    ensure_not_deleted(&thread)?;
+    let announcement = body.announcement.unwrap_or(thread.announcement);
+    if announcement != thread.announcement
+        && !app_state.db_client.has_permission(user_id, Permission::CanAdmin, None).await? {
+        return Err(ForumError::Forbidden);
+    }
+    if body.until.is_some_and(|until| until <= chrono::Utc::now()) {
+        return Err(ForumError::Forum("The pin has to end in the future".to_string()));
+    }
+
+    app_state.db_client.sticky_thread(body.thread_id, body.sticky, announcement, body.until).await?;
+    app_state.db_client
+        .log_moderation(user_id, ModerationAction::PinThread, ModerationTarget::Thread, &body.thread_id.to_string(),
+            Some(thread.section), body.reason.as_deref(),
+            Some(serde_json::json!({ "sticky": thread.sticky, "announcement": thread.announcement, "until": thread.sticky_until })),
+            Some(serde_json::json!({ "sticky": body.sticky, "announcement": announcement, "until": body.until })))
+        .await?;
+    app_state.db_client
+        .publish_event(&ForumEvent::ThreadChanged { section: thread.section, thread_id: body.thread_id })
+        .await?;
+
+    Ok(Json(forum::Response {
+        status: "success",
+        message: "thread updated".to_string(),
+    }))
+}
+
+/// Moves a thread to another section, both sections need `CanMove`
+/// PUT /forum/threads/move
+pub async fn move_thread(Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<forum::MoveThreadDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    let user = &user.user;
+    app_state.update_session(&user.id)?;
+    let thread = app_state.db_client.get_thread_info(body.thread_id as i32).await?;
+    ensure_not_deleted(&thread)?;
+    if thread.section == body.section {
+        return Err(ForumError::Forum("The thread already is in this section".to_string()));
+    }
+    ensure_permission(&app_state, user.id, Permission::CanMove, thread.section).await?;
+    ensure_section_visible(&app_state, body.section, Some(user.role)).await?;
+    ensure_permission(&app_state, user.id, Permission::CanMove, body.section).await?;
+
+    let stub = body.stub.unwrap_or(true);
+    app_state.db_client.move_thread(body.thread_id, body.section, stub).await?;
+    app_state.db_client
+        .log_moderation(user.id, ModerationAction::MoveThread, ModerationTarget::Thread, &body.thread_id.to_string(),
+            Some(thread.section), body.reason.as_deref(),
+            Some(serde_json::json!({ "section": thread.section })),
+            Some(serde_json::json!({ "section": body.section, "stub": stub })))
+        .await?;
+    app_state.db_client
+        .publish_event(&ForumEvent::ThreadChanged { section: thread.section, thread_id: body.thread_id })
+        .await?;
+    app_state.db_client
+        .publish_event(&ForumEvent::NewThread { section: body.section, thread_id: body.thread_id })
+        .await?;
+
+    Ok(Json(forum::Response {
+        status: "success",
+        message: "thread moved".to_string(),
+    }))
+}
+
+/// Moves the posts of `source` into `target` and leaves a redirect behind
+/// PUT /forum/threads/merge
+pub async fn merge_threads(Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<forum::MergeThreadsDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    let user = &user.user;
+    app_state.update_session(&user.id)?;
+    if body.source == body.target {
+        return Err(ForumError::Forum("A thread can't be merged into itself".to_string()));
+    }
+    let source = app_state.db_client.get_thread_info(body.source as i32).await?;
+    let target = app_state.db_client.get_thread_info(body.target as i32).await?;
+    ensure_not_deleted(&source)?;
+    ensure_not_deleted(&target)?;
+    if source.pending || target.pending {
+        return Err(ForumError::Forum("Threads awaiting approval can't be merged".to_string()));
+    }
+    ensure_permission(&app_state, user.id, Permission::CanMove, source.section).await?;
+    ensure_section_visible(&app_state, target.section, Some(user.role)).await?;
+    ensure_permission(&app_state, user.id, Permission::CanMove, target.section).await?;
+
+    app_state.db_client.merge_threads(body.source, body.target).await?;
+    app_state.db_client
+        .log_moderation(user.id, ModerationAction::MergeThread, ModerationTarget::Thread, &body.source.to_string(),
+            Some(source.section), body.reason.as_deref(),
+            Some(serde_json::json!({ "title": source.title, "section": source.section })),
+            Some(serde_json::json!({ "merged_into": body.target, "section": target.section })))
+        .await?;
+    app_state.db_client
+        .publish_event(&ForumEvent::ThreadChanged { section: source.section, thread_id: body.source })
+        .await?;
+    app_state.db_client
+        .publish_event(&ForumEvent::ThreadChanged { section: target.section, thread_id: body.target })
+        .await?;
+
+    Ok(Json(forum::Response {
+        status: "success",
+        message: "threads merged".to_string(),
+    }))
+}
+
+/// Moves the selected posts of a thread into a new one
+/// POST /forum/threads/split
+pub async fn split_thread(Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<forum::SplitThreadDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    let user = &user.user;
+    app_state.update_session(&user.id)?;
+    let thread = app_state.db_client.get_thread_info(body.thread_id as i32).await?;
+    ensure_not_deleted(&thread)?;
+    if thread.pending {
+        return Err(ForumError::Forum("Threads awaiting approval can't be split".to_string()));
+    }
+    let section = body.section.unwrap_or(thread.section);
+    ensure_permission(&app_state, user.id, Permission::CanMove, thread.section).await?;
+    if section != thread.section {
+        ensure_section_visible(&app_state, section, Some(user.role)).await?;
+        ensure_permission(&app_state, user.id, Permission::CanMove, section).await?;
+    }
+
+    let thread_id = app_state.db_client
+        .split_thread(body.thread_id, &body.post_ids, &body.title, section)
+        .await?;
+    app_state.db_client
+        .log_moderation(user.id, ModerationAction::SplitThread, ModerationTarget::Thread, &body.thread_id.to_string(),
+            Some(thread.section), body.reason.as_deref(),
+            Some(serde_json::json!({ "post_ids": body.post_ids })),
+            Some(serde_json::json!({ "thread_id": thread_id, "title": body.title, "section": section })))
+        .await?;
+    app_state.db_client
+        .publish_event(&ForumEvent::ThreadChanged { section: thread.section, thread_id: body.thread_id })
+        .await?;
+    app_state.db_client
+        .publish_event(&ForumEvent::NewThread { section, thread_id })
+        .await?;
+
+    Ok(Json(forum::SplitThreadResponseDto {
+        status: "success",
+        thread_id,
+    }))
+}
+
 pub async fn get_sections(
     Extension(app_state): Extension<Arc<AppState>>,
     user: Option<Extension<JWTAuthMiddeware>>,
 ) -> ForumResult<impl IntoResponse> {
+    let role = caller_role(&user);
     let user_id = user.map(|u| u.user.id);
 
     if let Some(user_id) = user_id {
         app_state.update_session(&user_id)?;
+        app_state.set_location(&user_id, Location::Index);
         app_state.db_client.update_user_activity(user_id).await?;
     }
 
-    let sections = app_state.db_client.get_sections(user_id).await?;
+    let sections = app_state.db_client.get_sections(user_id, role).await?;
 
     let response = forum::GetSectionsResponseDto { sections };
 
@@ -192,11 +641,15 @@ pub async fn get_sections(
 
 pub async fn add_section(
     Extension(app_state): Extension<Arc<AppState>>,
-    Extension(_user): Extension<JWTAuthMiddeware>,
+    Extension(user): Extension<JWTAuthMiddeware>,
     Json(body): Json<forum::CreateSectionDto>,
 ) -> ForumResult<impl IntoResponse> {
     
-    app_state.db_client.create_section(&body.name, &body.description, &body.allowed_for).await?;
+    let section = app_state.db_client.create_section(&body.name, &body.description, &body.allowed_for, body.moderated).await?;
+    app_state.db_client
+        .log_moderation(user.user.id, ModerationAction::CreateSection, ModerationTarget::Section, &section.to_string(),
+            Some(section), None, None, Some(serde_json::json!(body)))
+        .await?;
 
     let r = Response {
         status: "success",
@@ -210,13 +663,22 @@ pub async fn get_threads(
     Path(thread_id) : Path<i64>,
     Query(query_params): Query<forum::GetThreadsDto>,
     Extension(app_state): Extension<Arc<AppState>>,
+    user: Option<Extension<JWTAuthMiddeware>>,
 ) -> ForumResult<impl IntoResponse> {
+    ensure_section_visible(&app_state, thread_id, caller_role(&user)).await?;
+    if let Some(user) = &user {
+        app_state.set_location(&user.user.id, Location::Section { section: thread_id });
+    }
 
-    let threads = app_state.db_client.get_section(thread_id, query_params.page.unwrap_or(1), query_params.limit.unwrap_or(10))
+    let role = caller_role(&user);
+    let user_id = user.map(|u| u.user.id);
+    let staff = is_staff(&app_state, user_id, thread_id).await?;
+    let threads = app_state.db_client.get_section(thread_id, query_params.page.unwrap_or(1), query_params.limit.unwrap_or(10), user_id, staff)
         .await
         ?;
+    let announcements = app_state.db_client.get_announcements(user_id, role).await?;
 
-    let response = forum::GetSectionResponseDto { threads };
+    let response = forum::GetSectionResponseDto { announcements, threads };
 
     Ok(Json(response))
 }
@@ -231,12 +693,50 @@ pub async fn reply_thread(Extension(app_state): Extension<Arc<AppState>>,
 
     app_state.update_session(&user_id)?;
     app_state.db_client.update_user_activity(user_id).await?;
-    app_state.db_client.add_post(user_id, body.t_id, body.content.as_str(), body.post_id)
+    let thread = app_state.db_client.get_thread_info(body.t_id as i32).await?;
+    ensure_section_visible(&app_state, thread.section, Some(user.role)).await?;
+    ensure_permission(&app_state, user_id, Permission::CanPost, thread.section).await?;
+    ensure_not_deleted(&thread)?;
+    let staff = is_staff(&app_state, Some(user_id), thread.section).await?;
+    ensure_not_pending(&thread, Some(user_id), staff)?;
+    if thread.moved_to.is_some() {
+        return Err(ForumError::Forum("This thread has been moved".to_string()));
+    }
+    if let Some(parent) = body.post_id {
+        if app_state.db_client.get_post_topic(parent).await? != body.t_id {
+            return Err(ForumError::Forum("Replied post belongs to another thread".to_string()));
+        }
+        if app_state.db_client.post_deleted(parent).await? {
+            return Err(ForumError::NotFound);
+        }
+        if !staff && app_state.db_client.post_pending(parent).await?
+            && app_state.db_client.get_post_author(parent).await? != Some(user_id) {
+            return Err(ForumError::NotFound);
+        }
+    }
+    let checked = filter::check_content(&app_state, user_id, ContentSource::Forum, Some(thread.section),
+        None, &body.content, false).await?;
+    let (content, mentioned) = render_content(&app_state, &checked.content).await?;
+    let pending = checked.moderate || app_state.db_client
+        .needs_approval(user_id, thread.section, app_state.env.approval_min_posts, app_state.env.approval_min_account_days)
+        .await?;
+    let post_id = app_state.db_client.add_post(user_id, body.t_id, content.as_str(), checked.content.as_str(), body.post_id, pending)
         .await
         ?;
+    app_state.db_client.subscribe_thread(user_id, body.t_id).await?;
+    if app_state.presence.stop_typing(body.t_id, user_id) {
+        presence::publish_presence(&app_state, thread.section, body.t_id);
+    }
+    record_mentions(&app_state, user_id, thread.section, body.t_id, Some(post_id), &mentioned, pending).await?;
+    if !pending {
+        app_state.db_client.notify_reply(post_id, user_id).await?;
+        app_state.db_client
+            .publish_event(&ForumEvent::NewPost { section: thread.section, thread_id: body.t_id, post_id })
+            .await?;
+    }
     let response = forum::Response {
         status: "success",
-        message: "post added".to_string(),
+        message: if pending { "post awaits approval" } else { "post added" }.to_string(),
     };
 
     Ok(Json(response))
@@ -250,30 +750,54 @@ pub async fn update_post(Extension(app_state): Extension<Arc<AppState>>,
     body.validate()?;
     let user = &user.user;
     let user_id = uuid::Uuid::parse_str(&user.id.to_string())?;
-    let user_role = user.role;
 
     app_state.update_session(&user_id)?;
     app_state.db_client.update_user_activity(user_id).await?;
-    if user_role != UserRole::Admin && 
-        user_role != UserRole::Mod && 
-        let Some(author_id) = app_state.db_client.get_post_author(body.post_id).await? &&
-        author_id != user_id  {
-            return Err(ForumError::Unauthorized);
+    let section = app_state.db_client.get_post_section(body.post_id).await?;
+    if app_state.db_client.post_deleted(body.post_id).await? {
+        return Err(ForumError::NotFound);
+    }
+    let by_staff = app_state.db_client.get_post_author(body.post_id).await? != Some(user_id);
+    if by_staff {
+        ensure_permission(&app_state, user_id, Permission::CanModerate, section).await?;
     }
 
-    app_state.db_client.update_post(body.post_id, body.content.as_str())
+    let before = app_state.db_client.get_post_source(body.post_id).await?;
+    let checked = filter::check_content(&app_state, user_id, ContentSource::Forum, Some(section),
+        None, &body.content, true).await?;
+    let (content, mentioned) = render_content(&app_state, &checked.content).await?;
+    app_state.db_client.update_post(body.post_id, content.as_str(), checked.content.as_str(), user_id, body.reason.as_deref())
         .await
         ?;
+    if checked.moderate {
+        app_state.db_client.hold_post(body.post_id).await?;
+    }
+    if by_staff {
+        app_state.db_client
+            .log_moderation(user_id, ModerationAction::EditPost, ModerationTarget::Post, &body.post_id.to_string(),
+                Some(section), body.reason.as_deref(),
+                Some(serde_json::json!({ "content": before })),
+                Some(serde_json::json!({ "content": checked.content })))
+            .await?;
+    }
+    let thread_id = app_state.db_client.get_post_topic(body.post_id).await?;
+    let pending = app_state.db_client.post_pending(body.post_id).await?;
+    record_mentions(&app_state, user_id, section, thread_id, Some(body.post_id), &mentioned, pending).await?;
+    app_state.db_client
+        .publish_event(&ForumEvent::PostEdited { section, thread_id, post_id: body.post_id })
+        .await?;
 
     let response = forum::Response {
         status: "success",
-        message: "post updated".to_string(),
+        message: if checked.moderate { "post awaits approval" } else { "post updated" }.to_string(),
     };
 
     Ok(Json(response))
 
 }
 
+/// Soft deletes a post, it stays visible to moderators until it is purged
+/// DELETE /forum/post
 pub async fn delete_post(Extension(app_state): Extension<Arc<AppState>>,
     Extension(user): Extension<JWTAuthMiddeware>,
     Json(body): Json<forum::DeletePostDto>,
@@ -281,22 +805,33 @@ pub async fn delete_post(Extension(app_state): Extension<Arc<AppState>>,
     body.validate()?;
     let user = &user.user;
     let user_id = uuid::Uuid::parse_str(&user.id.to_string()).unwrap();
-    let user_role = user.role;
 
     app_state.update_session(&user_id)?;
     app_state.db_client.update_user_activity(user_id).await?;
-    if user_role != UserRole::Admin && 
-        user_role != UserRole::Mod && 
-        let Some(author_id) = app_state.db_client.get_post_author(body.post_id).await? &&
-        author_id != user_id  {
-            return Err(ForumError::Unauthorized);
-    } 
-
-    if app_state.db_client.posts_since(body.post_id).await? > 0 && user.role == UserRole::User {
+    let section = app_state.db_client.get_post_section(body.post_id).await?;
+    let moderator = app_state.db_client.has_permission(user_id, Permission::CanModerate, Some(section)).await?;
+    if !moderator && app_state.db_client.get_post_author(body.post_id).await? != Some(user_id) {
+        return Err(ForumError::Unauthorized);
+    }
+
+    if !moderator && app_state.db_client.posts_since(body.post_id).await? > 0 {
         return Err(ForumError::Forum("Cannot delete posts that have answers".to_string()));
     }
 
-    app_state.db_client.delete_post(body.post_id)
+    let thread_id = app_state.db_client.get_post_topic(body.post_id).await?;
+    let author = app_state.db_client.get_post_author(body.post_id).await?;
+    let content = app_state.db_client.get_post_source(body.post_id).await?;
+    app_state.db_client.delete_post(body.post_id, user_id, body.reason.as_deref())
+        .await?;
+    if author != Some(user_id) {
+        app_state.db_client
+            .log_moderation(user_id, ModerationAction::DeletePost, ModerationTarget::Post, &body.post_id.to_string(),
+                Some(section), body.reason.as_deref(),
+                Some(serde_json::json!({ "thread_id": thread_id, "author": author, "content": content })), None)
+            .await?;
+    }
+    app_state.db_client
+        .publish_event(&ForumEvent::PostDeleted { section, thread_id, post_id: body.post_id })
         .await?;
 
     let response = forum::Response {
@@ -308,17 +843,181 @@ pub async fn delete_post(Extension(app_state): Extension<Arc<AppState>>,
 
 }
 
-pub async fn list_active(Extension(app_state): Extension<Arc<AppState>>) -> ForumResult<impl IntoResponse> {
-    let active = app_state.list_active();
+/// Brings back a soft deleted post
+/// PUT /forum/post/restore
+pub async fn restore_post(Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<forum::RestorePostDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    let user_id = user.user.id;
+    app_state.update_session(&user_id)?;
+    let section = app_state.db_client.get_post_section(body.post_id).await?;
+    ensure_permission(&app_state, user_id, Permission::CanModerate, section).await?;
+    let thread_id = app_state.db_client.get_post_topic(body.post_id).await?;
+    app_state.db_client.restore_post(body.post_id).await?;
+    app_state.db_client
+        .log_moderation(user_id, ModerationAction::RestorePost, ModerationTarget::Post, &body.post_id.to_string(),
+            Some(section), body.reason.as_deref(), None, Some(serde_json::json!({ "thread_id": thread_id })))
+        .await?;
+    app_state.db_client
+        .publish_event(&ForumEvent::PostEdited { section, thread_id, post_id: body.post_id })
+        .await?;
+
+    Ok(Json(forum::Response {
+        status: "success",
+        message: "post restored".to_string(),
+    }))
+}
+
+/// Full-text search across threads and posts
+/// GET /forum/search?q=...&section=&author=&from=&to=&page=&limit=
+/// Results from sections the caller's role cannot read are never returned
+pub async fn search(
+    Query(query_params): Query<forum::SearchDto>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    user: Option<Extension<JWTAuthMiddeware>>,
+) -> ForumResult<impl IntoResponse> {
+    query_params.validate()?;
+
+    let role = caller_role(&user);
+    if let Some(user) = user {
+        app_state.update_session(&user.user.id)?;
+    }
+
+    let results = app_state.db_client.search(&query_params, role).await?;
+
+    Ok(Json(forum::SearchResponseDto { results }))
+}
+
+/// Reactions users can choose from
+/// GET /forum/reactions
+pub async fn reaction_kinds(
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> ForumResult<impl IntoResponse> {
+    let kinds = app_state.db_client.get_reaction_kinds().await?;
+    Ok(Json(forum::ReactionKindsResponseDto { kinds }))
+}
+
+/// Who reacted to a post and with what
+/// GET /forum/post/{post_id}/reactions
+pub async fn post_reactions(
+    Path(post_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    user: Option<Extension<JWTAuthMiddeware>>,
+) -> ForumResult<impl IntoResponse> {
+    let section = app_state.db_client.get_post_section(post_id).await?;
+    ensure_section_visible(&app_state, section, caller_role(&user)).await?;
+    ensure_post_listed(&app_state, user.map(|u| u.user.id), post_id, section).await?;
+
+    let reactions = app_state.db_client.get_post_reactions(post_id).await?;
+    Ok(Json(forum::PostReactionsResponseDto { reactions }))
+}
+
+/// PUT /forum/post/{post_id}/reactions
+/// Adding a reaction twice is a no-op
+pub async fn add_reaction(
+    Path(post_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<forum::ReactionDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    let user = &user.user;
+    app_state.update_session(&user.id)?;
+    app_state.db_client.update_user_activity(user.id).await?;
+
+    let section = app_state.db_client.get_post_section(post_id).await?;
+    ensure_section_visible(&app_state, section, Some(user.role)).await?;
+    if app_state.db_client.post_deleted(post_id).await? {
+        return Err(ForumError::NotFound);
+    }
+    ensure_post_listed(&app_state, Some(user.id), post_id, section).await?;
+    app_state.db_client.add_reaction(post_id, user.id, &body.kind).await?;
+
+    Ok(Json(forum::Response {
+        status: "success",
+        message: "reaction added".to_string(),
+    }))
+}
+
+/// DELETE /forum/post/{post_id}/reactions
+/// Removing a reaction that isn't there is a no-op
+pub async fn remove_reaction(
+    Path(post_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<forum::ReactionDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    let user = &user.user;
+    app_state.update_session(&user.id)?;
+    app_state.db_client.update_user_activity(user.id).await?;
+
+    app_state.db_client.remove_reaction(post_id, user.id, &body.kind).await?;
+
+    Ok(Json(forum::Response {
+        status: "success",
+        message: "reaction removed".to_string(),
+    }))
+}
+
+/// Users seen in the last minutes and where they are, with `thread` also who reads and types there.
+/// Invisible users are only listed for staff, locations in hidden sections are left out
+/// GET /forum/active?thread=1
+pub async fn list_active(
+    Query(query_params): Query<forum::ActiveUsersQueryDto>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    user: Option<Extension<JWTAuthMiddeware>>,
+) -> ForumResult<impl IntoResponse> {
+    let role = caller_role(&user);
+    let caller = user.as_ref().map(|u| u.user.id);
+    let staff = match caller {
+        Some(id) => app_state.db_client.has_permission(id, Permission::CanWarn, None).await?,
+        None => false,
+    };
+
+    let mut visible_sections = std::collections::HashMap::new();
+    let mut users = Vec::new();
+    for mut session in app_state.list_active() {
+        if session.invisible && !staff && caller != Some(session.user_id) {
+            continue;
+        }
+        if let Some(section) = session.location.and_then(|l| l.section()) {
+            let visible = match visible_sections.get(&section) {
+                Some(visible) => *visible,
+                None => {
+                    let visible = app_state.db_client.section_visible(section, role).await?;
+                    visible_sections.insert(section, visible);
+                    visible
+                }
+            };
+            if !visible {
+                session.location = None;
+            }
+        }
+        users.push(session);
+    }
+
+    let presence = match query_params.thread {
+        Some(thread_id) => {
+            let thread = app_state.db_client.get_thread_info(thread_id as i32).await?;
+            ensure_section_visible(&app_state, thread.section, role).await?;
+            Some(presence::thread_presence(&app_state, thread_id))
+        }
+        None => None,
+    };
+
     Ok(Json(
-        ActiveUsersDto { count: active.len(), users: active }
+        ActiveUsersDto { count: users.len(), users, presence }
     ))
 }
 /// Upload image for posts
-/// POST /forum/upload_image
+/// POST /forum/upload_image?thread=1
 /// Requires authentication
 /// Multipart form with "avatar" field containing image file
 pub async fn upload_image(
+    Query(query_params): Query<forum::UploadImageQueryDto>,
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(user): Extension<JWTAuthMiddeware>,
     multipart: Multipart,
@@ -328,9 +1027,13 @@ pub async fn upload_image(
 
     app_state.update_session(&user_id)?;
     app_state.db_client.update_user_activity(user_id).await?;
-    
+
+    let thread = app_state.db_client.get_thread_info(query_params.thread as i32).await?;
+    ensure_section_visible(&app_state, thread.section, Some(user.role)).await?;
+
     // Save uploaded image
     let filename = file_upload::save_uploaded_image(multipart, &app_state.env, user_id).await?;
+    app_state.db_client.add_upload(&filename, user_id, thread.id).await?;
 
     // Generate full URL for the avatar
     let avatar_url = file_upload::get_avatar_url(&app_state.env, &filename);
@@ -343,3 +1046,103 @@ pub async fn upload_image(
     })))
 }
 
+/// Get notified about new replies in a thread
+/// PUT /forum/threads/{thread_id}/subscription
+pub async fn subscribe_thread(
+    Path(thread_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    let user = &user.user;
+    app_state.update_session(&user.id)?;
+    let thread = app_state.db_client.get_thread_info(thread_id as i32).await?;
+    ensure_section_visible(&app_state, thread.section, Some(user.role)).await?;
+    app_state.db_client.subscribe_thread(user.id, thread_id).await?;
+
+    Ok(Json(forum::Response {
+        status: "success",
+        message: "subscribed".to_string(),
+    }))
+}
+
+/// DELETE /forum/threads/{thread_id}/subscription
+pub async fn unsubscribe_thread(
+    Path(thread_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    app_state.db_client.unsubscribe_thread(user.user.id, thread_id).await?;
+
+    Ok(Json(forum::Response {
+        status: "success",
+        message: "unsubscribed".to_string(),
+    }))
+}
+
+/// Get notified about new threads in a section
+/// PUT /forum/section/{s_id}/subscription
+pub async fn subscribe_section(
+    Path(s_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    let user = &user.user;
+    app_state.update_session(&user.id)?;
+    ensure_section_visible(&app_state, s_id, Some(user.role)).await?;
+    app_state.db_client.subscribe_section(user.id, s_id).await?;
+
+    Ok(Json(forum::Response {
+        status: "success",
+        message: "subscribed".to_string(),
+    }))
+}
+
+/// DELETE /forum/section/{s_id}/subscription
+pub async fn unsubscribe_section(
+    Path(s_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    app_state.db_client.unsubscribe_section(user.user.id, s_id).await?;
+
+    Ok(Json(forum::Response {
+        status: "success",
+        message: "unsubscribed".to_string(),
+    }))
+}
+
+/// Marks every thread of a section as read
+/// PUT /forum/section/{s_id}/read
+pub async fn mark_section_read(
+    Path(s_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    let user = &user.user;
+    app_state.update_session(&user.id)?;
+    ensure_section_visible(&app_state, s_id, Some(user.role)).await?;
+    app_state.db_client.mark_section_read(user.id, s_id).await?;
+
+    Ok(Json(forum::Response {
+        status: "success",
+        message: "section marked as read".to_string(),
+    }))
+}
+
+/// Marks every visible thread as read
+/// PUT /forum/read
+pub async fn mark_forum_read(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    let user = &user.user;
+    app_state.update_session(&user.id)?;
+    app_state.db_client.mark_forum_read(user.id, user.role).await?;
+
+    Ok(Json(forum::Response {
+        status: "success",
+        message: "forum marked as read".to_string(),
+    }))
+}
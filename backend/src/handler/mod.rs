@@ -2,3 +2,12 @@ pub mod auth;
 pub mod user;
 pub mod forum;
 pub mod oauth;
+pub mod admin;
+pub mod chat;
+pub mod events;
+pub mod presence;
+pub mod revision;
+pub mod report;
+pub mod ban;
+pub mod approval;
+pub mod filter;
@@ -1,8 +1,10 @@
-use crate::{AppState, models::{User, UserRole}, utils::token::create_token};
+use std::net::SocketAddr;
+
+use crate::{AppState, db::ban::BanExt, handler::ban::ensure_not_banned, models::{User, UserRole}, utils::token::create_token};
 
 use async_trait::async_trait;
 use axum::{
-    Json, Router, extract::{Query, State}, http::StatusCode, response::{IntoResponse, Redirect},
+    Json, Router, extract::{ConnectInfo, Query, State}, http::StatusCode, response::{IntoResponse, Redirect},
     routing::get
 };
 use axum_extra::{extract::cookie::PrivateCookieJar};
@@ -21,9 +23,10 @@ pub struct AuthCallbackQuery {
 
 #[async_trait]
 pub trait OauthExt {
-    async fn find_or_create_user_from_google(&self, user_info: &GoogleUserInfo) -> ForumResult<User>;
-    async fn find_or_create_user_from_facebook(&self, user_info: &FacebookUserInfo) -> ForumResult<User>;
-    async fn find_or_create_user_from_discord(&self, user_info: &DiscordUserInfo) -> ForumResult<User>;
+    /// Also tells whether the account was created by this call
+    async fn find_or_create_user_from_google(&self, user_info: &GoogleUserInfo) -> ForumResult<(User, bool)>;
+    async fn find_or_create_user_from_facebook(&self, user_info: &FacebookUserInfo) -> ForumResult<(User, bool)>;
+    async fn find_or_create_user_from_discord(&self, user_info: &DiscordUserInfo) -> ForumResult<(User, bool)>;
 }
 
 pub fn auth_router() -> Router<AppState> { 
@@ -42,7 +45,7 @@ impl OauthExt for crate::db::DBClient {
     async fn find_or_create_user_from_google(
         &self,
         user_info: &GoogleUserInfo)
-        -> ForumResult<User> {
+        -> ForumResult<(User, bool)> {
         // First, try to find user by oauth_uid
         let existing_user = sqlx::query_as!(User, 
             r#"
@@ -55,7 +58,7 @@ impl OauthExt for crate::db::DBClient {
             .await?;
 
         if let Some(user) = existing_user {
-            return Ok(user);
+            return Ok((user, false));
         }
 
         // If not found, try to find by email
@@ -81,7 +84,7 @@ impl OauthExt for crate::db::DBClient {
                 .fetch_one(&self.pool)
                 .await?;
 
-            return Ok(updated_user);
+            return Ok((updated_user, false));
         }
 
         // Create new user
@@ -99,13 +102,13 @@ impl OauthExt for crate::db::DBClient {
             .fetch_one(&self.pool)
             .await?;
 
-        Ok(new_user)
+        Ok((new_user, true))
 }
 
 async fn find_or_create_user_from_facebook(
     &self,
     user_info: &FacebookUserInfo,
-) -> ForumResult<User> {
+) -> ForumResult<(User, bool)> {
     // First, try to find user by oauth_uid
     let existing_user = sqlx::query_as!(User, 
         r#"
@@ -118,7 +121,7 @@ async fn find_or_create_user_from_facebook(
         .await?;
 
     if let Some(user) = existing_user {
-        return Ok(user);
+        return Ok((user, false));
     }
 
     // If not found, try to find by email
@@ -145,7 +148,7 @@ async fn find_or_create_user_from_facebook(
                 .fetch_one(&self.pool)
                 .await?;
 
-            return Ok(updated_user);
+            return Ok((updated_user, false));
         }
     }
 
@@ -164,13 +167,13 @@ async fn find_or_create_user_from_facebook(
         .fetch_one(&self.pool)
         .await?;
 
-    Ok(new_user)
+    Ok((new_user, true))
 }
 
 async fn find_or_create_user_from_discord(
     &self,
     user_info: &DiscordUserInfo,
-) -> ForumResult<User> {
+) -> ForumResult<(User, bool)> {
     // First, try to find user by oauth_uid
     let existing_user = sqlx::query_as!(User, 
         r#"
@@ -183,7 +186,7 @@ async fn find_or_create_user_from_discord(
         .await?;
 
     if let Some(user) = existing_user {
-        return Ok(user);
+        return Ok((user, false));
     }
 
     // If not found, try to find by email
@@ -210,7 +213,7 @@ async fn find_or_create_user_from_discord(
                 .fetch_one(&self.pool)
                 .await?;
 
-            return Ok(updated_user);
+            return Ok((updated_user, false));
         }
     }
 
@@ -229,7 +232,7 @@ async fn find_or_create_user_from_discord(
         .fetch_one(&self.pool)
         .await?;
 
-    Ok(new_user)
+    Ok((new_user, true))
 }
 }
 
@@ -288,6 +291,7 @@ pub async fn discord_auth_start(
 #[axum::debug_handler]
 pub async fn google_auth_callback(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     jar: PrivateCookieJar,
     Query(query): Query<AuthCallbackQuery>,
 ) -> ForumResult<Json<Response>> {
@@ -313,8 +317,13 @@ pub async fn google_auth_callback(
     match state.oauth_service.exchange_google_code(query.code, pkce_verifier).await {
         Ok(user_info) => {
             // Find or create user in database
-
-            let user = state.db_client.find_or_create_user_from_google(&user_info).await?;
+            ensure_not_banned(&state, addr.ip(), Some(&user_info.email)).await?;
+            let (user, created) = state.db_client.find_or_create_user_from_google(&user_info).await?;
+            if user.is_banned() {
+                return Err(ForumError::Banned);
+            }
+            ensure_not_banned(&state, addr.ip(), Some(&user.email)).await?;
+            state.db_client.record_ip(user.id, addr.ip(), created).await?;
 
             let secret = env!("JWT_SECRET_KEY");
             let expires_in_seconds = env!("JWT_MAXAGE").parse::<i64>().unwrap_or(60);
@@ -375,6 +384,7 @@ pub async fn facebook_auth_start(
 #[axum::debug_handler]
 pub async fn facebook_auth_callback(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     jar: PrivateCookieJar,
     Query(query): Query<AuthCallbackQuery>,
 ) -> ForumResult<Json<Response>> {
@@ -392,7 +402,13 @@ pub async fn facebook_auth_callback(
     match state.oauth_service.exchange_facebook_code(query.code).await {
         Ok(user_info) => {
             // Find or create user in database
-            let user = state.db_client.find_or_create_user_from_facebook(&user_info).await?;
+            ensure_not_banned(&state, addr.ip(), user_info.email.as_deref()).await?;
+            let (user, created) = state.db_client.find_or_create_user_from_facebook(&user_info).await?;
+            if user.is_banned() {
+                return Err(ForumError::Banned);
+            }
+            ensure_not_banned(&state, addr.ip(), Some(&user.email)).await?;
+            state.db_client.record_ip(user.id, addr.ip(), created).await?;
 
             // Generate JWT token
             let secret = env!("JWT_SECRET_KEY");
@@ -429,6 +445,7 @@ pub async fn facebook_auth_callback(
 #[axum::debug_handler]
 pub async fn discord_auth_callback(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     jar: PrivateCookieJar,
     Query(query): Query<AuthCallbackQuery>,
 ) -> ForumResult<Json<Response>> {
@@ -446,7 +463,13 @@ pub async fn discord_auth_callback(
     match state.oauth_service.exchange_discord_code(query.code).await {
         Ok(user_info) => {
             // Find or create user in database
-            let user = state.db_client.find_or_create_user_from_discord(&user_info).await?;
+            ensure_not_banned(&state, addr.ip(), user_info.email.as_deref()).await?;
+            let (user, created) = state.db_client.find_or_create_user_from_discord(&user_info).await?;
+            if user.is_banned() {
+                return Err(ForumError::Banned);
+            }
+            ensure_not_banned(&state, addr.ip(), Some(&user.email)).await?;
+            state.db_client.record_ip(user.id, addr.ip(), created).await?;
 
             // Generate JWT token
             let secret = env!("JWT_SECRET_KEY");
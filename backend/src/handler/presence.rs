@@ -0,0 +1,161 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, atomic::{AtomicU64, Ordering}};
+use std::time::{Duration, Instant};
+
+use axum::{Extension, Json, extract::Path, response::IntoResponse};
+use dashmap::DashMap;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{AppState, db::forum::ForumExt,
+    dto::forum,
+    error::ForumResult,
+    handler::forum::{ensure_permission, ensure_section_visible},
+    middleware::JWTAuthMiddeware,
+    models::{ForumEvent, Permission},
+};
+
+/// How long a typing notice lasts unless the client renews it
+pub const TYPING_TIMEOUT: Duration = Duration::from_secs(6);
+
+/// Readers and typists of the threads whose streams are open on this instance
+#[derive(Debug, Clone, Default)]
+pub struct PresenceHub {
+    /// Open streams per thread, guests have no user id
+    readers: Arc<DashMap<i64, HashMap<u64, Option<Uuid>>>>,
+    typing: Arc<DashMap<i64, HashMap<Uuid, Instant>>>,
+    next_reader: Arc<AtomicU64>,
+}
+
+impl PresenceHub {
+    fn join(&self, thread_id: i64, user_id: Option<Uuid>) -> u64 {
+        let reader = self.next_reader.fetch_add(1, Ordering::Relaxed);
+        self.readers.entry(thread_id).or_default().insert(reader, user_id);
+        reader
+    }
+
+    fn leave(&self, thread_id: i64, reader: u64) {
+        if let Some(mut readers) = self.readers.get_mut(&thread_id) {
+            readers.remove(&reader);
+        }
+        self.readers.remove_if(&thread_id, |_, r| r.is_empty());
+    }
+
+    fn start_typing(&self, thread_id: i64, user_id: Uuid) {
+        self.typing.entry(thread_id).or_default().insert(user_id, Instant::now());
+    }
+
+    /// Returns whether the user was shown as typing
+    pub fn stop_typing(&self, thread_id: i64, user_id: Uuid) -> bool {
+        let removed = self.typing.get_mut(&thread_id)
+            .map(|mut t| t.remove(&user_id).is_some())
+            .unwrap_or(false);
+        self.typing.remove_if(&thread_id, |_, t| t.is_empty());
+        removed
+    }
+}
+
+/// Who is in a thread right now, invisible users are counted but not named
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ThreadPresence {
+    pub readers: usize,
+    pub users: Vec<String>,
+    pub typing: Vec<String>,
+}
+
+/// Names of the users in `ids` that don't hide from the online list
+fn visible_names(app_state: &AppState, ids: impl Iterator<Item = Uuid>) -> Vec<String> {
+    let mut names: Vec<String> = ids
+        .filter_map(|id| app_state.active_users.get(&id))
+        .filter(|s| !s.invisible)
+        .map(|s| s.username.clone())
+        .collect();
+    names.sort();
+    names
+}
+
+pub fn thread_presence(app_state: &AppState, thread_id: i64) -> ThreadPresence {
+    let hub = &app_state.presence;
+    let (guests, users) = match hub.readers.get(&thread_id) {
+        Some(readers) => {
+            let users: HashSet<Uuid> = readers.values().flatten().copied().collect();
+            (readers.values().filter(|u| u.is_none()).count(), users)
+        }
+        None => (0, HashSet::new()),
+    };
+
+    let typing: Vec<Uuid> = match hub.typing.get_mut(&thread_id) {
+        Some(mut typing) => {
+            typing.retain(|_, since| since.elapsed() < TYPING_TIMEOUT);
+            typing.keys().copied().collect()
+        }
+        None => Vec::new(),
+    };
+    hub.typing.remove_if(&thread_id, |_, t| t.is_empty());
+
+    ThreadPresence {
+        readers: guests + users.len(),
+        users: visible_names(app_state, users.into_iter()),
+        typing: visible_names(app_state, typing.into_iter()),
+    }
+}
+
+/// Pushes the presence of a thread to its event streams
+pub fn publish_presence(app_state: &AppState, section: i64, thread_id: i64) {
+    let ThreadPresence { readers, users, typing } = thread_presence(app_state, thread_id);
+    // no receivers just means nobody is watching
+    let _ = app_state.events.send(ForumEvent::Presence { section, thread_id, readers, users, typing });
+}
+
+/// Counts a reader for as long as its event stream is open
+pub struct ReaderGuard {
+    app_state: Arc<AppState>,
+    section: i64,
+    thread_id: i64,
+    reader: u64,
+}
+
+impl ReaderGuard {
+    pub fn new(app_state: Arc<AppState>, section: i64, thread_id: i64, user_id: Option<Uuid>) -> Self {
+        let reader = app_state.presence.join(thread_id, user_id);
+        publish_presence(&app_state, section, thread_id);
+        Self { app_state, section, thread_id, reader }
+    }
+}
+
+impl Drop for ReaderGuard {
+    fn drop(&mut self) {
+        self.app_state.presence.leave(self.thread_id, self.reader);
+        publish_presence(&self.app_state, self.section, self.thread_id);
+    }
+}
+
+/// Tells the other readers that the caller is writing a reply,
+/// clients repeat it while typing since the notice runs out after `TYPING_TIMEOUT`
+/// PUT /forum/threads/{thread_id}/typing
+pub async fn typing(
+    Path(thread_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    let user = &user.user;
+    app_state.update_session(&user.id)?;
+    let thread = app_state.db_client.get_thread_info(thread_id as i32).await?;
+    ensure_section_visible(&app_state, thread.section, Some(user.role)).await?;
+    ensure_permission(&app_state, user.id, Permission::CanPost, thread.section).await?;
+
+    app_state.presence.start_typing(thread_id, user.id);
+    publish_presence(&app_state, thread.section, thread_id);
+
+    // takes the notice down again unless it has been renewed meanwhile
+    let expired = app_state.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(TYPING_TIMEOUT).await;
+        publish_presence(&expired, thread.section, thread_id);
+    });
+
+    Ok(Json(forum::Response {
+        status: "success",
+        message: "typing".to_string(),
+    }))
+}
@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, Router, extract::{Path, Query}, middleware::from_fn, response::IntoResponse, routing::{get, post, put}};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{AppState, db::{notification::NotificationExt, permission::PermissionExt, report::ReportExt},
+    dto::{Response, report},
+    error::{ForumError, ForumResult},
+    middleware::{JWTAuthMiddeware, auth, is_banned},
+    models::{ModerationAction, NotificationKind, Permission, Report, ReportStatus},
+};
+
+pub fn report_handler() -> Router<AppState> {
+    Router::new()
+        .route("/", post(create_report)
+            .layer(from_fn(is_banned))
+            .layer(from_fn(auth))
+            )
+        .route("/", get(get_reports).layer(from_fn(auth)))
+        .route("/{report_id}/claim", put(claim_report).layer(from_fn(auth)))
+        .route("/{report_id}/resolve", put(resolve_report).layer(from_fn(auth)))
+        .route("/{report_id}/dismiss", put(dismiss_report).layer(from_fn(auth)))
+}
+
+/// Reports of messages, chat posts and profiles have no section and need the global permission
+async fn ensure_report_moderator(app_state: &AppState, user_id: Uuid, report: &Report) -> ForumResult<()> {
+    if app_state.db_client.has_permission(user_id, Permission::CanModerate, report.section).await? {
+        Ok(())
+    } else {
+        Err(ForumError::Forbidden)
+    }
+}
+
+/// Flag a post, thread, private message, chat post or profile for the moderators
+/// POST /reports
+pub async fn create_report(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<report::CreateReportDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    let user_id = user.user.id;
+    app_state.update_session(&user_id)?;
+    let item = app_state.db_client.reported_item(body.target_kind, &body.target_id, user_id).await?;
+    if item.author == Some(user_id) {
+        return Err(ForumError::Forum("You can't report yourself".to_string()));
+    }
+    let comment = body.comment.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    app_state.db_client
+        .create_report(user_id, body.target_kind, &body.target_id, &item, body.reason, comment)
+        .await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "report sent".to_string(),
+    }))
+}
+
+/// Reports the caller can moderate
+/// GET /reports?status=&target_kind=&page=&limit=
+pub async fn get_reports(
+    Query(query_params): Query<report::ReportQueryDto>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    query_params.validate()?;
+    app_state.update_session(&user.user.id)?;
+    let reports = app_state.db_client.get_reports(user.user.id, &query_params).await?;
+
+    Ok(Json(report::ReportsResponseDto { reports }))
+}
+
+/// Take over a report, and every other pending report on the same item
+/// PUT /reports/{report_id}/claim
+pub async fn claim_report(
+    Path(report_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    let user_id = user.user.id;
+    app_state.update_session(&user_id)?;
+    let report = app_state.db_client.get_report(report_id).await?;
+    ensure_report_moderator(&app_state, user_id, &report).await?;
+    app_state.db_client.claim_report(report_id, user_id).await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "report claimed".to_string(),
+    }))
+}
+
+async fn handle_report(app_state: &AppState, report_id: i64, user_id: Uuid, status: ReportStatus,
+    action: Option<ModerationAction>, note: Option<&str>) -> ForumResult<()> {
+    let report = app_state.db_client.get_report(report_id).await?;
+    ensure_report_moderator(app_state, user_id, &report).await?;
+    let note = note.map(str::trim).filter(|n| !n.is_empty());
+    let reporters = app_state.db_client.handle_report(report_id, user_id, status, action, note).await?;
+
+    let outcome = match status {
+        ReportStatus::Resolved => "Your report was handled",
+        _ => "Your report was dismissed",
+    };
+    let content = match note {
+        Some(note) => format!("{}: {}", outcome, note),
+        None => outcome.to_string(),
+    };
+    for reporter in reporters {
+        app_state.db_client
+            .notify(reporter, NotificationKind::ReportHandled, None, report.thread_id, None, Some(&content))
+            .await?;
+    }
+    Ok(())
+}
+
+/// Close the reports on an item, optionally recording what was done about it
+/// PUT /reports/{report_id}/resolve
+pub async fn resolve_report(
+    Path(report_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<report::HandleReportDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    app_state.update_session(&user.user.id)?;
+    handle_report(&app_state, report_id, user.user.id, ReportStatus::Resolved, body.action, body.note.as_deref()).await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "report resolved".to_string(),
+    }))
+}
+
+/// Close the reports on an item without action
+/// PUT /reports/{report_id}/dismiss
+pub async fn dismiss_report(
+    Path(report_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<report::HandleReportDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    app_state.update_session(&user.user.id)?;
+    handle_report(&app_state, report_id, user.user.id, ReportStatus::Dismissed, None, body.note.as_deref()).await?;
+
+    Ok(Json(Response {
+        status: "success",
+        message: "report dismissed".to_string(),
+    }))
+}
@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::{Path, Query}, response::IntoResponse};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{AppState, db::{event::EventExt, forum::ForumExt, moderation::ModerationExt},
+    dto::forum,
+    error::{ForumError, ForumResult},
+    handler::forum::{ensure_permission, ensure_section_visible, render_content},
+    middleware::JWTAuthMiddeware,
+    models::{ForumEvent, ModerationAction, ModerationTarget, Permission, Revision, User},
+    utils::diff::diff_lines,
+};
+
+/// Where an edited post lives and who wrote it
+struct Edited {
+    section: i64,
+    thread_id: i64,
+    author: Option<Uuid>,
+}
+
+async fn edited_post(app_state: &AppState, post_id: i64) -> ForumResult<Edited> {
+    Ok(Edited {
+        section: app_state.db_client.get_post_section(post_id).await?,
+        thread_id: app_state.db_client.get_post_topic(post_id).await?,
+        author: app_state.db_client.get_post_author(post_id).await?,
+    })
+}
+
+async fn edited_thread(app_state: &AppState, thread_id: i64) -> ForumResult<Edited> {
+    let thread = app_state.db_client.get_thread_info(thread_id as i32).await?;
+    Ok(Edited { section: thread.section, thread_id, author: Some(thread.author) })
+}
+
+async fn edited_by_revision(app_state: &AppState, revision: &Revision) -> ForumResult<Edited> {
+    match (revision.post_id, revision.thread_id) {
+        (Some(post_id), _) => edited_post(app_state, post_id).await,
+        (None, Some(thread_id)) => edited_thread(app_state, thread_id).await,
+        (None, None) => Err(ForumError::NotFound),
+    }
+}
+
+/// Edit histories are open to the author and the moderators of the section
+async fn ensure_history_visible(app_state: &AppState, user: &User, edited: &Edited) -> ForumResult<()> {
+    ensure_section_visible(app_state, edited.section, Some(user.role)).await?;
+    if edited.author != Some(user.id) {
+        ensure_permission(app_state, user.id, Permission::CanModerate, edited.section).await?;
+    }
+    Ok(())
+}
+
+/// Earlier versions of a reply
+/// GET /forum/post/{post_id}/revisions
+pub async fn post_revisions(
+    Path(post_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    let user = &user.user;
+    app_state.update_session(&user.id)?;
+    let edited = edited_post(&app_state, post_id).await?;
+    ensure_history_visible(&app_state, user, &edited).await?;
+    let revisions = app_state.db_client.get_post_revisions(post_id).await?;
+
+    Ok(Json(forum::RevisionsResponseDto { revisions }))
+}
+
+/// Earlier titles and opening posts of a thread
+/// GET /forum/threads/{thread_id}/revisions
+pub async fn thread_revisions(
+    Path(thread_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    let user = &user.user;
+    app_state.update_session(&user.id)?;
+    let edited = edited_thread(&app_state, thread_id).await?;
+    ensure_history_visible(&app_state, user, &edited).await?;
+    let revisions = app_state.db_client.get_thread_revisions(thread_id).await?;
+
+    Ok(Json(forum::RevisionsResponseDto { revisions }))
+}
+
+/// Line diff from a revision to a later one of the same post, or to the current content
+/// GET /forum/revisions/{revision_id}/diff?to=
+pub async fn revision_diff(
+    Path(revision_id): Path<i64>,
+    Query(query_params): Query<forum::RevisionDiffDto>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    let user = &user.user;
+    app_state.update_session(&user.id)?;
+    let from = app_state.db_client.get_revision(revision_id).await?;
+    let edited = edited_by_revision(&app_state, &from).await?;
+    ensure_history_visible(&app_state, user, &edited).await?;
+
+    let (title, content) = match query_params.to {
+        Some(to) => {
+            let to = app_state.db_client.get_revision(to).await?;
+            if (to.post_id, to.thread_id) != (from.post_id, from.thread_id) {
+                return Err(ForumError::Forum("Revisions of different posts can't be compared".to_string()));
+            }
+            (to.title, to.content)
+        }
+        None => match from.post_id {
+            Some(post_id) => (None, app_state.db_client.get_post_source(post_id).await?),
+            None => {
+                let thread = app_state.db_client.get_thread_info(edited.thread_id as i32).await?;
+                (Some(thread.title), thread.source)
+            }
+        },
+    };
+
+    Ok(Json(forum::RevisionDiffResponseDto {
+        lines: diff_lines(&from.content, &content),
+        title_before: from.title,
+        title_after: title,
+    }))
+}
+
+/// Puts the content of a revision back, the replaced content becomes a revision itself
+/// PUT /forum/revisions/{revision_id}/restore
+pub async fn restore_revision(
+    Path(revision_id): Path<i64>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Json(body): Json<forum::RestoreRevisionDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    let user = &user.user;
+    app_state.update_session(&user.id)?;
+    let revision = app_state.db_client.get_revision(revision_id).await?;
+    let edited = edited_by_revision(&app_state, &revision).await?;
+    ensure_section_visible(&app_state, edited.section, Some(user.role)).await?;
+    ensure_permission(&app_state, user.id, Permission::CanModerate, edited.section).await?;
+    let reason = body.reason.unwrap_or_else(|| format!("Restored revision {}", revision.id));
+    let (content, _) = render_content(&app_state, &revision.content).await?;
+
+    match revision.post_id {
+        Some(post_id) => {
+            let before = app_state.db_client.get_post_source(post_id).await?;
+            app_state.db_client.update_post(post_id, &content, &revision.content, user.id, Some(&reason)).await?;
+            app_state.db_client
+                .log_moderation(user.id, ModerationAction::RestoreRevision, ModerationTarget::Post, &post_id.to_string(),
+                    Some(edited.section), Some(&reason),
+                    Some(serde_json::json!({ "content": before })),
+                    Some(serde_json::json!({ "revision": revision.id, "content": revision.content })))
+                .await?;
+            app_state.db_client
+                .publish_event(&ForumEvent::PostEdited { section: edited.section, thread_id: edited.thread_id, post_id })
+                .await?;
+        }
+        None => {
+            let thread = app_state.db_client.get_thread_info(edited.thread_id as i32).await?;
+            if thread.moved_to.is_some() {
+                return Err(ForumError::Forum("This thread has been moved".to_string()));
+            }
+            let title = revision.title.as_deref().unwrap_or(&thread.title);
+            app_state.db_client
+                .update_thread(edited.thread_id, title, &content, &revision.content, user.id, Some(&reason))
+                .await?;
+            app_state.db_client
+                .log_moderation(user.id, ModerationAction::RestoreRevision, ModerationTarget::Thread, &edited.thread_id.to_string(),
+                    Some(edited.section), Some(&reason),
+                    Some(serde_json::json!({ "title": thread.title, "content": thread.source })),
+                    Some(serde_json::json!({ "revision": revision.id, "title": title, "content": revision.content })))
+                .await?;
+            app_state.db_client
+                .publish_event(&ForumEvent::ThreadChanged { section: edited.section, thread_id: edited.thread_id })
+                .await?;
+        }
+    }
+
+    Ok(Json(forum::Response {
+        status: "success",
+        message: "revision restored".to_string(),
+    }))
+}
@@ -3,20 +3,24 @@ use std::sync::Arc;
 use axum::{Extension, Json, Router, extract::{Path, Query}, middleware::{self, from_fn}, response::IntoResponse, routing::{get, post, put}};
 use axum::extract::Multipart;
 use validator::Validate;
-use crate::{AppState, error::ForumError, middleware::{auth, is_banned}};
-use crate::{db::user::UserExt,
-    models::UserRole,
+use crate::{AppState, error::ForumError, handler::{filter, forum::caller_role}, middleware::{auth, is_banned, optional_auth}};
+use crate::{db::{filter::ContentSource, message::MessageExt, moderation::ModerationExt, notification::NotificationExt, permission::PermissionExt, privacy::PrivacyExt, user::UserExt},
+    models::{ModerationAction, ModerationTarget, NotificationKind, Permission, PmPrivacy, PrivacySettings, User, UserRole},
     dto::user,
     error::ForumResult,
-    middleware::{role_check, JWTAuthMiddeware},
+    middleware::{permission_check, JWTAuthMiddeware},
     utils::password,
     utils::file_upload,
+    utils::markdown,
+    mail::mails::send_warning_email,
 };
 use tracing::error;
 
 pub fn user_handler() -> Router<AppState> {
-    let admin_mod_only = middleware::from_fn(|state, req, next|
-                    role_check(state, req, next, vec![UserRole::Admin, UserRole::Mod]) );
+    let can_warn = middleware::from_fn(|state, req, next|
+                    permission_check(state, req, next, Permission::CanWarn) );
+    let can_pm = middleware::from_fn(|state, req, next|
+                    permission_check(state, req, next, Permission::CanPm) );
 
     Router::new()
         .route("/me", get(get_me)
@@ -25,19 +29,43 @@ pub fn user_handler() -> Router<AppState> {
         .route("/user/{uuid}", get(get_user_data))
         .route("/user/{uuid}", post(update_user_data).layer(from_fn(auth)))
         .route("/list", get(get_users))
-        .route("/{user_id}/posts", get(user_posts))
-        .route("/{user_id}/threads", get(user_threads))
+        .route("/{user_id}/posts", get(user_posts).layer(from_fn(optional_auth)))
+        .route("/{user_id}/threads", get(user_threads).layer(from_fn(optional_auth)))
         .route("/{user_id}/warnings", get(user_warnings).layer(from_fn(auth)) )
-        .route("/message", post(send_pm).layer(from_fn(auth)) )
+        .route("/message", post(send_pm)
+            .layer(can_pm.clone())
+            .layer(from_fn(auth))
+            )
         .route("/unban", put(unban_user)
-            .layer(admin_mod_only.clone()) 
+            .layer(can_warn.clone()) 
             .layer(from_fn(auth))
             )
         .route("/warn", put(warn_user)
-            .layer(admin_mod_only)
+            .layer(can_warn.clone())
+            .layer(from_fn(auth))
+        )
+        .route("/warning_categories", get(warning_categories)
+            .layer(can_warn)
             .layer(from_fn(auth))
         )
         .route("/pms", get(get_pms).layer(from_fn(auth)) )
+        .route("/pms/{conversation_id}", get(get_conversation)
+            .delete(delete_conversation)
+            .layer(from_fn(auth)) )
+        .route("/pms/{conversation_id}", post(reply_pm)
+            .layer(can_pm)
+            .layer(from_fn(auth)) )
+        .route("/notifications", get(get_notifications).layer(from_fn(auth)) )
+        .route("/notifications/unread", get(unread_notifications).layer(from_fn(auth)) )
+        .route("/notifications/read", put(read_notifications).layer(from_fn(auth)) )
+        .route("/subscriptions", get(get_subscriptions).layer(from_fn(auth)) )
+        .route("/blocks", get(get_blocks).layer(from_fn(auth)) )
+        .route("/blocks/{user_id}", put(block_user)
+            .delete(unblock_user)
+            .layer(from_fn(auth)) )
+        .route("/privacy", get(get_privacy)
+            .put(update_privacy)
+            .layer(from_fn(auth)) )
         .route("/avatar", post(upload_avatar))
 }
 
@@ -195,6 +223,8 @@ pub async fn update_user_password(
     Ok(Json(response))
 }
 
+/// Update a user's profile, roles are only changed by admins
+/// POST /users/user/{uuid}
 pub async fn update_user_data(
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(user): Extension<JWTAuthMiddeware>,
@@ -212,15 +242,29 @@ pub async fn update_user_data(
         .await?;
 
     let _ = result.ok_or(ForumError::InvalidToken )?;
-    if user_id != body_uid 
-        && user.role != UserRole::Admin
-        && user.role != UserRole::Mod {
+    let target = app_state.db_client
+        .get_user(Some(body_uid), None, None)
+        .await?
+        .ok_or(ForumError::NotFound)?;
+
+    // Users edit their own profile, others need the user moderation rights and only admins
+    // may touch an admin account or hand out roles
+    let is_admin = app_state.db_client.has_permission(user_id, Permission::CanAdmin, None).await?;
+    let user_role = if user_id == body_uid {
+        target.role
+    } else {
+        if !app_state.db_client.has_permission(user_id, Permission::CanWarn, None).await?
+            || (!is_admin && app_state.db_client.has_permission(body_uid, Permission::CanAdmin, None).await?) {
             return Err(ForumError::Forbidden);
-    }
-
-    let user_role = UserRole::from_str(&body.role)?;
+        }
+        let role = UserRole::from_str(&body.role)?;
+        if role != target.role && !is_admin {
+            return Err(ForumError::Forbidden);
+        }
+        role
+    };
 
-    app_state.db_client.update_user_data(user_id, 
+    app_state.db_client.update_user_data(body_uid, 
         &body.name, 
         &body.email, 
         user_role, 
@@ -240,35 +284,86 @@ pub async fn update_user_data(
 
 }
 
+/// Warn a user, the escalation rules may ban them
+/// PUT /users/warn
 pub async fn warn_user(
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(user): Extension<JWTAuthMiddeware>,
     Json(body): Json<user::WarnUserDto>,
 ) -> ForumResult<impl IntoResponse> {
-
+    body.validate()?;
     let user = &user.user;
     let user_id = uuid::Uuid::parse_str(&user.id.to_string()).unwrap();
 
     app_state.update_session(&user_id)?;
+    let warned = app_state.db_client
+        .get_user(Some(body.uuid), None, None)
+        .await?
+        .ok_or(ForumError::NotFound)?;
+    let outcome = app_state.db_client
+        .warn_user(body.uuid, body.comment.as_deref(), user_id, body.category, body.banned)
+        .await?;
     app_state.db_client
-        .warn_user(body.uuid, body.comment.as_deref(), user_id, body.banned)
+        .notify(body.uuid, NotificationKind::Warning, Some(user_id), None, None, body.comment.as_deref())
+        .await?;
+    app_state.db_client
+        .log_moderation(user_id, ModerationAction::WarnUser, ModerationTarget::User, &body.uuid.to_string(),
+            None, body.comment.as_deref(),
+            Some(serde_json::json!({ "banned_until": warned.banned_until })),
+            Some(serde_json::json!({
+                "ban_days": body.banned,
+                "category": body.category,
+                "points": outcome.points,
+                "level": outcome.level,
+                "escalated_ban_days": outcome.escalated,
+                "banned_until": outcome.banned_until,
+            })))
         .await?;
 
-    let response = user::Response {
-        message: "User warned".to_string(),
+    let banned_until = outcome.banned_until.map(|b| b.format("%Y-%m-%d %H:%M UTC").to_string());
+    if let Err(e) = send_warning_email(&warned.email, &warned.name, body.comment.as_deref().unwrap_or("-"),
+        outcome.level, banned_until.as_deref()).await {
+        error!("Failed to send the warning email: {}", e);
+    }
+
+    Ok(Json(user::WarnUserResponseDto {
         status: "success",
-    };
+        outcome,
+    }))
+}
 
-    Ok(Json(response))
+/// Categories to pick from when warning
+/// GET /users/warning_categories
+pub async fn warning_categories(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+) -> ForumResult<impl IntoResponse> {
+    app_state.update_session(&user.user.id)?;
+    let categories = app_state.db_client.get_warning_categories().await?;
+
+    Ok(Json(user::WarningCategoriesResponseDto { categories }))
 }
 
 pub async fn unban_user(
     Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user): Extension<JWTAuthMiddeware>,
     Json(body): Json<user::UnbanUserDto>,
 ) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    app_state.update_session(&user.user.id)?;
+    let banned = app_state.db_client
+        .get_user(Some(body.uuid), None, None)
+        .await?
+        .ok_or(ForumError::NotFound)?;
     app_state.db_client
         .unban_user(body.uuid)
         .await?;
+    app_state.db_client
+        .log_moderation(user.user.id, ModerationAction::UnbanUser, ModerationTarget::User, &body.uuid.to_string(),
+            None, body.reason.as_deref(),
+            Some(serde_json::json!({ "banned_until": banned.banned_until })),
+            Some(serde_json::json!({ "banned_until": null })))
+        .await?;
 
     let response = user::Response {
         message: "User unbanned".to_string(),
@@ -281,9 +376,10 @@ pub async fn unban_user(
 pub async fn user_posts(
     Path(user_id) : Path<uuid::Uuid>,
     Extension(app_state): Extension<Arc<AppState>>,
+    user: Option<Extension<JWTAuthMiddeware>>,
 ) -> ForumResult<impl IntoResponse> {
     let result = app_state.db_client
-        .get_user_posts(Some(user_id), None)
+        .get_user_posts(Some(user_id), None, caller_role(&user))
         .await?;
 
     let response = user::UserPostsResponseDto {
@@ -296,9 +392,10 @@ pub async fn user_posts(
 pub async fn user_threads(
     Path(user_id) : Path<uuid::Uuid>,
     Extension(app_state): Extension<Arc<AppState>>,
+    user: Option<Extension<JWTAuthMiddeware>>,
 ) -> ForumResult<impl IntoResponse> {
     let threads = app_state.db_client
-        .get_user_threads(Some(user_id), None)
+        .get_user_threads(Some(user_id), None, caller_role(&user))
         .await?;
 
     let response = user::UserThreadsResponseDto {
@@ -323,16 +420,144 @@ pub async fn user_warnings(
     Ok(Json(response))
 }
 
+/// Blocks and the recipient's privacy settings, staff get past the privacy settings but not blocks
+async fn check_pm_allowed(app_state: &AppState, sender: &User, recipient: &User) -> ForumResult<()> {
+    let refused = || ForumError::Http((403, format!("{} doesn't accept messages from you", recipient.name)));
+
+    if app_state.db_client.is_blocked(recipient.id, sender.id).await? {
+        return Err(refused());
+    }
+    if app_state.db_client.has_permission(sender.id, Permission::CanWarn, None).await? {
+        return Ok(());
+    }
+    let privacy = app_state.db_client.get_privacy(recipient.id).await?;
+    match privacy.pm_privacy {
+        PmPrivacy::Everyone => Ok(()),
+        PmPrivacy::Nobody => Err(refused()),
+        PmPrivacy::MinPosts => {
+            if app_state.db_client.post_count(sender.id).await? >= privacy.pm_min_posts as i64 {
+                Ok(())
+            } else {
+                Err(refused())
+            }
+        }
+    }
+}
+
+/// Starts a conversation with one or more users
+/// POST /users/message
 pub async fn send_pm(
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(user): Extension<JWTAuthMiddeware>,
     Json(body): Json<user::SendPmDto>,
 ) -> ForumResult<impl IntoResponse> {
-    let user = &user.user;
-    let user_id = uuid::Uuid::parse_str(&user.id.to_string()).unwrap();
+    body.validate()?;
+    let user_id = user.user.id;
+    app_state.update_session(&user_id)?;
+
+    let mut recipients = Vec::new();
+    for name in &body.recipients {
+        let recipient = app_state.db_client.get_user(None, Some(name.trim()), None)
+            .await?
+            .ok_or_else(|| ForumError::NoSuchUser(name.clone()))?;
+        if recipient.id != user_id && !recipients.contains(&recipient.id) {
+            check_pm_allowed(&app_state, &user.user, &recipient).await?;
+            recipients.push(recipient.id);
+        }
+    }
+    if recipients.is_empty() {
+        return Err(ForumError::BadRequest);
+    }
 
-    app_state.db_client.send_pm(user_id, body.recipient_id, body.content.as_str())
+    let checked = filter::check_content(&app_state, user_id, ContentSource::Message, None,
+        Some(body.subject.trim()), &body.content, false).await?;
+    let subject = checked.title.unwrap_or_else(|| body.subject.trim().to_string());
+    let content = markdown::render(&checked.content, &file_upload::uploads_url(&app_state.env));
+    let conversation_id = app_state.db_client
+        .create_conversation(user_id, &recipients, &subject, &content, &checked.content)
         .await?;
+    for r in &recipients {
+        app_state.db_client
+            .notify(*r, NotificationKind::Pm, Some(user_id), None, None, Some(&subject))
+            .await?;
+    }
+
+    Ok(Json(user::SendPmResponseDto { status: "success", conversation_id }))
+}
+
+/// Conversations of the current user, latest activity first
+/// GET /users/pms?folder=inbox&page=1&limit=20
+pub async fn get_pms(
+    Query(params): Query<user::GetUserPmsDto>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> ForumResult<impl IntoResponse> {
+    let user_id = user.user.id;
+    let sent = match params.folder.as_deref() {
+        None | Some("inbox") => false,
+        Some("sent") => true,
+        Some(_) => return Err(ForumError::BadRequest),
+    };
+
+    app_state.update_session(&user_id)?;
+    let conversations = app_state.db_client
+        .get_conversations(user_id, sent, params.page.unwrap_or(1), params.limit.unwrap_or(20))
+        .await?;
+    let unread = app_state.db_client.unread_conversations(user_id).await?;
+
+    Ok(Json(user::UserPmsResponseDto { conversations, unread }))
+}
+
+/// Messages of a conversation, marks them as read
+/// GET /users/pms/{conversation_id}
+pub async fn get_conversation(
+    Path(conversation_id): Path<i64>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> ForumResult<impl IntoResponse> {
+    let user_id = user.user.id;
+
+    app_state.update_session(&user_id)?;
+    let conversation = app_state.db_client.get_conversation(user_id, conversation_id).await?;
+    let participants = app_state.db_client.get_participants(conversation_id).await?;
+    let messages = app_state.db_client.get_messages(user_id, conversation_id).await?;
+    app_state.db_client.mark_conversation_read(user_id, conversation_id).await?;
+
+    Ok(Json(user::ConversationResponseDto { conversation, participants, messages }))
+}
+
+/// Adds a message to a conversation
+/// POST /users/pms/{conversation_id}
+pub async fn reply_pm(
+    Path(conversation_id): Path<i64>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(body): Json<user::ReplyPmDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    let user_id = user.user.id;
+
+    app_state.update_session(&user_id)?;
+    let conversation = app_state.db_client.get_conversation(user_id, conversation_id).await?;
+    let participants = app_state.db_client.get_participants(conversation_id).await?;
+    // joining a conversation accepts messages in it, only blocks still apply
+    if !app_state.db_client.has_permission(user_id, Permission::CanWarn, None).await? {
+        for p in &participants {
+            if app_state.db_client.is_blocked(p.user_id, user_id).await? {
+                return Err(ForumError::Http((403, format!("{} doesn't accept messages from you", p.name))));
+            }
+        }
+    }
+    let checked = filter::check_content(&app_state, user_id, ContentSource::Message, None,
+        None, &body.content, false).await?;
+    let content = markdown::render(&checked.content, &file_upload::uploads_url(&app_state.env));
+    app_state.db_client.add_message(conversation_id, user_id, &content, &checked.content).await?;
+
+    for p in participants {
+        app_state.db_client
+            .notify(p.user_id, NotificationKind::Pm, Some(user_id), None, None, Some(&conversation.subject))
+            .await?;
+    }
 
     let response = user::Response {
         message: "Private message sent".to_string(),
@@ -342,23 +567,192 @@ pub async fn send_pm(
     Ok(Json(response))
 }
 
-pub async fn get_pms(
-    Path((page,limit)) : Path<(u32,usize)>,
+/// Deletes a conversation for the current user only
+/// DELETE /users/pms/{conversation_id}
+pub async fn delete_conversation(
+    Path(conversation_id): Path<i64>,
     Extension(user): Extension<JWTAuthMiddeware>,
     Extension(app_state): Extension<Arc<AppState>>,
 ) -> ForumResult<impl IntoResponse> {
-    let user = &user.user;
-    let user_id = uuid::Uuid::parse_str(&user.id.to_string()).unwrap();
+    let user_id = user.user.id;
 
     app_state.update_session(&user_id)?;
-    let pms = app_state.db_client.get_pms(user_id, page, limit)
+    app_state.db_client.delete_conversation(user_id, conversation_id).await?;
+
+    let response = user::Response {
+        message: "Conversation deleted".to_string(),
+        status: "success",
+    };
+
+    Ok(Json(response))
+}
+
+/// Users blocked by the current user
+/// GET /users/blocks
+pub async fn get_blocks(
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> ForumResult<impl IntoResponse> {
+    let user_id = user.user.id;
+
+    app_state.update_session(&user_id)?;
+    let blocked = app_state.db_client.get_blocked_users(user_id).await?;
+
+    Ok(Json(user::BlocksResponseDto { blocked }))
+}
+
+/// Blocks messages, mentions and notifications from a user and hides their posts
+/// PUT /users/blocks/{user_id}
+pub async fn block_user(
+    Path(blocked_id): Path<uuid::Uuid>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> ForumResult<impl IntoResponse> {
+    let user_id = user.user.id;
+    if blocked_id == user_id {
+        return Err(ForumError::BadRequest);
+    }
+
+    app_state.update_session(&user_id)?;
+    app_state.db_client.get_user(Some(blocked_id), None, None)
+        .await?
+        .ok_or(ForumError::NotFound)?;
+    app_state.db_client.block_user(user_id, blocked_id).await?;
+
+    let response = user::Response {
+        message: "User blocked".to_string(),
+        status: "success",
+    };
+
+    Ok(Json(response))
+}
+
+/// DELETE /users/blocks/{user_id}
+pub async fn unblock_user(
+    Path(blocked_id): Path<uuid::Uuid>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> ForumResult<impl IntoResponse> {
+    let user_id = user.user.id;
+
+    app_state.update_session(&user_id)?;
+    app_state.db_client.unblock_user(user_id, blocked_id).await?;
+
+    let response = user::Response {
+        message: "User unblocked".to_string(),
+        status: "success",
+    };
+
+    Ok(Json(response))
+}
+
+/// Privacy settings of the current user
+/// GET /users/privacy
+pub async fn get_privacy(
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> ForumResult<impl IntoResponse> {
+    let user_id = user.user.id;
+
+    app_state.update_session(&user_id)?;
+    let settings = app_state.db_client.get_privacy(user_id).await?;
+
+    Ok(Json(settings))
+}
+
+/// PUT /users/privacy
+pub async fn update_privacy(
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(body): Json<user::PrivacySettingsDto>,
+) -> ForumResult<impl IntoResponse> {
+    body.validate()?;
+    let user_id = user.user.id;
+
+    app_state.update_session(&user_id)?;
+    let invisible = match body.invisible {
+        Some(invisible) => invisible,
+        None => app_state.db_client.get_privacy(user_id).await?.invisible,
+    };
+    let settings = PrivacySettings {
+        pm_privacy: body.pm_privacy,
+        pm_min_posts: body.pm_min_posts.unwrap_or(0),
+        invisible,
+    };
+    app_state.db_client.set_privacy(user_id, &settings).await?;
+    app_state.set_invisible(&user_id, invisible);
+
+    let response = user::Response {
+        message: "Privacy settings saved".to_string(),
+        status: "success",
+    };
+
+    Ok(Json(response))
+}
+
+/// Notifications of the current user, newest first
+/// GET /users/notifications?unread=true&page=1&limit=20
+pub async fn get_notifications(
+    Query(params): Query<user::GetNotificationsDto>,
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> ForumResult<impl IntoResponse> {
+    let user_id = user.user.id;
+
+    app_state.update_session(&user_id)?;
+    let notifications = app_state.db_client
+        .get_notifications(user_id, params.unread.unwrap_or(false), params.page.unwrap_or(1), params.limit.unwrap_or(20))
         .await?;
+    let unread = app_state.db_client.unread_notifications(user_id).await?;
+
+    Ok(Json(user::NotificationsResponseDto { notifications, unread }))
+}
+
+/// Number of unread notifications, polled by the header bell
+/// GET /users/notifications/unread
+pub async fn unread_notifications(
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> ForumResult<impl IntoResponse> {
+    let unread = app_state.db_client.unread_notifications(user.user.id).await?;
+
+    Ok(Json(user::UnreadCountResponseDto { unread }))
+}
+
+/// PUT /users/notifications/read
+pub async fn read_notifications(
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Json(body): Json<user::ReadNotificationsDto>,
+) -> ForumResult<impl IntoResponse> {
+    let user_id = user.user.id;
+
+    app_state.update_session(&user_id)?;
+    app_state.db_client.mark_notifications_read(user_id, body.ids.as_deref()).await?;
 
-    let response = user::UserPmsResponseDto { pms };
+    let response = user::Response {
+        message: "Notifications marked as read".to_string(),
+        status: "success",
+    };
 
     Ok(Json(response))
 }
 
+/// Threads and sections the current user is subscribed to
+/// GET /users/subscriptions
+pub async fn get_subscriptions(
+    Extension(user): Extension<JWTAuthMiddeware>,
+    Extension(app_state): Extension<Arc<AppState>>,
+) -> ForumResult<impl IntoResponse> {
+    let user_id = user.user.id;
+
+    app_state.update_session(&user_id)?;
+    let threads = app_state.db_client.get_thread_subscriptions(user_id).await?;
+    let sections = app_state.db_client.get_section_subscriptions(user_id).await?;
+
+    Ok(Json(user::SubscriptionsResponseDto { threads, sections }))
+}
+
 /// Upload user avatar
 /// POST /users/avatar
 /// Requires authentication
@@ -54,3 +54,35 @@ pub async fn send_forgot_password_email(
     Ok(())
 }
 
+
+pub async fn send_warning_email(
+    to_email: &str,
+    username: &str,
+    comment: &str,
+    level: i64,
+    banned_until: Option<&str>
+) -> ForumResult<()> {
+    let subject = "You received a warning";
+    let template_path = "src/mail/templates/Warning-email.html";
+    let ban = match banned_until {
+        Some(until) => format!("You are banned until {}.", until),
+        None => String::new(),
+    };
+    let placeholders = vec![
+        ("{{username}}".to_string(), username.to_string()),
+        ("{{comment}}".to_string(), escape_html(comment)),
+        ("{{level}}".to_string(), level.to_string()),
+        ("{{ban}}".to_string(), ban)
+    ];
+
+    send_email(to_email, subject, template_path, &placeholders).await?;
+    Ok(())
+}
+
+/// Warning comments are written by moderators and end up in the html body
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -12,6 +12,7 @@ use std::env;
 use axum::{
     Extension, Router, 
     extract::FromRef,
+    middleware::from_fn,
     http::{header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE}, Method},
 };
 use axum_server::tls_rustls::RustlsConfig;
@@ -46,6 +47,10 @@ pub struct AppState {
     pub db_client: DBClient,
     pub key: Key,
     pub active_users: Arc<DashMap<Uuid, UserSession>>,
+    pub chat: handler::chat::ChatHub,
+    pub presence: handler::presence::PresenceHub,
+    /// Forum events received through LISTEN, see `db::event`
+    pub events: tokio::sync::broadcast::Sender<models::ForumEvent>,
 }
 
 impl AppState {
@@ -69,6 +74,31 @@ impl AppState {
         }
     }
 
+    /// Starts tracking a user the first time they show up after a restart or login
+    pub fn open_session(&self, user_id: Uuid, username: &str, invisible: bool) {
+        self.active_users
+            .entry(user_id)
+            .and_modify(|s| s.last_seen = Utc::now())
+            .or_insert_with(|| UserSession::new(user_id, username.to_string(), invisible));
+    }
+
+    pub fn close_session(&self, user_id: &Uuid) {
+        self.active_users.remove(user_id);
+    }
+
+    pub fn set_location(&self, user_id: &Uuid, location: models::Location) {
+        if let Some(mut s) = self.active_users.get_mut(user_id) {
+            s.location = Some(location);
+            s.last_seen = Utc::now();
+        }
+    }
+
+    pub fn set_invisible(&self, user_id: &Uuid, invisible: bool) {
+        if let Some(mut s) = self.active_users.get_mut(user_id) {
+            s.invisible = invisible;
+        }
+    }
+
     pub fn list_active(&self) -> Vec<UserSession> {
         self.active_users.iter()
             .filter_map(|x| {
@@ -89,16 +119,21 @@ pub struct UserSession {
     pub username: String,
     pub login_time: DateTime<Utc>,
     pub last_seen: DateTime<Utc>,
+    pub location: Option<models::Location>,
+    /// Only shown to staff
+    pub invisible: bool,
 }
 
 impl UserSession {
-    fn new(user_id: Uuid, username: String) -> Self {
+    fn new(user_id: Uuid, username: String, invisible: bool) -> Self {
         let now = Utc::now();
         Self {
             user_id,
             username,
             login_time: now,
             last_seen: now,
+            location: None,
+            invisible,
         }
     }
 }
@@ -117,7 +152,15 @@ pub fn create_router(app_state: Arc<AppState>) -> Router<AppState> {
         .nest("/auth", handler::auth::auth_handler())
         .nest("/users", handler::user::user_handler() )
         .nest("/forum", handler::forum::forum_handler() )
-        .nest_service("/uploads", ServeDir::new(&app_state.env.upload_dir))
+        .nest("/admin", handler::admin::admin_handler() )
+        .nest("/reports", handler::report::report_handler() )
+        .nest("/bans", handler::ban::ban_handler() )
+        .nest("/approvals", handler::approval::approval_handler() )
+        .nest("/uploads", Router::new()
+            .fallback_service(ServeDir::new(&app_state.env.upload_dir))
+            .layer(from_fn(middleware::upload_visibility))
+            .layer(from_fn(middleware::optional_auth))
+            )
         .layer(TraceLayer::new_for_http())
         .layer(Extension(app_state))
 }
@@ -182,12 +225,39 @@ async fn main() -> Result<()> {
 
     let db_client = DBClient::new(pool);
 
+    if config.purge_after_days > 0 {
+        let purge_db = db_client.clone();
+        let days = config.purge_after_days;
+        tokio::spawn(async move {
+            use db::forum::ForumExt;
+            loop {
+                match purge_db.purge_deleted(days).await {
+                    Ok((0, 0)) => {}
+                    Ok((threads, posts)) => tracing::info!("purged {} deleted threads and {} deleted posts", threads, posts),
+                    Err(e) => tracing::error!("purging deleted content failed: {:?}", e),
+                }
+                tokio::time::sleep(tokio::time::Duration::from_secs(60 * 60)).await;
+            }
+        });
+    }
+
+    let (events, _) = tokio::sync::broadcast::channel(256);
+    let listener_db = db_client.clone();
+    let listener_tx = events.clone();
+    tokio::spawn(async move {
+        use db::event::EventExt;
+        listener_db.listen_events(listener_tx).await;
+    });
+
     let app_state = AppState {
         oauth_service: OAuthService::from_env(),
         env: config.clone(),
         db_client,
         key: Key::generate(),
         active_users: Arc::new(DashMap::new()),
+        chat: handler::chat::ChatHub::default(),
+        presence: handler::presence::PresenceHub::default(),
+        events,
     };
 
     let a = Arc::new(app_state.clone());
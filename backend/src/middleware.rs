@@ -12,9 +12,9 @@ use axum_extra::extract::cookie::CookieJar;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    db::user::UserExt,
+    db::{forum::ForumExt, permission::PermissionExt, privacy::PrivacyExt, user::UserExt},
     error::{ForumError, ForumResult},
-    models::{User, UserRole},
+    models::{Permission, User},
     utils::token,
     AppState
 };
@@ -24,29 +24,20 @@ pub struct JWTAuthMiddeware {
     pub user: User,
 }
 
-pub async fn auth(
-    cookie_jar: CookieJar,
-    app_state: Extension<Arc<AppState>>,
-    mut req: Request,
-    next: Next,
-) -> ForumResult<impl IntoResponse> {
-    let cookies = cookie_jar
+fn request_token(cookie_jar: &CookieJar, req: &Request) -> Option<String> {
+    cookie_jar
             .get("token")
             .map(|cookie| cookie.value().to_string())
             .or_else(|| {
                 req.headers()
                     .get(header::AUTHORIZATION)
                     .and_then(|auth_header| auth_header.to_str().ok())
-                    .and_then(|auth_value| {
-                        if auth_value.starts_with("Bearer ") {
-                            Some(auth_value[7..].to_owned())
-                        } else {
-                            None
-                        }
-                    })  
-            });
+                    .and_then(|auth_value| auth_value.strip_prefix("Bearer ").map(str::to_owned))
+            })
+}
 
-    let token = cookies.ok_or(ForumError::Unauthorized)?;
+async fn authenticate(token: Option<String>, app_state: &AppState) -> ForumResult<User> {
+    let token = token.ok_or(ForumError::Unauthorized)?;
 
     let token_details = 
         match token::decode_token(token, app_state.env.jwt_secret.as_bytes()) {
@@ -67,7 +58,26 @@ pub async fn auth(
                 ForumError::NoSuchUser(user_id.to_string())
             })?;
 
-    let user = user.ok_or(ForumError::NoSuchUser(user_id.to_string()))?;
+    user.ok_or(ForumError::NoSuchUser(user_id.to_string()))
+}
+
+/// Registers the user in `active_users`, the privacy settings are only read for new sessions
+async fn track_session(user: &User, app_state: &AppState) -> ForumResult<()> {
+    if !app_state.active_users.contains_key(&user.id) {
+        let invisible = app_state.db_client.get_privacy(user.id).await?.invisible;
+        app_state.open_session(user.id, &user.name, invisible);
+    }
+    Ok(())
+}
+
+pub async fn auth(
+    cookie_jar: CookieJar,
+    app_state: Extension<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> ForumResult<impl IntoResponse> {
+    let user = authenticate(request_token(&cookie_jar, &req), &app_state).await?;
+    track_session(&user, &app_state).await?;
 
     req.extensions_mut().insert(JWTAuthMiddeware {
         user: user.clone(),
@@ -77,19 +87,40 @@ pub async fn auth(
 
 }
 
+/// Like `auth`, but lets anonymous requests through without the `JWTAuthMiddeware` extension
+pub async fn optional_auth(
+    cookie_jar: CookieJar,
+    app_state: Extension<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> ForumResult<impl IntoResponse> {
+    if let Ok(user) = authenticate(request_token(&cookie_jar, &req), &app_state).await {
+        track_session(&user, &app_state).await?;
+        req.extensions_mut().insert(JWTAuthMiddeware {
+            user,
+        });
+    }
+
+    Ok(next.run(req).await)
+}
+
 
-pub async fn role_check(
-    Extension(_app_state): Extension<Arc<AppState>>,
+/// Checks a forum-wide permission of the caller's groups, section overrides
+/// are not taken into account here and must be checked by the handler
+pub async fn permission_check(
+    Extension(app_state): Extension<Arc<AppState>>,
     req: Request,
     next: Next,
-    required_roles: Vec<UserRole>,
+    required: Permission,
 ) -> ForumResult<impl IntoResponse> {
-    let user = req
+    let user_id = req
             .extensions()
             .get::<JWTAuthMiddeware>()
-            .ok_or(ForumError::Unauthorized)?;
+            .ok_or(ForumError::Unauthorized)?
+            .user
+            .id;
 
-    if !required_roles.contains(&user.user.role) {
+    if !app_state.db_client.has_permission(user_id, required, None).await? {
         return Err(ForumError::Forbidden)
     }
 
@@ -110,3 +141,21 @@ pub async fn is_banned(
         Ok(next.run(req).await)
     }
 }
+
+/// Hides uploaded files that are only linked from sections the caller can't read
+pub async fn upload_visibility(
+    Extension(app_state): Extension<Arc<AppState>>,
+    req: Request,
+    next: Next) -> ForumResult<impl IntoResponse> {
+    let role = req
+        .extensions()
+        .get::<JWTAuthMiddeware>()
+        .map(|u| u.user.role);
+    let filename = req.uri().path().trim_start_matches('/').to_string();
+
+    if app_state.db_client.upload_visible(&filename, role).await? {
+        Ok(next.run(req).await)
+    } else {
+        Err(ForumError::NotFound)
+    }
+}
@@ -13,7 +13,7 @@ pub enum UserRole {
 }
 
 impl UserRole {
-    pub fn to_str(&self) -> String {
+    pub fn to_str(self) -> String {
         match self {
             Self::Admin => "Admin".to_string(),
             Self::Mod => "Mod".to_string(),
@@ -47,6 +47,199 @@ impl std::fmt::Display for UserRole {
     }
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "forum.permission", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+pub enum Permission {
+    CanPost,
+    CanCreateThread,
+    CanLock,
+    CanMove,
+    CanWarn,
+    CanUpload,
+    CanPm,
+    CanModerate,
+    /// Deleting shoutbox messages, the shoutbox belongs to no section
+    CanModerateChat,
+    CanAdmin,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "forum.notification_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    Reply,
+    Quote,
+    Mention,
+    NewThread,
+    Pm,
+    Warning,
+    /// A moderator resolved or dismissed the user's report
+    ReportHandled,
+    /// A moderator let the user's pending thread or post through
+    Approved,
+    Rejected,
+}
+
+/// Privileged operations recorded in the moderation log
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "forum.moderation_action", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    LockThread,
+    UnlockThread,
+    PinThread,
+    EditThread,
+    DeleteThread,
+    MoveThread,
+    MergeThread,
+    SplitThread,
+    EditPost,
+    DeletePost,
+    CreateSection,
+    WarnUser,
+    UnbanUser,
+    UpdateGroup,
+    UpdatePermissions,
+    UpdateModerators,
+    RestoreRevision,
+    RestoreThread,
+    RestorePost,
+    AddBan,
+    RemoveBan,
+    ApproveThread,
+    ApprovePost,
+    RejectThread,
+    RejectPost,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "forum.moderation_target", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationTarget {
+    User,
+    Thread,
+    Post,
+    Section,
+    Group,
+    Ban,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "forum.ban_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum BanKind {
+    Ip,
+    Email,
+    EmailDomain,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "forum.filter_action", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum FilterAction {
+    Replace,
+    Block,
+    /// Forum posts wait for approval, chat and private messages are refused
+    Moderate,
+}
+
+/// Censored word or regular expression
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct WordFilter {
+    pub id: i32,
+    pub pattern: String,
+    pub is_regex: bool,
+    pub action: FilterAction,
+    /// Used by the replace action
+    pub replacement: String,
+}
+
+/// Limits against spam, moderators aren't held to them
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SpamSettings {
+    /// Links allowed in one post or message of a new user
+    pub max_links: i32,
+    /// Users with fewer threads and posts count as new
+    pub new_user_posts: i32,
+    /// Identical content within this time is refused, 0 is off
+    pub duplicate_minutes: i32,
+    /// Shortest time between two posts or two private messages, 0 is off
+    pub flood_seconds: i32,
+}
+
+/// Ban of an address range, an email address or a whole email domain
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Ban {
+    pub id: i64,
+    pub kind: BanKind,
+    /// Address range of ip bans
+    pub ip: Option<String>,
+    /// Email address or domain
+    pub value: Option<String>,
+    pub reason: Option<String>,
+    pub created_by_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Account that logged in or registered from one of the addresses of another
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SharedIpUser {
+    pub id: uuid::Uuid,
+    pub name: String,
+    pub registration_ip: Option<String>,
+    pub last_login_ip: Option<String>,
+    pub banned_until: Option<DateTime<Utc>>,
+}
+
+/// Kinds of content that can be reported
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "forum.report_target", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReportTarget {
+    Post,
+    Thread,
+    /// Private message, only reportable by members of its conversation
+    Message,
+    Chat,
+    User,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "forum.report_reason", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReportReason {
+    Spam,
+    Abuse,
+    OffTopic,
+    Illegal,
+    Other,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "forum.report_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReportStatus {
+    Open,
+    /// A moderator is looking into it
+    Claimed,
+    Resolved,
+    Dismissed,
+}
+
+/// Who may start a private conversation with a user
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "forum.pm_privacy", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PmPrivacy {
+    #[default]
+    Everyone,
+    MinPosts,
+    Nobody,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq)]
 #[sqlx(type_name = "forum.user_status", rename_all = "lowercase")]
 pub enum UserStatus {
@@ -92,6 +285,65 @@ impl User {
     }
 }
 
+/// Live updates pushed to readers of a thread or section
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ForumEvent {
+    NewThread { section: i64, thread_id: i64 },
+    NewPost { section: i64, thread_id: i64, post_id: i64 },
+    PostEdited { section: i64, thread_id: i64, post_id: i64 },
+    PostDeleted { section: i64, thread_id: i64, post_id: i64 },
+    ThreadLocked { section: i64, thread_id: i64, locked: bool },
+    /// Posts were moved in or out, or the thread changed its section or was pinned
+    ThreadChanged { section: i64, thread_id: i64 },
+    /// Who is reading and typing in a thread, only known to the instance holding the streams
+    Presence { section: i64, thread_id: i64, readers: usize, users: Vec<String>, typing: Vec<String> },
+}
+
+impl ForumEvent {
+    pub fn section(&self) -> i64 {
+        match self {
+            Self::NewThread { section, .. }
+            | Self::NewPost { section, .. }
+            | Self::PostEdited { section, .. }
+            | Self::PostDeleted { section, .. }
+            | Self::ThreadLocked { section, .. }
+            | Self::ThreadChanged { section, .. }
+            | Self::Presence { section, .. } => *section,
+        }
+    }
+
+    pub fn thread_id(&self) -> i64 {
+        match self {
+            Self::NewThread { thread_id, .. }
+            | Self::NewPost { thread_id, .. }
+            | Self::PostEdited { thread_id, .. }
+            | Self::PostDeleted { thread_id, .. }
+            | Self::ThreadLocked { thread_id, .. }
+            | Self::ThreadChanged { thread_id, .. }
+            | Self::Presence { thread_id, .. } => *thread_id,
+        }
+    }
+}
+
+/// Page an online user is looking at
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Location {
+    Index,
+    Section { section: i64 },
+    Thread { section: i64, thread_id: i64 },
+}
+
+impl Location {
+    pub fn section(&self) -> Option<i64> {
+        match self {
+            Self::Index => None,
+            Self::Section { section } | Self::Thread { section, .. } => Some(*section),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct ChatPost {
     pub id: i32,
@@ -107,6 +359,17 @@ pub struct Section {
     pub name: String,
     pub description: Option<String>,
     pub new_posts: bool,
+    /// Threads with posts the caller hasn't read yet
+    pub unread: i64,
+    /// New threads and posts wait for approval
+    pub moderated: bool,
+    pub moderators: Vec<SectionModerator>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SectionModerator {
+    pub id: uuid::Uuid,
+    pub name: String,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
@@ -116,16 +379,50 @@ pub struct SectionsAllowed {
     pub role: UserRole,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Group {
+    pub id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub role: Option<UserRole>,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SectionPermission {
+    pub section: i64,
+    pub group_id: i32,
+    pub permission: Permission,
+    pub allowed: bool,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct Thread {
     pub id: i64,
     pub title: String,
     pub created_at: DateTime<Utc>,
+    /// Sanitised HTML
     pub content: String,
+    /// Markdown the HTML was rendered from
+    pub source: String,
     pub author: uuid::Uuid,
     pub section: i64,
     pub locked: bool,
+    /// Only while `sticky_until` hasn't passed
     pub sticky: bool,
+    /// Pinned in every section, with the same expiry as `sticky`
+    pub announcement: bool,
+    pub sticky_until: Option<DateTime<Utc>>,
+    /// Where a redirect stub points to
+    pub moved_to: Option<i64>,
+    pub modified_at: Option<DateTime<Utc>>,
+    pub modified_by_name: Option<String>,
+    /// Soft deleted threads are only shown to moderators
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub deleted_by_name: Option<String>,
+    pub delete_reason: Option<String>,
+    /// Waiting for approval, only shown to the author and moderators
+    pub pending: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
@@ -137,14 +434,74 @@ pub struct ThreadPosts {
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct Post {
     pub id: i64,
+    /// Sanitised HTML
     pub content: String,
+    /// Markdown the HTML was rendered from
+    pub source: String,
     pub author: Option<uuid::Uuid>,
     pub author_name: Option<String>,
     pub topic: i64,
     pub comments: Option<i64>,
     pub created_at: DateTime<Utc>,
     pub modified_at: Option<DateTime<Utc>>,
+    pub modified_by_name: Option<String>,
+    /// Soft deleted posts are only shown to moderators
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub deleted_by_name: Option<String>,
+    pub delete_reason: Option<String>,
     pub likes: i32,
+    /// Waiting for approval, only shown to the author and moderators
+    pub pending: bool,
+}
+
+/// Content of a post or opening post before one of its edits
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Revision {
+    pub id: i64,
+    pub thread_id: Option<i64>,
+    pub post_id: Option<i64>,
+    pub editor: Option<uuid::Uuid>,
+    pub editor_name: Option<String>,
+    pub edited_at: DateTime<Utc>,
+    /// Only kept for opening posts
+    pub title: Option<String>,
+    /// Markdown source
+    pub content: String,
+    pub reason: Option<String>,
+}
+
+/// A post with its nesting level in the reply tree, top level replies have depth 0
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ThreadedPost {
+    #[serde(flatten)]
+    pub post: Post,
+    pub depth: i32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ReactionKind {
+    pub name: String,
+    pub emoji: String,
+    pub position: i32,
+}
+
+/// Number of reactions of one kind on a post
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ReactionCount {
+    pub post_id: i64,
+    pub kind: String,
+    pub emoji: String,
+    pub count: i64,
+    pub reacted: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct PostReaction {
+    pub kind: String,
+    pub emoji: String,
+    pub user_id: uuid::Uuid,
+    pub user_name: String,
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
@@ -162,12 +519,173 @@ pub struct UserWarning {
     pub comment: Option<String>,
     pub warned_by: String,
     pub banned: bool,
+    pub category: Option<String>,
+    pub points: i32,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct WarningCategory {
+    pub id: i32,
+    pub name: String,
+    pub points: i32,
+    /// Warnings of the category stop counting after this many days, never when None
+    pub expire_days: Option<i32>,
+}
+
+/// Reaching `points` active warning points within `period_days` bans for `ban_days`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct EscalationRule {
+    pub id: i32,
+    pub points: i32,
+    pub period_days: i32,
+    pub ban_days: i32,
+}
+
+/// Result of a warning after the escalation rules ran
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WarningOutcome {
+    pub points: i32,
+    /// Active warning points of the user, this warning included
+    pub level: i64,
+    pub banned_until: Option<DateTime<Utc>>,
+    /// Ban length in days from a matched escalation rule
+    pub escalated: Option<i32>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq)]
+pub struct PrivacySettings {
+    pub pm_privacy: PmPrivacy,
+    pub pm_min_posts: i32,
+    pub invisible: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct BlockedUser {
+    pub user_id: uuid::Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Conversation {
+    pub id: i64,
+    pub subject: String,
+    pub created_by: Option<uuid::Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Names of everyone in the conversation
+    pub participants: Vec<String>,
+    pub last_author: Option<String>,
+    pub unread: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ConversationParticipant {
+    pub user_id: uuid::Uuid,
+    pub name: String,
+    pub avatar: Option<String>,
+    pub last_read_message: i64,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct PrivateMessage {
     pub id: i64,
+    pub conversation_id: i64,
     pub author: Option<uuid::Uuid>,
-    pub receiver: uuid::Uuid,
+    pub author_name: Option<String>,
+    /// Sanitised HTML
+    pub content: String,
+    /// Markdown the HTML was rendered from
+    pub source: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Notification {
+    pub id: i64,
+    pub kind: NotificationKind,
+    pub actor: Option<uuid::Uuid>,
+    pub actor_name: Option<String>,
+    pub thread_id: Option<i64>,
+    pub thread_title: Option<String>,
+    pub section: Option<i64>,
+    pub post_id: Option<i64>,
+    pub content: Option<String>,
+    pub is_read: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// `before` and `after` only hold the fields the action changed
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ModerationLogEntry {
+    pub id: i64,
+    pub actor: Option<uuid::Uuid>,
+    pub actor_name: Option<String>,
+    pub action: ModerationAction,
+    pub target_kind: ModerationTarget,
+    pub target_id: String,
+    pub section: Option<i64>,
+    pub reason: Option<String>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A flagged item in the moderator queue
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Report {
+    pub id: i64,
+    pub reporter: Option<uuid::Uuid>,
+    pub reporter_name: Option<String>,
+    pub target_kind: ReportTarget,
+    pub target_id: String,
+    pub reported_user: Option<uuid::Uuid>,
+    pub reported_user_name: Option<String>,
+    pub section: Option<i64>,
+    /// Thread of reported posts and threads, for linking
+    pub thread_id: Option<i64>,
+    pub reason: ReportReason,
+    pub comment: Option<String>,
+    /// Content of the item when it was reported
+    pub snapshot: String,
+    pub status: ReportStatus,
+    pub claimed_by_name: Option<String>,
+    pub handled_by_name: Option<String>,
+    pub action: Option<ModerationAction>,
+    pub resolution: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub handled_at: Option<DateTime<Utc>>,
+    /// Reports on the same item, this one included
+    pub report_count: i64,
+}
+
+/// A thread or post waiting in the approval queue
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct PendingItem {
+    pub thread_id: i64,
+    /// None for new threads
+    pub post_id: Option<i64>,
+    pub section: i64,
+    pub section_name: String,
+    pub title: String,
+    /// Markdown source
     pub content: String,
+    pub author: Option<uuid::Uuid>,
+    pub author_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ThreadSubscription {
+    pub thread_id: i64,
+    pub title: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SectionSubscription {
+    pub section: i64,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
 }
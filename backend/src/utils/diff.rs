@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+/// Texts with more line pairs than this are shown as fully replaced
+const MAX_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffKind {
+    Same,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+fn line(kind: DiffKind, text: &str) -> DiffLine {
+    DiffLine { kind, text: text.to_string() }
+}
+
+/// Line based diff from `old` to `new` over their longest common subsequence
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    if old.len().saturating_mul(new.len()) > MAX_CELLS {
+        return old.iter().map(|l| line(DiffKind::Removed, l))
+            .chain(new.iter().map(|l| line(DiffKind::Added, l)))
+            .collect();
+    }
+
+    // lcs[i][j] is the common length of old[i..] and new[j..]
+    let mut lcs = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            out.push(line(DiffKind::Same, old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(line(DiffKind::Removed, old[i]));
+            i += 1;
+        } else {
+            out.push(line(DiffKind::Added, new[j]));
+            j += 1;
+        }
+    }
+    out.extend(old[i..].iter().map(|l| line(DiffKind::Removed, l)));
+    out.extend(new[j..].iter().map(|l| line(DiffKind::Added, l)));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use DiffKind::*;
+
+    fn kinds(diff: &[DiffLine]) -> Vec<(DiffKind, &str)> {
+        diff.iter().map(|l| (l.kind, l.text.as_str())).collect()
+    }
+
+    #[test]
+    fn equal_texts_are_unchanged() {
+        let diff = diff_lines("a\nb", "a\nb");
+        assert_eq!(kinds(&diff), vec![(Same, "a"), (Same, "b")]);
+    }
+
+    #[test]
+    fn changed_line_is_removed_then_added() {
+        let diff = diff_lines("a\nb\nc", "a\nx\nc");
+        assert_eq!(kinds(&diff), vec![(Same, "a"), (Removed, "b"), (Added, "x"), (Same, "c")]);
+    }
+
+    #[test]
+    fn insertions_and_deletions_keep_common_lines() {
+        let diff = diff_lines("a\nb\nc\nd", "b\nc\nd\ne");
+        assert_eq!(kinds(&diff), vec![(Removed, "a"), (Same, "b"), (Same, "c"), (Same, "d"), (Added, "e")]);
+    }
+
+    #[test]
+    fn empty_sides() {
+        assert_eq!(kinds(&diff_lines("", "a\nb")), vec![(Added, "a"), (Added, "b")]);
+        assert_eq!(kinds(&diff_lines("a", "")), vec![(Removed, "a")]);
+        assert!(diff_lines("", "").is_empty());
+    }
+
+    #[test]
+    fn huge_texts_are_fully_replaced() {
+        let text = ["x"; 2001].join("\n");
+        let diff = diff_lines(&text, &text);
+        assert_eq!(diff.len(), 4002);
+        assert!(diff[..2001].iter().all(|l| l.kind == Removed));
+        assert!(diff[2001..].iter().all(|l| l.kind == Added));
+    }
+}
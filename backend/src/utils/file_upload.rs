@@ -102,10 +102,15 @@ pub async fn delete_old_avatar(
     Ok(())
 }
 
+/// Where uploaded files are served from, images in posts have to point here
+pub fn uploads_url(config: &Config) -> String {
+    format!("{}/uploads", config.public_url.trim_end_matches('/'))
+}
+
 /// Gets the full URL for an avatar file
 pub fn get_avatar_url(config: &Config, filename: &str) -> String {
     // In production, you might want to use a CDN or different URL structure
-    format!("{}/uploads/{}", config.host_url, filename)
+    format!("{}/{}", uploads_url(config), filename)
 }
 
 async fn serve_avatar(Path(image_id): Path<String>) -> ForumResult<impl IntoResponse> {
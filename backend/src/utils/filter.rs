@@ -0,0 +1,189 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use regex::{NoExpand, Regex, RegexBuilder};
+use tracing::error;
+
+use crate::models::{FilterAction, WordFilter};
+
+/// Patterns come from admins, this still keeps a careless one from eating memory
+const PATTERN_SIZE_LIMIT: usize = 1 << 20;
+
+static LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\b(?:https?://|www\.)[^\s"'<>]+"#).unwrap()
+});
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Case insensitive regex of a filter, plain words only match as a whole word
+pub fn compile(pattern: &str, is_regex: bool) -> Result<Regex, regex::Error> {
+    let pattern = if is_regex {
+        pattern.to_string()
+    } else {
+        let word = pattern.trim();
+        let start = if word.starts_with(is_word_char) { r"\b" } else { "" };
+        let end = if word.ends_with(is_word_char) { r"\b" } else { "" };
+        format!("{}{}{}", start, regex::escape(word), end)
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(true)
+        .size_limit(PATTERN_SIZE_LIMIT)
+        .build()
+}
+
+/// Result of running the word filters over a text
+#[derive(Debug, Default)]
+pub struct Filtered {
+    pub text: String,
+    /// A filter with the block action matched
+    pub blocked: bool,
+    /// A filter with the moderate action matched
+    pub moderate: bool,
+}
+
+struct Compiled {
+    regex: Regex,
+    action: FilterAction,
+    replacement: String,
+}
+
+/// The word filters, compiled once per check
+pub struct ContentFilter {
+    filters: Vec<Compiled>,
+}
+
+impl ContentFilter {
+    /// Patterns are checked when saved, one that doesn't compile anymore is skipped
+    pub fn new(filters: &[WordFilter]) -> Self {
+        let filters = filters.iter()
+            .filter_map(|f| match compile(&f.pattern, f.is_regex) {
+                Ok(regex) => Some(Compiled { regex, action: f.action, replacement: f.replacement.clone() }),
+                Err(e) => {
+                    error!("Skipping word filter {}: {}", f.id, e);
+                    None
+                }
+            })
+            .collect();
+        Self { filters }
+    }
+
+    /// Runs every filter over the text
+    pub fn apply(&self, text: &str) -> Filtered {
+        let mut result = Filtered {
+            text: text.to_string(),
+            ..Default::default()
+        };
+        for f in &self.filters {
+            if !f.regex.is_match(&result.text) {
+                continue;
+            }
+            match f.action {
+                FilterAction::Replace => {
+                    result.text = f.regex.replace_all(&result.text, NoExpand(&f.replacement)).into_owned();
+                }
+                FilterAction::Block => result.blocked = true,
+                FilterAction::Moderate => result.moderate = true,
+            }
+        }
+        result
+    }
+}
+
+/// Number of different web addresses in the text, a link showing its own address counts once
+pub fn count_links(text: &str) -> usize {
+    LINK.find_iter(text)
+        .map(|m| m.as_str().trim_end_matches(['.', ',', ')', '!', '?']).to_ascii_lowercase())
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(pattern: &str, is_regex: bool, action: FilterAction) -> WordFilter {
+        WordFilter {
+            id: 0,
+            pattern: pattern.to_string(),
+            is_regex,
+            action,
+            replacement: "***".to_string(),
+        }
+    }
+
+    #[test]
+    fn plain_words_match_whole_words_ignoring_case() {
+        let regex = compile("darn", false).unwrap();
+        assert!(regex.is_match("Darn it"));
+        assert!(regex.is_match("oh, DARN!"));
+        assert!(!regex.is_match("darnit"));
+        assert!(!regex.is_match("undarn"));
+    }
+
+    #[test]
+    fn plain_words_are_escaped() {
+        let regex = compile("a.b", false).unwrap();
+        assert!(regex.is_match("see a.b here"));
+        assert!(!regex.is_match("see axb here"));
+        // no word boundary next to a non word character
+        assert!(compile("$$$", false).unwrap().is_match("cash$$$now"));
+    }
+
+    #[test]
+    fn regex_patterns_are_used_as_is() {
+        let regex = compile(r"fr[e3]{2}\s*money", true).unwrap();
+        assert!(regex.is_match("get FR33 money"));
+        assert!(regex.is_match("freemoney"));
+        assert!(compile("(unclosed", true).is_err());
+    }
+
+    #[test]
+    fn replace_keeps_replacement_literal() {
+        let mut f = word("darn", false, FilterAction::Replace);
+        f.replacement = "$1".to_string();
+        let filtered = ContentFilter::new(&[f]).apply("darn, Darn and darning");
+        assert_eq!(filtered.text, "$1, $1 and darning");
+        assert!(!filtered.blocked);
+        assert!(!filtered.moderate);
+    }
+
+    #[test]
+    fn block_and_moderate_leave_the_text_alone() {
+        let filter = ContentFilter::new(&[
+            word("spam", false, FilterAction::Block),
+            word("casino", false, FilterAction::Moderate),
+        ]);
+        let blocked = filter.apply("buy spam");
+        assert!(blocked.blocked);
+        assert!(!blocked.moderate);
+        assert_eq!(blocked.text, "buy spam");
+
+        let moderated = filter.apply("online casino");
+        assert!(!moderated.blocked);
+        assert!(moderated.moderate);
+
+        let clean = filter.apply("nothing to see");
+        assert!(!clean.blocked && !clean.moderate);
+    }
+
+    #[test]
+    fn broken_patterns_are_skipped() {
+        let filter = ContentFilter::new(&[
+            word("(", true, FilterAction::Block),
+            word("bad", false, FilterAction::Replace),
+        ]);
+        let filtered = filter.apply("bad (");
+        assert_eq!(filtered.text, "*** (");
+        assert!(!filtered.blocked);
+    }
+
+    #[test]
+    fn counts_distinct_links() {
+        assert_eq!(count_links("no links here"), 0);
+        assert_eq!(count_links("see https://a.io and www.b.io/x"), 2);
+        assert_eq!(count_links("https://a.io, (https://a.io) and HTTPS://A.IO."), 1);
+        assert_eq!(count_links(r#"<a href="http://a.io/x">http://a.io/y</a>"#), 2);
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::{HashMap, HashSet};
+
+use ammonia::{Builder, UrlRelative};
+use pulldown_cmark::{Options, Parser, html::push_html};
+
+const TAGS: [&str; 25] = [
+    "a", "blockquote", "br", "code", "del", "em", "h1", "h2", "h3", "h4", "h5", "h6", "hr",
+    "img", "li", "ol", "p", "pre", "strong", "table", "tbody", "td", "th", "thead", "tr",
+];
+
+/// HTML of the Markdown, raw HTML in the source is still in there
+pub fn to_html(source: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    let mut html = String::new();
+    push_html(&mut html, Parser::new_ext(source, options));
+    html
+}
+
+/// Keeps the allowed tags, links get `rel=nofollow` and images have to come from `upload_url`
+pub fn sanitize(html: &str, upload_url: &str) -> String {
+    let upload_url = format!("{}/", upload_url.trim_end_matches('/'));
+    Builder::default()
+        .tags(HashSet::from(TAGS))
+        .tag_attributes(HashMap::from([
+            ("a", HashSet::from(["href", "title"])),
+            ("img", HashSet::from(["src", "alt", "title"])),
+        ]))
+        .generic_attributes(HashSet::new())
+        .allowed_classes(HashMap::from([("a", HashSet::from(["mention"]))]))
+        .url_schemes(HashSet::from(["http", "https", "mailto"]))
+        .url_relative(UrlRelative::PassThrough)
+        .link_rel(Some("nofollow noopener noreferrer"))
+        .attribute_filter(move |element, attribute, value| {
+            if element == "img" && attribute == "src" && !value.starts_with(&upload_url) {
+                return None;
+            }
+            Some(value.into())
+        })
+        .clean(html)
+        .to_string()
+}
+
+/// Sanitised HTML of the Markdown
+pub fn render(source: &str, upload_url: &str) -> String {
+    sanitize(&to_html(source), upload_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UPLOADS: &str = "http://localhost:8080/uploads";
+
+    #[test]
+    fn strips_scripts_and_event_handlers() {
+        let html = sanitize(
+            r#"<p onclick="steal()">hi</p><script>alert(1)</script><img onerror="steal()">"#,
+            UPLOADS,
+        );
+        assert!(!html.contains("script"));
+        assert!(!html.contains("alert"));
+        assert!(!html.contains("onclick"));
+        assert!(!html.contains("onerror"));
+        assert!(html.contains("<p>hi</p>"));
+    }
+
+    #[test]
+    fn drops_javascript_links() {
+        let html = render("[click](javascript:alert(1))", UPLOADS);
+        assert!(!html.contains("javascript"));
+        assert!(html.contains("click"));
+    }
+
+    #[test]
+    fn links_get_nofollow() {
+        let html = render("[site](https://example.com)", UPLOADS);
+        assert!(html.contains(r#"href="https://example.com""#));
+        assert!(html.contains(r#"rel="nofollow noopener noreferrer""#));
+    }
+
+    #[test]
+    fn keeps_uploaded_images_only() {
+        let html = render("![cat](http://localhost:8080/uploads/cat.png)", UPLOADS);
+        assert!(html.contains(r#"src="http://localhost:8080/uploads/cat.png""#));
+
+        for source in [
+            "![cat](https://tracker.example/cat.png)",
+            "![cat](http://localhost:8080/uploadsevil/cat.png)",
+            "![cat](/uploads/cat.png)",
+        ] {
+            let html = render(source, UPLOADS);
+            assert!(html.contains("<img"), "{source}");
+            assert!(!html.contains("src="), "{source}");
+        }
+    }
+
+    #[test]
+    fn trailing_slash_on_upload_url_is_ignored() {
+        let html = render(
+            "![cat](http://localhost:8080/uploads/cat.png)",
+            "http://localhost:8080/uploads/",
+        );
+        assert!(html.contains(r#"src="http://localhost:8080/uploads/cat.png""#));
+    }
+
+    #[test]
+    fn keeps_mention_class_only() {
+        let html = sanitize(
+            r#"<a class="mention" href="/user/1">@bob</a><p class="big">x</p>"#,
+            UPLOADS,
+        );
+        assert!(html.contains(r#"class="mention""#));
+        assert!(!html.contains("big"));
+    }
+
+    #[test]
+    fn raw_html_outside_the_allow_list_is_removed() {
+        let html = render("<iframe src=\"https://evil.example\"></iframe><b>bold</b>", UPLOADS);
+        assert!(!html.contains("iframe"));
+        assert!(!html.contains("<b>"));
+        assert!(html.contains("bold"));
+    }
+}
@@ -0,0 +1,127 @@
+use uuid::Uuid;
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+/// Walks the text parts of a HTML fragment and calls `f` for every `@name`,
+/// tags and text already inside a link are left alone.
+/// Returning Some from `f` replaces the mention including the `@`
+fn scan(html: &str, mut f: impl FnMut(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut in_link = false;
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        if rest.starts_with('<') {
+            let end = rest.find('>').map(|i| i + 1).unwrap_or(rest.len());
+            let tag = rest[..end].to_ascii_lowercase();
+            if tag.starts_with("<a ") || tag == "<a>" {
+                in_link = true;
+            } else if tag.starts_with("</a") {
+                in_link = false;
+            }
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        let end = rest.find('<').unwrap_or(rest.len());
+        let text = &rest[..end];
+        rest = &rest[end..];
+        if in_link {
+            out.push_str(text);
+            continue;
+        }
+
+        let mut prev: Option<char> = None;
+        let mut i = 0;
+        while i < text.len() {
+            let c = text[i..].chars().next().unwrap_or_default();
+            let starts_mention = c == '@' && !prev.is_some_and(|p| p.is_alphanumeric() || p == '_');
+            if starts_mention {
+                let name_len = text[i + 1..]
+                    .find(|c: char| !is_name_char(c))
+                    .unwrap_or(text.len() - i - 1);
+                // a sentence may end right after the name
+                let name = text[i + 1..i + 1 + name_len].trim_end_matches(['.', '-']);
+                if !name.is_empty() {
+                    match f(name) {
+                        Some(r) => out.push_str(&r),
+                        None => {
+                            out.push('@');
+                            out.push_str(name);
+                        }
+                    }
+                    i += 1 + name.len();
+                    prev = name.chars().last();
+                    continue;
+                }
+            }
+            out.push(c);
+            prev = Some(c);
+            i += c.len_utf8();
+        }
+    }
+
+    out
+}
+
+/// Unique user names mentioned in the content, in order of appearance
+pub fn extract_mentions(html: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    scan(html, |name| {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+        None
+    });
+    names
+}
+
+/// Turns mentions of known users into links to their profile page
+pub fn link_mentions(html: &str, users: &[(Uuid, String)]) -> String {
+    scan(html, |name| {
+        users.iter()
+            .find(|(_, n)| n == name)
+            .map(|(id, n)| format!(r#"<a href="/user/{}" class="mention">@{}</a>"#, id, n))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_unique_names_in_order() {
+        let names = extract_mentions("<p>@bob and @eve, then @bob again</p>");
+        assert_eq!(names, vec!["bob", "eve"]);
+    }
+
+    #[test]
+    fn trailing_punctuation_is_not_part_of_the_name() {
+        assert_eq!(extract_mentions("<p>thanks @bob.</p>"), vec!["bob"]);
+        assert_eq!(extract_mentions("<p>ask @first.last-</p>"), vec!["first.last"]);
+        assert_eq!(extract_mentions("<p>@bob's post</p>"), vec!["bob"]);
+    }
+
+    #[test]
+    fn email_addresses_are_not_mentions() {
+        assert!(extract_mentions("<p>mail bob@example.com</p>").is_empty());
+        assert!(extract_mentions("<p>a lone @ sign</p>").is_empty());
+    }
+
+    #[test]
+    fn tags_and_links_are_skipped() {
+        assert!(extract_mentions(r#"<a href="/x">@bob</a>"#).is_empty());
+        assert!(extract_mentions(r#"<img alt="@bob">"#).is_empty());
+        assert_eq!(extract_mentions(r#"<a href="/x">@bob</a> @eve"#), vec!["eve"]);
+    }
+
+    #[test]
+    fn links_known_users_only() {
+        let id = Uuid::nil();
+        let html = link_mentions("<p>@bob and @nobody</p>", &[(id, "bob".to_string())]);
+        assert_eq!(html, format!(r#"<p><a href="/user/{}" class="mention">@bob</a> and @nobody</p>"#, id));
+    }
+}
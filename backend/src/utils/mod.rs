@@ -1,6 +1,10 @@
 pub mod token;
 pub mod password;
 pub mod file_upload;
+pub mod mention;
+pub mod diff;
+pub mod filter;
+pub mod markdown;
 
 #[macro_export]
 macro_rules! make_enum {
@@ -0,0 +1,124 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::{Route, UserContext, dto::PendingItem, forum::{approve, pending_items, reject}};
+
+#[derive(Clone, Properties, PartialEq)]
+struct RowProps {
+    item: PendingItem,
+    on_changed: Callback<()>,
+}
+
+#[component]
+fn PendingRow(props: &RowProps) -> Html {
+    let reason = use_state(String::new);
+    let error = use_state(String::new);
+    let item = &props.item;
+
+    let r_c = reason.clone();
+    let on_reason = Callback::from(move |e: InputEvent| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        r_c.set(input.value());
+    });
+
+    let (e_c, done, i_c) = (error.clone(), props.on_changed.clone(), item.clone());
+    let on_approve = Callback::from(move |_| {
+        let (e_c, done, item) = (e_c.clone(), done.clone(), i_c.clone());
+        wasm_bindgen_futures::spawn_local(async move {
+            match approve(&item).await {
+                Ok(_) => done.emit(()),
+                Err(e) => e_c.set(format!("Approving failed: {:?}", e)),
+            }
+        });
+    });
+    let (r_c, e_c, done, i_c) = (reason.clone(), error.clone(), props.on_changed.clone(), item.clone());
+    let on_reject = Callback::from(move |_| {
+        let reason = Some(r_c.trim().to_string()).filter(|r| !r.is_empty());
+        let (e_c, done, item) = (e_c.clone(), done.clone(), i_c.clone());
+        wasm_bindgen_futures::spawn_local(async move {
+            match reject(&item, reason).await {
+                Ok(_) => done.emit(()),
+                Err(e) => e_c.set(format!("Rejecting failed: {:?}", e)),
+            }
+        });
+    });
+
+    html! {
+        <div class="border border-zinc-800 rounded-xl p-2 space-y-1 text-sm">
+            <div class="space-x-2">
+                <span class="text-emerald-500">{item.created_at.format(crate::DATEFORMAT).to_string()}</span>
+                <span class="text-zinc-400">{item.section_name.clone()}</span>
+                <Link<Route> to={Route::Topic { s_id: item.section, id: item.thread_id }}>
+                    {if item.post_id.is_some() { format!("Reply in \"{}\"", item.title) } else { format!("New thread \"{}\"", item.title) }}
+                </Link<Route>>
+                if let (Some(author), Some(name)) = (&item.author, &item.author_name) {
+                    <Link<Route> to={Route::User { id: author.clone() }}>{format!("by {}", name)}</Link<Route>>
+                }
+            </div>
+            <div class="whitespace-pre-wrap text-xs text-zinc-400 bg-violet-950/20 p-1">{item.content.clone()}</div>
+            <div class="space-x-2 text-xs">
+                <button onclick={on_approve}>{"Approve"}</button>
+                <input type="text" maxlength="255" placeholder="Reason sent to the author" class="bg-violet-950/20"
+                    value={(*reason).clone()} oninput={on_reason}/>
+                <button onclick={on_reject}>{"Reject"}</button>
+            </div>
+            <span class="text-red-500">{(*error).clone()}</span>
+        </div>
+    }
+}
+
+/// Threads and posts held back for approval
+#[component]
+pub fn Approvals() -> Html {
+    let ctx = use_context::<UserContext>().expect("no context");
+    let page = use_state(|| 1u32);
+    let reload = use_state(|| 0u32);
+    let list = use_state(Vec::<PendingItem>::new);
+    let error = use_state(String::new);
+
+    let l_c = list.clone();
+    let err_c = error.clone();
+    use_effect_with((*page, *reload), move |(page, _)| {
+        let page = *page;
+        wasm_bindgen_futures::spawn_local(async move {
+            match pending_items(page).await {
+                Ok(items) => {
+                    err_c.set(String::new());
+                    l_c.set(items);
+                }
+                Err(e) => {
+                    crate::c_error!("Failed to load the approval queue: {:?}", e);
+                    err_c.set("Failed to load the approval queue".to_string());
+                }
+            }
+        });
+    });
+
+    let r_c = reload.clone();
+    let on_changed = Callback::from(move |_| r_c.set(*r_c + 1));
+    let pg_c = page.clone();
+    let pg_prev = Callback::from(move |_| pg_c.set((*pg_c - 1).max(1)));
+    let pg_c = page.clone();
+    let pg_next = Callback::from(move |_| pg_c.set(*pg_c + 1));
+
+    if !ctx.is_mod() && !ctx.is_admin() {
+        return html! { <div class="text-zinc-400">{"Only moderators can approve posts"}</div> };
+    }
+
+    html! {
+        <div class="space-y-4">
+            <h2 class="text-xl font-bold">{"Approval queue"}</h2>
+            <span class="text-red-500">{(*error).clone()}</span>
+            { for list.iter().map(|i| html! {
+                <PendingRow key={format!("{}-{:?}", i.thread_id, i.post_id)} item={i.clone()} on_changed={on_changed.clone()}/>
+            }) }
+            if list.is_empty() {
+                <div class="text-zinc-400">{"Nothing waits for approval"}</div>
+            }
+            <div class="space-x-2">
+                <button onclick={pg_prev} disabled={*page == 1}>{"Previous page"}</button>
+                <button onclick={pg_next} disabled={list.is_empty()}>{"Next page"}</button>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,130 @@
+use yew::prelude::*;
+
+use crate::{UserContext, dto::Ban, user::{add_ban, bans, remove_ban}};
+
+const KINDS: [(&str, &str); 3] = [("ip", "IP or range"), ("email", "Email"), ("email_domain", "Email domain")];
+
+/// IP, email and email domain bans for the staff
+#[component]
+pub fn Bans() -> Html {
+    let ctx = use_context::<UserContext>().expect("no context");
+    let kind = use_state(|| "ip".to_string());
+    let value = use_state(String::new);
+    let reason = use_state(String::new);
+    let days = use_state(String::new);
+    let reload = use_state(|| 0u32);
+    let list = use_state(Vec::<Ban>::new);
+    let error = use_state(String::new);
+
+    let l_c = list.clone();
+    let err_c = error.clone();
+    use_effect_with(*reload, move |_| {
+        wasm_bindgen_futures::spawn_local(async move {
+            match bans().await {
+                Ok(b) => l_c.set(b),
+                Err(e) => {
+                    crate::c_error!("Failed to load the bans: {:?}", e);
+                    err_c.set("Failed to load the bans".to_string());
+                }
+            }
+        });
+    });
+
+    let k_c = kind.clone();
+    let on_kind = Callback::from(move |e: Event| {
+        let input: web_sys::HtmlSelectElement = e.target_unchecked_into();
+        k_c.set(input.value());
+    });
+    let text = |state: UseStateHandle<String>| Callback::from(move |e: InputEvent| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        state.set(input.value());
+    });
+
+    let (k_c, v_c, r_c, d_c, e_c, rl_c) = (kind.clone(), value.clone(), reason.clone(), days.clone(), error.clone(), reload.clone());
+    let on_add = Callback::from(move |_| {
+        let value = v_c.trim().to_string();
+        if value.is_empty() {
+            e_c.set("Nothing to ban".to_string());
+            return;
+        }
+        let days = match d_c.trim() {
+            "" => None,
+            d => match d.parse::<i32>() {
+                Ok(d) if d > 0 => Some(d),
+                _ => {
+                    e_c.set("The length is a number of days".to_string());
+                    return;
+                }
+            },
+        };
+        let reason = Some(r_c.trim().to_string()).filter(|r| !r.is_empty());
+        let (kind, v_c, r_c, e_c, rl_c) = ((*k_c).clone(), v_c.clone(), r_c.clone(), e_c.clone(), rl_c.clone());
+        wasm_bindgen_futures::spawn_local(async move {
+            match add_ban(&kind, &value, reason, days).await {
+                Ok(_) => {
+                    v_c.set(String::new());
+                    r_c.set(String::new());
+                    e_c.set(String::new());
+                    rl_c.set(*rl_c + 1);
+                }
+                Err(e) => e_c.set(format!("Ban failed: {:?}", e)),
+            }
+        });
+    });
+    let on_remove = |id: i64| {
+        let (e_c, rl_c) = (error.clone(), reload.clone());
+        Callback::from(move |_| {
+            let (e_c, rl_c) = (e_c.clone(), rl_c.clone());
+            wasm_bindgen_futures::spawn_local(async move {
+                match remove_ban(id).await {
+                    Ok(_) => rl_c.set(*rl_c + 1),
+                    Err(e) => e_c.set(format!("Removing failed: {:?}", e)),
+                }
+            });
+        })
+    };
+
+    if !ctx.is_mod() && !ctx.is_admin() {
+        return html! { <div class="text-zinc-400">{"Only the staff can manage bans"}</div> };
+    }
+
+    html! {
+        <div class="space-y-4">
+            <h2 class="text-xl font-bold">{"Bans"}</h2>
+            <div class="flex space-x-2 text-sm">
+                <select class="bg-violet-950/20" onchange={on_kind}>
+                    { for KINDS.iter().map(|(k, label)| html! {
+                        <option value={*k} selected={*kind == *k}>{*label}</option>
+                    }) }
+                </select>
+                <input type="text" maxlength="255" placeholder="10.0.0.0/24, user@mail.com, mail.com" class="bg-violet-950/20"
+                    value={(*value).clone()} oninput={text(value.clone())}/>
+                <input type="text" maxlength="255" placeholder="Reason" class="bg-violet-950/20"
+                    value={(*reason).clone()} oninput={text(reason.clone())}/>
+                <input type="text" placeholder="Days, empty for ever" class="bg-violet-950/20 w-40"
+                    value={(*days).clone()} oninput={text(days.clone())}/>
+                <button onclick={on_add}>{"Ban"}</button>
+            </div>
+            <span class="text-red-500">{(*error).clone()}</span>
+            <table class="text-sm w-full">
+                <tr class="text-left">
+                    <th>{"Kind"}</th><th>{"Banned"}</th><th>{"Reason"}</th><th>{"By"}</th><th>{"Since"}</th><th>{"Until"}</th><th/>
+                </tr>
+                { for list.iter().map(|b| html! {
+                    <tr key={b.id}>
+                        <td>{b.kind.replace('_', " ")}</td>
+                        <td>{b.ip.clone().or_else(|| b.value.clone()).unwrap_or_default()}</td>
+                        <td>{b.reason.clone().unwrap_or_default()}</td>
+                        <td>{b.created_by_name.clone().unwrap_or_else(|| "Deleted User".to_string())}</td>
+                        <td>{b.created_at.format(crate::DATEFORMAT).to_string()}</td>
+                        <td>{b.expires_at.map(|e| e.format(crate::DATEFORMAT).to_string()).unwrap_or_else(|| "never".to_string())}</td>
+                        <td><button onclick={on_remove(b.id)}>{"Lift"}</button></td>
+                    </tr>
+                }) }
+            </table>
+            if list.is_empty() {
+                <div class="text-zinc-400">{"No bans"}</div>
+            }
+        </div>
+    }
+}
@@ -1,15 +1,22 @@
 use wasm_bindgen::JsCast;
 use web_sys::{HtmlInputElement};
 use yew::prelude::*;
-use ammonia::clean;
-use pulldown_cmark::{Parser, Options, html::push_html};
 
-use crate::{UserContext, bind::{upload_file_with_fetch}, dto::Resp, forum::{add_post, edit_post}};
+use crate::{UserContext, bind::{upload_file_with_fetch}, dto::Resp, forum::{add_post, edit_post, typing}};
+
+/// Milliseconds between two typing notices, the server shows one for six seconds
+const TYPING_INTERVAL: f64 = 3000.0;
 
 #[derive(Clone, Properties, PartialEq)]
 pub struct Props {
     pub id: i64,
     pub post_id: Option<i64>,
+    /// Post the new reply answers to
+    #[prop_or_default]
+    pub reply_to: Option<i64>,
+    /// Markdown of the post being edited
+    #[prop_or_default]
+    pub source: String,
     pub set_to_load: Callback<()>,
 }
 
@@ -22,7 +29,8 @@ pub fn Editor(props: &Props) -> Html {
     let error = use_state(String::new);
     //let image_data = use_state(Vec::<u8>::new);
     let is_image = use_state(|| false);
-    let post_id = props.post_id.clone();
+    let post_id = props.post_id;
+    let reply_to = props.reply_to;
     let s_c = props.set_to_load.clone();
     let r_c = raw.clone();
     let e_c = error.clone();
@@ -30,17 +38,15 @@ pub fn Editor(props: &Props) -> Html {
         e.prevent_default();
         e_c.set(String::new());
         let text = r_c.clone();
-        let md_parse = Parser::new_ext(text.as_str(), Options::empty());
-        let mut unsafe_html = String::new();
-        push_html(&mut unsafe_html, md_parse);
-        let safe_html = clean(&*unsafe_html);
+        // the server renders and sanitises the Markdown
+        let source = (*text).clone();
         let s_c = s_c.clone();
         let e_c = e_c.clone();
         crate::c_log!("SUBMIT: {:?}", post_id);
         match post_id {
             None => { // New post
                 wasm_bindgen_futures::spawn_local(async move {
-                    match add_post(thread_id, &safe_html).await {
+                    match add_post(thread_id, reply_to, &source).await {
                         Ok(_) => {
                             s_c.emit(());
                             text.set(String::new());
@@ -54,7 +60,7 @@ pub fn Editor(props: &Props) -> Html {
             }
             Some(post_id) => { // Edit post
                 wasm_bindgen_futures::spawn_local(async move {
-                    match edit_post(post_id, &safe_html).await {
+                    match edit_post(post_id, &source).await {
                         Ok(_) => {
                             s_c.emit(());
                             text.set(String::new());
@@ -70,23 +76,29 @@ pub fn Editor(props: &Props) -> Html {
     });
 
     let r_c = raw.clone();
-    use_effect_with((), move |_| {
-        if let Some(post_id) = post_id {
-            let window = web_sys::window().unwrap();
-            let document = window.document().unwrap();
-            let element = document.get_element_by_id(&format!("post-{}",post_id))
-                .expect("nothing to edit");
-            let text = element.inner_html();
-            r_c.set(text);
+    use_effect_with(props.source.clone(), move |source| {
+        if post_id.is_some() {
+            r_c.set(source.clone());
         }
     });
 
     let r_c = raw.clone();
+    let last_typing = use_mut_ref(|| 0f64);
     let on_text_input = {
         Callback::from(move |e: InputEvent| {
             let input: web_sys::HtmlInputElement = e.target_unchecked_into();
             let v = input.value();
             r_c.set(v);
+            // only replies are announced, edits aren't interesting to the other readers
+            let now = js_sys::Date::now();
+            if post_id.is_none() && now - *last_typing.borrow() > TYPING_INTERVAL {
+                *last_typing.borrow_mut() = now;
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Err(e) = typing(thread_id).await {
+                        crate::c_error!("Typing notice failed: {:?}", e);
+                    }
+                });
+            }
         })
     };
 
@@ -116,10 +128,10 @@ pub fn Editor(props: &Props) -> Html {
 
                 let insert = insert.clone();
                 wasm_bindgen_futures::spawn_local(async move {
-                    if let Ok(res) = upload_file_with_fetch("/forum/upload_image", &file).await {
+                    if let Ok(res) = upload_file_with_fetch(&format!("/forum/upload_image?thread={}", thread_id), &file).await {
                         crate::c_log!("{}", res.avatar_url);
                         crate::c_log!("{}", res.filename);
-                        insert.emit(res.avatar_url.replace("0.0.0.0",crate::ADDR));
+                        insert.emit(format!("{}/uploads/{}", crate::ADDR, res.filename));
                     }
                 });
             } else {
@@ -4,6 +4,7 @@ use yew_router::prelude::*;
 
 use crate::UserContext;
 use crate::Route;
+use super::notifications::NotificationBell;
 
 #[component]
 pub fn Header() -> Html {
@@ -19,6 +20,16 @@ pub fn Header() -> Html {
     let on_home = Callback::from(move |_| n_c.push(&Route::Content));
     let n_c = navigator.clone();
     let on_user_list = Callback::from(move |_| n_c.push(&Route::UserList));
+    let n_c = navigator.clone();
+    let on_search = Callback::from(move |_| n_c.push(&Route::Search));
+    let n_c = navigator.clone();
+    let on_log = Callback::from(move |_| n_c.push(&Route::ModerationLog));
+    let n_c = navigator.clone();
+    let on_reports = Callback::from(move |_| n_c.push(&Route::Reports));
+    let n_c = navigator.clone();
+    let on_bans = Callback::from(move |_| n_c.push(&Route::Bans));
+    let n_c = navigator.clone();
+    let on_approvals = Callback::from(move |_| n_c.push(&Route::Approvals));
 
     let c_c = ctx.clone();
     let on_logout = Callback::from(move |_| {
@@ -44,6 +55,7 @@ pub fn Header() -> Html {
                     <div class="space-x-2 flex">
                         <div class="rounded-2xl border-zinc-800 border px-2 py-1 bg-indigo-950/50">{"anonymous"}</div>
                         <button onclick={on_home}>{"Home"}</button> 
+                        <button onclick={on_search.clone()}>{"Search"}</button> 
                         <button onclick={on_login}>{"Login"}</button> 
                         <button onclick={on_reg}>{"Register"}</button> 
                     </div>
@@ -52,9 +64,19 @@ pub fn Header() -> Html {
                 html! { 
                     <div class="space-x-2 flex">
                         <div class="rounded-2xl border-zinc-800 border px-2 py-1 bg-indigo-950/50">{ctx.name()}</div>
+                        <NotificationBell/>
                         <button onclick={on_home}>{"Home"}</button> 
+                        <button onclick={on_search}>{"Search"}</button> 
                         <button onclick={on_logout}>{"Logout"}</button> 
                         <button onclick={on_user_list}>{"Users"}</button> 
+                        if ctx.is_mod() || ctx.is_admin() {
+                            <button onclick={on_reports}>{"Reports"}</button>
+                            <button onclick={on_bans}>{"Bans"}</button>
+                            <button onclick={on_approvals}>{"Approvals"}</button>
+                        }
+                        if ctx.is_admin() {
+                            <button onclick={on_log}>{"Log"}</button>
+                        }
                     </div>
                 }
             }}
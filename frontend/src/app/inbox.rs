@@ -1,8 +1,312 @@
 use yew::prelude::*;
-use yew_router::prelude::*;
+
+use crate::{UserContext, dto::{Conversation, ConversationResponseDto},
+    user::{conversation, conversations, delete_conversation, reply_pm, send_pm}};
+
+use super::report::ReportButton;
+
+const FOLDERS: [(&str, &str); 2] = [("inbox", "Inbox"), ("sent", "Sent")];
 
 #[component]
 pub fn Inbox() -> Html {
+    let ctx = use_context::<UserContext>().expect("no context");
+    let folder = use_state(|| "inbox");
+    let page = use_state(|| 1u32);
+    let list = use_state(Vec::<Conversation>::new);
+    let unread = use_state(|| 0i64);
+    let opened = use_state(|| None::<ConversationResponseDto>);
+    let composing = use_state(|| false);
+    let reload = use_state(|| 0u32);
+    let error = use_state(String::new);
+
+    let reply = use_state(String::new);
+    let recipients = use_state(String::new);
+    let subject = use_state(String::new);
+    let content = use_state(String::new);
+
+    let l_c = list.clone();
+    let u_c = unread.clone();
+    use_effect_with((*folder, *page, *reload), move |(folder, page, _)| {
+        let (folder, page) = (*folder, *page);
+        wasm_bindgen_futures::spawn_local(async move {
+            match conversations(folder, page).await {
+                Ok(r) => {
+                    u_c.set(r.unread);
+                    l_c.set(r.conversations);
+                }
+                Err(e) => { crate::c_error!("Failed to load messages: {:?}", e); }
+            }
+        });
+    });
+
+    // message bodies come as sanitised HTML from the server, so they're set directly on the elements
+    use_effect_with((*opened).clone(), move |opened| {
+        if let Some(c) = opened
+            && let Some(document) = web_sys::window().and_then(|w| w.document()) {
+            for m in &c.messages {
+                if let Some(element) = document.get_element_by_id(&format!("pm-{}", m.id)) {
+                    element.set_inner_html(&m.content);
+                }
+            }
+        }
+    });
+
+    let open_conversation = {
+        let opened = opened.clone();
+        let composing = composing.clone();
+        let reload = reload.clone();
+        let error = error.clone();
+        Callback::from(move |id: i64| {
+            let opened = opened.clone();
+            let reload = reload.clone();
+            let error = error.clone();
+            composing.set(false);
+            error.set(String::new());
+            wasm_bindgen_futures::spawn_local(async move {
+                match conversation(id).await {
+                    Ok(c) => {
+                        opened.set(Some(c));
+                        // the unread counters changed
+                        reload.set(*reload + 1);
+                    }
+                    Err(e) => { crate::c_error!("Failed to load conversation: {:?}", e); }
+                }
+            });
+        })
+    };
+
+    let set_folder = {
+        let folder = folder.clone();
+        let page = page.clone();
+        let opened = opened.clone();
+        Callback::from(move |f: &'static str| {
+            folder.set(f);
+            page.set(1);
+            opened.set(None);
+        })
+    };
+
+    let on_prev = {
+        let page = page.clone();
+        Callback::from(move |_| page.set((*page - 1).max(1)))
+    };
+    let on_next = {
+        let page = page.clone();
+        Callback::from(move |_| page.set(*page + 1))
+    };
+
+    let on_compose = {
+        let composing = composing.clone();
+        let opened = opened.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            opened.set(None);
+            error.set(String::new());
+            composing.set(true);
+        })
+    };
+
+    let on_input = |state: UseStateHandle<String>| Callback::from(move |e: InputEvent| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        state.set(input.value());
+    });
+    let on_textarea = |state: UseStateHandle<String>| Callback::from(move |e: InputEvent| {
+        let input: web_sys::HtmlTextAreaElement = e.target_unchecked_into();
+        state.set(input.value());
+    });
+
+    let on_send = {
+        let recipients = recipients.clone();
+        let subject = subject.clone();
+        let content = content.clone();
+        let error = error.clone();
+        let open_conversation = open_conversation.clone();
+        let folder = folder.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let names: Vec<String> = recipients.split(',')
+                .map(|n| n.trim().to_string())
+                .filter(|n| !n.is_empty())
+                .collect();
+            let recipients = recipients.clone();
+            let subject = subject.clone();
+            let content = content.clone();
+            let error = error.clone();
+            let open_conversation = open_conversation.clone();
+            let folder = folder.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match send_pm(names, &subject, &content).await {
+                    Ok(id) => {
+                        recipients.set(String::new());
+                        subject.set(String::new());
+                        content.set(String::new());
+                        folder.set("sent");
+                        open_conversation.emit(id);
+                    }
+                    Err(e) => {
+                        crate::c_error!("Failed to send message: {:?}", e);
+                        error.set("Message could not be sent, check the recipients".to_string());
+                    }
+                }
+            });
+        })
+    };
+
+    let on_reply = {
+        let reply = reply.clone();
+        let opened = opened.clone();
+        let error = error.clone();
+        let open_conversation = open_conversation.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let Some(id) = opened.as_ref().map(|c| c.conversation.id) else {
+                return;
+            };
+            let reply = reply.clone();
+            let error = error.clone();
+            let open_conversation = open_conversation.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match reply_pm(id, &reply).await {
+                    Ok(_) => {
+                        reply.set(String::new());
+                        open_conversation.emit(id);
+                    }
+                    Err(e) => {
+                        crate::c_error!("Failed to send message: {:?}", e);
+                        error.set("Message could not be sent".to_string());
+                    }
+                }
+            });
+        })
+    };
+
+    let on_delete = {
+        let opened = opened.clone();
+        let reload = reload.clone();
+        Callback::from(move |_| {
+            let Some(id) = opened.as_ref().map(|c| c.conversation.id) else {
+                return;
+            };
+            let opened = opened.clone();
+            let reload = reload.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match delete_conversation(id).await {
+                    Ok(_) => {
+                        opened.set(None);
+                        reload.set(*reload + 1);
+                    }
+                    Err(e) => { crate::c_error!("Failed to delete conversation: {:?}", e); }
+                }
+            });
+        })
+    };
+
+    if ctx.is_none() {
+        return html! {
+            <p class="text-zinc-500">{"Log in to read your messages"}</p>
+        };
+    }
+
     html! {
+        <div class="space-y-5">
+            <div class="flex items-center space-x-2">
+                { for FOLDERS.iter().map(|(f, label)| {
+                    let set_folder = set_folder.clone();
+                    let f = *f;
+                    let label = if f == "inbox" && *unread > 0 {
+                        format!("{} ({})", label, *unread)
+                    } else {
+                        label.to_string()
+                    };
+                    html! {
+                        <button class={classes!("px-2","rounded-xl", if *folder == f { "bg-indigo-800" } else { "bg-zinc-800/50" })}
+                            onclick={Callback::from(move |_| set_folder.emit(f))}>
+                            {label}
+                        </button>
+                    }
+                }) }
+                <button class="px-2 rounded-xl bg-fuchsia-800 hover:bg-fuchsia-600" onclick={on_compose}>{"New message"}</button>
+            </div>
+            <span class="text-red-500">{(*error).clone()}</span>
+
+            if *composing {
+                <form onsubmit={on_send} class="space-y-5 p-2 grid grid-cols-1">
+                    <input type="text" required=true placeholder="Recipients, separated by commas" class="bg-fuchsia-950/40"
+                        value={(*recipients).clone()} oninput={on_input(recipients.clone())}/>
+                    <input type="text" required=true maxlength="255" placeholder="Subject" class="bg-fuchsia-950/40"
+                        value={(*subject).clone()} oninput={on_input(subject.clone())}/>
+                    <textarea rows="10" required=true maxlength="20000" placeholder="Message, Markdown is supported" class="bg-fuchsia-950/40"
+                        value={(*content).clone()} oninput={on_textarea(content.clone())}/>
+                    <input type="submit" value="Send" class="hover:bg-fuchsia-600 bg-fuchsia-800 rounded-xl"/>
+                </form>
+            }
+
+            if let Some(c) = &*opened {
+                <div class="bg-zinc-900/50 border border-zinc-800 rounded-2xl p-5 space-y-3">
+                    <div class="flex items-center justify-between">
+                        <div>
+                            <span class="text-xl text-cyan-200">{&c.conversation.subject}</span>
+                            <div class="text-xs text-zinc-500">
+                                { c.participants.iter().map(|p| p.name.clone()).collect::<Vec<_>>().join(", ") }
+                            </div>
+                        </div>
+                        <button class="text-sm text-zinc-400 hover:text-red-400" onclick={on_delete}>{"Delete"}</button>
+                    </div>
+                    { for c.messages.iter().map(|m| html! {
+                        <div class="border-t border-zinc-800 pt-2">
+                            <div class="text-xs text-zinc-500">
+                                <span class="text-zinc-300">{m.author_name.clone().unwrap_or_else(|| "Deleted user".to_string())}</span>
+                                {" · "}{m.created_at.format(crate::DATEFORMAT).to_string()}
+                                if m.author.is_some() && m.author != Some(ctx.id()) {
+                                    {" · "}<ReportButton kind="message" target_id={m.id.to_string()}/>
+                                }
+                            </div>
+                            <div id={format!("pm-{}", m.id)} class="text-zinc-300"></div>
+                        </div>
+                    }) }
+                    <form onsubmit={on_reply} class="grid grid-cols-1 space-y-2">
+                        <textarea rows="5" required=true maxlength="20000" placeholder="Reply, Markdown is supported"
+                            class="bg-black/0 p-3 border rounded-2xl border-zinc-800"
+                            value={(*reply).clone()} oninput={on_textarea(reply.clone())}/>
+                        <input type="submit" value="Reply" class="px-4 py-2 bg-indigo-800 rounded-xl font-medium hover:bg-violet-600 transition-colors"/>
+                    </form>
+                </div>
+            }
+
+            <div class="space-y-1">
+                if list.is_empty() {
+                    <p class="text-sm text-zinc-500">{"No messages"}</p>
+                }
+                { for list.iter().map(|c| {
+                    let open_conversation = open_conversation.clone();
+                    let id = c.id;
+                    let class = if c.unread > 0 { "text-zinc-100" } else { "text-zinc-500" };
+                    html! {
+                        <div class={classes!("cursor-pointer", "rounded-lg", "px-2", "py-1", "hover:bg-zinc-800", class)}
+                            onclick={Callback::from(move |_| open_conversation.emit(id))}>
+                            <div class="flex justify-between">
+                                <span>
+                                    {&c.subject}
+                                    if c.unread > 0 {
+                                        <span class="ml-2 rounded-full bg-red-700 px-1.5 text-xs">{c.unread}</span>
+                                    }
+                                </span>
+                                <span class="text-xs text-zinc-500">{c.updated_at.format(crate::DATEFORMAT).to_string()}</span>
+                            </div>
+                            <div class="text-xs text-zinc-500">
+                                {c.participants.join(", ")}
+                                if let Some(last) = &c.last_author {
+                                    {format!(" · last by {}", last)}
+                                }
+                            </div>
+                        </div>
+                    }
+                }) }
+            </div>
+            <div class="space-x-2">
+                <button onclick={on_prev} disabled={*page == 1}>{"Previous page"}</button>
+                <button onclick={on_next} disabled={list.is_empty()}>{"Next page"}</button>
+            </div>
+        </div>
     }
 }
@@ -14,8 +14,8 @@ pub fn Login(props: &Props) -> Html {
         .unwrap_throw();
 
     let navigator = use_navigator().expect("navigator missing!");
-    let username = use_state(|| String::new());
-    let password = use_state(|| String::new());
+    let username = use_state(String::new);
+    let password = use_state(String::new);
     let error = use_state(|| None::<String>);
 
     let on_google_click = {
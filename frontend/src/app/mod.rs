@@ -8,6 +8,16 @@ pub mod topic;
 pub mod user_page;
 pub mod user_list;
 pub mod inbox;
+pub mod search;
+pub mod moderation_log;
+pub mod reports;
+pub mod bans;
+pub mod approvals;
 
 mod user;
 mod editor;
+mod notifications;
+mod shoutbox;
+mod thread_tools;
+mod revisions;
+mod report;
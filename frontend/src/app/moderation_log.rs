@@ -0,0 +1,162 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::{Route, UserContext, dto::ModerationLogEntry, forum::moderation_log};
+
+const ACTIONS: [&str; 25] = [
+    "lock_thread", "unlock_thread", "pin_thread", "edit_thread", "delete_thread", "move_thread",
+    "merge_thread", "split_thread", "edit_post", "delete_post", "create_section", "warn_user",
+    "unban_user", "update_group", "update_permissions", "update_moderators", "restore_revision",
+    "restore_thread", "restore_post", "add_ban", "remove_ban", "approve_thread", "approve_post",
+    "reject_thread", "reject_post",
+];
+const TARGETS: [&str; 6] = ["user", "thread", "post", "section", "group", "ban"];
+
+fn snapshot(value: &Option<serde_json::Value>) -> String {
+    value.as_ref()
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "–".to_string())
+}
+
+fn target(entry: &ModerationLogEntry) -> Html {
+    let label = format!("{} {}", entry.target_kind, entry.target_id);
+    match (entry.target_kind.as_str(), entry.section, entry.target_id.parse::<i64>()) {
+        ("thread", Some(s_id), Ok(id)) => html! {
+            <Link<Route> to={Route::Topic { s_id, id }}>{label}</Link<Route>>
+        },
+        ("user", _, _) => html! {
+            <Link<Route> to={Route::User { id: entry.target_id.clone() }}>{label}</Link<Route>>
+        },
+        _ => html! { {label} },
+    }
+}
+
+/// Privileged operations of the staff, only readable by admins
+#[component]
+pub fn ModerationLog() -> Html {
+    let ctx = use_context::<UserContext>().expect("no context");
+    let action = use_state(String::new);
+    let target_kind = use_state(String::new);
+    let target_id = use_state(String::new);
+    let actor = use_state(|| None::<(String, String)>);
+    let page = use_state(|| 1u32);
+    let entries = use_state(Vec::<ModerationLogEntry>::new);
+    let error = use_state(String::new);
+
+    let e_c = entries.clone();
+    let err_c = error.clone();
+    let filters = ((*action).clone(), (*target_kind).clone(), (*target_id).clone(),
+        (*actor).clone().map(|(id, _)| id), *page);
+    use_effect_with(filters, move |(action, target_kind, target_id, actor, page)| {
+        let (action, target_kind, target_id, actor, page) =
+            (action.clone(), target_kind.clone(), target_id.clone(), actor.clone(), *page);
+        wasm_bindgen_futures::spawn_local(async move {
+            match moderation_log(&action, &target_kind, target_id.trim(), actor.as_deref(), page).await {
+                Ok(list) => {
+                    err_c.set(String::new());
+                    e_c.set(list);
+                }
+                Err(e) => {
+                    crate::c_error!("Failed to load the moderation log: {:?}", e);
+                    err_c.set("Failed to load the moderation log".to_string());
+                }
+            }
+        });
+    });
+
+    let select = |state: UseStateHandle<String>, page: UseStateHandle<u32>| Callback::from(move |e: Event| {
+        let input: web_sys::HtmlSelectElement = e.target_unchecked_into();
+        page.set(1);
+        state.set(input.value());
+    });
+    let t_c = target_id.clone();
+    let pg_c = page.clone();
+    let on_target_input = Callback::from(move |e: InputEvent| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        pg_c.set(1);
+        t_c.set(input.value());
+    });
+    let a_c = actor.clone();
+    let pg_c = page.clone();
+    let on_clear_actor = Callback::from(move |_| {
+        pg_c.set(1);
+        a_c.set(None);
+    });
+    let pg_c = page.clone();
+    let pg_prev = Callback::from(move |_| pg_c.set((*pg_c - 1).max(1)));
+    let pg_c = page.clone();
+    let pg_next = Callback::from(move |_| pg_c.set(*pg_c + 1));
+
+    if !ctx.is_admin() {
+        return html! { <div class="text-zinc-400">{"Only administrators can read the moderation log"}</div> };
+    }
+
+    html! {
+        <div class="space-y-4">
+            <h2 class="text-xl font-bold">{"Moderation log"}</h2>
+            <div class="flex space-x-2 text-sm">
+                <select class="bg-violet-950/20" onchange={select(action.clone(), page.clone())}>
+                    <option value="" selected={action.is_empty()}>{"All actions"}</option>
+                    { for ACTIONS.iter().map(|a| html! {
+                        <option value={*a} selected={*action == *a}>{a.replace('_', " ")}</option>
+                    }) }
+                </select>
+                <select class="bg-violet-950/20" onchange={select(target_kind.clone(), page.clone())}>
+                    <option value="" selected={target_kind.is_empty()}>{"All targets"}</option>
+                    { for TARGETS.iter().map(|t| html! {
+                        <option value={*t} selected={*target_kind == *t}>{*t}</option>
+                    }) }
+                </select>
+                <input type="text" maxlength="64" placeholder="Target id" class="bg-violet-950/20"
+                    value={(*target_id).clone()} oninput={on_target_input}/>
+                if let Some((_, name)) = (*actor).clone() {
+                    <button onclick={on_clear_actor} class="rounded-xl border border-zinc-800 px-2">
+                        {format!("by {} ✕", name)}
+                    </button>
+                }
+            </div>
+            <span class="text-red-500">{(*error).clone()}</span>
+            <table class="w-full text-sm">
+                <tr class="text-left text-zinc-400">
+                    <th>{"When"}</th>
+                    <th>{"Who"}</th>
+                    <th>{"Action"}</th>
+                    <th>{"Target"}</th>
+                    <th>{"Reason"}</th>
+                    <th>{"Before"}</th>
+                    <th>{"After"}</th>
+                </tr>
+                { for entries.iter().map(|entry| {
+                    let a_c = actor.clone();
+                    let pg_c = page.clone();
+                    let who = entry.actor.clone().zip(entry.actor_name.clone());
+                    let name = entry.actor_name.clone().unwrap_or_else(|| "Deleted User".to_string());
+                    let on_actor = Callback::from(move |_| {
+                        if let Some(who) = who.clone() {
+                            pg_c.set(1);
+                            a_c.set(Some(who));
+                        }
+                    });
+                    html! {
+                        <tr class="border-t border-zinc-800 align-top">
+                            <td class="text-emerald-500 whitespace-nowrap">{entry.created_at.format(crate::DATEFORMAT).to_string()}</td>
+                            <td><button onclick={on_actor}>{name}</button></td>
+                            <td>{entry.action.replace('_', " ")}</td>
+                            <td>{target(entry)}</td>
+                            <td>{entry.reason.clone().unwrap_or_default()}</td>
+                            <td class="break-all font-mono text-xs text-zinc-400">{snapshot(&entry.before)}</td>
+                            <td class="break-all font-mono text-xs text-zinc-400">{snapshot(&entry.after)}</td>
+                        </tr>
+                    }
+                }) }
+            </table>
+            if entries.is_empty() {
+                <div class="text-zinc-400">{"No entries"}</div>
+            }
+            <div class="space-x-2">
+                <button onclick={pg_prev} disabled={*page == 1}>{"Previous page"}</button>
+                <button onclick={pg_next} disabled={entries.is_empty()}>{"Next page"}</button>
+            </div>
+        </div>
+    }
+}
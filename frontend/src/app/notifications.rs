@@ -0,0 +1,137 @@
+use yew::prelude::*;
+use wasm_bindgen::UnwrapThrowExt;
+use yew_router::prelude::*;
+
+use crate::{Route, dto::Notification, user::{notifications, read_notifications, unread_notifications}};
+
+fn describe(n: &Notification) -> String {
+    let actor = n.actor_name.clone().unwrap_or_else(|| "Someone".to_string());
+    let title = n.thread_title.clone().unwrap_or_default();
+    let content = n.content.clone().unwrap_or_default();
+    match n.kind.as_str() {
+        "reply" => format!("{} replied in \"{}\"", actor, title),
+        "quote" => format!("{} replied to your post in \"{}\"", actor, title),
+        "mention" => format!("{} mentioned you in \"{}\"", actor, title),
+        "new_thread" => format!("{} started \"{}\"", actor, title),
+        "pm" => format!("{} sent you a message: {}", actor, content),
+        "warning" => format!("You were warned by {}: {}", actor, content),
+        "report_handled" | "approved" | "rejected" => content,
+        _ => format!("{} {}", actor, title),
+    }
+}
+
+/// Bell with the unread count, opens a list of the latest notifications
+#[component]
+pub fn NotificationBell() -> Html {
+    let navigator = use_navigator().unwrap_throw();
+    let location = use_location();
+    let unread = use_state(|| 0i64);
+    let open = use_state(|| false);
+    let items = use_state(Vec::<Notification>::new);
+
+    // refresh the counter on every navigation
+    let u_c = unread.clone();
+    use_effect_with(location.map(|l| l.path().to_string()), move |_| {
+        wasm_bindgen_futures::spawn_local(async move {
+            match unread_notifications().await {
+                Ok(n) => u_c.set(n),
+                Err(e) => { crate::c_error!("Failed to load notifications: {:?}", e); }
+            }
+        });
+    });
+
+    let o_c = open.clone();
+    let i_c = items.clone();
+    let u_c = unread.clone();
+    let on_toggle = Callback::from(move |_| {
+        let show = !*o_c;
+        o_c.set(show);
+        if show {
+            let i_c = i_c.clone();
+            let u_c = u_c.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match notifications(false).await {
+                    Ok(r) => {
+                        u_c.set(r.unread);
+                        i_c.set(r.notifications);
+                    }
+                    Err(e) => { crate::c_error!("Failed to load notifications: {:?}", e); }
+                }
+            });
+        }
+    });
+
+    let i_c = items.clone();
+    let u_c = unread.clone();
+    let on_read_all = Callback::from(move |_| {
+        let i_c = i_c.clone();
+        let u_c = u_c.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match read_notifications(None).await {
+                Ok(_) => {
+                    u_c.set(0);
+                    i_c.set(i_c.iter()
+                        .cloned()
+                        .map(|n| Notification { is_read: true, ..n })
+                        .collect());
+                }
+                Err(e) => { crate::c_error!("Failed to mark notifications: {:?}", e); }
+            }
+        });
+    });
+
+    let on_open_item = {
+        let open = open.clone();
+        let unread = unread.clone();
+        Callback::from(move |n: Notification| {
+            open.set(false);
+            if !n.is_read {
+                unread.set((*unread - 1).max(0));
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Err(e) = read_notifications(Some(vec![n.id])).await {
+                        crate::c_error!("Failed to mark notification: {:?}", e);
+                    }
+                });
+            }
+            if n.kind == "pm" {
+                navigator.push(&Route::Messages);
+            } else if let (Some(s_id), Some(id)) = (n.section, n.thread_id) {
+                navigator.push(&Route::Topic { s_id, id });
+            }
+        })
+    };
+
+    html! {
+        <div class="relative">
+            <button onclick={on_toggle} title="Notifications">
+                {"🔔"}
+                if *unread > 0 {
+                    <span class="ml-1 rounded-full bg-red-700 px-1.5 text-xs">{*unread}</span>
+                }
+            </button>
+            if *open {
+                <div class="absolute right-0 z-10 mt-2 w-96 rounded-xl border border-zinc-800 bg-zinc-900 p-2 shadow-lg">
+                    <div class="flex items-center justify-between px-2 pb-2 text-sm text-zinc-400">
+                        <span>{"Notifications"}</span>
+                        <button onclick={on_read_all}>{"Mark all read"}</button>
+                    </div>
+                    if items.is_empty() {
+                        <p class="px-2 py-1 text-sm text-zinc-500">{"Nothing new"}</p>
+                    }
+                    { for items.iter().map(|n| {
+                        let cb = on_open_item.clone();
+                        let n_c = n.clone();
+                        let class = if n.is_read { "text-zinc-500" } else { "text-zinc-100" };
+                        html! {
+                            <div class={classes!("cursor-pointer", "rounded-lg", "px-2", "py-1", "text-sm", "hover:bg-zinc-800", class)}
+                                onclick={Callback::from(move |_| cb.emit(n_c.clone()))}>
+                                <div>{describe(n)}</div>
+                                <div class="text-xs text-zinc-500">{n.created_at.format(crate::DATEFORMAT).to_string()}</div>
+                            </div>
+                        }
+                    }) }
+                </div>
+            }
+        </div>
+    }
+}
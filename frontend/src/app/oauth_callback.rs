@@ -54,43 +54,37 @@ fn extract_token_from_url(search: &str, hash: &str) -> Option<String> {
     // Try to extract token from query parameters (e.g., ?token=...)
     if !search.is_empty() {
         let params = web_sys::UrlSearchParams::new_with_str(search).ok()?;
-        if let Some(token) = params.get("token") {
-            if !token.is_empty() {
-                return Some(token);
-            }
+        if let Some(token) = params.get("token")
+            && !token.is_empty() {
+            return Some(token);
         }
         // Also check for other common parameter names
-        if let Some(token) = params.get("access_token") {
-            if !token.is_empty() {
-                return Some(token);
-            }
+        if let Some(token) = params.get("access_token")
+            && !token.is_empty() {
+            return Some(token);
         }
-        if let Some(token) = params.get("jwt") {
-            if !token.is_empty() {
-                return Some(token);
-            }
+        if let Some(token) = params.get("jwt")
+            && !token.is_empty() {
+            return Some(token);
         }
     }
 
     // Try to extract token from fragment (e.g., #token=...)
     if !hash.is_empty() {
         // Remove the leading '#'
-        let hash_str = if hash.starts_with('#') { &hash[1..] } else { hash };
+        let hash_str = hash.strip_prefix('#').unwrap_or(hash);
         let params = web_sys::UrlSearchParams::new_with_str(hash_str).ok()?;
-        if let Some(token) = params.get("token") {
-            if !token.is_empty() {
-                return Some(token);
-            }
+        if let Some(token) = params.get("token")
+            && !token.is_empty() {
+            return Some(token);
         }
-        if let Some(token) = params.get("access_token") {
-            if !token.is_empty() {
-                return Some(token);
-            }
+        if let Some(token) = params.get("access_token")
+            && !token.is_empty() {
+            return Some(token);
         }
-        if let Some(token) = params.get("jwt") {
-            if !token.is_empty() {
-                return Some(token);
-            }
+        if let Some(token) = params.get("jwt")
+            && !token.is_empty() {
+            return Some(token);
         }
     }
 
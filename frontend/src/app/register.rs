@@ -6,10 +6,10 @@ use crate::user::register;
 #[component]
 pub fn Register() -> Html {
     let navigator = use_navigator().expect("navigator missing!");
-    let username = use_state(|| String::new());
-    let email = use_state(|| String::new());
-    let password = use_state(|| String::new());
-    let password_confirm = use_state(|| String::new());
+    let username = use_state(String::new);
+    let email = use_state(String::new);
+    let password = use_state(String::new);
+    let password_confirm = use_state(String::new);
     let error = use_state(|| None::<String>);
 
     let on_submit = {
@@ -0,0 +1,74 @@
+use yew::prelude::*;
+
+use crate::{UserContext, forum::create_report};
+
+pub const REASONS: [&str; 5] = ["spam", "abuse", "off_topic", "illegal", "other"];
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct Props {
+    /// post, thread, message, chat or user
+    pub kind: AttrValue,
+    pub target_id: AttrValue,
+}
+
+/// Small "report" link opening a form with the reason and a comment
+#[component]
+pub fn ReportButton(props: &Props) -> Html {
+    let ctx = use_context::<UserContext>().expect("no context");
+    let open = use_state(|| false);
+    let reason = use_state(|| REASONS[0].to_string());
+    let comment = use_state(String::new);
+    let status = use_state(String::new);
+
+    let o_c = open.clone();
+    let on_toggle = Callback::from(move |_| o_c.set(!*o_c));
+    let r_c = reason.clone();
+    let on_reason = Callback::from(move |e: Event| {
+        let input: web_sys::HtmlSelectElement = e.target_unchecked_into();
+        r_c.set(input.value());
+    });
+    let c_c = comment.clone();
+    let on_comment = Callback::from(move |e: InputEvent| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        c_c.set(input.value());
+    });
+
+    let (kind, target_id) = (props.kind.clone(), props.target_id.clone());
+    let (o_c, r_c, c_c, s_c) = (open.clone(), reason.clone(), comment.clone(), status.clone());
+    let on_send = Callback::from(move |_| {
+        let (kind, target_id) = (kind.clone(), target_id.clone());
+        let (o_c, r_c, c_c, s_c) = (o_c.clone(), r_c.clone(), c_c.clone(), s_c.clone());
+        let note = Some(c_c.trim().to_string()).filter(|c| !c.is_empty());
+        wasm_bindgen_futures::spawn_local(async move {
+            match create_report(&kind, &target_id, &r_c, note).await {
+                Ok(_) => {
+                    o_c.set(false);
+                    c_c.set(String::new());
+                    s_c.set("reported".to_string());
+                }
+                Err(e) => s_c.set(format!("Reporting failed: {:?}", e)),
+            }
+        });
+    });
+
+    if ctx.is_none() || ctx.banned() {
+        return html! {};
+    }
+
+    html! {
+        <span class="text-xs text-zinc-500 space-x-1">
+            <button class="hover:text-zinc-200" onclick={on_toggle}>{"report"}</button>
+            if *open {
+                <select class="bg-violet-950/20" onchange={on_reason}>
+                    { for REASONS.iter().map(|r| html! {
+                        <option value={*r} selected={*reason == *r}>{r.replace('_', " ")}</option>
+                    }) }
+                </select>
+                <input type="text" maxlength="500" placeholder="Comment" class="bg-violet-950/20"
+                    value={(*comment).clone()} oninput={on_comment}/>
+                <button class="hover:text-zinc-200" onclick={on_send}>{"send"}</button>
+            }
+            <span>{(*status).clone()}</span>
+        </span>
+    }
+}
@@ -0,0 +1,203 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::{Route, UserContext, dto::Report, forum::{claim_report, handle_report, reports}};
+
+const STATUSES: [&str; 4] = ["open", "claimed", "resolved", "dismissed"];
+const TARGETS: [&str; 5] = ["post", "thread", "message", "chat", "user"];
+/// Moderation actions a resolution can point to
+const ACTIONS: [&str; 7] = ["delete_post", "delete_thread", "edit_post", "edit_thread", "lock_thread", "move_thread", "warn_user"];
+
+fn target(report: &Report) -> Html {
+    let label = format!("{} {}", report.target_kind, report.target_id);
+    match (report.section, report.thread_id) {
+        (Some(s_id), Some(id)) => html! {
+            <Link<Route> to={Route::Topic { s_id, id }}>{label}</Link<Route>>
+        },
+        _ => html! { {label} },
+    }
+}
+
+#[derive(Clone, Properties, PartialEq)]
+struct RowProps {
+    report: Report,
+    on_changed: Callback<()>,
+}
+
+#[component]
+fn ReportRow(props: &RowProps) -> Html {
+    let action = use_state(String::new);
+    let note = use_state(String::new);
+    let error = use_state(String::new);
+    let report = &props.report;
+    let id = report.id;
+
+    let a_c = action.clone();
+    let on_action = Callback::from(move |e: Event| {
+        let input: web_sys::HtmlSelectElement = e.target_unchecked_into();
+        a_c.set(input.value());
+    });
+    let n_c = note.clone();
+    let on_note = Callback::from(move |e: InputEvent| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        n_c.set(input.value());
+    });
+
+    let e_c = error.clone();
+    let done = props.on_changed.clone();
+    let on_claim = Callback::from(move |_| {
+        let e_c = e_c.clone();
+        let done = done.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match claim_report(id).await {
+                Ok(_) => done.emit(()),
+                Err(e) => e_c.set(format!("Claiming failed: {:?}", e)),
+            }
+        });
+    });
+    let on_handle = |dismiss: bool| {
+        let (a_c, n_c, e_c, done) = (action.clone(), note.clone(), error.clone(), props.on_changed.clone());
+        Callback::from(move |_| {
+            let action = Some((*a_c).clone()).filter(|a| !dismiss && !a.is_empty());
+            let note = Some(n_c.trim().to_string()).filter(|n| !n.is_empty());
+            let (e_c, done) = (e_c.clone(), done.clone());
+            wasm_bindgen_futures::spawn_local(async move {
+                match handle_report(id, dismiss, action, note).await {
+                    Ok(_) => done.emit(()),
+                    Err(e) => e_c.set(format!("Handling failed: {:?}", e)),
+                }
+            });
+        })
+    };
+
+    let pending = report.status == "open" || report.status == "claimed";
+    html! {
+        <div class="border border-zinc-800 rounded-xl p-2 space-y-1 text-sm">
+            <div class="space-x-2">
+                <span class="text-emerald-500">{report.created_at.format(crate::DATEFORMAT).to_string()}</span>
+                <span>{target(report)}</span>
+                if let (Some(user), Some(name)) = (&report.reported_user, &report.reported_user_name) {
+                    <Link<Route> to={Route::User { id: user.clone() }}>{format!("by {}", name)}</Link<Route>>
+                }
+                <span class="text-red-400">{report.reason.replace('_', " ")}</span>
+                <span class="text-zinc-400">{format!("{} report(s)", report.report_count)}</span>
+                <span class="text-zinc-400">{format!("from {}", report.reporter_name.clone().unwrap_or_else(|| "Deleted User".to_string()))}</span>
+            </div>
+            if let Some(comment) = &report.comment {
+                <div class="italic">{comment.clone()}</div>
+            }
+            <div class="whitespace-pre-wrap text-xs text-zinc-400 bg-violet-950/20 p-1">{report.snapshot.clone()}</div>
+            <div class="space-x-2 text-xs">
+                <span>{report.status.clone()}</span>
+                if let Some(name) = &report.claimed_by_name {
+                    <span class="text-zinc-400">{format!("claimed by {}", name)}</span>
+                }
+                if let Some(name) = &report.handled_by_name {
+                    <span class="text-zinc-400">{format!("handled by {}", name)}</span>
+                }
+                if let Some(action) = &report.action {
+                    <span class="text-zinc-400">{action.replace('_', " ")}</span>
+                }
+                if let Some(resolution) = &report.resolution {
+                    <span class="text-zinc-400">{resolution.clone()}</span>
+                }
+            </div>
+            if pending {
+                <div class="space-x-2 text-xs">
+                    if report.status == "open" {
+                        <button onclick={on_claim}>{"Claim"}</button>
+                    }
+                    <select class="bg-violet-950/20" onchange={on_action}>
+                        <option value="" selected={action.is_empty()}>{"No action"}</option>
+                        { for ACTIONS.iter().map(|a| html! {
+                            <option value={*a} selected={*action == *a}>{a.replace('_', " ")}</option>
+                        }) }
+                    </select>
+                    <input type="text" maxlength="255" placeholder="Note to the reporter" class="bg-violet-950/20"
+                        value={(*note).clone()} oninput={on_note}/>
+                    <button onclick={on_handle(false)}>{"Resolve"}</button>
+                    <button onclick={on_handle(true)}>{"Dismiss"}</button>
+                </div>
+            }
+            <span class="text-red-500">{(*error).clone()}</span>
+        </div>
+    }
+}
+
+/// Queue of reported items for the moderators
+#[component]
+pub fn Reports() -> Html {
+    let ctx = use_context::<UserContext>().expect("no context");
+    let status = use_state(|| "open".to_string());
+    let target_kind = use_state(String::new);
+    let page = use_state(|| 1u32);
+    let reload = use_state(|| 0u32);
+    let list = use_state(Vec::<Report>::new);
+    let error = use_state(String::new);
+
+    let l_c = list.clone();
+    let err_c = error.clone();
+    let filters = ((*status).clone(), (*target_kind).clone(), *page, *reload);
+    use_effect_with(filters, move |(status, target_kind, page, _)| {
+        let (status, target_kind, page) = (status.clone(), target_kind.clone(), *page);
+        wasm_bindgen_futures::spawn_local(async move {
+            match reports(&status, &target_kind, page).await {
+                Ok(r) => {
+                    err_c.set(String::new());
+                    l_c.set(r);
+                }
+                Err(e) => {
+                    crate::c_error!("Failed to load the reports: {:?}", e);
+                    err_c.set("Failed to load the reports".to_string());
+                }
+            }
+        });
+    });
+
+    let select = |state: UseStateHandle<String>, page: UseStateHandle<u32>| Callback::from(move |e: Event| {
+        let input: web_sys::HtmlSelectElement = e.target_unchecked_into();
+        page.set(1);
+        state.set(input.value());
+    });
+    let r_c = reload.clone();
+    let on_changed = Callback::from(move |_| r_c.set(*r_c + 1));
+    let pg_c = page.clone();
+    let pg_prev = Callback::from(move |_| pg_c.set((*pg_c - 1).max(1)));
+    let pg_c = page.clone();
+    let pg_next = Callback::from(move |_| pg_c.set(*pg_c + 1));
+
+    if !ctx.is_mod() && !ctx.is_admin() {
+        return html! { <div class="text-zinc-400">{"Only moderators can read the reports"}</div> };
+    }
+
+    html! {
+        <div class="space-y-4">
+            <h2 class="text-xl font-bold">{"Reports"}</h2>
+            <div class="flex space-x-2 text-sm">
+                <select class="bg-violet-950/20" onchange={select(status.clone(), page.clone())}>
+                    <option value="" selected={status.is_empty()}>{"All states"}</option>
+                    { for STATUSES.iter().map(|s| html! {
+                        <option value={*s} selected={*status == *s}>{*s}</option>
+                    }) }
+                </select>
+                <select class="bg-violet-950/20" onchange={select(target_kind.clone(), page.clone())}>
+                    <option value="" selected={target_kind.is_empty()}>{"All items"}</option>
+                    { for TARGETS.iter().map(|t| html! {
+                        <option value={*t} selected={*target_kind == *t}>{*t}</option>
+                    }) }
+                </select>
+            </div>
+            <span class="text-red-500">{(*error).clone()}</span>
+            { for list.iter().map(|r| html! {
+                <ReportRow key={r.id} report={r.clone()} on_changed={on_changed.clone()}/>
+            }) }
+            if list.is_empty() {
+                <div class="text-zinc-400">{"No reports"}</div>
+            }
+            <div class="space-x-2">
+                <button onclick={pg_prev} disabled={*page == 1}>{"Previous page"}</button>
+                <button onclick={pg_next} disabled={list.is_empty()}>{"Next page"}</button>
+            </div>
+        </div>
+    }
+}
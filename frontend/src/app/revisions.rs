@@ -0,0 +1,118 @@
+use yew::prelude::*;
+
+use crate::{dto::{Revision, RevisionDiffResponseDto}, forum::{restore_revision, revision_diff, revisions}};
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct Props {
+    pub thread_id: i64,
+    /// The opening post when missing
+    pub post_id: Option<i64>,
+    pub moderator: bool,
+    pub on_restored: Callback<()>,
+}
+
+fn diff_view(diff: &RevisionDiffResponseDto) -> Html {
+    html! {
+        <div class="font-mono text-xs whitespace-pre-wrap">
+            if let (Some(before), Some(after)) = (&diff.title_before, &diff.title_after)
+                && before != after {
+                <div class="bg-red-950/40">{format!("- {}", before)}</div>
+                <div class="bg-emerald-950/40">{format!("+ {}", after)}</div>
+            }
+            { for diff.lines.iter().map(|l| {
+                let (sign, class) = match l.kind.as_str() {
+                    "added" => ("+", "bg-emerald-950/40"),
+                    "removed" => ("-", "bg-red-950/40"),
+                    _ => (" ", "text-zinc-500"),
+                };
+                html! { <div class={class}>{format!("{} {}", sign, l.text)}</div> }
+            }) }
+        </div>
+    }
+}
+
+/// Edit history of a post with the changes of each edit
+#[component]
+pub fn Revisions(props: &Props) -> Html {
+    let list = use_state(Vec::<Revision>::new);
+    let open = use_state(|| None::<(i64, RevisionDiffResponseDto)>);
+    let error = use_state(String::new);
+
+    let l_c = list.clone();
+    let e_c = error.clone();
+    use_effect_with((props.thread_id, props.post_id), move |(thread_id, post_id)| {
+        let (thread_id, post_id) = (*thread_id, *post_id);
+        wasm_bindgen_futures::spawn_local(async move {
+            match revisions(thread_id, post_id).await {
+                Ok(r) => l_c.set(r),
+                Err(e) => e_c.set(format!("Failed to load the history: {:?}", e)),
+            }
+        });
+    });
+
+    // revisions hold the content before their edit, so an edit goes from
+    // its revision to the next newer one, or to the current content
+    let o_c = open.clone();
+    let e_c = error.clone();
+    let on_diff = Callback::from(move |(revision, to): (i64, Option<i64>)| {
+        let o_c = o_c.clone();
+        let e_c = e_c.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match revision_diff(revision, to).await {
+                Ok(d) => o_c.set(Some((revision, d))),
+                Err(e) => e_c.set(format!("Failed to compare: {:?}", e)),
+            }
+        });
+    });
+
+    let e_c = error.clone();
+    let done = props.on_restored.clone();
+    let on_restore = Callback::from(move |revision: i64| {
+        let e_c = e_c.clone();
+        let done = done.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match restore_revision(revision, None).await {
+                Ok(_) => done.emit(()),
+                Err(e) => e_c.set(format!("Restoring failed: {:?}", e)),
+            }
+        });
+    });
+
+    html! {
+        <div class="space-y-1 text-xs border border-zinc-800 rounded-xl p-2">
+            <span class="text-red-500">{(*error).clone()}</span>
+            if list.is_empty() {
+                <span class="text-zinc-500">{"No earlier versions"}</span>
+            }
+            { for list.iter().enumerate().map(|(i, r)| {
+                let to = i.checked_sub(1).map(|newer| list[newer].id);
+                let on_diff = on_diff.clone();
+                let on_restore = on_restore.clone();
+                let id = r.id;
+                html! {
+                    <div class="space-y-1">
+                        <div class="space-x-2">
+                            <span class="text-emerald-500">{r.edited_at.format(crate::DATEFORMAT).to_string()}</span>
+                            <span>{format!("edited by {}", r.editor_name.clone().unwrap_or_else(|| "Deleted User".to_string()))}</span>
+                            if let Some(reason) = &r.reason {
+                                <span class="text-zinc-500">{format!("({})", reason)}</span>
+                            }
+                            <button class="hover:text-zinc-200" onclick={Callback::from(move |_| on_diff.emit((id, to)))}>
+                                {"changes"}
+                            </button>
+                            if props.moderator {
+                                <button class="hover:text-zinc-200" onclick={Callback::from(move |_| on_restore.emit(id))}>
+                                    {"restore the version before"}
+                                </button>
+                            }
+                        </div>
+                        if let Some((open_id, diff)) = &*open
+                            && *open_id == r.id {
+                            {diff_view(diff)}
+                        }
+                    </div>
+                }
+            }) }
+        </div>
+    }
+}
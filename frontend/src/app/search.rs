@@ -0,0 +1,191 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+use crate::{Route, dto::{SearchResultDto, Section}, forum::{get_sections, search}};
+
+#[component]
+pub fn Search() -> Html {
+    let query = use_state(String::new);
+    let section = use_state(|| None::<i64>);
+    let author = use_state(String::new);
+    let from = use_state(String::new);
+    let to = use_state(String::new);
+    let page = use_state(|| 1);
+    let results = use_state(Vec::<SearchResultDto>::new);
+    let sections = use_state(Vec::<Section>::new);
+    let searched = use_state(|| false);
+    let error = use_state(String::new);
+
+    let s_c = sections.clone();
+    use_effect_with((), move |_| {
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Ok(list) = get_sections().await {
+                s_c.set(list);
+            }
+        });
+    });
+
+    let run_search = {
+        let query = query.clone();
+        let section = section.clone();
+        let author = author.clone();
+        let from = from.clone();
+        let to = to.clone();
+        let results = results.clone();
+        let searched = searched.clone();
+        let error = error.clone();
+        Callback::from(move |pg: i32| {
+            let q = (*query).clone();
+            let s = *section;
+            let a = (*author).clone();
+            let f = (*from).clone();
+            let t = (*to).clone();
+            let results = results.clone();
+            let searched = searched.clone();
+            let error = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let a = (!a.is_empty()).then_some(a);
+                let f = (!f.is_empty()).then_some(f);
+                let t = (!t.is_empty()).then_some(t);
+                match search(&q, s, a.as_deref(), f.as_deref(), t.as_deref(), Some(pg)).await {
+                    Ok(r) => {
+                        error.set(String::new());
+                        results.set(r);
+                    }
+                    Err(e) => {
+                        crate::c_error!("Search failed: {:?}", e);
+                        error.set("Search failed".to_string());
+                        results.set(Vec::new());
+                    }
+                }
+                searched.set(true);
+            });
+        })
+    };
+
+    let pg_c = page.clone();
+    let r_c = run_search.clone();
+    let on_submit = Callback::from(move |e: SubmitEvent| {
+        e.prevent_default();
+        pg_c.set(1);
+        r_c.emit(1);
+    });
+
+    let q_c = query.clone();
+    let on_query_input = Callback::from(move |e: InputEvent| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        q_c.set(input.value());
+    });
+
+    let a_c = author.clone();
+    let on_author_input = Callback::from(move |e: InputEvent| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        a_c.set(input.value());
+    });
+
+    let f_c = from.clone();
+    let on_from_input = Callback::from(move |e: InputEvent| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        f_c.set(input.value());
+    });
+
+    let t_c = to.clone();
+    let on_to_input = Callback::from(move |e: InputEvent| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        t_c.set(input.value());
+    });
+
+    let s_c = section.clone();
+    let on_section_change = Callback::from(move |e: Event| {
+        let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+        s_c.set(select.value().parse::<i64>().ok());
+    });
+
+    let pg_c = page.clone();
+    let r_c = run_search.clone();
+    let pg_next = Callback::from(move |_| {
+        let next = *pg_c + 1;
+        pg_c.set(next);
+        r_c.emit(next);
+    });
+
+    let pg_c = page.clone();
+    let r_c = run_search.clone();
+    let pg_first = Callback::from(move |_| {
+        pg_c.set(1);
+        r_c.emit(1);
+    });
+
+    html! {
+        <div class="space-y-5">
+            <form id="search" onsubmit={on_submit} class="grid grid-cols-6 gap-2 p-2">
+                <input type="text" placeholder="Search..." required=true minlength="2" maxlength="200"
+                    class="bg-fuchsia-950/40 col-span-5" value={(*query).clone()} oninput={on_query_input}/>
+                <input type="submit" value="Search" class="hover:bg-fuchsia-600 bg-fuchsia-800 rounded-xl"/>
+                <select class="bg-fuchsia-950/40 col-span-2" onchange={on_section_change}>
+                    <option value="" selected={section.is_none()}>{"All sections"}</option>
+                    {for sections.iter().map(|s| html! {
+                        <option value={s.id.to_string()} selected={*section == Some(s.id)}>{&s.name}</option>
+                    })}
+                </select>
+                <input type="text" placeholder="Author" maxlength="100"
+                    class="bg-fuchsia-950/40 col-span-2" value={(*author).clone()} oninput={on_author_input}/>
+                <input type="date" class="bg-fuchsia-950/40" value={(*from).clone()} oninput={on_from_input}/>
+                <input type="date" class="bg-fuchsia-950/40" value={(*to).clone()} oninput={on_to_input}/>
+            </form>
+            <span class="text-red-500">{(*error).clone()}</span>
+            {if *searched && results.is_empty() {
+                html! { <div class="text-zinc-400">{"Nothing found"}</div> }
+            } else {
+                html! {
+                    <div class="grid grid-cols-1 gap-4">
+                        {for results.iter().map(|r| html! {
+                            <Link<Route> to={Route::Topic { s_id: r.section_id, id: r.thread_id }}>
+                                <div class="bg-zinc-900/50 border border-zinc-800 rounded-2xl p-5 hover:border-cyan-700 grid grid-cols-1">
+                                    <div class="space-x-2">
+                                        <span class="text-l font-bold text-cyan-200">{&r.title}</span>
+                                        <span class="text-xs text-zinc-500">{if r.post_id.is_some() {"reply"} else {"thread"}}</span>
+                                    </div>
+                                    <span class="text-zinc-400">{highlight(&r.snippet)}</span>
+                                    <div class="space-x-2 text-xs">
+                                        <span>{r.author_name.clone().unwrap_or_default()}</span>
+                                        <span class="text-emerald-500">{r.created_at.format(crate::DATEFORMAT).to_string()}</span>
+                                    </div>
+                                </div>
+                            </Link<Route>>
+                        })}
+                    </div>
+                }
+            }}
+            {if *searched {
+                html! {
+                    <div class="space-x-2">
+                        <button onclick={pg_first} disabled={*page == 1}>{"First page"}</button>
+                        <button onclick={pg_next} disabled={results.is_empty()}>{"Next page"}</button>
+                    </div>
+                }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
+
+/// Turns the `<mark>` delimiters of a snippet into elements without
+/// handing the rest of the text to the browser as HTML
+fn highlight(snippet: &str) -> Html {
+    let mut parts = Vec::new();
+    for (i, chunk) in snippet.split("<mark>").enumerate() {
+        if i == 0 {
+            parts.push(html! { {chunk} });
+            continue;
+        }
+        match chunk.split_once("</mark>") {
+            Some((marked, rest)) => {
+                parts.push(html! { <mark class="bg-cyan-900 text-zinc-100">{marked}</mark> });
+                parts.push(html! { {rest} });
+            }
+            None => parts.push(html! { {chunk} }),
+        }
+    }
+    html! { <>{for parts}</> }
+}
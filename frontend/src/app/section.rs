@@ -1,7 +1,7 @@
 use yew::prelude::*;
 use yew_router::prelude::*;
 use wasm_bindgen::UnwrapThrowExt;
-use crate::{UserContext, dto::ThreadListItemDto, forum::get_topics};
+use crate::{UserContext, dto::ThreadListItemDto, forum::{get_topics, mark_section_read, subscribe_section}, user::subscriptions};
 
 #[derive(Clone, Properties, PartialEq)]
 pub struct Props {
@@ -10,13 +10,15 @@ pub struct Props {
 
 #[component]
 pub fn Section(props: &Props) -> Html {
-    let topic_list = use_state(|| Vec::<ThreadListItemDto>::new());
+    let topic_list = use_state(Vec::<ThreadListItemDto>::new);
+    let announcements = use_state(Vec::<ThreadListItemDto>::new);
     let loaded = use_state(|| false);
     let ctx = use_context::<UserContext>().expect("no context");
     let page = use_state(|| None::<i32>);
     let limit = use_state(|| None::<usize>);
     let anon = ctx.is_none();
     let is_some = use_state(|| false);
+    let subscribed = use_state(|| false);
     let section_id = props.id;
     let t_c = topic_list.clone();
     let l_c = loaded.clone();
@@ -48,20 +50,72 @@ pub fn Section(props: &Props) -> Html {
     let pg_c = page.clone();
     let lim_c = limit.clone();
     let l_c = loaded.clone();
+    let a_c = announcements.clone();
     use_effect_with((), move |_| {
         wasm_bindgen_futures::spawn_local(async move {
             let topic_list = t_c.clone();
-            let threads = get_topics(section_id, *pg_c, *lim_c).await
+            let response = get_topics(section_id, *pg_c, *lim_c).await
                 .unwrap_throw();
-            some_c.set(threads.len()>0);
-            topic_list.set(threads);
+            some_c.set(!response.threads.is_empty());
+            a_c.set(response.announcements);
+            topic_list.set(response.threads);
             l_c.set(true);
         });
     });
 
+    let sb_c = subscribed.clone();
+    use_effect_with(anon, move |anon| {
+        if !*anon {
+            wasm_bindgen_futures::spawn_local(async move {
+                match subscriptions().await {
+                    Ok(r) => sb_c.set(r.sections.iter().any(|s| s.section == section_id)),
+                    Err(e) => { crate::c_error!("Failed to load subscriptions: {:?}", e); }
+                }
+            });
+        }
+    });
+
+    let sb_c = subscribed.clone();
+    let on_subscribe = Callback::from(move |_| {
+        let sb_c = sb_c.clone();
+        let subscribe = !*sb_c;
+        wasm_bindgen_futures::spawn_local(async move {
+            match subscribe_section(section_id, subscribe).await {
+                Ok(_) => sb_c.set(subscribe),
+                Err(e) => { crate::c_error!("Failed to change subscription: {:?}", e); }
+            }
+        });
+    });
+
+    let t_c = topic_list.clone();
+    let on_read = Callback::from(move |_| {
+        let t_c = t_c.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match mark_section_read(section_id).await {
+                Ok(_) => t_c.set(t_c.iter()
+                    .cloned()
+                    .map(|t| ThreadListItemDto { unread: false, unread_posts: 0, ..t })
+                    .collect()),
+                Err(e) => { crate::c_error!("Failed to mark section as read: {:?}", e); }
+            }
+        });
+    });
+
     html! {
         <div>
             <Link<crate::Route> to={crate::Route::Topic { id: 0, s_id: section_id }}>{"New thread"}</Link<crate::Route>>
+            if !anon {
+                <button class="ml-2" onclick={on_subscribe}>
+                    {if *subscribed { "Unsubscribe" } else { "Subscribe" }}
+                </button>
+                <button class="ml-2" onclick={on_read}>{"Mark section read"}</button>
+            }
+            if !announcements.is_empty() {
+                <div class="grid grid-cols-1 gap-2 my-4">
+                    <p class="text-sm text-amber-400">{"Announcements"}</p>
+                    {for announcements.iter().map(thread_card)}
+                </div>
+            }
             {
             if (*topic_list).is_empty() {
         
@@ -87,20 +141,7 @@ pub fn Section(props: &Props) -> Html {
                 }}
                     <p class="text-zinc-400 text-sm mb-1"> {format!("Section {}", props.id)} </p>
                     <div class="grid grid-cols-1 md:grid-cols-2 gap-4 mb-8">
-                            {for (*topic_list).iter().map(|topic| {
-                                html! { 
-                                    <Link<crate::Route> to={crate::Route::Topic { id: topic.id, s_id: section_id }}>
-                                        <div class={classes!(vec!["flex","items-end","gap-2","border","rounded-2xl","p-5 hover:border-cyan-700", thread_border(topic.sticky), thread_background(topic.sticky)])}>
-                                            <div class="grid grid-cols-2 gap-1">
-                                                <span class="text-xs mb-1">{&topic.author_name}</span>
-                                                <span class="text-l font-bold">{&topic.title}</span>
-                                                <span class="text-xs text-red-500">{if topic.locked {"LOCKED"} else {""}}</span>
-                                                <span class="text-xs text-emerald-500">{&topic.created_at.format(crate::DATEFORMAT).to_string()}</span>
-                                            </div>
-                                        </div> 
-                                    </Link<crate::Route>>
-                                }
-                            })}
+                            {for (*topic_list).iter().map(thread_card)}
                     </div>
                                     </div>
             }}
@@ -114,6 +155,37 @@ pub fn Section(props: &Props) -> Html {
     }
 }
 
+fn thread_card(topic: &ThreadListItemDto) -> Html {
+    let pinned = topic.sticky || topic.announcement;
+    html! {
+        <Link<crate::Route> to={crate::Route::Topic { id: topic.id, s_id: topic.section_id }}>
+            <div class={classes!(vec!["flex","items-end","gap-2","border","rounded-2xl","p-5 hover:border-cyan-700", thread_border(pinned), thread_background(pinned)])}>
+                <div class="grid grid-cols-2 gap-1">
+                    <span class="text-xs mb-1">{&topic.author_name}</span>
+                    <span class="text-l font-bold">{&topic.title}</span>
+                    <span class="text-xs">
+                        if topic.deleted_at.is_some() {
+                            <span class="text-red-500">{"DELETED "}</span>
+                        } else if topic.pending {
+                            <span class="text-amber-500">{"AWAITS APPROVAL "}</span>
+                        } else if topic.moved_to.is_some() {
+                            <span class="text-zinc-500">{"MOVED "}</span>
+                        } else {
+                            <span class="text-red-500">{if topic.locked {"LOCKED "} else {""}}</span>
+                        }
+                        if topic.unread_posts > 0 {
+                            <span class="text-cyan-500">{format!("{} new", topic.unread_posts)}</span>
+                        } else if topic.unread {
+                            <span class="text-cyan-500">{"NEW"}</span>
+                        }
+                    </span>
+                    <span class="text-xs text-emerald-500">{&topic.created_at.format(crate::DATEFORMAT).to_string()}</span>
+                </div>
+            </div>
+        </Link<crate::Route>>
+    }
+}
+
 fn thread_background(sticky: bool) -> &'static str {
     match sticky {
         false => "bg-zinc-900/50",
@@ -2,11 +2,12 @@ use wasm_bindgen::UnwrapThrowExt;
 use web_sys::window;
 use yew::prelude::*;
 use yew_router::prelude::*;
-use crate::{Route, dto::{CreateSectionDto, Section}, forum::{create_section, get_sections}};
+use crate::{Route, dto::{CreateSectionDto, Section}, forum::{active_users, create_section, get_sections, mark_forum_read, set_section_approval}, user::UserSession};
+use super::shoutbox::Shoutbox;
 
 #[component]
 pub fn SectionList() -> Html {
-    let section_list = use_state(|| Vec::<Section>::new());
+    let section_list = use_state(Vec::<Section>::new);
     let new_section_data = use_state(|| None::<CreateSectionDto>);
     let loaded = use_state(|| false);
     let ctx = use_context::<crate::UserContext>()
@@ -24,6 +25,31 @@ pub fn SectionList() -> Html {
         })
     });
 
+    let online = use_state(Vec::<UserSession>::new);
+    let o_c = online.clone();
+    use_effect_with((), move |_| {
+        wasm_bindgen_futures::spawn_local(async move {
+            match active_users(None).await {
+                Ok(active) => o_c.set(active.users),
+                Err(e) => { crate::c_error!("Error loading online users: {:?}", e); }
+            }
+        })
+    });
+
+    let sl_c = section_list.clone();
+    let on_read_all = Callback::from(move |_| {
+        let sl_c = sl_c.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match mark_forum_read().await {
+                Ok(_) => sl_c.set(sl_c.iter()
+                    .cloned()
+                    .map(|s| Section { new_posts: false, unread: 0, ..s })
+                    .collect()),
+                Err(e) => { crate::c_error!("Error marking forum as read: {:?}", e); }
+            }
+        });
+    });
+
     let n_c = new_section_data.clone();
     let on_st_cr_sec = Callback::from(move |e: MouseEvent| {
         e.prevent_default();
@@ -74,6 +100,16 @@ pub fn SectionList() -> Html {
         }
     });
 
+    // Callback for the approval checkbox
+    let n_c_mod = new_section_data.clone();
+    let on_moderated_change = Callback::from(move |e: Event| {
+        let checkbox = e.target_unchecked_into::<web_sys::HtmlInputElement>();
+        if let Some(mut dto) = (*n_c_mod).clone() {
+            dto.moderated = checkbox.checked();
+            n_c_mod.set(Some(dto));
+        }
+    });
+
     // Callback for updating allowed_for checkboxes
     let n_c_allowed = new_section_data.clone();
     let on_allowed_change = Callback::from(move |e: Event| {
@@ -112,9 +148,32 @@ pub fn SectionList() -> Html {
         }
     });
 
+    // Admins switch approval for a section right on its card
+    let sl_c = section_list.clone();
+    let on_toggle_approval = Callback::from(move |(e, s_id, moderated): (MouseEvent, i64, bool)| {
+        e.prevent_default();
+        e.stop_propagation();
+        let sl_c = sl_c.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match set_section_approval(s_id, moderated).await {
+                Ok(_) => {
+                    let mut list = (*sl_c).clone();
+                    if let Some(s) = list.iter_mut().find(|s| s.id == s_id) {
+                        s.moderated = moderated;
+                    }
+                    sl_c.set(list);
+                }
+                Err(e) => { crate::c_error!("Failed to change approval: {:?}", e); }
+            }
+        });
+    });
+
     html! {
         <div class="section-list">
             <div class="rounded-2xl grid grid-cols-2 gap-4">
+                if ctx.is_some() {
+                    <button class="col-span-2 justify-self-end" onclick={on_read_all}>{"Mark all read"}</button>
+                }
                 {if ctx.is_admin() && new_section_data.is_none() {
                     html! { <button class="bg-fuchsia-950/30 col-span-2 font-medium hover:bg-fuchsia-950/50" onclick={on_st_cr_sec}>{"Add section"}</button> }
                 } else { html! { {""} } } }
@@ -152,6 +211,10 @@ pub fn SectionList() -> Html {
                                     checked={new_s.allowed_for.contains(&"User".to_string())}
                                     onchange={on_allowed_change.clone()}/>
                             </div>
+                            <label for="s_moderated">{"approve posts"}</label>
+                            <input type="checkbox" id="s_moderated"
+                                checked={new_s.moderated}
+                                onchange={on_moderated_change.clone()}/>
                         </form>
                     }
                 } else {
@@ -162,21 +225,57 @@ pub fn SectionList() -> Html {
                         <Link<Route> to={Route::Section { id: section.id }}>
                         <div class={classes!("rounded-2xl","items-center","justify-between","p-4",
                             "bg-zinc-900/50","border","hover:bg-zinc-700/30","transition-colors", new_posts(section.new_posts))}>
-                            <p class="font-medium px-2 text-indigo-200">{&section.name}</p>
+                            <p class="font-medium px-2 text-indigo-200">
+                                {&section.name}
+                                if section.unread > 0 {
+                                    <span class="ml-2 text-xs text-cyan-500">{format!("{} unread", section.unread)}</span>
+                                }
+                            </p>
                             {if let Some(desc) = &section.description {
                                 html! {<p class="py-3 px-2 text-sm text-zinc-400">{desc}</p>}
                             } else {
                                 html! {<p class="py-3 px-2 text-sm text-zinc-400">{"-"}</p>}
                             }}
+                            {if !section.moderators.is_empty() {
+                                let names: Vec<&str> = section.moderators.iter().map(|m| m.name.as_str()).collect();
+                                html! {<p class="px-2 text-xs text-zinc-500">{format!("Moderators: {}", names.join(", "))}</p>}
+                            } else {
+                                html! {}
+                            }}
+                            if section.moderated {
+                                <p class="px-2 text-xs text-amber-500">{"New posts are approved by a moderator first"}</p>
+                            }
+                            if ctx.is_admin() {
+                                <button class="px-2 text-xs text-zinc-500 hover:text-zinc-300" onclick={{
+                                    let (cb, s_id, moderated) = (on_toggle_approval.clone(), section.id, !section.moderated);
+                                    Callback::from(move |e: MouseEvent| cb.emit((e, s_id, moderated)))
+                                }}>
+                                    {if section.moderated { "Stop approving posts" } else { "Approve posts first" }}
+                                </button>
+                            }
                         </div>
                         </Link<Route>>
                     }
                 })}
             </div>
+            <p class="mt-4 text-sm text-zinc-500">
+                {format!("Online ({}): ", online.len())}
+                { for online.iter().map(|u| html! {
+                    <Link<Route> to={Route::User { id: u.user_id.clone() }} classes={classes!(online_class(u.invisible))}>
+                        {format!("{} ", u.username)}
+                    </Link<Route>>
+                }) }
+            </p>
+            <Shoutbox/>
         </div>
     }
 }
 
+/// Staff also see invisible users, greyed out
+fn online_class(invisible: bool) -> &'static str {
+    if invisible { "text-zinc-600 italic" } else { "text-cyan-300" }
+}
+
 fn new_posts(n: bool) -> &'static str {
     if n { "border-cyan-500" } else { "border-zinc-900" }
 }
@@ -0,0 +1,158 @@
+use std::rc::Rc;
+
+use wasm_bindgen::{JsCast, closure::Closure};
+use web_sys::{MessageEvent, WebSocket};
+use yew::prelude::*;
+
+use crate::{UserContext, dto::{ChatCommand, ChatEvent, ChatPost}, user::blocked_users};
+use super::report::ReportButton;
+
+#[derive(Default, PartialEq)]
+struct ChatState {
+    posts: Vec<ChatPost>,
+    error: Option<String>,
+}
+
+impl Reducible for ChatState {
+    type Action = ChatEvent;
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut posts = self.posts.clone();
+        let mut error = None;
+        match action {
+            ChatEvent::History { posts: history } => posts = history,
+            ChatEvent::Message { post } => posts.push(post),
+            ChatEvent::Deleted { id } => posts.retain(|p| p.id != id),
+            ChatEvent::Error { message } => error = Some(message),
+        }
+        ChatState { posts, error }.into()
+    }
+}
+
+fn send(ws: &Option<WebSocket>, command: &ChatCommand) {
+    if let Some(ws) = ws
+        && let Ok(text) = serde_json::to_string(command)
+        && let Err(e) = ws.send_with_str(&text) {
+        crate::c_error!("Chat send failed: {:?}", e);
+    }
+}
+
+/// Live chat on the front page, fed by the /forum/chat socket
+#[component]
+pub fn Shoutbox() -> Html {
+    let ctx = use_context::<UserContext>().expect("no context");
+    let chat = use_reducer(ChatState::default);
+    let socket = use_mut_ref(|| None::<WebSocket>);
+    let text = use_state(String::new);
+    let blocked = use_state(Vec::<String>::new);
+    let can_post = ctx.is_some() && !ctx.banned();
+    let moderator = ctx.is_mod() || ctx.is_admin();
+
+    // reconnect when the user logs in or out, the socket authenticates with the cookie
+    let c_c = chat.clone();
+    let s_c = socket.clone();
+    use_effect_with(ctx.id(), move |_| {
+        let url = format!("{}/forum/chat", crate::ADDR.replacen("http", "ws", 1));
+        let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |e: MessageEvent| {
+            if let Some(text) = e.data().as_string() {
+                match serde_json::from_str::<ChatEvent>(&text) {
+                    Ok(event) => c_c.dispatch(event),
+                    Err(e) => { crate::c_error!("Unknown chat event: {:?}", e); }
+                }
+            }
+        });
+        match WebSocket::new(&url) {
+            Ok(ws) => {
+                ws.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+                *s_c.borrow_mut() = Some(ws);
+            }
+            Err(e) => { crate::c_error!("Chat connection failed: {:?}", e); }
+        }
+        move || {
+            if let Some(ws) = s_c.borrow_mut().take() {
+                ws.set_onmessage(None);
+                let _ = ws.close();
+            }
+            drop(on_message);
+        }
+    });
+
+    let b_c = blocked.clone();
+    use_effect_with(ctx.is_some(), move |logged_in| {
+        if *logged_in {
+            wasm_bindgen_futures::spawn_local(async move {
+                match blocked_users().await {
+                    Ok(list) => b_c.set(list.into_iter().map(|b| b.user_id).collect()),
+                    Err(e) => { crate::c_error!("Failed to load blocked users: {:?}", e); }
+                }
+            });
+        }
+    });
+
+    let t_c = text.clone();
+    let on_input = Callback::from(move |e: InputEvent| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        t_c.set(input.value());
+    });
+
+    let t_c = text.clone();
+    let s_c = socket.clone();
+    let on_submit = Callback::from(move |e: SubmitEvent| {
+        e.prevent_default();
+        let content = t_c.trim().to_string();
+        if content.is_empty() {
+            return;
+        }
+        send(&s_c.borrow(), &ChatCommand::Post { content });
+        t_c.set(String::new());
+    });
+
+    let on_delete = {
+        let socket = socket.clone();
+        Callback::from(move |id: i32| send(&socket.borrow(), &ChatCommand::Delete { id }))
+    };
+
+    html! {
+        <div class="mt-8 bg-zinc-900/50 border border-zinc-800 rounded-2xl p-4 space-y-2">
+            <p class="font-medium text-indigo-200">{"Shoutbox"}</p>
+            <div class="max-h-64 overflow-y-auto space-y-1 text-sm">
+                if chat.posts.is_empty() {
+                    <p class="text-zinc-500">{"Nobody said anything yet"}</p>
+                }
+                { for chat.posts.iter().map(|p| {
+                    let on_delete = on_delete.clone();
+                    let id = p.id;
+                    html! {
+                        <div class="flex space-x-2">
+                            <span class="text-xs text-zinc-600">{p.added.format(crate::DATEFORMAT).to_string()}</span>
+                            <span class="text-cyan-300">{&p.author_name}</span>
+                            if blocked.contains(&p.author) {
+                                <span class="text-zinc-600 italic">{"blocked user"}</span>
+                            } else {
+                                <span class="flex-1 text-zinc-300">{&p.content}</span>
+                            }
+                            if p.author != ctx.id() {
+                                <ReportButton kind="chat" target_id={id.to_string()}/>
+                            }
+                            if moderator {
+                                <button class="text-xs text-zinc-500 hover:text-red-400"
+                                    onclick={Callback::from(move |_| on_delete.emit(id))}>
+                                    {"delete"}
+                                </button>
+                            }
+                        </div>
+                    }
+                }) }
+            </div>
+            if let Some(error) = &chat.error {
+                <span class="text-red-500 text-sm">{error}</span>
+            }
+            if can_post {
+                <form onsubmit={on_submit} class="flex space-x-2">
+                    <input type="text" maxlength="255" class="flex-1 bg-fuchsia-950/30 rounded-xl px-2"
+                        value={(*text).clone()} oninput={on_input}/>
+                    <input type="submit" value="Send" class="px-3 bg-indigo-800 rounded-xl hover:bg-violet-600"/>
+                </form>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,248 @@
+use yew::prelude::*;
+use yew_router::hooks::use_navigator;
+
+use crate::{Route, UserContext, dto::{Section, Thread}, forum::{delete_thread, get_sections, merge_threads, move_thread, restore_thread, split_thread, sticky_thread}};
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct Props {
+    pub thread: Thread,
+    /// Posts ticked for splitting
+    pub selected: Vec<i64>,
+    pub on_done: Callback<()>,
+}
+
+/// `datetime-local` inputs hold the user's local time
+fn parse_local(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    if value.is_empty() {
+        return None;
+    }
+    let millis = js_sys::Date::new(&wasm_bindgen::JsValue::from_str(value)).get_time();
+    chrono::DateTime::from_timestamp_millis(millis as i64)
+}
+
+/// Pin, move, merge, split and delete actions of the moderators
+#[component]
+pub fn ThreadTools(props: &Props) -> Html {
+    let ctx = use_context::<UserContext>().expect("no context");
+    let navigator = use_navigator().expect("no navigator");
+    let sticky = use_state(|| props.thread.sticky);
+    let announcement = use_state(|| props.thread.announcement);
+    let until = use_state(String::new);
+    let sections = use_state(Vec::<Section>::new);
+    let target_section = use_state(|| None::<i64>);
+    let stub = use_state(|| true);
+    let merge_target = use_state(String::new);
+    let split_title = use_state(String::new);
+    let reason = use_state(String::new);
+    let error = use_state(String::new);
+    let thread = props.thread.clone();
+
+    let s_c = sections.clone();
+    use_effect_with((), move |_| {
+        wasm_bindgen_futures::spawn_local(async move {
+            match get_sections().await {
+                Ok(list) => s_c.set(list),
+                Err(e) => { crate::c_error!("Failed to load sections: {:?}", e); }
+            }
+        });
+    });
+
+    let ts_c = target_section.clone();
+    let on_section_change = Callback::from(move |e: Event| {
+        let input: web_sys::HtmlSelectElement = e.target_unchecked_into();
+        ts_c.set(input.value().parse().ok());
+    });
+    let st_c = stub.clone();
+    let on_stub_change = Callback::from(move |e: Event| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        st_c.set(input.checked());
+    });
+    let text_input = |state: UseStateHandle<String>| Callback::from(move |e: InputEvent| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        state.set(input.value());
+    });
+
+    let checkbox = |state: UseStateHandle<bool>| Callback::from(move |e: Event| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        state.set(input.checked());
+    });
+
+    // blank means no reason for the moderation log
+    let reason_of = |state: &UseStateHandle<String>| {
+        let r = state.trim().to_string();
+        (!r.is_empty()).then_some(r)
+    };
+
+    let sk_c = sticky.clone();
+    let an_c = announcement.clone();
+    let u_c = until.clone();
+    let e_c = error.clone();
+    let done = props.on_done.clone();
+    let admin = ctx.is_admin();
+    let r_c = reason.clone();
+    let on_pin = Callback::from(move |e: SubmitEvent| {
+        e.prevent_default();
+        let (sticky, until) = (*sk_c, parse_local(&u_c));
+        // only admins may touch announcements
+        let announcement = admin.then_some(*an_c);
+        let reason = reason_of(&r_c);
+        let e_c = e_c.clone();
+        let done = done.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match sticky_thread(thread.id, sticky, announcement, until, reason).await {
+                Ok(_) => done.emit(()),
+                Err(e) => e_c.set(format!("Pinning failed: {:?}", e)),
+            }
+        });
+    });
+
+    let thread = props.thread.clone();
+    let ts_c = target_section.clone();
+    let st_c = stub.clone();
+    let e_c = error.clone();
+    let done = props.on_done.clone();
+    let r_c = reason.clone();
+    let on_move = Callback::from(move |e: SubmitEvent| {
+        e.prevent_default();
+        let Some(section) = *ts_c else { return };
+        let stub = *st_c;
+        let reason = reason_of(&r_c);
+        let e_c = e_c.clone();
+        let done = done.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match move_thread(thread.id, section, stub, reason).await {
+                Ok(_) => done.emit(()),
+                Err(e) => e_c.set(format!("Move failed: {:?}", e)),
+            }
+        });
+    });
+
+    let thread = props.thread.clone();
+    let mt_c = merge_target.clone();
+    let e_c = error.clone();
+    let done = props.on_done.clone();
+    let r_c = reason.clone();
+    let on_merge = Callback::from(move |e: SubmitEvent| {
+        e.prevent_default();
+        let Ok(target) = mt_c.trim().parse::<i64>() else {
+            e_c.set("Enter the number of the thread to merge into".to_string());
+            return;
+        };
+        let reason = reason_of(&r_c);
+        let e_c = e_c.clone();
+        let done = done.clone();
+        // the thread becomes a redirect, which sends the reader on after the reload
+        wasm_bindgen_futures::spawn_local(async move {
+            match merge_threads(thread.id, target, reason).await {
+                Ok(_) => done.emit(()),
+                Err(e) => e_c.set(format!("Merge failed: {:?}", e)),
+            }
+        });
+    });
+
+    let thread = props.thread.clone();
+    let selected = props.selected.clone();
+    let t_c = split_title.clone();
+    let ts_c = target_section.clone();
+    let e_c = error.clone();
+    let r_c = reason.clone();
+    let on_split = Callback::from(move |e: SubmitEvent| {
+        e.prevent_default();
+        let selected = selected.clone();
+        let title = (*t_c).clone();
+        let section = *ts_c;
+        let reason = reason_of(&r_c);
+        let e_c = e_c.clone();
+        let navigator = navigator.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match split_thread(thread.id, selected, &title, section, reason).await {
+                Ok(id) => navigator.push(&Route::Topic { s_id: section.unwrap_or(thread.section), id }),
+                Err(e) => e_c.set(format!("Split failed: {:?}", e)),
+            }
+        });
+    });
+
+    // deleted threads stay visible to moderators, who can bring them back
+    let thread = props.thread.clone();
+    let e_c = error.clone();
+    let done = props.on_done.clone();
+    let r_c = reason.clone();
+    let on_delete = Callback::from(move |e: SubmitEvent| {
+        e.prevent_default();
+        let reason = reason_of(&r_c);
+        let deleted = thread.deleted_at.is_some();
+        let e_c = e_c.clone();
+        let done = done.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let result = if deleted {
+                restore_thread(thread.id, reason).await
+            } else {
+                delete_thread(thread.id, reason).await
+            };
+            match result {
+                Ok(_) => done.emit(()),
+                Err(e) => e_c.set(format!("Deleting failed: {:?}", e)),
+            }
+        });
+    });
+
+    html! {
+        <div class="space-y-2 text-sm border border-zinc-800 rounded-xl p-2">
+            <span class="text-red-500">{(*error).clone()}</span>
+            <input type="text" maxlength="255" placeholder="Reason (optional)" class="bg-violet-950/20 w-full"
+                value={(*reason).clone()} oninput={text_input(reason.clone())}/>
+            <form onsubmit={on_pin} class="space-x-2">
+                <label class="space-x-1">
+                    <input type="checkbox" checked={*sticky} onchange={checkbox(sticky.clone())}/>
+                    <span>{"sticky"}</span>
+                </label>
+                if admin {
+                    <label class="space-x-1">
+                        <input type="checkbox" checked={*announcement} onchange={checkbox(announcement.clone())}/>
+                        <span>{"announcement"}</span>
+                    </label>
+                }
+                <span>{"until"}</span>
+                <input type="datetime-local" class="bg-violet-950/20"
+                    value={(*until).clone()} oninput={text_input(until.clone())}/>
+                if let Some(until) = props.thread.sticky_until {
+                    <span class="text-zinc-500">{format!("(now {})", until.format(crate::DATEFORMAT))}</span>
+                }
+                <input type="submit" value="Pin"
+                    class="px-3 bg-indigo-800 rounded-xl hover:bg-violet-600"/>
+            </form>
+            <form onsubmit={on_move} class="space-x-2">
+                <select class="bg-violet-950/20" onchange={on_section_change}>
+                    <option value="" selected={target_section.is_none()}>{"Section…"}</option>
+                    { for sections.iter().filter(|s| s.id != props.thread.section).map(|s| html! {
+                        <option value={s.id.to_string()} selected={*target_section == Some(s.id)}>{&s.name}</option>
+                    }) }
+                </select>
+                <label class="space-x-1">
+                    <input type="checkbox" checked={*stub} onchange={on_stub_change}/>
+                    <span>{"leave a redirect"}</span>
+                </label>
+                <input type="submit" value="Move" disabled={target_section.is_none()}
+                    class="px-3 bg-indigo-800 rounded-xl hover:bg-violet-600 disabled:bg-zinc-900"/>
+            </form>
+            <form onsubmit={on_merge} class="space-x-2">
+                <input type="number" min="1" placeholder="Thread #" class="bg-violet-950/20 w-24"
+                    value={(*merge_target).clone()} oninput={text_input(merge_target.clone())}/>
+                <input type="submit" value="Merge into"
+                    class="px-3 bg-indigo-800 rounded-xl hover:bg-violet-600"/>
+            </form>
+            <form onsubmit={on_split} class="space-x-2">
+                <input type="text" minlength="3" placeholder="Title of the new thread" class="bg-violet-950/20"
+                    value={(*split_title).clone()} oninput={text_input(split_title.clone())}/>
+                <input type="submit" disabled={props.selected.is_empty()}
+                    value={format!("Split {} selected posts", props.selected.len())}
+                    class="px-3 bg-indigo-800 rounded-xl hover:bg-violet-600 disabled:bg-zinc-900"/>
+            </form>
+            <form onsubmit={on_delete} class="space-x-2">
+                <input type="submit"
+                    value={if props.thread.deleted_at.is_some() { "Restore thread" } else { "Delete thread" }}
+                    class="px-3 bg-red-900 rounded-xl hover:bg-red-700"/>
+            </form>
+        </div>
+    }
+}
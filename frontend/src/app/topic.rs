@@ -4,10 +4,21 @@ use web_sys::Element;
 use yew::prelude::*;
 use wasm_bindgen::UnwrapThrowExt;
 use yew_router::hooks::use_navigator;
-use crate::{dto::{Post, Thread, UserData}, forum::{delete_post, edit_thread, get_thread, new_thread}};
+use crate::{dto::{ForumEvent, Post, ThreadPresence, PostReaction, ReactionCount, ReactionKind, Thread, UserData}, forum::{delete_post, edit_thread, get_post_reactions, get_reaction_kinds, get_thread, new_thread, react, restore_post, subscribe_thread}, user::blocked_users};
 use super::user::User;
 use super::editor::Editor;
-use wasm_bindgen::JsCast;
+use super::thread_tools::ThreadTools;
+use super::revisions::Revisions;
+use super::report::ReportButton;
+use wasm_bindgen::{JsCast, closure::Closure};
+
+/// How replies are laid out on the topic page
+#[derive(Clone, Copy, PartialEq)]
+enum ReplyView {
+    Flat,
+    Quoted,
+    Threaded,
+}
 
 #[derive(Clone, Properties, PartialEq)]
 pub struct Props {
@@ -22,11 +33,27 @@ pub fn Topic(props: &Props) -> Html {
     let s_id = props.section;
     let meta = use_state(Thread::default);
     let posts = use_state(Vec::<Post>::new);
+    let reactions = use_state(Vec::<ReactionCount>::new);
+    let reaction_kinds = use_state(Vec::<ReactionKind>::new);
+    let liked_by = use_state(|| None::<(i64, Vec<PostReaction>)>);
+    let view = use_state(|| ReplyView::Flat);
+    let reply_to = use_state(|| None::<i64>);
+    let subscribed = use_state(|| false);
     let page = use_state(|| 1);
     let limit = use_state(|| 10);
+    // The first load opens the page with the oldest unread post
+    let jump_unread = use_state(|| true);
+    let first_unread = use_state(|| None::<i64>);
     let loaded = use_state(|| false);
+    // Posts of blocked users stay collapsed until revealed
+    let blocked = use_state(Vec::<String>::new);
+    let revealed = use_state(Vec::<i64>::new);
     let editing = use_state(|| None::<i64>);
     let thread_edit = use_state(|| false);
+    // Posts ticked by a moderator for splitting
+    let selected = use_state(Vec::<i64>::new);
+    // Edit history shown for a reply, or for the opening post when the inner value is missing
+    let history = use_state(|| None::<Option<i64>>);
     let navigator = use_navigator().unwrap_throw();
     let ctx = use_context::<crate::UserContext>()
         .expect("Expected context");
@@ -35,6 +62,61 @@ pub fn Topic(props: &Props) -> Html {
     let p_c = posts.clone();
     let l_c = loaded.clone();
 
+    let k_c = reaction_kinds.clone();
+    use_effect_with((), move |_| {
+        wasm_bindgen_futures::spawn_local(async move {
+            match get_reaction_kinds().await {
+                Ok(kinds) => k_c.set(kinds),
+                Err(e) => { crate::c_error!("Failed to load reactions: {:?}", e); }
+            }
+        });
+    });
+
+    let b_c = blocked.clone();
+    use_effect_with(anon, move |anon| {
+        if !*anon {
+            wasm_bindgen_futures::spawn_local(async move {
+                match blocked_users().await {
+                    Ok(list) => b_c.set(list.into_iter().map(|b| b.user_id).collect()),
+                    Err(e) => { crate::c_error!("Failed to load blocked users: {:?}", e); }
+                }
+            });
+        }
+    });
+
+    let presence = use_state(ThreadPresence::default);
+    // Reload whenever the thread changes elsewhere, presence updates are only shown
+    let l_c2 = loaded.clone();
+    let pr_c = presence.clone();
+    use_effect_with(id, move |id| {
+        let url = format!("{}/forum/threads/{}/events", crate::ADDR, id);
+        let on_event = Closure::<dyn FnMut(web_sys::MessageEvent)>::new(move |e: web_sys::MessageEvent| {
+            let data = e.data().as_string().unwrap_or_default();
+            match serde_json::from_str::<ForumEvent>(&data) {
+                Ok(ForumEvent::Presence { readers, users, typing, .. }) => {
+                    pr_c.set(ThreadPresence { readers, users, typing });
+                }
+                Ok(_) => l_c2.set(false),
+                Err(e) => { crate::c_error!("Unknown thread event: {:?}", e); }
+            }
+        });
+        let init = web_sys::EventSourceInit::new();
+        init.set_with_credentials(true);
+        let source = web_sys::EventSource::new_with_event_source_init_dict(&url, &init);
+        match &source {
+            Ok(source) => source.set_onmessage(Some(on_event.as_ref().unchecked_ref())),
+            Err(e) => { crate::c_error!("Failed to follow the thread: {:?}", e); }
+        }
+        move || {
+            if let Ok(source) = source {
+                source.set_onmessage(None);
+                source.close();
+            }
+            drop(on_event);
+        }
+    });
+
+    let fu_c = first_unread.clone();
     use_effect_with(l_c, move |_| {
         p_c.iter().for_each(|p| {
             let id = p.id;
@@ -45,6 +127,21 @@ pub fn Topic(props: &Props) -> Html {
                 .expect("No element id!");
             val.set_inner_html(&p.content);
         });
+        if let Some(first) = *fu_c
+            && let Some(val) = web_sys::window()
+                .and_then(|w| w.document())
+                .and_then(|d| d.get_element_by_id(&format!("post-{}", first))) {
+            val.scroll_into_view();
+        }
+    });
+
+    // the thread body is sanitised HTML as well, the element is only there outside the edit form
+    use_effect_with((meta.content.clone(), *thread_edit), move |(content, _)| {
+        if let Some(val) = web_sys::window()
+            .and_then(|w| w.document())
+            .and_then(|d| d.get_element_by_id("thread-body")) {
+            val.set_inner_html(content);
+        }
     });
 
     if id == 0 || *thread_edit { // NEW THREAD
@@ -63,7 +160,7 @@ pub fn Topic(props: &Props) -> Html {
             let mut th = (*m_c).clone();
             let input: web_sys::HtmlInputElement = e.target_unchecked_into();
             let v = input.value();
-            th.content = v;
+            th.source = v;
             m_c.set(th);
         });
 
@@ -76,7 +173,7 @@ pub fn Topic(props: &Props) -> Html {
             if id > 0 {
                 wasm_bindgen_futures::spawn_local(async move {
                     let meta = (*meta).clone();
-                    if let Err(e) = edit_thread(id, &meta.title, &meta.content).await {
+                    if let Err(e) = edit_thread(id, &meta.title, &meta.source).await {
                         crate::c_error!("{:?}", e);
                     }
                 });
@@ -84,7 +181,7 @@ pub fn Topic(props: &Props) -> Html {
                 let n_c = n_c.clone();
                 wasm_bindgen_futures::spawn_local(async move {
                     let meta = (*meta).clone();
-                    if let Err(e) = new_thread(&meta.title, &meta.content, s_id, vec![]).await {
+                    if let Err(e) = new_thread(&meta.title, &meta.source, s_id, vec![]).await {
                         crate::c_error!("{:?}", e);
                     }
                     n_c.push(&crate::Route::Section { id: s_id });
@@ -93,8 +190,8 @@ pub fn Topic(props: &Props) -> Html {
         });
         html! {
             <form id="new_thread" onsubmit={n_th_submit} class="space-y-5 p-2 space-x-2 grid grid-cols-1">
-                <input type="text" maxlength="20" value={(*meta).title.clone()} class="bg-fuchsia-950/40" oninput={on_title_change}/>
-                <textarea rows="10" maxlength="250" value={(*meta).content.clone()} class="bg-fuchsia-950/40" oninput={on_content_change}/>
+                <input type="text" maxlength="20" value={meta.title.clone()} class="bg-fuchsia-950/40" oninput={on_title_change}/>
+                <textarea rows="10" maxlength="250" value={meta.source.clone()} class="bg-fuchsia-950/40" oninput={on_content_change}/>
                 <input type="submit" value="New thread" class="disabled:bg-zinc-900 disabled:hover:bg-zinc-900 hover:bg-fuchsia-600 bg-fuchsia-800 rounded-xl" disabled={anon}/>
             </form>
         }
@@ -102,9 +199,11 @@ pub fn Topic(props: &Props) -> Html {
 
         let l_c = loaded.clone();
         let e_c = editing.clone();
+        let rt_c = reply_to.clone();
         let set_to_load = Callback::<()>::from(move |_| {
             l_c.set(false);
             e_c.set(None);
+            rt_c.set(None);
         });
 
        
@@ -114,13 +213,13 @@ pub fn Topic(props: &Props) -> Html {
             if let Some(target) = e.target() {
                 let elem: web_sys::Element = target.dyn_into().unwrap();
                 let id = elem.id();
-                    if id.starts_with("delete-") {
-                    let post_id: i64 = (&id[7..]).parse()
-                        .expect(&format!("failed to parse: {}", id));
+                    if let Some(post_id) = id.strip_prefix("delete-") {
+                    let post_id: i64 = post_id.parse()
+                        .unwrap_or_else(|_| panic!("failed to parse: {}", id));
 
                     let l_c = l_c.clone();
                     wasm_bindgen_futures::spawn_local(async move {
-                        if let Err(e) = delete_post(post_id).await {
+                        if let Err(e) = delete_post(post_id, None).await {
                             crate::c_error!("Failed to delete post: {:?}", e);
                         }
                         l_c.set(false);
@@ -136,27 +235,75 @@ pub fn Topic(props: &Props) -> Html {
             if let Some(target) = e.target()
                 && let Ok(element) = target.dyn_into::<Element>() {
                 let element_id = element.id();
-                if element_id.starts_with("edit-") {
-                    let id: i64 = (&element_id[5..])
+                if let Some(id) = element_id.strip_prefix("edit-") {
+                    let id: i64 = id
                         .parse()
-                        .expect(&format!("Failed to parse ID: {}", element_id));
+                        .unwrap_or_else(|_| panic!("Failed to parse ID: {}", element_id));
                     e_c.set(Some(id));
                     l_c.set(false);
                 }
             }
         });
 
+        let l_c = loaded.clone();
+        let on_react = Callback::from(move |(post_id, kind, add): (i64, String, bool)| {
+            let l_c = l_c.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Err(e) = react(post_id, &kind, add).await {
+                    crate::c_error!("Failed to react: {:?}", e);
+                }
+                l_c.set(false);
+            });
+        });
+
+        let lb_c = liked_by.clone();
+        let on_liked_by = Callback::from(move |post_id: i64| {
+            if lb_c.as_ref().is_some_and(|(p, _)| *p == post_id) {
+                lb_c.set(None);
+                return;
+            }
+            let lb_c = lb_c.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match get_post_reactions(post_id).await {
+                    Ok(list) => lb_c.set(Some((post_id, list))),
+                    Err(e) => { crate::c_error!("Failed to load reactions: {:?}", e); }
+                }
+            });
+        });
+
         let m_c = meta.clone();
         let p_c = posts.clone();
+        let r_c = reactions.clone();
+        let sb_c = subscribed.clone();
         let l_c = loaded.clone();
         let pg_c = page.clone();
+        let j_c = jump_unread.clone();
+        let fu_c = first_unread.clone();
+        let threaded = *view == ReplyView::Threaded;
+        let n_c = navigator.clone();
         if id>=0 && !*loaded {
             let p_c2 = p_c.clone();
+            let jump = *j_c && !anon;
             wasm_bindgen_futures::spawn_local(async move {
-                let th = get_thread(id, Some(*pg_c), Some(*limit)).await
+                let th = get_thread(id, Some(*pg_c), Some(*limit), threaded, jump).await
                     .unwrap_throw();
+                // Redirects of moved and merged threads send the reader on
+                if let Some(target) = th.info.moved_to {
+                    match get_thread(target, Some(1), Some(1), false, false).await {
+                        Ok(t) => n_c.replace(&crate::Route::Topic { s_id: t.info.section, id: target }),
+                        Err(e) => { crate::c_error!("Moved thread is gone: {:?}", e); }
+                    }
+                    return;
+                }
+                if jump && th.page > 0 {
+                    pg_c.set(th.page);
+                }
+                j_c.set(false);
+                fu_c.set(if jump { th.first_unread } else { None });
                 m_c.set(th.info);
                 p_c2.set(th.posts);
+                r_c.set(th.reactions);
+                sb_c.set(th.subscribed);
                 l_c.set(true);
             });
         }
@@ -174,43 +321,222 @@ pub fn Topic(props: &Props) -> Html {
             l_c.set(false);
         });
 
+        let v_c = view.clone();
+        let l_c = loaded.clone();
+        let set_view = Callback::from(move |v: ReplyView| {
+            // Only the threaded view changes the order posts come back in
+            if (*v_c == ReplyView::Threaded) != (v == ReplyView::Threaded) {
+                l_c.set(false);
+            }
+            v_c.set(v);
+        });
+        let view_button = |v: ReplyView, label: &'static str| {
+            let set_view = set_view.clone();
+            html! {
+                <button class={classes!("px-2","rounded-xl", if *view == v { "bg-indigo-800" } else { "bg-zinc-800/50" })}
+                    onclick={Callback::from(move |_| set_view.emit(v))}>
+                    {label}
+                </button>
+            }
+        };
+
+        let sb_c = subscribed.clone();
+        let on_subscribe = Callback::from(move |_| {
+            let sb_c = sb_c.clone();
+            let subscribe = !*sb_c;
+            wasm_bindgen_futures::spawn_local(async move {
+                match subscribe_thread(id, subscribe).await {
+                    Ok(_) => sb_c.set(subscribe),
+                    Err(e) => { crate::c_error!("Failed to change subscription: {:?}", e); }
+                }
+            });
+        });
+
+        let rt_c = reply_to.clone();
+        let on_reply = Callback::from(move |post_id: Option<i64>| rt_c.set(post_id));
+
+        let moderator = ctx.is_mod() || ctx.is_admin();
+        let sel_c = selected.clone();
+        let on_select = Callback::from(move |post_id: i64| {
+            let mut s = (*sel_c).clone();
+            match s.iter().position(|p| *p == post_id) {
+                Some(i) => { s.remove(i); }
+                None => s.push(post_id),
+            }
+            sel_c.set(s);
+        });
+        let h_c = history.clone();
+        let on_history = Callback::from(move |target: Option<i64>| {
+            h_c.set(if *h_c == Some(target) { None } else { Some(target) });
+        });
+        let l_c = loaded.clone();
+        let on_restored = Callback::from(move |_| l_c.set(false));
+        let l_c = loaded.clone();
+        let on_restore_post = Callback::from(move |post_id: i64| {
+            let l_c = l_c.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Err(e) = restore_post(post_id, None).await {
+                    crate::c_error!("Failed to restore post: {:?}", e);
+                }
+                l_c.set(false);
+            });
+        });
+        // Only moderators get deleted content, marked with who removed it and why
+        let deleted_note = |by: &Option<String>, reason: &Option<String>| {
+            let by = by.clone().unwrap_or_else(|| "Deleted User".to_string());
+            match reason {
+                Some(reason) => format!("Deleted by {}: {}", by, reason),
+                None => format!("Deleted by {}", by),
+            }
+        };
+        let edited = |at: Option<chrono::DateTime<chrono::Utc>>, by: &Option<String>| match at {
+            Some(at) => format!("edited {} by {}", at.format(crate::DATEFORMAT),
+                by.clone().unwrap_or_else(|| "Deleted User".to_string())),
+            None => String::new(),
+        };
+        let history_button = |target: Option<i64>| {
+            let on_history = on_history.clone();
+            html! {
+                <button class="text-xs text-zinc-500 hover:text-zinc-300"
+                    onclick={Callback::from(move |_| on_history.emit(target))}>
+                    {"History"}
+                </button>
+            }
+        };
+
+        let l_c = loaded.clone();
+        let sel_c = selected.clone();
+        let on_tools_done = Callback::from(move |_| {
+            sel_c.set(Vec::new());
+            l_c.set(false);
+        });
+
         
         html! {
             <div class="space-y-5">
                 <div class="space-x-2">
                     <button onclick={pg_first} disabled={*page == 1}>{"First page"}</button>
                     <button onclick={pg_next} disabled={posts.is_empty()}>{"Next page"}</button>
+                    <span class="text-zinc-500 text-sm">{"View:"}</span>
+                    {view_button(ReplyView::Flat, "Flat")}
+                    {view_button(ReplyView::Quoted, "Quoted")}
+                    {view_button(ReplyView::Threaded, "Threaded")}
+                    if !anon {
+                        <button onclick={on_subscribe}>
+                            {if *subscribed { "Unsubscribe" } else { "Subscribe" }}
+                        </button>
+                    }
                 </div>
+                if moderator {
+                    <ThreadTools thread={(*meta).clone()} selected={(*selected).clone()} on_done={on_tools_done}/>
+                }
                 <div class="bg-zinc-900/50 border border-zinc-800 rounded-2xl p-5 grid grid-cols-6 space-x-2">
                     <div>
                         <User user_id={meta.author.clone()} user_cache={user_cache.clone()}/>
                     </div>
                     <div class="col-span-5 grid grid-cols-1">
+                        if meta.deleted_at.is_some() {
+                            <span class="text-xs text-red-500">{deleted_note(&meta.deleted_by_name, &meta.delete_reason)}</span>
+                        }
+                        if meta.pending {
+                            <span class="text-xs text-amber-500">{"Awaits approval by a moderator"}</span>
+                        }
                         <span class="text-xl text-cyan-200">{&meta.title}</span>
-                        <span class="text-zinc-400">{&meta.content}</span>
+                        <div id="thread-body" class="text-zinc-400"></div>
+                        if meta.deleted_at.is_none() && !meta.pending && ctx.id() != meta.author {
+                            <div><ReportButton kind="thread" target_id={id.to_string()}/></div>
+                        }
+                        if meta.modified_at.is_some() {
+                            <div class="space-x-2">
+                                <span class="text-zinc-700 text-xs">{edited(meta.modified_at, &meta.modified_by_name)}</span>
+                                if moderator || ctx.id() == meta.author {
+                                    {history_button(None)}
+                                }
+                            </div>
+                        }
+                        if *history == Some(None) {
+                            <Revisions thread_id={id} post_id={None} moderator={moderator} on_restored={on_restored.clone()}/>
+                        }
                     </div>
-                    {if ctx.is_mod() || ctx.is_admin() || ctx.id() == meta.author {
-                        html! { {""} }
-                    } else {
-                        html! { {""} }
-                    }}
                 </div>
                 {for posts.iter().map(|p| {
                     let post_id = format!("post-{}", p.id);
                     let author = p.author.clone().unwrap_or_default();
+                    let indent = if *view == ReplyView::Threaded {
+                        format!("margin-left: {}rem", 2 * p.depth.min(6))
+                    } else {
+                        String::new()
+                    };
+                    let quote = match p.comments {
+                        Some(parent) if *view == ReplyView::Quoted => {
+                            match posts.iter().find(|q| q.id == parent) {
+                                Some(q) => html! {
+                                    <blockquote class="border-l-2 border-zinc-600 pl-2 mb-2 text-xs text-zinc-500">
+                                        <span class="text-zinc-400">{format!("{} wrote: ", q.author_name.clone().unwrap_or_default())}</span>
+                                        {quote_snippet(&q.content)}
+                                    </blockquote>
+                                },
+                                None => html! {
+                                    <span class="mb-2 text-xs text-zinc-500">{format!("In reply to #{}", parent)}</span>
+                                },
+                            }
+                        }
+                        _ => html! {},
+                    };
+                    let on_reply = on_reply.clone();
+                    let on_select = on_select.clone();
+                    let on_restore_post = on_restore_post.clone();
+                    let reply_id = p.id;
+                    let collapsed = blocked.contains(&author) && !revealed.contains(&p.id);
+                    let on_reveal = {
+                        let revealed = revealed.clone();
+                        Callback::from(move |_| {
+                            let mut r = (*revealed).clone();
+                            r.push(reply_id);
+                            revealed.set(r);
+                        })
+                    };
                     html! {
-                        <div class="grid grid-cols-6 space-x-2">
+                        <div class="grid grid-cols-6 space-x-2" style={indent}>
                             <div>
                                 <User user_id={p.author.clone().unwrap_or_default()} user_cache={user_cache.clone()}/>
                             </div>
                             <div class="col-span-5 grid grid-cols-1 bg-zinc-900/50 p-5 rounded-2xl">
-                                    <span class="text-zinc-400 row-span-6" id={post_id}>
+                                    if moderator {
+                                        <label class="text-xs text-zinc-500 space-x-1">
+                                            <input type="checkbox" checked={selected.contains(&p.id)}
+                                                onchange={Callback::from(move |_| on_select.emit(reply_id))}/>
+                                            <span>{format!("#{}", p.id)}</span>
+                                        </label>
+                                    }
+                                    {quote}
+                                    if p.pending {
+                                        <div class="text-xs text-amber-500">{"Awaits approval by a moderator"}</div>
+                                    }
+                                    if p.deleted_at.is_some() {
+                                        <div class="space-x-2 text-xs">
+                                            <span class="text-red-500">{deleted_note(&p.deleted_by_name, &p.delete_reason)}</span>
+                                            <button class="text-zinc-500 hover:text-zinc-300"
+                                                onclick={Callback::from(move |_| on_restore_post.emit(reply_id))}>
+                                                {"Restore"}
+                                            </button>
+                                        </div>
+                                    }
+                                    if *first_unread == Some(p.id) {
+                                        <span class="text-xs text-cyan-500 mb-1">{"New"}</span>
+                                    }
+                                    if collapsed {
+                                        <button class="text-left text-xs text-zinc-500 hover:text-zinc-300" onclick={on_reveal}>
+                                            {"Post by a blocked user, click to show"}
+                                        </button>
+                                    }
+                                    <span class={classes!("text-zinc-400", "row-span-6", collapsed.then_some("hidden"))} id={post_id}>
                                         // Here goes Html
                                     </span>
                                     <div class="flex justify-end flex-col">
                                         <span>{if ctx.is_mod() || ctx.is_admin() || ctx.id()==author {
                                             if let Some(p_id) = (*editing) && p_id ==  p.id {
-                                                html! { <Editor id={id} post_id={Some(p.id)} set_to_load={set_to_load.clone()} /> }
+                                                html! { <Editor id={id} post_id={Some(p.id)} source={p.source.clone()} set_to_load={set_to_load.clone()} /> }
                                             } else {
                                                 html! {
                                                     <div class="space-x-2">
@@ -231,19 +557,112 @@ pub fn Topic(props: &Props) -> Html {
                                         } else {
                                             html! {""}
                                         }}</span>
-                                        <span class="text-zinc-700 text-xs">{p.created_at.format(crate::DATEFORMAT).to_string()}</span>
+                                        <div class="space-x-1 text-sm">
+                                            {for reaction_kinds.iter().map(|k| {
+                                                let count = reactions.iter()
+                                                    .find(|r| r.post_id == p.id && r.kind == k.name);
+                                                let reacted = count.is_some_and(|r| r.reacted);
+                                                let n = count.map(|r| r.count).unwrap_or(0);
+                                                let on_react = on_react.clone();
+                                                let (post_id, kind) = (p.id, k.name.clone());
+                                                html! {
+                                                    <button
+                                                        class={classes!("px-2","rounded-xl","hover:bg-violet-600","disabled:hover:bg-transparent",
+                                                            if reacted { "bg-indigo-800" } else { "bg-zinc-800/50" })}
+                                                        title={k.name.clone()}
+                                                        disabled={anon || ctx.banned()}
+                                                        onclick={Callback::from(move |_| on_react.emit((post_id, kind.clone(), !reacted)))}>
+                                                        {format!("{} {}", k.emoji, n)}
+                                                    </button>
+                                                }
+                                            })}
+                                            {if !anon && !ctx.banned() {
+                                                html! {
+                                                    <button class="text-xs text-zinc-500 hover:text-zinc-300"
+                                                        onclick={Callback::from(move |_| on_reply.emit(Some(reply_id)))}>
+                                                        {"Reply"}
+                                                    </button>
+                                                }
+                                            } else {
+                                                html! {}
+                                            }}
+                                            {if p.likes > 0 {
+                                                let on_liked_by = on_liked_by.clone();
+                                                let post_id = p.id;
+                                                html! {
+                                                    <button class="text-xs text-zinc-500 hover:text-zinc-300"
+                                                        onclick={Callback::from(move |_| on_liked_by.emit(post_id))}>
+                                                        {"liked by"}
+                                                    </button>
+                                                }
+                                            } else {
+                                                html! {}
+                                            }}
+                                        </div>
+                                        {if let Some((lb_id, list)) = liked_by.as_ref() && *lb_id == p.id {
+                                            html! {
+                                                <div class="text-xs text-zinc-400 space-x-2">
+                                                    {for list.iter().map(|r| html! {
+                                                        <span>{format!("{} {}", r.emoji, r.user_name)}</span>
+                                                    })}
+                                                </div>
+                                            }
+                                        } else {
+                                            html! {}
+                                        }}
+                                        <div class="space-x-2">
+                                            <span class="text-zinc-700 text-xs">{p.created_at.format(crate::DATEFORMAT).to_string()}</span>
+                                            if p.modified_at.is_some() {
+                                                <span class="text-zinc-700 text-xs">{edited(p.modified_at, &p.modified_by_name)}</span>
+                                                if moderator || ctx.id() == author {
+                                                    {history_button(Some(p.id))}
+                                                }
+                                            }
+                                            if p.deleted_at.is_none() && !p.pending && ctx.id() != author {
+                                                <ReportButton kind="post" target_id={p.id.to_string()}/>
+                                            }
+                                        </div>
+                                        if *history == Some(Some(p.id)) {
+                                            <Revisions thread_id={id} post_id={Some(p.id)} moderator={moderator} on_restored={on_restored.clone()}/>
+                                        }
                                     </div>
                             </div>
                         </div>
                     }
                 })}
+                <div class="text-xs text-zinc-500 space-y-1">
+                    if presence.readers > 0 {
+                        <p>{reading_line(&presence)}</p>
+                    }
+                    if !presence.typing.is_empty() {
+                        <p class="text-cyan-500">{typing_line(&presence.typing)}</p>
+                    }
+                </div>
                 {
                     if ctx.banned() {
                         html! { <span class="text-weight-bold text-red-500">{"You have been banned"}</span> }
                     } else if !ctx.is_some() {
                         html! { {""} }
                     } else {
-                        html! { <Editor id={id} post_id={None} set_to_load={set_to_load.clone()} /> }
+                        let on_reply = on_reply.clone();
+                        html! {
+                            <div>
+                                {if let Some(parent) = *reply_to {
+                                    html! {
+                                        <div class="space-x-2 text-sm text-zinc-400">
+                                            <span>{format!("Replying to #{}", parent)}</span>
+                                            <button class="text-xs hover:text-zinc-200"
+                                                onclick={Callback::from(move |_| on_reply.emit(None))}>
+                                                {"cancel"}
+                                            </button>
+                                        </div>
+                                    }
+                                } else {
+                                    html! {}
+                                }}
+                                <Editor id={id} post_id={None} reply_to={*reply_to} set_to_load={set_to_load.clone()} />
+                            </div>
+                        }
                     }
                 }
             </div>
@@ -251,3 +670,37 @@ pub fn Topic(props: &Props) -> Html {
     }
 }
 
+fn reading_line(presence: &ThreadPresence) -> String {
+    let others = presence.readers.saturating_sub(presence.users.len());
+    match (presence.users.is_empty(), others) {
+        (true, _) => format!("{} reading", presence.readers),
+        (false, 0) => format!("{} reading: {}", presence.readers, presence.users.join(", ")),
+        (false, n) => format!("{} reading: {} and {} more", presence.readers, presence.users.join(", "), n),
+    }
+}
+
+fn typing_line(typing: &[String]) -> String {
+    match typing {
+        [one] => format!("{} is typing a reply…", one),
+        _ => format!("{} are typing replies…", typing.join(", ")),
+    }
+}
+
+/// Plain text start of a post for quoting, the content itself is HTML
+fn quote_snippet(content: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in content.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text.trim();
+    match text.char_indices().nth(120) {
+        Some((i, _)) => format!("{}…", &text[..i]),
+        None => text.to_string(),
+    }
+}
@@ -1,8 +1,10 @@
 use web_sys::{HtmlInputElement, Response};
 use yew::prelude::*;
+use yew_router::prelude::*;
 use wasm_bindgen::{UnwrapThrowExt, JsCast};
 
-use crate::{bind::upload_file_with_fetch, c_log, dto::UserData, user::{unban_user, update_user, user, warn_user}};
+use crate::{Route, bind::upload_file_with_fetch, c_log, dto::{PrivacySettings, SharedIpUser, UserData, WarningCategory}, user::{block_user, blocked_users, privacy, shared_ip_users, unban_user, update_privacy, update_user, user, warn_user, warning_categories}};
+use super::report::ReportButton;
 
 macro_rules! display_thing {
     ($name:ident, $value:expr) => {
@@ -97,7 +99,12 @@ pub fn UserPage(props: &Props) -> Html {
     let self_edit = use_state(|| false);
     let ban_length = use_state_eq(|| 0);
     let ban_comment = use_state(String::new);
+    let categories = use_state(Vec::<WarningCategory>::new);
+    let category = use_state(|| None::<i32>);
+    let shared = use_state(Vec::<SharedIpUser>::new);
     let error = use_state(String::new);
+    let blocked = use_state(|| false);
+    let privacy_settings = use_state(PrivacySettings::default);
     let ctx = use_context::<crate::UserContext>()
         .expect("Expected context");
 
@@ -122,6 +129,12 @@ pub fn UserPage(props: &Props) -> Html {
         b_c.set(value);
     });
 
+    let cat_c = category.clone();
+    let on_category_change = Callback::from(move |e: Event| {
+        let input: web_sys::HtmlSelectElement = e.target_unchecked_into();
+        cat_c.set(input.value().parse().ok());
+    });
+
     let b_c = ban_comment.clone();
     let on_comment_change = Callback::from(move |e: InputEvent| {
         let input: web_sys::HtmlInputElement = e.target_unchecked_into();
@@ -133,6 +146,7 @@ pub fn UserPage(props: &Props) -> Html {
 
     let e_c = error.clone();
     let bc_c = ban_comment.clone();
+    let cat_c = category.clone();
     let on_ban_submit = {
         let u_c = user_data.clone();
         Callback::from(move |e: SubmitEvent| {
@@ -140,13 +154,14 @@ pub fn UserPage(props: &Props) -> Html {
             let user_id = u_c.id.clone();
             let warned_by = c_c.id();
             let banned = if *b_l > 0 { Some(*b_l) } else { None };
+            let category = *cat_c;
             let c = (*bc_c).clone();
             let comment = if c.is_empty() { None } else { Some(c) };
             e_c.set(String::new());
             let e_c = e_c.clone();
 
             wasm_bindgen_futures::spawn_local(async move {
-                if let Err(e) = warn_user(&user_id, comment.as_deref(), &warned_by, banned).await {
+                if let Err(e) = warn_user(&user_id, comment.as_deref(), &warned_by, category, banned).await {
                     let es: Response = e.dyn_into()
                         .expect("unexpected error response");
                     let es_str = es.as_string().unwrap_or_default();
@@ -161,6 +176,10 @@ pub fn UserPage(props: &Props) -> Html {
     let e_c = edit_mode.clone();
     let c_c = ctx.clone();
     let s_c = self_edit.clone();
+    let bl_c = blocked.clone();
+    let p_c = privacy_settings.clone();
+    let cs_c = categories.clone();
+    let sh_c = shared.clone();
     use_effect_with((), move |_| {
         wasm_bindgen_futures::spawn_local(async move {
             let user = user(&user_id).await
@@ -172,6 +191,27 @@ pub fn UserPage(props: &Props) -> Html {
                 c_log!("ctx=None");
                 false
             };
+            if s {
+                match privacy().await {
+                    Ok(p) => p_c.set(p),
+                    Err(e) => { crate::c_error!("Failed to load privacy settings: {:?}", e); }
+                }
+            } else if c_c.is_some() {
+                match blocked_users().await {
+                    Ok(list) => bl_c.set(list.iter().any(|b| b.user_id == user.id)),
+                    Err(e) => { crate::c_error!("Failed to load blocked users: {:?}", e); }
+                }
+            }
+            if !s && (c_c.is_mod() || c_c.is_admin()) {
+                match warning_categories().await {
+                    Ok(list) => cs_c.set(list),
+                    Err(e) => { crate::c_error!("Failed to load warning categories: {:?}", e); }
+                }
+                match shared_ip_users(&user.id).await {
+                    Ok(list) => sh_c.set(list),
+                    Err(e) => { crate::c_error!("Failed to load accounts sharing an address: {:?}", e); }
+                }
+            }
             u_c.set(user);
             c_log!("s={}",s);
             s_c.set(s);
@@ -180,6 +220,58 @@ pub fn UserPage(props: &Props) -> Html {
         });
     });
 
+    let on_block = {
+        let blocked = blocked.clone();
+        let u_c = user_data.clone();
+        let e_c = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let blocked = blocked.clone();
+            let user_id = u_c.id.clone();
+            let e_c = e_c.clone();
+            let block = !*blocked;
+            wasm_bindgen_futures::spawn_local(async move {
+                match block_user(&user_id, block).await {
+                    Ok(_) => blocked.set(block),
+                    Err(e) => e_c.set(format!("Error saving data: {:?}", e)),
+                }
+            });
+        })
+    };
+
+    let p_c = privacy_settings.clone();
+    let on_pm_privacy_change = Callback::from(move |e: Event| {
+        let input: web_sys::HtmlSelectElement = e.target_unchecked_into();
+        let mut p = (*p_c).clone();
+        p.pm_privacy = input.value();
+        p_c.set(p);
+    });
+    let p_c = privacy_settings.clone();
+    let on_invisible_change = Callback::from(move |e: Event| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        let mut p = (*p_c).clone();
+        p.invisible = input.checked();
+        p_c.set(p);
+    });
+    let p_c = privacy_settings.clone();
+    let on_pm_min_posts_input = Callback::from(move |e: InputEvent| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        let mut p = (*p_c).clone();
+        p.pm_min_posts = input.value().parse().unwrap_or(0);
+        p_c.set(p);
+    });
+    let p_c = privacy_settings.clone();
+    let e_c = error.clone();
+    let on_privacy_submit = Callback::from(move |e: SubmitEvent| {
+        e.prevent_default();
+        let settings = (*p_c).clone();
+        let e_c = e_c.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = update_privacy(&settings).await {
+                e_c.set(format!("Error saving data: {:?}", e));
+            }
+        });
+    });
+
     let e_c = error.clone();
     let on_submit = {
         let u_c = user_data.clone();
@@ -262,6 +354,20 @@ pub fn UserPage(props: &Props) -> Html {
         u_c.set(u);
     });
 
+    let block_button = if ctx.is_some() && !*self_edit {
+        html! {
+            <div class="space-x-2">
+                <button class="px-3 py-1 bg-zinc-800 rounded-xl font-medium hover:bg-red-800 transition-colors"
+                    onclick={on_block}>
+                    {if *blocked { "Unblock" } else { "Block" }}
+                </button>
+                <ReportButton kind="user" target_id={props.id.clone()}/>
+            </div>
+        }
+    } else {
+        html! {}
+    };
+
     let c_c = ctx.clone();
     let user = user_data.clone();
     if !*edit_mode && !*self_edit {
@@ -271,6 +377,9 @@ pub fn UserPage(props: &Props) -> Html {
                 {display_thing!(name, user.name.clone())}
                 {display_thing!(email, user.email.clone())}
                 {display_thing!(role, user.role.clone())}
+                if let Some(level) = user.warning_level.filter(|l| *l > 0) {
+                    {display_thing!(warnings, format!("{} points", level))}
+                }
                 {display_thing!(description, user.description.clone().unwrap_or_default())}
                 {display_thing!(facebook, user.facebook.clone().unwrap_or_default())}
                 {display_thing!(discord, user.discord.clone().unwrap_or_default())}
@@ -279,6 +388,7 @@ pub fn UserPage(props: &Props) -> Html {
                     <div class="flex-none w-20">{"avatar"}</div>
                     <img src={format!("{}/uploads/{}", crate::ADDR, user.avatar())} class="w-32"/>
                 </div>
+                {block_button}
             </div>
         }
     } else {
@@ -291,6 +401,9 @@ pub fn UserPage(props: &Props) -> Html {
                         {display_thing!(name, user.name.clone(), on_name_input)}
                         {display_thing!(email, user.email.clone(), on_email_input, "email")}
                         {display_thing!(role, user.role.clone())}
+                        if let Some(level) = user.warning_level.filter(|l| *l > 0) {
+                            {display_thing!(warnings, format!("{} points", level))}
+                        }
                         {display_thing!(description, user.description.clone().unwrap_or_default(), on_description_input)}
                         {display_thing!(facebook, user.facebook.clone().unwrap_or_default(), on_facebook_input)}
                         {display_thing!(discord, user.discord.clone().unwrap_or_default(), on_discord_input)}
@@ -321,6 +434,30 @@ pub fn UserPage(props: &Props) -> Html {
                         class="px-3 py-1 bg-indigo-700 rounded-xl font-medium hover:bg-violet-600 transition-colors"
                         value="Update"/>
                 </form>
+                if *self_edit {
+                    <form id="privacy" onsubmit={on_privacy_submit} class="space-x-2">
+                        <span>{"Private messages from"}</span>
+                        <select class="bg-violet-950/20" onchange={on_pm_privacy_change}>
+                            <option value="everyone" selected={privacy_settings.pm_privacy == "everyone"}>{"everyone"}</option>
+                            <option value="min_posts" selected={privacy_settings.pm_privacy == "min_posts"}>{"users with enough posts"}</option>
+                            <option value="nobody" selected={privacy_settings.pm_privacy == "nobody"}>{"nobody"}</option>
+                        </select>
+                        if privacy_settings.pm_privacy == "min_posts" {
+                            <input type="number" min="0"
+                                class="bg-violet-950/20 w-20"
+                                value={privacy_settings.pm_min_posts.to_string()}
+                                oninput={on_pm_min_posts_input}/>
+                        }
+                        <label class="space-x-1">
+                            <input type="checkbox" checked={privacy_settings.invisible} onchange={on_invisible_change}/>
+                            <span>{"Hide me from the online list"}</span>
+                        </label>
+                        <input type="submit"
+                            class="px-3 py-1 bg-indigo-700 rounded-xl font-medium hover:bg-violet-600 transition-colors"
+                            value="Save"/>
+                    </form>
+                }
+                {block_button}
                 {if !*self_edit {
                     html! { 
                         <div>
@@ -335,6 +472,14 @@ pub fn UserPage(props: &Props) -> Html {
                             } else {
                                 html! { 
                                     <form id="ban_user" onsubmit={on_ban_submit} class="space-x-2">
+                                        <select class="bg-violet-950/20" onchange={on_category_change}>
+                                            <option value="" selected={category.is_none()}>{"general"}</option>
+                                            { for categories.iter().filter(|c| c.name != "general").map(|c| html! {
+                                                <option value={c.id.to_string()} selected={*category == Some(c.id)}>
+                                                    {format!("{} ({} points)", c.name, c.points)}
+                                                </option>
+                                            }) }
+                                        </select>
                                         <input type="number" 
                                             name="length" 
                                             value={format!("{}",*ban_length.clone())}
@@ -346,10 +491,25 @@ pub fn UserPage(props: &Props) -> Html {
                                             oninput={on_comment_change}/>
                                         <input type="submit" 
                                             class="px-3 py-1 bg-indigo-700 rounded-xl font-medium hover:bg-violet-600 transition-colors"
-                                            value="Ban User"/>
+                                            value={if *ban_length > 0 { "Ban User" } else { "Warn User" }}/>
                                     </form>
                                 }
                             }}
+                            if !shared.is_empty() {
+                                <div class="p-2 text-sm space-y-1">
+                                    <div class="font-bold">{"Accounts sharing an address"}</div>
+                                    { for shared.iter().map(|u| html! {
+                                        <div class="space-x-2">
+                                            <Link<Route> to={Route::User { id: u.id.clone() }}>{u.name.clone()}</Link<Route>>
+                                            <span class="text-zinc-400">{format!("registered from {}, last login from {}",
+                                                u.registration_ip.as_deref().unwrap_or("-"), u.last_login_ip.as_deref().unwrap_or("-"))}</span>
+                                            if u.banned_until.is_some_and(|b| b > chrono::Utc::now()) {
+                                                <span class="text-red-400">{"banned"}</span>
+                                            }
+                                        </div>
+                                    }) }
+                                </div>
+                            }
                         </div>
                     }
                 } else {
@@ -255,7 +255,7 @@ pub async fn upload_file_with_fetch(url: &str, file: &File) -> Result<ImageUploa
     let window = web_sys::window().unwrap();
 
     let form_data = FormData::new()?;
-    form_data.append_with_blob("file", &file)?;
+    form_data.append_with_blob("file", file)?;
 
     // Create fetch request
     let opts = RequestInit::new();
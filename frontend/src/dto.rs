@@ -130,6 +130,27 @@ pub struct WarnUserDto {
     pub comment: Option<String>,
     pub warned_by: String,
     pub banned: Option<i32>,
+    pub category: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct WarningCategory {
+    pub id: i32,
+    pub name: String,
+    pub points: i32,
+    pub expire_days: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WarningCategoriesResponseDto {
+    pub categories: Vec<WarningCategory>,
+}
+
+impl From<JsValue> for WarningCategoriesResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -154,6 +175,8 @@ pub struct UserData {
     pub last_online: Option<DateTime<Utc>>,
     pub banned: Option<bool>,
     pub posts_n: Option<i64>,
+    /// Active warning points
+    pub warning_level: Option<i64>,
 }
 
 impl UserData {
@@ -216,6 +239,19 @@ pub struct Section {
     pub name: String,
     pub description: Option<String>,
     pub new_posts: bool,
+    #[serde(default)]
+    pub unread: i64,
+    #[serde(default)]
+    pub moderators: Vec<SectionModerator>,
+    /// New threads and posts wait for approval
+    #[serde(default)]
+    pub moderated: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SectionModerator {
+    pub id: String,
+    pub name: String,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -230,7 +266,7 @@ impl From<JsValue> for GetSectionsResponseDto {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct ThreadListItemDto { 
     pub id: i64,
     pub title: String,
@@ -241,11 +277,29 @@ pub struct ThreadListItemDto {
     pub section_id: i64,
     pub locked: bool,
     pub sticky: bool,
-
+    #[serde(default)]
+    pub announcement: bool,
+    #[serde(default)]
+    pub sticky_until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub unread: bool,
+    #[serde(default)]
+    pub unread_posts: i64,
+    /// Set on the redirects left behind by moved threads
+    #[serde(default)]
+    pub moved_to: Option<i64>,
+    /// Deleted threads are only listed for moderators
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Waiting for approval, only listed for the author and moderators
+    #[serde(default)]
+    pub pending: bool,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct GetSectionResponseDto {
+    #[serde(default)]
+    pub announcements: Vec<ThreadListItemDto>,
     pub threads: Vec<ThreadListItemDto>,
 }
 
@@ -267,6 +321,7 @@ pub struct CreateThreadDto {
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct DeleteThreadDto {
     pub thread_id: i64,
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -287,6 +342,7 @@ pub struct CreateSectionDto {
     pub name: String,
     pub description: String,
     pub allowed_for: Vec<String>,
+    pub moderated: bool,
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -310,6 +366,19 @@ pub struct UpdatePostDto {
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct DeletePostDto {
     pub post_id: i64,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct RestorePostDto {
+    pub post_id: i64,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct RestoreThreadDto {
+    pub thread_id: i64,
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -329,6 +398,14 @@ pub struct ReplyThreadDto {
 pub struct GetThreadResponseDto {
     pub info: Thread,
     pub posts: Vec<Post>,
+    #[serde(default)]
+    pub reactions: Vec<ReactionCount>,
+    #[serde(default)]
+    pub subscribed: bool,
+    #[serde(default)]
+    pub page: i32,
+    #[serde(default)]
+    pub first_unread: Option<i64>,
 }
 
 impl From<JsValue> for GetThreadResponseDto {
@@ -341,14 +418,32 @@ impl From<JsValue> for GetThreadResponseDto {
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
 pub struct Post {
     pub id: i64,
+    /// Sanitised HTML
     pub content: String,
+    /// Markdown written by the author, used for editing
+    #[serde(default)]
+    pub source: String,
     pub author: Option<String>,
     pub author_name: Option<String>,
     pub topic: i64,
     pub comments: Option<i64>,
     pub created_at: DateTime<Utc>,
     pub modified_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub modified_by_name: Option<String>,
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub deleted_by_name: Option<String>,
+    #[serde(default)]
+    pub delete_reason: Option<String>,
     pub likes: i32,
+    /// Waiting for approval, only shown to the author and moderators
+    #[serde(default)]
+    pub pending: bool,
+    /// Nesting level in the reply tree, only meaningful in threaded view
+    #[serde(default)]
+    pub depth: i32,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
@@ -356,11 +451,105 @@ pub struct Thread {
     pub id: i64,
     pub title: String,
     pub created_at: DateTime<Utc>,
+    /// Sanitised HTML
     pub content: String,
+    /// Markdown written by the author, used for editing
+    #[serde(default)]
+    pub source: String,
     pub author: String,
     pub section: i64,
     pub locked: bool,
     pub sticky: bool,
+    #[serde(default)]
+    pub announcement: bool,
+    #[serde(default)]
+    pub sticky_until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub moved_to: Option<i64>,
+    #[serde(default)]
+    pub modified_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub modified_by_name: Option<String>,
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub deleted_by_name: Option<String>,
+    #[serde(default)]
+    pub delete_reason: Option<String>,
+    #[serde(default)]
+    pub pending: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct StickyThreadDto {
+    pub thread_id: i64,
+    pub sticky: bool,
+    pub announcement: Option<bool>,
+    pub until: Option<DateTime<Utc>>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct MoveThreadDto {
+    pub thread_id: i64,
+    pub section: i64,
+    pub stub: Option<bool>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct MergeThreadsDto {
+    pub source: i64,
+    pub target: i64,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct SplitThreadDto {
+    pub thread_id: i64,
+    pub post_ids: Vec<i64>,
+    pub title: String,
+    pub section: Option<i64>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SplitThreadResponseDto {
+    pub status: String,
+    pub thread_id: i64,
+}
+
+impl From<JsValue> for SplitThreadResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct SearchResultDto {
+    pub kind: String,
+    pub thread_id: i64,
+    pub post_id: Option<i64>,
+    pub title: String,
+    pub section_id: i64,
+    pub author: Option<String>,
+    pub author_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub rank: f32,
+    pub snippet: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SearchResponseDto {
+    pub results: Vec<SearchResultDto>,
+}
+
+impl From<JsValue> for SearchResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
@@ -378,3 +567,556 @@ impl From<JsValue> for ImageUploadResponse {
     }
 }
 
+
+// ----- Reactions -----
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct ReactionKind {
+    pub name: String,
+    pub emoji: String,
+    pub position: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReactionKindsResponseDto {
+    pub kinds: Vec<ReactionKind>,
+}
+
+impl From<JsValue> for ReactionKindsResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct ReactionCount {
+    pub post_id: i64,
+    pub kind: String,
+    pub emoji: String,
+    pub count: i64,
+    pub reacted: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct PostReaction {
+    pub kind: String,
+    pub emoji: String,
+    pub user_id: String,
+    pub user_name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PostReactionsResponseDto {
+    pub reactions: Vec<PostReaction>,
+}
+
+impl From<JsValue> for PostReactionsResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct ReactionDto {
+    pub kind: String,
+}
+
+// ----- Notifications -----
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Notification {
+    pub id: i64,
+    pub kind: String,
+    pub actor: Option<String>,
+    pub actor_name: Option<String>,
+    pub thread_id: Option<i64>,
+    pub thread_title: Option<String>,
+    pub section: Option<i64>,
+    pub post_id: Option<i64>,
+    pub content: Option<String>,
+    pub is_read: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NotificationsResponseDto {
+    pub notifications: Vec<Notification>,
+    pub unread: i64,
+}
+
+impl From<JsValue> for NotificationsResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UnreadCountResponseDto {
+    pub unread: i64,
+}
+
+impl From<JsValue> for UnreadCountResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct ReadNotificationsDto {
+    pub ids: Option<Vec<i64>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ThreadSubscription {
+    pub thread_id: i64,
+    pub title: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SectionSubscription {
+    pub section: i64,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SubscriptionsResponseDto {
+    pub threads: Vec<ThreadSubscription>,
+    pub sections: Vec<SectionSubscription>,
+}
+
+impl From<JsValue> for SubscriptionsResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+// ----- Private messages -----
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Conversation {
+    pub id: i64,
+    pub subject: String,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub participants: Vec<String>,
+    pub last_author: Option<String>,
+    pub unread: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ConversationParticipant {
+    pub user_id: String,
+    pub name: String,
+    pub avatar: Option<String>,
+    pub last_read_message: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct PrivateMessage {
+    pub id: i64,
+    pub conversation_id: i64,
+    pub author: Option<String>,
+    pub author_name: Option<String>,
+    /// Sanitised HTML
+    pub content: String,
+    #[serde(default)]
+    pub source: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UserPmsResponseDto {
+    pub conversations: Vec<Conversation>,
+    pub unread: i64,
+}
+
+impl From<JsValue> for UserPmsResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ConversationResponseDto {
+    pub conversation: Conversation,
+    pub participants: Vec<ConversationParticipant>,
+    pub messages: Vec<PrivateMessage>,
+}
+
+impl From<JsValue> for ConversationResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct SendPmDto {
+    pub recipients: Vec<String>,
+    pub subject: String,
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct ReplyPmDto {
+    pub content: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SendPmResponseDto {
+    pub status: String,
+    pub conversation_id: i64,
+}
+
+impl From<JsValue> for SendPmResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+// ----- Privacy -----
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct PrivacySettings {
+    /// "everyone", "min_posts" or "nobody"
+    pub pm_privacy: String,
+    pub pm_min_posts: i32,
+    /// Hidden from the online list
+    pub invisible: bool,
+}
+
+impl From<JsValue> for PrivacySettings {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct BlockedUser {
+    pub user_id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BlocksResponseDto {
+    pub blocked: Vec<BlockedUser>,
+}
+
+impl From<JsValue> for BlocksResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+// ----- Shoutbox -----
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ChatPost {
+    pub id: i32,
+    pub added: DateTime<Utc>,
+    pub author: String,
+    pub author_name: String,
+    pub content: String,
+}
+
+/// Server to client shoutbox messages
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatEvent {
+    History { posts: Vec<ChatPost> },
+    Message { post: ChatPost },
+    Deleted { id: i32 },
+    Error { message: String },
+}
+
+/// Client to server shoutbox messages
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatCommand {
+    Post { content: String },
+    Delete { id: i32 },
+}
+
+/// Live thread and section updates, read from the server-sent event streams
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ForumEvent {
+    NewThread { section: i64, thread_id: i64 },
+    NewPost { section: i64, thread_id: i64, post_id: i64 },
+    PostEdited { section: i64, thread_id: i64, post_id: i64 },
+    PostDeleted { section: i64, thread_id: i64, post_id: i64 },
+    ThreadLocked { section: i64, thread_id: i64, locked: bool },
+    ThreadChanged { section: i64, thread_id: i64 },
+    Presence { section: i64, thread_id: i64, readers: usize, users: Vec<String>, typing: Vec<String> },
+}
+
+/// Page an online user is looking at
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Location {
+    Index,
+    Section { section: i64 },
+    Thread { section: i64, thread_id: i64 },
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ThreadPresence {
+    pub readers: usize,
+    pub users: Vec<String>,
+    pub typing: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ActiveUsersDto {
+    pub count: usize,
+    pub users: Vec<crate::user::UserSession>,
+    pub presence: Option<ThreadPresence>,
+}
+
+impl From<JsValue> for ActiveUsersDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+// ----- Moderation log -----
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ModerationLogEntry {
+    pub id: i64,
+    pub actor: Option<String>,
+    pub actor_name: Option<String>,
+    pub action: String,
+    pub target_kind: String,
+    pub target_id: String,
+    pub section: Option<i64>,
+    pub reason: Option<String>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ModerationLogResponseDto {
+    pub entries: Vec<ModerationLogEntry>,
+}
+
+impl From<JsValue> for ModerationLogResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+// ----- Revisions -----
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Revision {
+    pub id: i64,
+    pub thread_id: Option<i64>,
+    pub post_id: Option<i64>,
+    pub editor: Option<String>,
+    pub editor_name: Option<String>,
+    pub edited_at: DateTime<Utc>,
+    pub title: Option<String>,
+    /// Content before the edit
+    pub content: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RevisionsResponseDto {
+    pub revisions: Vec<Revision>,
+}
+
+impl From<JsValue> for RevisionsResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct DiffLine {
+    /// "same", "added" or "removed"
+    pub kind: String,
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct RevisionDiffResponseDto {
+    pub title_before: Option<String>,
+    pub title_after: Option<String>,
+    pub lines: Vec<DiffLine>,
+}
+
+impl From<JsValue> for RevisionDiffResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct RestoreRevisionDto {
+    pub reason: Option<String>,
+}
+
+// ----- Reports -----
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CreateReportDto {
+    pub target_kind: String,
+    pub target_id: String,
+    pub reason: String,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct HandleReportDto {
+    pub action: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Report {
+    pub id: i64,
+    pub reporter: Option<String>,
+    pub reporter_name: Option<String>,
+    pub target_kind: String,
+    pub target_id: String,
+    pub reported_user: Option<String>,
+    pub reported_user_name: Option<String>,
+    pub section: Option<i64>,
+    pub thread_id: Option<i64>,
+    pub reason: String,
+    pub comment: Option<String>,
+    /// Content of the item when it was reported
+    pub snapshot: String,
+    pub status: String,
+    pub claimed_by_name: Option<String>,
+    pub handled_by_name: Option<String>,
+    pub action: Option<String>,
+    pub resolution: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub handled_at: Option<DateTime<Utc>>,
+    pub report_count: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReportsResponseDto {
+    pub reports: Vec<Report>,
+}
+
+impl From<JsValue> for ReportsResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateBanDto {
+    pub kind: String,
+    pub value: String,
+    pub reason: Option<String>,
+    pub days: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Ban {
+    pub id: i64,
+    pub kind: String,
+    /// Address or CIDR range for ip bans
+    pub ip: Option<String>,
+    /// Email address or domain for the other kinds
+    pub value: Option<String>,
+    pub reason: Option<String>,
+    pub created_by_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BansResponseDto {
+    pub bans: Vec<Ban>,
+}
+
+impl From<JsValue> for BansResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SharedIpUser {
+    pub id: String,
+    pub name: String,
+    pub registration_ip: Option<String>,
+    pub last_login_ip: Option<String>,
+    pub banned_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SharedIpUsersResponseDto {
+    pub users: Vec<SharedIpUser>,
+}
+
+impl From<JsValue> for SharedIpUsersResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct PendingItem {
+    pub thread_id: i64,
+    /// None for new threads
+    pub post_id: Option<i64>,
+    pub section: i64,
+    pub section_name: String,
+    pub title: String,
+    pub content: String,
+    pub author: Option<String>,
+    pub author_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PendingResponseDto {
+    pub items: Vec<PendingItem>,
+}
+
+impl From<JsValue> for PendingResponseDto {
+    fn from(value: JsValue) -> Self {
+        from_value(value)
+            .unwrap_throw()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RejectDto {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SectionApprovalDto {
+    pub moderated: bool,
+}
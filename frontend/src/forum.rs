@@ -1,11 +1,7 @@
-use serde::{Serialize, Deserialize};
 use wasm_bindgen::JsValue;
-use web_sys::{Headers, RequestInit};
-use chrono::{DateTime, Utc};
-use serde_wasm_bindgen::{from_value, to_value};
 use wasm_bindgen::UnwrapThrowExt;
 
-use crate::{bind::{delete, get, post, put}, c_log, dto::{CreateSectionDto, CreateThreadDto, DeletePostDto, GetSectionResponseDto, GetSectionsResponseDto, GetThreadResponseDto, ReplyThreadDto, Section, ThreadListItemDto, UpdatePostDto, UpdateThreadDto}};
+use crate::{bind::{delete, get, post, put}, dto::{ActiveUsersDto, CreateReportDto, CreateSectionDto, HandleReportDto, PendingItem, PendingResponseDto, RejectDto, SectionApprovalDto, MergeThreadsDto, MoveThreadDto, SplitThreadDto, SplitThreadResponseDto, StickyThreadDto, CreateThreadDto, DeletePostDto, DeleteThreadDto, GetSectionResponseDto, GetSectionsResponseDto, GetThreadResponseDto, ModerationLogEntry, ModerationLogResponseDto, PostReaction, PostReactionsResponseDto, ReactionDto, ReactionKind, ReactionKindsResponseDto, ReplyThreadDto, Report, ReportsResponseDto, RestorePostDto, RestoreRevisionDto, RestoreThreadDto, Revision, RevisionDiffResponseDto, RevisionsResponseDto, SearchResponseDto, SearchResultDto, Section, UpdatePostDto, UpdateThreadDto}};
 
 pub async fn get_sections() -> Result<Vec<Section>, JsValue> {
     let sections = get("/forum/list").await?;
@@ -13,7 +9,8 @@ pub async fn get_sections() -> Result<Vec<Section>, JsValue> {
     Ok(response.sections)
 }
 
-pub async fn get_topics(section_id: i64, page: Option<i32>, limit: Option<usize>) -> Result<Vec<ThreadListItemDto>, JsValue> {    
+/// Threads of a section, pinned first, and the announcements of all sections
+pub async fn get_topics(section_id: i64, page: Option<i32>, limit: Option<usize>) -> Result<GetSectionResponseDto, JsValue> {    
     
     let mut addr = format!("/forum/section/{}",section_id);
     let mut params = String::new();
@@ -28,13 +25,19 @@ pub async fn get_topics(section_id: i64, page: Option<i32>, limit: Option<usize>
         addr.push_str(&params);
     }
     let threads = get(&addr).await?;
-    let response = GetSectionResponseDto::from(threads);
-    Ok(response.threads)
+    Ok(GetSectionResponseDto::from(threads))
 }
 
-pub async fn get_thread(thread_id: i64, page: Option<i32>, limit: Option<usize>) -> Result<GetThreadResponseDto, JsValue> {
+/// With `unread` set the server picks the page holding the first unread post
+pub async fn get_thread(thread_id: i64, page: Option<i32>, limit: Option<usize>, threaded: bool, unread: bool) -> Result<GetThreadResponseDto, JsValue> {
     let mut addr = format!("/forum/threads/{}",thread_id);
     let mut params = String::new();
+    if threaded {
+        params.push_str("&threaded=true");
+    }
+    if unread {
+        params.push_str("&unread=true");
+    }
     if let Some(page) = page {
         params.push_str(&format!("&page={}",page));
     }
@@ -50,9 +53,9 @@ pub async fn get_thread(thread_id: i64, page: Option<i32>, limit: Option<usize>)
     Ok(response)
 }
 
-pub async fn add_post(thread_id: i64, content: &str) -> Result<(), JsValue> {
-    let addr = format!("/forum/post/new");
-    let rt = ReplyThreadDto { post_id: None, t_id: thread_id, content: content.to_string() };
+pub async fn add_post(thread_id: i64, reply_to: Option<i64>, content: &str) -> Result<(), JsValue> {
+    let addr = "/forum/post/new".to_string();
+    let rt = ReplyThreadDto { post_id: reply_to, t_id: thread_id, content: content.to_string() };
     let body = serde_json::to_string(&rt)
         .unwrap_throw();
     post(&addr, JsValue::from_str(body.as_str())).await?;
@@ -99,9 +102,10 @@ pub async fn new_thread(title: &str, content: &str, section: i64, hash_tags: Vec
     Ok(())
 }
 
-pub async fn delete_post(post_id: i64) -> Result<(), JsValue> {
+pub async fn delete_post(post_id: i64, reason: Option<String>) -> Result<(), JsValue> {
     let dto = DeletePostDto {
-        post_id
+        post_id,
+        reason,
     };
     let body = serde_json::to_string(&dto)
         .unwrap_throw();
@@ -122,3 +126,265 @@ pub async fn create_section(dto: &CreateSectionDto) -> Result<(), JsValue> {
     put("/forum/section/add", JsValue::from_str(body.as_str())).await?;
     Ok(())
 }
+
+pub async fn search(q: &str, section: Option<i64>, author: Option<&str>, from: Option<&str>, to: Option<&str>, page: Option<i32>) -> Result<Vec<SearchResultDto>, JsValue> {
+    let mut addr = format!("/forum/search?q={}", js_sys::encode_uri_component(q));
+    if let Some(section) = section {
+        addr.push_str(&format!("&section={}", section));
+    }
+    if let Some(author) = author {
+        addr.push_str(&format!("&author={}", js_sys::encode_uri_component(author)));
+    }
+    // <input type="date"> gives us a plain date, the backend wants a full timestamp
+    if let Some(from) = from {
+        addr.push_str(&format!("&from={}T00:00:00Z", from));
+    }
+    if let Some(to) = to {
+        addr.push_str(&format!("&to={}T23:59:59Z", to));
+    }
+    if let Some(page) = page {
+        addr.push_str(&format!("&page={}", page));
+    }
+    let results = get(&addr).await?;
+    let response = SearchResponseDto::from(results);
+    Ok(response.results)
+}
+
+pub async fn get_reaction_kinds() -> Result<Vec<ReactionKind>, JsValue> {
+    let kinds = get("/forum/reactions").await?;
+    let response = ReactionKindsResponseDto::from(kinds);
+    Ok(response.kinds)
+}
+
+pub async fn get_post_reactions(post_id: i64) -> Result<Vec<PostReaction>, JsValue> {
+    let reactions = get(&format!("/forum/post/{}/reactions", post_id)).await?;
+    let response = PostReactionsResponseDto::from(reactions);
+    Ok(response.reactions)
+}
+
+/// Adds or removes the caller's reaction of the given kind
+pub async fn react(post_id: i64, kind: &str, add: bool) -> Result<(), JsValue> {
+    let dto = ReactionDto { kind: kind.to_string() };
+    let body = serde_json::to_string(&dto)
+        .unwrap_throw();
+    let addr = format!("/forum/post/{}/reactions", post_id);
+
+    if add {
+        put(&addr, JsValue::from_str(body.as_str())).await?;
+    } else {
+        delete(&addr, JsValue::from_str(body.as_str())).await?;
+    }
+    Ok(())
+}
+
+/// Subscribes to or unsubscribes from reply notifications of a thread
+pub async fn subscribe_thread(thread_id: i64, subscribe: bool) -> Result<(), JsValue> {
+    let addr = format!("/forum/threads/{}/subscription", thread_id);
+
+    if subscribe {
+        put(&addr, JsValue::from_str("{}")).await?;
+    } else {
+        delete(&addr, JsValue::from_str("{}")).await?;
+    }
+    Ok(())
+}
+
+/// Subscribes to or unsubscribes from new thread notifications of a section
+pub async fn subscribe_section(section: i64, subscribe: bool) -> Result<(), JsValue> {
+    let addr = format!("/forum/section/{}/subscription", section);
+
+    if subscribe {
+        put(&addr, JsValue::from_str("{}")).await?;
+    } else {
+        delete(&addr, JsValue::from_str("{}")).await?;
+    }
+    Ok(())
+}
+
+pub async fn mark_section_read(section: i64) -> Result<(), JsValue> {
+    put(&format!("/forum/section/{}/read", section), JsValue::from_str("{}")).await?;
+    Ok(())
+}
+
+pub async fn mark_forum_read() -> Result<(), JsValue> {
+    put("/forum/read", JsValue::from_str("{}")).await?;
+    Ok(())
+}
+
+/// Users online, with `thread` also who reads and types there
+pub async fn active_users(thread: Option<i64>) -> Result<ActiveUsersDto, JsValue> {
+    let addr = match thread {
+        Some(thread) => format!("/forum/active?thread={}", thread),
+        None => "/forum/active".to_string(),
+    };
+    let active = get(&addr).await?;
+    Ok(ActiveUsersDto::from(active))
+}
+
+/// Shows the caller as typing a reply for a few seconds
+pub async fn typing(thread_id: i64) -> Result<(), JsValue> {
+    put(&format!("/forum/threads/{}/typing", thread_id), JsValue::from_str("{}")).await?;
+    Ok(())
+}
+
+/// Moves a thread to another section, `stub` leaves a redirect in the old one
+pub async fn move_thread(thread_id: i64, section: i64, stub: bool, reason: Option<String>) -> Result<(), JsValue> {
+    let dto = MoveThreadDto { thread_id, section, stub: Some(stub), reason };
+    let body = serde_json::to_string(&dto).expect("SJ");
+    put("/forum/threads/move", JsValue::from_str(&body)).await?;
+    Ok(())
+}
+
+/// Moves the posts of `source` into `target`
+pub async fn merge_threads(source: i64, target: i64, reason: Option<String>) -> Result<(), JsValue> {
+    let dto = MergeThreadsDto { source, target, reason };
+    let body = serde_json::to_string(&dto).expect("SJ");
+    put("/forum/threads/merge", JsValue::from_str(&body)).await?;
+    Ok(())
+}
+
+/// Moves the selected posts into a new thread and returns its id
+pub async fn split_thread(thread_id: i64, post_ids: Vec<i64>, title: &str, section: Option<i64>, reason: Option<String>) -> Result<i64, JsValue> {
+    let dto = SplitThreadDto { thread_id, post_ids, title: title.to_string(), section, reason };
+    let body = serde_json::to_string(&dto).expect("SJ");
+    let response = post("/forum/threads/split", JsValue::from_str(&body)).await?;
+    Ok(SplitThreadResponseDto::from(response).thread_id)
+}
+
+/// Pins a thread until `until`, `announcement` pins it in every section
+pub async fn sticky_thread(thread_id: i64, sticky: bool, announcement: Option<bool>, until: Option<chrono::DateTime<chrono::Utc>>, reason: Option<String>) -> Result<(), JsValue> {
+    let dto = StickyThreadDto { thread_id, sticky, announcement, until, reason };
+    let body = serde_json::to_string(&dto).expect("SJ");
+    put("/forum/threads/sticky", JsValue::from_str(&body)).await?;
+    Ok(())
+}
+
+/// Entries of the moderation log, empty filters are left out
+pub async fn moderation_log(action: &str, target_kind: &str, target_id: &str, actor: Option<&str>, page: u32) -> Result<Vec<ModerationLogEntry>, JsValue> {
+    let mut addr = format!("/admin/log?page={}", page);
+    for (key, value) in [("action", action), ("target_kind", target_kind), ("target_id", target_id), ("actor", actor.unwrap_or_default())] {
+        if !value.is_empty() {
+            addr.push_str(&format!("&{}={}", key, js_sys::encode_uri_component(value)));
+        }
+    }
+    let response = get(&addr).await?;
+    Ok(ModerationLogResponseDto::from(response).entries)
+}
+
+/// Earlier versions of a reply, or of the opening post when `post_id` is None
+pub async fn revisions(thread_id: i64, post_id: Option<i64>) -> Result<Vec<Revision>, JsValue> {
+    let addr = match post_id {
+        Some(post_id) => format!("/forum/post/{}/revisions", post_id),
+        None => format!("/forum/threads/{}/revisions", thread_id),
+    };
+    let response = get(&addr).await?;
+    Ok(RevisionsResponseDto::from(response).revisions)
+}
+
+/// Diff from a revision to `to`, or to the current content
+pub async fn revision_diff(revision_id: i64, to: Option<i64>) -> Result<RevisionDiffResponseDto, JsValue> {
+    let mut addr = format!("/forum/revisions/{}/diff", revision_id);
+    if let Some(to) = to {
+        addr.push_str(&format!("?to={}", to));
+    }
+    let response = get(&addr).await?;
+    Ok(RevisionDiffResponseDto::from(response))
+}
+
+pub async fn restore_revision(revision_id: i64, reason: Option<String>) -> Result<(), JsValue> {
+    let dto = RestoreRevisionDto { reason };
+    let body = serde_json::to_string(&dto).expect("SJ");
+    put(&format!("/forum/revisions/{}/restore", revision_id), JsValue::from_str(&body)).await?;
+    Ok(())
+}
+
+/// Deleted posts stay visible to moderators until they are purged
+pub async fn restore_post(post_id: i64, reason: Option<String>) -> Result<(), JsValue> {
+    let dto = RestorePostDto { post_id, reason };
+    let body = serde_json::to_string(&dto).expect("SJ");
+    put("/forum/post/restore", JsValue::from_str(&body)).await?;
+    Ok(())
+}
+
+pub async fn delete_thread(thread_id: i64, reason: Option<String>) -> Result<(), JsValue> {
+    let dto = DeleteThreadDto { thread_id, reason };
+    let body = serde_json::to_string(&dto).expect("SJ");
+    delete("/forum/threads", JsValue::from_str(&body)).await?;
+    Ok(())
+}
+
+pub async fn restore_thread(thread_id: i64, reason: Option<String>) -> Result<(), JsValue> {
+    let dto = RestoreThreadDto { thread_id, reason };
+    let body = serde_json::to_string(&dto).expect("SJ");
+    put("/forum/threads/restore", JsValue::from_str(&body)).await?;
+    Ok(())
+}
+
+pub async fn create_report(target_kind: &str, target_id: &str, reason: &str, comment: Option<String>) -> Result<(), JsValue> {
+    let dto = CreateReportDto {
+        target_kind: target_kind.to_string(),
+        target_id: target_id.to_string(),
+        reason: reason.to_string(),
+        comment,
+    };
+    let body = serde_json::to_string(&dto).expect("SJ");
+    post("/reports", JsValue::from_str(&body)).await?;
+    Ok(())
+}
+
+/// Reports the user can moderate, empty filters match everything
+pub async fn reports(status: &str, target_kind: &str, page: u32) -> Result<Vec<Report>, JsValue> {
+    let mut addr = format!("/reports?page={}", page);
+    for (key, value) in [("status", status), ("target_kind", target_kind)] {
+        if !value.is_empty() {
+            addr.push_str(&format!("&{}={}", key, value));
+        }
+    }
+    let response = get(&addr).await?;
+    Ok(ReportsResponseDto::from(response).reports)
+}
+
+pub async fn claim_report(report_id: i64) -> Result<(), JsValue> {
+    put(&format!("/reports/{}/claim", report_id), JsValue::from_str("{}")).await?;
+    Ok(())
+}
+
+/// Resolves the report, or dismisses it when `dismiss` is set
+pub async fn handle_report(report_id: i64, dismiss: bool, action: Option<String>, note: Option<String>) -> Result<(), JsValue> {
+    let dto = HandleReportDto { action, note };
+    let body = serde_json::to_string(&dto).expect("SJ");
+    let verb = if dismiss { "dismiss" } else { "resolve" };
+    put(&format!("/reports/{}/{}", report_id, verb), JsValue::from_str(&body)).await?;
+    Ok(())
+}
+
+/// Threads and posts waiting for the caller's approval
+pub async fn pending_items(page: u32) -> Result<Vec<PendingItem>, JsValue> {
+    let response = get(&format!("/approvals?page={}", page)).await?;
+    Ok(PendingResponseDto::from(response).items)
+}
+
+fn approval_addr(item: &PendingItem, verb: &str) -> String {
+    match item.post_id {
+        Some(post_id) => format!("/approvals/posts/{}/{}", post_id, verb),
+        None => format!("/approvals/threads/{}/{}", item.thread_id, verb),
+    }
+}
+
+pub async fn approve(item: &PendingItem) -> Result<(), JsValue> {
+    put(&approval_addr(item, "approve"), JsValue::from_str("{}")).await?;
+    Ok(())
+}
+
+/// The reason is sent to the author
+pub async fn reject(item: &PendingItem, reason: Option<String>) -> Result<(), JsValue> {
+    let body = serde_json::to_string(&RejectDto { reason }).expect("SJ");
+    put(&approval_addr(item, "reject"), JsValue::from_str(&body)).await?;
+    Ok(())
+}
+
+pub async fn set_section_approval(s_id: i64, moderated: bool) -> Result<(), JsValue> {
+    let body = serde_json::to_string(&SectionApprovalDto { moderated }).expect("SJ");
+    put(&format!("/admin/sections/{}/approval", s_id), JsValue::from_str(&body)).await?;
+    Ok(())
+}
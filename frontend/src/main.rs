@@ -18,6 +18,11 @@ use crate::app::topic::Topic;
 use crate::app::section_list::SectionList;
 use crate::app::user_page::UserPage;
 use crate::app::inbox::Inbox;
+use crate::app::search::Search;
+use crate::app::moderation_log::ModerationLog;
+use crate::app::reports::Reports;
+use crate::app::bans::Bans;
+use crate::app::approvals::Approvals;
 use std::collections::HashMap;
 
 mod bind;
@@ -128,6 +133,16 @@ pub enum Route {
     UserList,
     #[at("/messages")]
     Messages,
+    #[at("/search")]
+    Search,
+    #[at("/admin/log")]
+    ModerationLog,
+    #[at("/reports")]
+    Reports,
+    #[at("/bans")]
+    Bans,
+    #[at("/approvals")]
+    Approvals,
     #[not_found]
     #[at("/404")]
     NotFound,
@@ -192,12 +207,17 @@ fn App() -> Html {
                                 on_oauth_start={on_oauth_start}
                                 />
                             },
-                        Route::Topic {s_id, id} => html! { <Topic id={id} section={s_id} user_cache={u_c.clone()}/> },
+                        Route::Topic {s_id, id} => html! { <Topic key={id} id={id} section={s_id} user_cache={u_c.clone()}/> },
                         Route::User {id} => html! { <UserPage id={id} /> },
                         Route::UserList => html! { <UserList/> },
                         Route::Section {id} => html! { <Section id={id} /> },
                         Route::OAuthCallback => html! { <OAuthCallback/> },
                         Route::Messages => html! { <Inbox/> },
+                        Route::Search => html! { <Search/> },
+                        Route::ModerationLog => html! { <ModerationLog/> },
+                        Route::Reports => html! { <Reports/> },
+                        Route::Bans => html! { <Bans/> },
+                        Route::Approvals => html! { <Approvals/> },
                         Route::NotFound => html! { <h1>{"404 not"}</h1> },
                     }
                 }} />
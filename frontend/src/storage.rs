@@ -10,28 +10,25 @@ pub fn get_item(key: &str) -> Option<String> {
 }
 
 pub fn set_item(key: &str, value: &str) -> Result<(), JsValue> {
-    if let Some(window) = window() {
-        if let Ok(Some(storage)) = window.local_storage() {
-            return storage.set_item(key, value);
-        }
+    if let Some(window) = window()
+        && let Ok(Some(storage)) = window.local_storage() {
+        return storage.set_item(key, value);
     }
     Err(JsValue::from_str("Failed to access local storage"))
 }
 
 pub fn remove_item(key: &str) -> Result<(), JsValue> {
-    if let Some(window) = window() {
-        if let Ok(Some(storage)) = window.local_storage() {
-            return storage.remove_item(key);
-        }
+    if let Some(window) = window()
+        && let Ok(Some(storage)) = window.local_storage() {
+        return storage.remove_item(key);
     }
     Err(JsValue::from_str("Failed to access local storage"))
 }
 
 pub fn clear() -> Result<(), JsValue> {
-    if let Some(window) = window() {
-        if let Ok(Some(storage)) = window.local_storage() {
-            return storage.clear();
-        }
+    if let Some(window) = window()
+        && let Ok(Some(storage)) = window.local_storage() {
+        return storage.clear();
     }
     Err(JsValue::from_str("Failed to access local storage"))
 }
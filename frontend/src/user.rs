@@ -3,9 +3,9 @@ use wasm_bindgen::JsValue;
 use chrono::{DateTime, Utc};
 use serde_wasm_bindgen::from_value;
 
-use crate::dto::{FilterUserDto, LoginUserDto, RegisterUserDto, UnbanUserDto, UserData, UserListResponseDto, UserLoginResponseDto, WarnUserDto};
+use crate::dto::{Ban, BansResponseDto, BlockedUser, CreateBanDto, SharedIpUser, SharedIpUsersResponseDto, Location, BlocksResponseDto, ConversationResponseDto, FilterUserDto, LoginUserDto, NotificationsResponseDto, PrivacySettings, ReadNotificationsDto, RegisterUserDto, ReplyPmDto, SendPmDto, SendPmResponseDto, SubscriptionsResponseDto, UnbanUserDto, UnreadCountResponseDto, UserData, UserListResponseDto, UserLoginResponseDto, UserPmsResponseDto, WarnUserDto, WarningCategoriesResponseDto, WarningCategory};
 
-use crate::bind::{get, post, put, set_cookie};
+use crate::bind::{delete, get, post, put, set_cookie};
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct UserSession {
@@ -13,6 +13,8 @@ pub struct UserSession {
     pub username: String,
     pub login_time: DateTime<Utc>,
     pub last_seen: DateTime<Utc>,
+    pub location: Option<Location>,
+    pub invisible: bool,
 }
 
 impl From<JsValue> for UserSession {
@@ -35,21 +37,28 @@ pub async fn user(user_id: &str) -> Result<UserData, JsValue> {
     Ok(body)
 }
 
-pub async fn warn_user(user_id: &str, comment: Option<&str>, warned_by: &str, ban_len: Option<i32>) -> Result<(), JsValue> {
+pub async fn warn_user(user_id: &str, comment: Option<&str>, warned_by: &str, category: Option<i32>, ban_len: Option<i32>) -> Result<(), JsValue> {
     let req = WarnUserDto {
         uuid: user_id.to_string(),
         comment: comment.map(|c: &str| c.to_string()),
         warned_by: warned_by.to_string(),
         banned: ban_len,
+        category,
     };
     let body = serde_json::to_string(&req)
         .expect("sj");
 
-    let _ = put("/users/warn", JsValue::from_str(&body)).await?;
+    put("/users/warn", JsValue::from_str(&body)).await?;
 
     Ok(())
 }
 
+/// Categories offered when warning, only readable with `CanWarn`
+pub async fn warning_categories() -> Result<Vec<WarningCategory>, JsValue> {
+    let response = get("/users/warning_categories").await?;
+    Ok(WarningCategoriesResponseDto::from(response).categories)
+}
+
 pub async fn unban_user(user_id: &str) -> Result<(), JsValue> {
     let req = UnbanUserDto {
         uuid: user_id.to_string(),
@@ -57,7 +66,7 @@ pub async fn unban_user(user_id: &str) -> Result<(), JsValue> {
     let body = serde_json::to_string(&req)
         .expect("sj");
 
-    let _ = put("/users/unban", JsValue::from_str(&body)).await?;
+    put("/users/unban", JsValue::from_str(&body)).await?;
 
     Ok(())
 }
@@ -125,3 +134,132 @@ pub async fn user_list(page: Option<usize>, limit: Option<usize>) -> Result<User
     let users = get(&addr).await?;
     Ok(UserListResponseDto::from(users))
 }
+
+pub async fn notifications(unread_only: bool) -> Result<NotificationsResponseDto, JsValue> {
+    let addr = format!("/users/notifications?unread={}", unread_only);
+    let response = get(&addr).await?;
+    Ok(NotificationsResponseDto::from(response))
+}
+
+pub async fn unread_notifications() -> Result<i64, JsValue> {
+    let response = get("/users/notifications/unread").await?;
+    Ok(UnreadCountResponseDto::from(response).unread)
+}
+
+pub async fn subscriptions() -> Result<SubscriptionsResponseDto, JsValue> {
+    let response = get("/users/subscriptions").await?;
+    Ok(SubscriptionsResponseDto::from(response))
+}
+
+/// Marks the given notifications as read, or all of them when `ids` is None
+pub async fn read_notifications(ids: Option<Vec<i64>>) -> Result<(), JsValue> {
+    let dto = ReadNotificationsDto { ids };
+    let body = serde_json::to_string(&dto)
+        .expect("SJ");
+
+    put("/users/notifications/read", JsValue::from_str(&body)).await?;
+    Ok(())
+}
+
+/// Conversations in the "inbox" or "sent" folder
+pub async fn conversations(folder: &str, page: u32) -> Result<UserPmsResponseDto, JsValue> {
+    let addr = format!("/users/pms?folder={}&page={}", folder, page);
+    let response = get(&addr).await?;
+    Ok(UserPmsResponseDto::from(response))
+}
+
+/// Loads a conversation and marks it as read
+pub async fn conversation(id: i64) -> Result<ConversationResponseDto, JsValue> {
+    let response = get(&format!("/users/pms/{}", id)).await?;
+    Ok(ConversationResponseDto::from(response))
+}
+
+/// Starts a new conversation, returns its id
+pub async fn send_pm(recipients: Vec<String>, subject: &str, content: &str) -> Result<i64, JsValue> {
+    let dto = SendPmDto {
+        recipients,
+        subject: subject.to_string(),
+        content: content.to_string(),
+    };
+    let body = serde_json::to_string(&dto)
+        .expect("SJ");
+
+    let response = post("/users/message", JsValue::from_str(&body)).await?;
+    Ok(SendPmResponseDto::from(response).conversation_id)
+}
+
+pub async fn reply_pm(id: i64, content: &str) -> Result<(), JsValue> {
+    let dto = ReplyPmDto { content: content.to_string() };
+    let body = serde_json::to_string(&dto)
+        .expect("SJ");
+
+    post(&format!("/users/pms/{}", id), JsValue::from_str(&body)).await?;
+    Ok(())
+}
+
+/// Removes the conversation from the current user's folders only
+pub async fn delete_conversation(id: i64) -> Result<(), JsValue> {
+    delete(&format!("/users/pms/{}", id), JsValue::from_str("{}")).await?;
+    Ok(())
+}
+
+pub async fn blocked_users() -> Result<Vec<BlockedUser>, JsValue> {
+    let response = get("/users/blocks").await?;
+    Ok(BlocksResponseDto::from(response).blocked)
+}
+
+/// Blocks or unblocks a user
+pub async fn block_user(user_id: &str, block: bool) -> Result<(), JsValue> {
+    let addr = format!("/users/blocks/{}", user_id);
+
+    if block {
+        put(&addr, JsValue::from_str("{}")).await?;
+    } else {
+        delete(&addr, JsValue::from_str("{}")).await?;
+    }
+    Ok(())
+}
+
+pub async fn privacy() -> Result<PrivacySettings, JsValue> {
+    let response = get("/users/privacy").await?;
+    Ok(PrivacySettings::from(response))
+}
+
+pub async fn update_privacy(settings: &PrivacySettings) -> Result<(), JsValue> {
+    let body = serde_json::to_string(settings)
+        .expect("SJ");
+
+    put("/users/privacy", JsValue::from_str(&body)).await?;
+    Ok(())
+}
+
+pub async fn bans() -> Result<Vec<Ban>, JsValue> {
+    let response = get("/bans").await?;
+    Ok(BansResponseDto::from(response).bans)
+}
+
+/// `value` is an address or CIDR range, an email address or a domain depending on `kind`
+pub async fn add_ban(kind: &str, value: &str, reason: Option<String>, days: Option<i32>) -> Result<(), JsValue> {
+    let dto = CreateBanDto {
+        kind: kind.to_string(),
+        value: value.to_string(),
+        reason,
+        days,
+    };
+    let body = serde_json::to_string(&dto)
+        .expect("SJ");
+
+    put("/bans", JsValue::from_str(&body)).await?;
+    Ok(())
+}
+
+pub async fn remove_ban(id: i64) -> Result<(), JsValue> {
+    delete(&format!("/bans/{}", id), JsValue::from_str("{}")).await?;
+    Ok(())
+}
+
+/// Accounts registered or logged in from the same address as the user
+pub async fn shared_ip_users(user_id: &str) -> Result<Vec<SharedIpUser>, JsValue> {
+    let response = get(&format!("/bans/users/{}", user_id)).await?;
+    Ok(SharedIpUsersResponseDto::from(response).users)
+}